@@ -0,0 +1,36 @@
+use std::fs;
+
+use aoc2016::utils::bespoke::{parse_facility, FacilitySearch};
+use aoc2016::utils::search::{self, SearchStrategy};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const PROBLEM_INPUT_FILE: &str = "./input/day11.txt";
+
+/// Benchmarks the AOC 2016 Day 11 Part 1 search using breadth-first search as the baseline against
+/// A* search, after reporting the number of states each strategy expands to reach the top floor.
+fn bench_day11_search(c: &mut Criterion) {
+    let floor_comps = parse_facility(&fs::read_to_string(PROBLEM_INPUT_FILE).unwrap()).unwrap();
+    let problem = FacilitySearch::new(floor_comps);
+    let strategies = [
+        ("bfs", SearchStrategy::BreadthFirst),
+        ("astar", SearchStrategy::AStar),
+    ];
+    for (name, strategy) in strategies {
+        let outcome = search::search(&problem, strategy).unwrap();
+        println!(
+            "day11_search/{name}: {} moves, {} states expanded",
+            outcome.moves(),
+            outcome.expanded()
+        );
+    }
+    let mut group = c.benchmark_group("day11_search");
+    for (name, strategy) in strategies {
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(search::search(black_box(&problem), strategy)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_day11_search);
+criterion_main!(benches);
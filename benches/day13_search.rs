@@ -0,0 +1,33 @@
+use std::fs;
+
+use aoc2016::utils::bespoke::{CubicleMaze, MazeSearch};
+use aoc2016::utils::search::{self, SearchStrategy};
+use aoc_utils::cartography::Point2D;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const PROBLEM_INPUT_FILE: &str = "./input/day13.txt";
+
+/// Benchmarks the AOC 2016 Day 13 Part 1 search using breadth-first search as the baseline against
+/// A* search.
+fn bench_day13_search(c: &mut Criterion) {
+    let seed = fs::read_to_string(PROBLEM_INPUT_FILE)
+        .unwrap()
+        .trim()
+        .parse::<i64>()
+        .unwrap();
+    let maze = CubicleMaze::new(seed);
+    let problem = MazeSearch::new(&maze, Point2D::new(1, 1), Point2D::new(31, 39));
+    let mut group = c.benchmark_group("day13_search");
+    for (name, strategy) in [
+        ("bfs", SearchStrategy::BreadthFirst),
+        ("astar", SearchStrategy::AStar),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(search::search(black_box(&problem), strategy)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_day13_search);
+criterion_main!(benches);
@@ -0,0 +1,58 @@
+use std::fs;
+
+use aoc2016::utils::bespoke::Ipv7Address;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+const PROBLEM_INPUT_FILE: &str = "./input/day07.txt";
+
+lazy_static! {
+    static ref REGEX_SUPERNET: Regex = Regex::new(r"([a-z]+\[|\][a-z]+\[|\][a-z]+)").unwrap();
+    static ref REGEX_HYPERNET: Regex = Regex::new(r"\[([a-z]+)\]").unwrap();
+    static ref REGEX_SQUARE_BRACE: Regex = Regex::new(r"\[|\]").unwrap();
+}
+
+/// Extracts the supernet and hypernet sequences from the given IPv7 address using the regex
+/// pipeline that was replaced by the bracket-aware splitter in Ipv7Address.
+fn extract_sequences_regex(address: &str) -> (Vec<String>, Vec<String>) {
+    let supernets = REGEX_SUPERNET
+        .find_iter(address)
+        .map(|cap| {
+            REGEX_SQUARE_BRACE
+                .replace_all(cap.unwrap().as_str(), "")
+                .to_string()
+        })
+        .collect::<Vec<String>>();
+    let hypernets = REGEX_HYPERNET
+        .captures_iter(address)
+        .map(|cap| cap.unwrap()[1].to_string())
+        .collect::<Vec<String>>();
+    (supernets, hypernets)
+}
+
+/// Benchmarks splitting the AOC 2016 Day 07 input addresses with the regex pipeline against the
+/// bracket-aware splitter.
+fn bench_ipv7_split(c: &mut Criterion) {
+    let raw_input = fs::read_to_string(PROBLEM_INPUT_FILE).unwrap();
+    let lines = raw_input.trim().lines().collect::<Vec<&str>>();
+    let mut group = c.benchmark_group("ipv7_split");
+    group.bench_function("regex", |b| {
+        b.iter(|| {
+            for line in lines.iter() {
+                black_box(extract_sequences_regex(black_box(line)));
+            }
+        })
+    });
+    group.bench_function("scanner", |b| {
+        b.iter(|| {
+            for line in lines.iter() {
+                black_box(black_box(line).parse::<Ipv7Address>().unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_ipv7_split);
+criterion_main!(benches);
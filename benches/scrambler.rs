@@ -0,0 +1,25 @@
+use std::fs;
+use std::str::FromStr;
+
+use aoc2016::utils::bespoke::Scrambler;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const PROBLEM_INPUT_FILE: &str = "./input/day21.txt";
+
+/// Benchmarks scrambling and unscrambling the AOC 2016 Day 21 passwords with the scrambling program
+/// from the problem input.
+fn bench_scrambler(c: &mut Criterion) {
+    let raw_input = fs::read_to_string(PROBLEM_INPUT_FILE).unwrap();
+    let scrambler = Scrambler::from_str(&raw_input).unwrap();
+    let mut group = c.benchmark_group("scrambler");
+    group.bench_function("scramble", |b| {
+        b.iter(|| black_box(scrambler.scramble(black_box("abcdefgh")).unwrap()))
+    });
+    group.bench_function("unscramble", |b| {
+        b.iter(|| black_box(scrambler.unscramble(black_box("fbgdceah")).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_scrambler);
+criterion_main!(benches);
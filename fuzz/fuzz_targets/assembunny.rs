@@ -0,0 +1,11 @@
+#![no_main]
+
+use aoc2016::utils::bespoke::ArbitraryProgram;
+use libfuzzer_sys::fuzz_target;
+
+/// Maximum number of instructions each generated program may execute.
+const INSTRUCTION_BUDGET: u64 = 100000;
+
+fuzz_target!(|program: ArbitraryProgram| {
+    program.run(INSTRUCTION_BUDGET);
+});
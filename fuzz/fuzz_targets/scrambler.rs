@@ -0,0 +1,8 @@
+#![no_main]
+
+use aoc2016::utils::bespoke::ArbitraryScramble;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|scramble: ArbitraryScramble| {
+    scramble.run();
+});
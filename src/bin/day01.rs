@@ -1,9 +1,9 @@
-use std::collections::HashSet;
 use std::fs;
 use std::time::Instant;
 
 use fancy_regex::Regex;
 
+use aoc2016::utils::events::EventStream;
 use aoc_utils::cartography::{CardinalDirection, Point2D};
 
 const PROBLEM_NAME: &str = "No Time for a Taxicab";
@@ -30,6 +30,8 @@ impl Turn {
 /// Processes the AOC 2016 Day 1 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -39,25 +41,33 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 1 input file in the format required by the solver functions.
@@ -84,35 +94,103 @@ fn process_input_file(filename: &str) -> Vec<(Turn, i64)> {
 /// Solves AOC 2016 Day 1 Part 1 // Processes each instruction and determines how far the
 /// protagonist ends up from the origin.
 fn solve_part1(instructions: &[(Turn, i64)]) -> u64 {
-    let mut direction = CardinalDirection::North;
-    let start_loc = Point2D::new(0, 0);
-    let mut loc = start_loc;
-    for (turn, steps) in instructions.iter() {
-        // Conduct the left or right turn
-        direction = match turn {
-            Turn::Left => direction.rotate90_counterclockwise(1),
-            Turn::Right => direction.rotate90_clockwise(1),
-        };
-        // Update the location by the number of steps conducted
-        match direction {
-            CardinalDirection::North => loc.shift(0, -steps),
-            CardinalDirection::East => loc.shift(*steps, 0),
-            CardinalDirection::South => loc.shift(0, *steps),
-            CardinalDirection::West => loc.shift(-steps, 0),
-        }
-    }
+    let end_loc = route_segments(instructions)
+        .last()
+        .map_or(Point2D::new(0, 0), |segment| segment.end());
     // Find the Manhattan distance between the end location and the start location
-    start_loc.get_manhattan_distance(&loc)
+    Point2D::new(0, 0).get_manhattan_distance(&end_loc)
 }
 
 /// Solves AOC 2016 Day 1 Part 2 // Determines the distance from the origin of the first location
 /// that the protagonist visits twice.
+///
+/// Each segment of the route is checked for intersections with the segments walked before it
+/// (excluding the immediately preceding segment, which only shares its start location), so the
+/// time and memory required do not depend on the number of steps taken.
 fn solve_part2(instructions: &[(Turn, i64)]) -> u64 {
+    let segments = route_segments(instructions);
+    for (i, segment) in segments.iter().enumerate() {
+        let first_revisit = segments[..i.saturating_sub(1)]
+            .iter()
+            .filter_map(|other| segment.first_intersection(other))
+            .min();
+        if let Some(step) = first_revisit {
+            let revisited_loc = segment.location_at(step);
+            // Find the Manhattan distance between the revisited location and the start location
+            return Point2D::new(0, 0).get_manhattan_distance(&revisited_loc);
+        }
+    }
+    panic!("Route does not visit any location twice!");
+}
+
+/// Represents a straight section of the route, consisting of a number of unit steps taken in the
+/// (dx, dy) direction from the start location.
+struct Segment {
+    start: Point2D,
+    dx: i64,
+    dy: i64,
+    steps: i64,
+}
+
+impl Segment {
+    /// Gets the location reached after taking the given number of steps along the segment.
+    fn location_at(&self, step: i64) -> Point2D {
+        self.start.peek_shift(self.dx * step, self.dy * step)
+    }
+
+    /// Gets the location at the end of the segment.
+    fn end(&self) -> Point2D {
+        self.location_at(self.steps)
+    }
+
+    /// Gets the number of steps along the line of the segment (which could be negative or beyond
+    /// the end of the segment) needed to reach the location, or None if the location does not lie
+    /// on the line of the segment.
+    fn line_steps_to(&self, loc: &Point2D) -> Option<i64> {
+        let (ox, oy) = (loc.x() - self.start.x(), loc.y() - self.start.y());
+        (ox * self.dy == oy * self.dx).then_some(ox * self.dx + oy * self.dy)
+    }
+
+    /// Gets the number of steps along the segment needed to reach the location, or None if the
+    /// location does not lie on the segment.
+    fn steps_to(&self, loc: &Point2D) -> Option<i64> {
+        self.line_steps_to(loc)
+            .filter(|&step| (0..=self.steps).contains(&step))
+    }
+
+    /// Checks if the segment runs horizontally (along the x-axis).
+    fn is_horizontal(&self) -> bool {
+        self.dy == 0
+    }
+
+    /// Finds the fewest steps (at least one) along the segment needed to reach a location that lies
+    /// on the other segment. Returns None if the segments do not intersect.
+    fn first_intersection(&self, other: &Segment) -> Option<i64> {
+        if self.is_horizontal() == other.is_horizontal() {
+            // Parallel segments only intersect if collinear, over their overlapping step ranges
+            let a = self.line_steps_to(&other.start)?;
+            let b = self.line_steps_to(&other.end())?;
+            let (low, high) = (a.min(b).max(1), a.max(b).min(self.steps));
+            return (low <= high).then_some(low);
+        }
+        // Perpendicular segments can only intersect at the crossing point of their lines
+        let crossing = if self.is_horizontal() {
+            Point2D::new(other.start.x(), self.start.y())
+        } else {
+            Point2D::new(self.start.x(), other.start.y())
+        };
+        other.steps_to(&crossing)?;
+        self.steps_to(&crossing).filter(|&step| step >= 1)
+    }
+}
+
+/// Splits the route given by the instructions into segments, starting at the origin and facing
+/// north.
+fn route_segments(instructions: &[(Turn, i64)]) -> Vec<Segment> {
     let mut direction = CardinalDirection::North;
-    let start_loc = Point2D::new(0, 0);
-    let mut loc = start_loc;
-    let mut visited: HashSet<Point2D> = HashSet::from([loc]);
-    'outer: for (turn, steps) in instructions.iter() {
+    let mut loc = Point2D::new(0, 0);
+    let mut segments: Vec<Segment> = vec![];
+    for (turn, steps) in instructions.iter() {
         // Conduct the left or right turn
         direction = match turn {
             Turn::Left => direction.rotate90_counterclockwise(1),
@@ -125,22 +203,34 @@ fn solve_part2(instructions: &[(Turn, i64)]) -> u64 {
             CardinalDirection::South => (0, 1),
             CardinalDirection::West => (-1, 0),
         };
-        // Conduct each step and check if the location has already been visited
-        for _ in 0..*steps {
-            loc.shift(dx, dy);
-            if !visited.insert(loc) {
-                break 'outer;
-            }
-        }
+        let segment = Segment {
+            start: loc,
+            dx,
+            dy,
+            steps: *steps,
+        };
+        loc = segment.end();
+        segments.push(segment);
     }
-    // Find the Manhattan distance between the end location and the start location
-    start_loc.get_manhattan_distance(&loc)
+    segments
 }
 
 #[cfg(test)]
 mod test {
+    use std::iter;
+
     use super::*;
 
+    /// Walks the route given by the instructions, starting at the origin and facing north, one step
+    /// at a time. Returns an iterator over each location the protagonist stands on (including the
+    /// origin), used to check the segments against a step-by-step walk.
+    fn walk(instructions: &[(Turn, i64)]) -> impl Iterator<Item = Point2D> {
+        let steps = route_segments(instructions)
+            .into_iter()
+            .flat_map(|segment| (1..=segment.steps).map(move |step| segment.location_at(step)));
+        iter::once(Point2D::new(0, 0)).chain(steps)
+    }
+
     /// Tests the Day 1 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day01_part1_actual() {
@@ -156,4 +246,38 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(166, solution);
     }
+
+    /// Tests that the walk visits each location along the route in order, using the Part 2
+    /// example from the problem description.
+    #[test]
+    fn test_day01_walk_example() {
+        let instructions = vec![
+            (Turn::Right, 8),
+            (Turn::Right, 4),
+            (Turn::Right, 4),
+            (Turn::Right, 8),
+        ];
+        let route = walk(&instructions).collect::<Vec<Point2D>>();
+        assert_eq!(25, route.len());
+        assert_eq!(Point2D::new(8, 0), route[8]);
+        assert_eq!(Point2D::new(4, -4), route[24]);
+        assert_eq!(4, solve_part2(&instructions));
+        assert_eq!(
+            route[24].get_manhattan_distance(&route[0]),
+            solve_part1(&instructions)
+        );
+    }
+
+    /// Tests that Part 1 finds the end of a route with a huge number of steps without walking it one
+    /// step at a time.
+    #[test]
+    fn test_day01_part1_huge_steps() {
+        let instructions = vec![
+            (Turn::Right, 1 << 40),
+            (Turn::Left, 1 << 40),
+            (Turn::Left, 1),
+        ];
+        assert_eq!((1 << 41) - 1, solve_part1(&instructions));
+        assert_eq!(0, solve_part1(&[]));
+    }
 }
@@ -1,42 +1,66 @@
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::time::Instant;
 
 use lazy_static::lazy_static;
 
+use aoc2016::utils::events::EventStream;
 use aoc_utils::cartography::Point2D;
 
 const PROBLEM_NAME: &str = "Bathroom Security";
 const PROBLEM_INPUT_FILE: &str = "./input/day02.txt";
 const PROBLEM_DAY: u64 = 2;
 
+/// Command line flag used to give the path of a file containing a custom keypad layout.
+const KEYPAD_LAYOUT_FLAG: &str = "--keypad";
+/// Command line flag used to print the finger's path over the keypad for each instruction line.
+const TRACE_FLAG: &str = "--trace";
+/// Layout of the simple keypad used in Part 1.
+const PART1_KEYPAD_LAYOUT: &str = "123\n456\n789";
+/// Layout of the complex keypad used in Part 2.
+const PART2_KEYPAD_LAYOUT: &str = "  1  \n 234 \n56789\n ABC \n  D  ";
+
 lazy_static! {
-    static ref PART1_KEYPAD: HashMap<Point2D, char> = HashMap::from([
-        (Point2D::new(0, 0), '1'),
-        (Point2D::new(1, 0), '2'),
-        (Point2D::new(2, 0), '3'),
-        (Point2D::new(0, 1), '4'),
-        (Point2D::new(1, 1), '5'),
-        (Point2D::new(2, 1), '6'),
-        (Point2D::new(0, 2), '7'),
-        (Point2D::new(1, 2), '8'),
-        (Point2D::new(2, 2), '9'),
-    ]);
-    static ref PART2_KEYPAD: HashMap<Point2D, char> = HashMap::from([
-        (Point2D::new(2, 0), '1'),
-        (Point2D::new(1, 1), '2'),
-        (Point2D::new(2, 1), '3'),
-        (Point2D::new(3, 1), '4'),
-        (Point2D::new(0, 2), '5'),
-        (Point2D::new(1, 2), '6'),
-        (Point2D::new(2, 2), '7'),
-        (Point2D::new(3, 2), '8'),
-        (Point2D::new(4, 2), '9'),
-        (Point2D::new(1, 3), 'A'),
-        (Point2D::new(2, 3), 'B'),
-        (Point2D::new(3, 3), 'C'),
-        (Point2D::new(2, 4), 'D'),
-    ]);
+    static ref PART1_KEYPAD: Keypad = Keypad::from_layout(PART1_KEYPAD_LAYOUT).unwrap();
+    static ref PART2_KEYPAD: Keypad = Keypad::from_layout(PART2_KEYPAD_LAYOUT).unwrap();
+}
+
+/// Represents a keypad, with the location of each button and the location of the button that the
+/// finger starts on.
+struct Keypad {
+    buttons: HashMap<Point2D, char>,
+    start_loc: Point2D,
+}
+
+impl Keypad {
+    /// Creates a keypad from the given layout, drawn as string art with one line per keypad row.
+    /// Spaces represent gaps in the keypad, and any other character is a button. The finger starts
+    /// on the "5" button if the keypad has one, otherwise on the first button in the layout.
+    ///
+    /// Returns None if the layout does not contain any buttons.
+    fn from_layout(layout: &str) -> Option<Keypad> {
+        let mut buttons: HashMap<Point2D, char> = HashMap::new();
+        let mut first_loc: Option<Point2D> = None;
+        let mut start_loc: Option<Point2D> = None;
+        for (y, row) in layout.lines().enumerate() {
+            for (x, c) in row.trim_end_matches('\r').chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                let loc = Point2D::new(x as i64, y as i64);
+                buttons.insert(loc, c);
+                first_loc.get_or_insert(loc);
+                if c == '5' {
+                    start_loc = Some(loc);
+                }
+            }
+        }
+        Some(Keypad {
+            buttons,
+            start_loc: start_loc.or(first_loc)?,
+        })
+    }
 }
 
 /// Represents the four different movement directions used in AOC 2016 Day 02.
@@ -63,6 +87,8 @@ impl Direction {
 /// Processes the AOC 2016 Day 02 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -72,25 +98,49 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Solve for custom keypad layout, if one was given
+    let custom_keypad = read_custom_keypad();
+    let custom_solution = custom_keypad
+        .as_ref()
+        .map(|keypad| solve_keypad(keypad, &input));
+    // Print the finger paths over each keypad if requested
+    if env::args().any(|arg| arg == TRACE_FLAG) {
+        print_keypad_trace("Part 1", &PART1_KEYPAD, &input);
+        print_keypad_trace("Part 2", &PART2_KEYPAD, &input);
+        if let Some(keypad) = custom_keypad.as_ref() {
+            print_keypad_trace("Custom keypad", keypad, &input);
+        }
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        if let Some(custom_solution) = custom_solution {
+            println!("[+] Custom keypad: {custom_solution}");
+        }
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 02 input file in the format required by the solver functions.
@@ -110,25 +160,47 @@ fn process_input_file(filename: &str) -> Vec<Vec<Direction>> {
         .collect::<Vec<Vec<Direction>>>()
 }
 
+/// Reads the custom keypad layout from the file given after the keypad layout flag on the command
+/// line. Returns None if the flag was not given.
+fn read_custom_keypad() -> Option<Keypad> {
+    let args = env::args().collect::<Vec<String>>();
+    let i_flag = args.iter().position(|arg| arg == KEYPAD_LAYOUT_FLAG)?;
+    let Some(filename) = args.get(i_flag + 1) else {
+        panic!("Missing keypad layout file after {KEYPAD_LAYOUT_FLAG} flag!");
+    };
+    let raw_layout = fs::read_to_string(filename).unwrap();
+    match Keypad::from_layout(&raw_layout) {
+        Some(keypad) => Some(keypad),
+        None => panic!("Keypad layout file does not contain any buttons! // {filename}"),
+    }
+}
+
 /// Solves AOC 2016 Day 02 Part 1 // Determines the keypad combination for the simple keypad.
 fn solve_part1(instructions: &[Vec<Direction>]) -> String {
-    process_keypad_instructions(&PART1_KEYPAD, instructions, &Point2D::new(1, 1))
+    solve_keypad(&PART1_KEYPAD, instructions)
 }
 
 /// Solves AOC 2016 Day 02 Part 2 // Determines the keypad combination for the complex keypad.
 fn solve_part2(instructions: &[Vec<Direction>]) -> String {
-    process_keypad_instructions(&PART2_KEYPAD, instructions, &Point2D::new(0, 2))
+    solve_keypad(&PART2_KEYPAD, instructions)
 }
 
 /// Processes the instructions for the keypad and determines the resulting keypad combination.
-fn process_keypad_instructions(
-    keypad: &HashMap<Point2D, char>,
-    instructions: &[Vec<Direction>],
-    start_loc: &Point2D,
-) -> String {
-    let mut combo = String::new();
-    let mut loc = *start_loc;
+fn solve_keypad(keypad: &Keypad, instructions: &[Vec<Direction>]) -> String {
+    trace_keypad_paths(keypad, instructions)
+        .iter()
+        .map(|path| *keypad.buttons.get(path.last().unwrap()).unwrap())
+        .collect::<String>()
+}
+
+/// Follows the instructions over the keypad, returning the path taken by the finger for each
+/// instruction line. Each path starts at the button pressed for the previous line (or the start
+/// button) and ends at the button pressed for the line.
+fn trace_keypad_paths(keypad: &Keypad, instructions: &[Vec<Direction>]) -> Vec<Vec<Point2D>> {
+    let mut paths: Vec<Vec<Point2D>> = vec![];
+    let mut loc = keypad.start_loc;
     for line in instructions {
+        let mut path: Vec<Point2D> = vec![loc];
         // Process each step in the current instruction line
         for dirn in line {
             // Get the new location that would result from following the current step
@@ -139,14 +211,46 @@ fn process_keypad_instructions(
                 Direction::Right => loc.peek_shift(1, 0),
             };
             // Only update the current location if the next location is on the keypad
-            if keypad.contains_key(&new_loc) {
+            if keypad.buttons.contains_key(&new_loc) {
                 loc = new_loc;
+                path.push(loc);
             }
         }
-        // Add the button to the keypad combination
-        combo.push(*keypad.get(&loc).unwrap());
+        paths.push(path);
+    }
+    paths
+}
+
+/// Renders the keypad with the finger's path highlighted. The pressed button (end of the path) is
+/// shown as [X], other buttons on the path as (X), and buttons not visited as  X .
+fn render_keypad_path(keypad: &Keypad, path: &[Point2D]) -> String {
+    let max_x = keypad.buttons.keys().map(|loc| loc.x()).max().unwrap();
+    let max_y = keypad.buttons.keys().map(|loc| loc.y()).max().unwrap();
+    let pressed = path.last().unwrap();
+    let mut output = String::new();
+    for y in 0..=max_y {
+        for x in 0..=max_x {
+            let loc = Point2D::new(x, y);
+            match keypad.buttons.get(&loc) {
+                Some(c) if loc == *pressed => output.push_str(&format!("[{c}]")),
+                Some(c) if path.contains(&loc) => output.push_str(&format!("({c})")),
+                Some(c) => output.push_str(&format!(" {c} ")),
+                None => output.push_str("   "),
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Prints the finger's path over the keypad and the button pressed for each instruction line.
+fn print_keypad_trace(name: &str, keypad: &Keypad, instructions: &[Vec<Direction>]) {
+    println!("{name}:");
+    for (i, path) in trace_keypad_paths(keypad, instructions).iter().enumerate() {
+        let button = keypad.buttons.get(path.last().unwrap()).unwrap();
+        println!("Line {} - pressed {button}", i + 1);
+        print!("{}", render_keypad_path(keypad, path));
     }
-    combo
 }
 
 #[cfg(test)]
@@ -168,4 +272,56 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!("57DD8", solution);
     }
+
+    /// Tests the keypad solver with a custom keypad layout, including gaps in the layout.
+    #[test]
+    fn test_day02_custom_keypad() {
+        let keypad = Keypad::from_layout("ab\nc d\n  e5").unwrap();
+        assert_eq!(Point2D::new(3, 2), keypad.start_loc);
+        let instructions = vec![
+            vec![Direction::Up, Direction::Up],
+            vec![Direction::Left, Direction::Left, Direction::Down],
+        ];
+        assert_eq!("5e", solve_keypad(&keypad, &instructions));
+        assert!(Keypad::from_layout("  \n ").is_none());
+    }
+
+    /// Tests that the keypad is rendered with the finger's path and pressed button highlighted.
+    #[test]
+    fn test_day02_render_keypad_path() {
+        let instructions = vec![vec![Direction::Up, Direction::Left, Direction::Left]];
+        let paths = trace_keypad_paths(&PART1_KEYPAD, &instructions);
+        assert_eq!(
+            "[1](2) 3 \n 4 (5) 6 \n 7  8  9 \n",
+            render_keypad_path(&PART1_KEYPAD, &paths[0])
+        );
+    }
+
+    /// Tests the rendered trace of the finger's path for each line of the example input given in
+    /// the problem description.
+    #[test]
+    fn test_day02_render_keypad_path_example() {
+        let instructions = ["ULL", "RRDDD", "LURDL", "UUUUD"]
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .map(|c| Direction::from_char(c).unwrap())
+                    .collect::<Vec<Direction>>()
+            })
+            .collect::<Vec<Vec<Direction>>>();
+        let rendered = trace_keypad_paths(&PART1_KEYPAD, &instructions)
+            .iter()
+            .map(|path| render_keypad_path(&PART1_KEYPAD, path))
+            .collect::<Vec<String>>();
+        assert_eq!(
+            vec![
+                "[1](2) 3 \n 4 (5) 6 \n 7  8  9 \n",
+                "(1)(2)(3)\n 4  5 (6)\n 7  8 [9]\n",
+                " 1  2  3 \n 4 (5)(6)\n 7 [8](9)\n",
+                " 1 (2) 3 \n 4 [5] 6 \n 7 (8) 9 \n",
+            ],
+            rendered
+        );
+        assert_eq!("1985", solve_part1(&instructions));
+    }
 }
@@ -1,13 +1,58 @@
+use std::collections::VecDeque;
+use std::fmt;
 use std::fs;
+use std::iter;
 use std::time::Instant;
 
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "Squares With Three Sides";
 const PROBLEM_INPUT_FILE: &str = "./input/day03.txt";
 const PROBLEM_DAY: u64 = 3;
 
+/// Number of sides in each triangle, which is the number of values in each row of the input file
+/// and the height of each vertical group of rows transposed in Part 2.
+const TRIANGLE_SIDES: usize = 3;
+
+/// Custom error type indicating that the triangle data in the input file is invalid.
+#[derive(Debug)]
+enum TriangleDataError {
+    /// A value on the given line is not a non-negative integer
+    InvalidValue { line: usize, text: String },
+    /// The given line does not have the expected number of values
+    WrongWidth {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// The rows at the end of the input do not fill a complete vertical group
+    IncompleteGroup { rows: usize, height: usize },
+}
+
+impl fmt::Display for TriangleDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TriangleDataError::InvalidValue { line, text } => {
+                write!(f, "line {line}: invalid value \"{text}\"")
+            }
+            TriangleDataError::WrongWidth {
+                line,
+                expected,
+                found,
+            } => write!(f, "line {line}: expected {expected} values, found {found}"),
+            TriangleDataError::IncompleteGroup { rows, height } => write!(
+                f,
+                "last group has {rows} rows, expected groups of {height} rows"
+            ),
+        }
+    }
+}
+
 /// Processes the AOC 2016 Day 03 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -17,94 +62,163 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 03 input file in the format required by the solver functions.
-/// Returned value is vector of three-tuples of values from the input file lines.
-fn process_input_file(filename: &str) -> Vec<(u64, u64, u64)> {
+/// Returned value is the raw input file contents, which the solver functions parse into rows of
+/// values as they are streamed.
+fn process_input_file(filename: &str) -> String {
     // Read contents of problem input file
-    let raw_input = fs::read_to_string(filename).unwrap();
-    // Process input file contents into data structure
-    let mut triangles: Vec<(u64, u64, u64)> = vec![];
-    for line in raw_input.lines() {
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let tri = line
-            .split_ascii_whitespace()
-            .map(|elem| elem.parse::<u64>().unwrap())
-            .collect::<Vec<u64>>();
-        if tri.len() != 3 {
-            panic!("Invalid input file line! // {line}");
-        }
-        triangles.push((tri[0], tri[1], tri[2]));
-    }
-    triangles
+    fs::read_to_string(filename).unwrap()
 }
 
 /// Solves AOC 2016 Day 03 Part 1 // Determines how many of the triangles are possible under the
 /// problem rules (i.e., the sum of any two sides is greater than the remaining side).
-fn solve_part1(triangles: &[(u64, u64, u64)]) -> usize {
-    get_valid_triangles_count(triangles)
+fn solve_part1(input: &str) -> usize {
+    get_valid_triangles_count(parse_rows(input, TRIANGLE_SIDES))
 }
 
 /// Solves AOC 2016 Day 03 Part 2 // Determines how many of the triangles are possible after
 /// conducting a vertical transposition of the triangles.
-fn solve_part2(triangles: &[(u64, u64, u64)]) -> usize {
-    let triangles = transpose_triangles(triangles);
-    get_valid_triangles_count(&triangles)
+fn solve_part2(input: &str) -> usize {
+    let rows = parse_rows(input, TRIANGLE_SIDES);
+    get_valid_triangles_count(transpose_rows(rows, TRIANGLE_SIDES))
 }
 
-/// Determines the number of triangles that are valid (i.e., the sum of any two sides is greater
-/// than the remaining side).
-fn get_valid_triangles_count(triangles: &[(u64, u64, u64)]) -> usize {
-    triangles
-        .iter()
-        .filter(|tri| is_triangle_valid(tri))
-        .count()
+/// Parses the lines of the input into rows of the given number of values, skipping empty lines.
+/// Rows are parsed lazily as the returned iterator is consumed.
+fn parse_rows(
+    input: &str,
+    width: usize,
+) -> impl Iterator<Item = Result<Vec<u64>, TriangleDataError>> + '_ {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(move |(i, line)| {
+            let row = line
+                .split_ascii_whitespace()
+                .map(|elem| {
+                    elem.parse::<u64>()
+                        .map_err(|_| TriangleDataError::InvalidValue {
+                            line: i + 1,
+                            text: elem.to_string(),
+                        })
+                })
+                .collect::<Result<Vec<u64>, TriangleDataError>>()?;
+            if row.len() != width {
+                return Err(TriangleDataError::WrongWidth {
+                    line: i + 1,
+                    expected: width,
+                    found: row.len(),
+                });
+            }
+            Ok(row)
+        })
 }
 
-/// Transposes the triangles by taking the vertical groups of three. Any rows at the end that are
-/// remaining from previous groups of three rows are excluded.
-fn transpose_triangles(triangles: &[(u64, u64, u64)]) -> Vec<(u64, u64, u64)> {
-    let mut transposed: Vec<(u64, u64, u64)> = vec![];
-    for i in (0..triangles.len()).step_by(3) {
-        if i + 2 >= triangles.len() {
-            break;
+/// Determines the number of triangles that are valid (i.e., the sum of any two sides is greater
+/// than the remaining side). Panics if the triangle data is invalid.
+fn get_valid_triangles_count(
+    triangles: impl Iterator<Item = Result<Vec<u64>, TriangleDataError>>,
+) -> usize {
+    let mut count = 0;
+    for tri in triangles {
+        match tri {
+            Ok(tri) => {
+                if is_triangle_valid(&tri) {
+                    count += 1;
+                }
+            }
+            Err(e) => panic!("Invalid triangle data in input file! // {e}"),
         }
-        // Left
-        transposed.push((triangles[i].0, triangles[i + 1].0, triangles[i + 2].0));
-        // Middle
-        transposed.push((triangles[i].1, triangles[i + 1].1, triangles[i + 2].1));
-        // Right
-        transposed.push((triangles[i].2, triangles[i + 1].2, triangles[i + 2].2));
     }
-    transposed
+    count
 }
 
-/// Checks if the sum of any two elements is greater than the remaining element.
-fn is_triangle_valid(tri: &(u64, u64, u64)) -> bool {
-    tri.0 + tri.1 > tri.2 && tri.0 + tri.2 > tri.1 && tri.1 + tri.2 > tri.0
+/// Transposes the rows by taking the columns of each vertical group of rows with the given height.
+/// Only one group of rows is held at a time, with the columns produced lazily as the returned
+/// iterator is consumed. An error is produced if the rows at the end do not fill a complete group.
+fn transpose_rows(
+    mut rows: impl Iterator<Item = Result<Vec<u64>, TriangleDataError>>,
+    height: usize,
+) -> impl Iterator<Item = Result<Vec<u64>, TriangleDataError>> {
+    let mut columns: VecDeque<Vec<u64>> = VecDeque::new();
+    let mut failed = false;
+    iter::from_fn(move || {
+        if let Some(column) = columns.pop_front() {
+            return Some(Ok(column));
+        }
+        if failed {
+            return None;
+        }
+        // Read the next group of rows
+        let mut group: Vec<Vec<u64>> = vec![];
+        for row in rows.by_ref().take(height) {
+            match row {
+                Ok(row) => group.push(row),
+                Err(e) => {
+                    failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        if group.is_empty() {
+            return None;
+        }
+        if group.len() < height {
+            failed = true;
+            return Some(Err(TriangleDataError::IncompleteGroup {
+                rows: group.len(),
+                height,
+            }));
+        }
+        // Split the group into its columns
+        for i in 0..group[0].len() {
+            columns.push_back(group.iter().map(|row| row[i]).collect::<Vec<u64>>());
+        }
+        columns.pop_front().map(Ok)
+    })
+}
+
+/// Checks if each element is less than the sum of the remaining elements. For three elements this
+/// is the same as the sum of any two elements being greater than the remaining element, and it holds
+/// for the columns of any group height used in the transposition. An empty row is never valid.
+///
+/// The sum is taken as a u128, so it cannot overflow for any row of u64 values that fits in memory.
+fn is_triangle_valid(tri: &[u64]) -> bool {
+    let total = tri.iter().map(|&side| u128::from(side)).sum::<u128>();
+    !tri.is_empty()
+        && tri
+            .iter()
+            .all(|&side| u128::from(side) < total - u128::from(side))
 }
 
 #[cfg(test)]
@@ -126,4 +240,52 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(1577, solution);
     }
+
+    /// Tests that rows are transposed in vertical groups, and that malformed data is reported.
+    #[test]
+    fn test_day03_transpose_rows() {
+        let input = "101 301 501\n102 302 502\n103 303 503\n201 401 601\n";
+        let rows = parse_rows(input, TRIANGLE_SIDES);
+        let columns = transpose_rows(rows, 3).collect::<Vec<_>>();
+        assert_eq!(vec![101, 102, 103], *columns[0].as_ref().unwrap());
+        assert_eq!(vec![501, 502, 503], *columns[2].as_ref().unwrap());
+        assert!(matches!(
+            columns[3],
+            Err(TriangleDataError::IncompleteGroup { rows: 1, height: 3 })
+        ));
+        let mut rows = parse_rows("1 2 3\n\n4 x 6\n7 8\n", TRIANGLE_SIDES).skip(1);
+        assert!(matches!(
+            rows.next(),
+            Some(Err(TriangleDataError::InvalidValue { line: 3, .. }))
+        ));
+        assert!(matches!(
+            rows.next(),
+            Some(Err(TriangleDataError::WrongWidth {
+                line: 4,
+                found: 2,
+                ..
+            }))
+        ));
+    }
+
+    /// Tests that the validity check uses every value in the row, so that groups with heights other
+    /// than the number of triangle sides are counted without panicking.
+    #[test]
+    fn test_day03_group_heights() {
+        assert!(is_triangle_valid(&[3, 4, 5]));
+        assert!(!is_triangle_valid(&[5, 10, 25]));
+        assert!(!is_triangle_valid(&[]));
+        assert!(is_triangle_valid(&[u64::MAX, u64::MAX, u64::MAX]));
+        assert!(!is_triangle_valid(&[u64::MAX, 1, u64::MAX - 1]));
+        let input = "3 4 5\n4 5 6\n5 6 7\n6 7 30\n";
+        let rows = parse_rows(input, TRIANGLE_SIDES);
+        assert_eq!(2, get_valid_triangles_count(transpose_rows(rows, 4)));
+        let rows = parse_rows(input, TRIANGLE_SIDES);
+        assert_eq!(0, get_valid_triangles_count(transpose_rows(rows, 2)));
+        let rows = parse_rows(input, TRIANGLE_SIDES);
+        assert!(transpose_rows(rows, 3).any(|column| matches!(
+            column,
+            Err(TriangleDataError::IncompleteGroup { rows: 1, .. })
+        )));
+    }
 }
@@ -1,19 +1,26 @@
+use std::env;
 use std::fs;
 use std::time::Instant;
 
 use fancy_regex::Regex;
 
 use aoc2016::utils::bespoke::Room;
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Security Through Obscurity";
 const PROBLEM_INPUT_FILE: &str = "./input/day04.txt";
 const PROBLEM_DAY: u64 = 4;
 
-const TARGET_DECRYPTED_NAME: &str = "northpole object storage";
+/// Pattern matching the decrypted name of the room where the North Pole objects are stored.
+const TARGET_ROOM_PATTERN: &str = r"^northpole object storage$";
+/// Command line flag used to give a regex pattern to search the decrypted room names for.
+const SEARCH_FLAG: &str = "--search";
 
 /// Processes the AOC 2016 Day 04 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -23,25 +30,39 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Search the decrypted room names, if a search pattern was given
+    if let Some(pattern) = read_search_pattern() {
+        for room in search_rooms(&input, &pattern) {
+            println!("{} - {}", room.sector_id(), room.decrypted_name());
+        }
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 04 input file in the format required by the solver functions.
@@ -78,12 +99,32 @@ fn solve_part1(rooms: &[Room]) -> u32 {
 
 /// Solves AOC 2016 Day 04 Part 2 // Finds the sector of the Room keeping the presents.
 fn solve_part2(rooms: &[Room]) -> u32 {
+    let pattern = Regex::new(TARGET_ROOM_PATTERN).unwrap();
+    search_rooms(rooms, &pattern)[0].sector_id()
+}
+
+/// Reads the regex pattern given after the search flag on the command line. Returns None if the
+/// flag was not given.
+fn read_search_pattern() -> Option<Regex> {
+    let args = env::args().collect::<Vec<String>>();
+    let i_flag = args.iter().position(|arg| arg == SEARCH_FLAG)?;
+    let Some(pattern) = args.get(i_flag + 1) else {
+        panic!("Missing search pattern after {SEARCH_FLAG} flag!");
+    };
+    match Regex::new(pattern) {
+        Ok(regex) => Some(regex),
+        Err(e) => panic!("Invalid search pattern! // {pattern} // {e}"),
+    }
+}
+
+/// Decrypts the names of the real rooms and returns the rooms with a decrypted name matching the
+/// given pattern, in the order they are given.
+fn search_rooms<'a>(rooms: &'a [Room], pattern: &Regex) -> Vec<&'a Room> {
     rooms
         .iter()
-        .filter(|room| room.decrypted_name() == TARGET_DECRYPTED_NAME)
-        .map(|room| room.sector_id())
-        .next()
-        .unwrap()
+        .filter(|room| room.is_real_room())
+        .filter(|room| pattern.is_match(room.decrypted_name()).unwrap_or(false))
+        .collect::<Vec<&Room>>()
 }
 
 #[cfg(test)]
@@ -105,4 +146,18 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(548, solution);
     }
+
+    /// Tests that the room search returns the real rooms with decrypted names matching the pattern.
+    #[test]
+    fn test_day04_search_rooms() {
+        let rooms = process_input_file(PROBLEM_INPUT_FILE);
+        let pattern = Regex::new(r"^north").unwrap();
+        let matches = search_rooms(&rooms, &pattern);
+        assert_eq!(1, matches.len());
+        assert_eq!("northpole object storage", matches[0].decrypted_name());
+        let pattern = Regex::new(r"candy").unwrap();
+        assert!(search_rooms(&rooms, &pattern)
+            .iter()
+            .all(|room| room.is_real_room() && room.decrypted_name().contains("candy")));
+    }
 }
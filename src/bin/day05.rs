@@ -1,108 +1,184 @@
+use std::env;
 use std::fs;
 use std::time::Instant;
 
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::hashmining::{HashMiner, Md5};
+use aoc2016::utils::metrics;
+use aoc2016::utils::nibbles::{leading_zero_nibbles, lower_hex_digit, nibbles};
+
 const PROBLEM_NAME: &str = "How About a Nice Game of Chess?";
 const PROBLEM_INPUT_FILE: &str = "./input/day05.txt";
 const PROBLEM_DAY: u64 = 5;
 
+/// Number of leading zeroes required for a "nice" md5 hex digest in the problem.
+const DEFAULT_DIFFICULTY: usize = 5;
+/// Number of characters in the door passcode.
+const PASSCODE_LENGTH: usize = 8;
+/// Command line flag used to override the door ID given in the input file.
+const DOOR_ID_FLAG: &str = "--door-id";
+/// Command line flag used to override the number of leading zeroes required for a "nice" digest.
+const DIFFICULTY_FLAG: &str = "--difficulty";
+
+/// Represents the parameters used to search for the door passcode.
+struct DoorConfig {
+    /// Door ID used as the prefix of the md5 hash inputs
+    door_id: String,
+    /// Number of leading zeroes required for a "nice" md5 hex digest
+    difficulty: usize,
+}
+
 /// Processes the AOC 2016 Day 05 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
-    let input = process_input_file(PROBLEM_INPUT_FILE);
+    let mut input = process_input_file(PROBLEM_INPUT_FILE);
+    apply_command_line_overrides(&mut input);
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 05 input file in the format required by the solver functions.
-/// Returned value is the string given in the input file.
-fn process_input_file(filename: &str) -> String {
+/// Returned value is the door configuration with the door ID given in the input file and the
+/// default difficulty.
+fn process_input_file(filename: &str) -> DoorConfig {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    raw_input.trim().to_string()
+    DoorConfig {
+        door_id: raw_input.trim().to_string(),
+        difficulty: DEFAULT_DIFFICULTY,
+    }
 }
 
-/// Solves AOC 2016 Day 05 Part 1 // Determines the eight-character door passcode by finding eight
-/// md5 hex digests starting with five zeroes and taking the sixth character.
-fn solve_part1(seed: &str) -> String {
-    let mut passcode = String::new();
-    let mut i: u64 = 0;
-    for _ in 0..8 {
-        // Find next character for the passcode
-        loop {
-            // Calculate md5 hex digest and increment index
-            let digest = md5::compute(format!("{seed}{i}").as_bytes());
-            let hex_digest = format!("{digest:x}");
-            i += 1;
-            // Check if md5 hex digest starting with five zeroes has been found
-            if hex_digest.starts_with("00000") {
-                passcode.push(hex_digest.chars().nth(5).unwrap());
-                break;
+/// Applies the door ID and difficulty overrides given on the command line to the door
+/// configuration.
+fn apply_command_line_overrides(config: &mut DoorConfig) {
+    let args = env::args().collect::<Vec<String>>();
+    for (flag, value) in args.iter().zip(args.iter().skip(1)) {
+        if flag == DOOR_ID_FLAG {
+            config.door_id = value.to_string();
+        } else if flag == DIFFICULTY_FLAG {
+            match value.parse::<usize>() {
+                Ok(difficulty) => config.difficulty = difficulty,
+                Err(_) => panic!("Invalid difficulty given! // {value}"),
             }
         }
     }
-    passcode
+}
+
+/// Solves AOC 2016 Day 05 Part 1 // Determines the eight-character door passcode by finding eight
+/// md5 hex digests starting with five zeroes and taking the sixth character.
+fn solve_part1(config: &DoorConfig) -> String {
+    find_sequential_passcode(config.door_id.as_bytes(), config.difficulty)
 }
 
 /// Solves AOC 2016 Day 05 Part 2 // Determines the eight-character door passcode by treating the
 /// sixth character of the "nice" md5 hex digests as the index into the door passcode.
-fn solve_part2(seed: &str) -> String {
-    let mut passcode: [Option<char>; 8] = [None; 8];
-    let mut i: u64 = 0;
-    for _ in 0..8 {
-        // Find the next character for the passcode
-        loop {
-            // Calculate md5 hex digest and increment index
-            let digest = md5::compute(format!("{seed}{i}").as_bytes());
-            let hex_digest = format!("{digest:x}");
-            i += 1;
-            // Check if md5 hex digest starting with five zeroes has been found
-            if hex_digest.starts_with("00000") {
-                let chars = hex_digest.chars().collect::<Vec<char>>();
-                if let Some(index) = chars[5].to_digit(10) {
-                    let index = index as usize;
-                    if index > 7 || passcode[index].is_some() {
-                        continue;
-                    }
-                    let passchar = chars[6];
-                    passcode[index] = Some(passchar);
-                    break;
-                }
-            }
+fn solve_part2(config: &DoorConfig) -> String {
+    find_positional_passcode(config.door_id.as_bytes(), config.difficulty)
+}
+
+/// Determines the door passcode by taking the hex digit following the leading zeroes of each
+/// "nice" md5 digest, in the order the digests are found.
+fn find_sequential_passcode(door_id: &[u8], difficulty: usize) -> String {
+    find_nice_digests(door_id, difficulty)
+        .take(PASSCODE_LENGTH)
+        .map(|digest| lower_hex_digit(nibbles(&digest).nth(difficulty).unwrap()) as char)
+        .collect::<String>()
+}
+
+/// Determines the door passcode by treating the hex digit following the leading zeroes of each
+/// "nice" md5 digest as the position in the passcode, and the next hex digit as the value.
+fn find_positional_passcode(door_id: &[u8], difficulty: usize) -> String {
+    let mut passcode: [Option<u8>; PASSCODE_LENGTH] = [None; PASSCODE_LENGTH];
+    for digest in find_nice_digests(door_id, difficulty) {
+        let mut digest_nibbles = nibbles(&digest).skip(difficulty);
+        // Only the first character found for each valid position is used
+        let index = digest_nibbles.next().unwrap() as usize;
+        if index >= PASSCODE_LENGTH || passcode[index].is_some() {
+            continue;
         }
+        passcode[index] = Some(lower_hex_digit(digest_nibbles.next().unwrap()));
+        if passcode.iter().all(|c| c.is_some()) {
+            break;
+        }
+    }
+    passcode
+        .iter()
+        .map(|c| c.unwrap() as char)
+        .collect::<String>()
+}
+
+/// Returns an iterator over the "nice" md5 digests (with hex digests starting with the number of
+/// zeroes given by the difficulty) of the door ID followed by an increasing integer index, in order
+/// of index.
+///
+/// Panics if the difficulty does not leave room for the passcode characters in the digest.
+fn find_nice_digests(door_id: &[u8], difficulty: usize) -> impl Iterator<Item = [u8; 16]> {
+    if difficulty > 30 {
+        panic!("Difficulty must be at most 30 leading zeroes! // {difficulty}");
     }
-    passcode.iter().map(|c| c.unwrap()).collect::<String>()
+    HashMiner::new(Md5::new(), door_id, move |digest: &[u8; 16]| {
+        leading_zero_nibbles(digest) >= difficulty
+    })
+    .map(|mined| *mined.digest())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[cfg(feature = "count-allocations")]
+    use aoc2016::utils::alloccount::CountingAllocator;
+
+    #[cfg(feature = "count-allocations")]
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
     /// Tests the Day 05 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day05_part1_actual() {
@@ -118,4 +194,26 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!("999828ec", solution);
     }
+
+    /// Tests both passcode searches against a synthetic door ID with a reduced difficulty.
+    #[test]
+    fn test_day05_synthetic_door() {
+        assert_eq!("6bc847f9", find_sequential_passcode(b"synthetic", 3));
+        assert_eq!("daff84d5", find_positional_passcode(b"synthetic", 3));
+    }
+
+    /// Tests that the passcode searches for a synthetic door ID do not make more allocations than
+    /// their recorded limits.
+    #[cfg(all(feature = "count-allocations", not(feature = "parallel")))]
+    #[test]
+    fn test_day05_allocations() {
+        use aoc2016::utils::alloccount::assert_allocations_within;
+
+        assert_allocations_within("part 1", 7_000, || {
+            find_sequential_passcode(b"synthetic", 3)
+        });
+        assert_allocations_within("part 2", 25_000, || {
+            find_positional_passcode(b"synthetic", 3)
+        });
+    }
 }
@@ -1,15 +1,24 @@
+use std::cmp::Reverse;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::env;
 use std::fs;
 use std::time::Instant;
 
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "Signals and Noise";
 const PROBLEM_INPUT_FILE: &str = "./input/day06.txt";
 const PROBLEM_DAY: u64 = 6;
 
+/// Command line flag used to print the character ranking report for each message position.
+const REPORT_FLAG: &str = "--report";
+
 /// Processes the AOC 2016 Day 06 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -19,25 +28,40 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the character ranking report if requested
+    if env::args().any(|arg| arg == REPORT_FLAG) {
+        print!(
+            "{}",
+            format_ranking_report(&rank_position_characters(&input))
+        );
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 06 input file in the format required by the solver functions.
@@ -57,35 +81,67 @@ fn process_input_file(filename: &str) -> Vec<Vec<char>> {
 /// Solves AOC 2016 Day 06 Part 1 // Determines the error-corrected message by taking the most
 /// common character at each index across all of the messages.
 fn solve_part1(messages: &[Vec<char>]) -> String {
-    let mut message_corrected = String::new();
-    let pos_char_counts = get_position_character_counts(messages);
-    for pos_count in pos_char_counts {
-        // Get the most common character at the current index
-        let c = pos_count
-            .iter()
-            .max_by_key(|a| a.1)
-            .map(|(k, _v)| k)
-            .unwrap();
-        message_corrected.push(*c);
-    }
-    message_corrected
+    rank_position_characters(messages)
+        .iter()
+        .map(|ranking| ranking[0].0)
+        .collect::<String>()
 }
 
 /// Solves AOC 2016 Day 06 Part 2 // Determines the error-corrected message by taking the least
 /// common character at each index across all of the messages.
 fn solve_part2(messages: &[Vec<char>]) -> String {
-    let mut message_corrected = String::new();
-    let pos_char_counts = get_position_character_counts(messages);
-    for pos_count in pos_char_counts {
-        // Get the least common character at the current index
-        let c = pos_count
+    rank_position_characters(messages)
+        .iter()
+        .map(|ranking| {
+            // Ties for least common are resolved in alphabetical order
+            let min_count = ranking.last().unwrap().1;
+            ranking
+                .iter()
+                .find(|(_, count)| *count == min_count)
+                .unwrap()
+                .0
+        })
+        .collect::<String>()
+}
+
+/// Ranks the characters observed at each index across all of the messages, from most to least
+/// common. Characters with the same count are ranked in alphabetical order, so ties are resolved
+/// deterministically.
+fn rank_position_characters(messages: &[Vec<char>]) -> Vec<Vec<(char, u64)>> {
+    get_position_character_counts(messages)
+        .into_iter()
+        .map(|pos_count| {
+            let mut ranking = pos_count.into_iter().collect::<Vec<(char, u64)>>();
+            ranking.sort_by_key(|&(c, count)| (Reverse(count), c));
+            ranking
+        })
+        .collect::<Vec<Vec<(char, u64)>>>()
+}
+
+/// Formats a report of the character ranking at each index. Each line shows the share of messages
+/// with the most common character (the confidence of the error-corrected character), followed by
+/// the ranked characters and their counts. Ties for most or least common are flagged.
+fn format_ranking_report(rankings: &[Vec<(char, u64)>]) -> String {
+    let mut report = String::new();
+    for (i, ranking) in rankings.iter().enumerate() {
+        let total = ranking.iter().map(|(_, count)| count).sum::<u64>();
+        let confidence = 100.0 * ranking[0].1 as f64 / total as f64;
+        let candidates = ranking
             .iter()
-            .min_by_key(|a| a.1)
-            .map(|(k, _v)| k)
-            .unwrap();
-        message_corrected.push(*c);
+            .map(|(c, count)| format!("{c}:{count}"))
+            .collect::<Vec<String>>()
+            .join(" ");
+        report.push_str(&format!("[{i}] {confidence:5.1}% | {candidates}"));
+        let n = ranking.len();
+        if n > 1 && ranking[0].1 == ranking[1].1 {
+            report.push_str(" | tie for most common");
+        }
+        if n > 1 && ranking[n - 1].1 == ranking[n - 2].1 {
+            report.push_str(" | tie for least common");
+        }
+        report.push('\n');
     }
-    message_corrected
+    report
 }
 
 /// Returns a vector of hashmaps containing the total number of times each character is observed at
@@ -128,4 +184,33 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!("lragovly", solution);
     }
+
+    /// Tests both error-correction methods against the example from the problem description.
+    #[test]
+    fn test_day06_example() {
+        let messages = [
+            "eedadn", "drvtee", "eandsr", "raavrd", "atevrs", "tsrnev", "sdttsa", "rasrtv",
+            "nssdts", "ntnada", "svetve", "tesnvt", "vntsnd", "vrdear", "dvrsen", "enarar",
+        ]
+        .iter()
+        .map(|message| message.chars().collect::<Vec<char>>())
+        .collect::<Vec<Vec<char>>>();
+        assert_eq!("easter", solve_part1(&messages));
+        assert_eq!("advent", solve_part2(&messages));
+    }
+
+    /// Tests that the ranking report shows each candidate character and flags ties.
+    #[test]
+    fn test_day06_ranking_report() {
+        let messages = ["ab", "ba", "ac"]
+            .iter()
+            .map(|message| message.chars().collect::<Vec<char>>())
+            .collect::<Vec<Vec<char>>>();
+        let rankings = rank_position_characters(&messages);
+        assert_eq!(vec![('a', 2), ('b', 1)], rankings[0]);
+        assert_eq!(
+            "[0]  66.7% | a:2 b:1\n[1]  33.3% | a:1 b:1 c:1 | tie for most common | tie for least common\n",
+            format_ranking_report(&rankings)
+        );
+    }
 }
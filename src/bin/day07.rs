@@ -1,24 +1,23 @@
-use std::collections::HashSet;
+use std::env;
 use std::fs;
 use std::time::Instant;
 
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
+use aoc2016::utils::bespoke::Ipv7Address;
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Internet Protocol Version 7";
 const PROBLEM_INPUT_FILE: &str = "./input/day07.txt";
 const PROBLEM_DAY: u64 = 7;
 
-lazy_static! {
-    static ref REGEX_SUPERNET: Regex = Regex::new(r"([a-z]+\[|\][a-z]+\[|\][a-z]+)").unwrap();
-    static ref REGEX_HYPERNET: Regex = Regex::new(r"\[([a-z]+)\]").unwrap();
-    static ref REGEX_SQUARE_BRACE: Regex = Regex::new(r"\[|\]").unwrap();
-    static ref REGEX_ABBA: Regex = Regex::new(r"([a-z])([a-z])\2\1").unwrap();
-}
+/// Command line flag used to print the substrings that cause each address to pass or fail the TLS
+/// and SSL checks.
+const EXPLAIN_FLAG: &str = "--explain";
 
 /// Processes the AOC 2016 Day 07 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -28,129 +27,103 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the explanation of each address check if requested
+    if env::args().any(|arg| arg == EXPLAIN_FLAG) {
+        print!("{}", format_explanation(&input));
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 07 input file in the format required by the solver functions.
-/// Returned value is vector of strings given as the lines of the input file.
-fn process_input_file(filename: &str) -> Vec<String> {
+/// Returned value is vector of IPv7 addresses given as the lines of the input file.
+fn process_input_file(filename: &str) -> Vec<Ipv7Address> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
     raw_input
         .trim()
         .lines()
-        .map(|line| line.trim().to_string())
+        .map(|line| line.trim())
         .filter(|line| !line.is_empty())
-        .collect::<Vec<String>>()
+        .map(|line| match line.parse::<Ipv7Address>() {
+            Ok(address) => address,
+            Err(e) => panic!("Bad IPv7 address in input file! // {line} // {e}"),
+        })
+        .collect::<Vec<Ipv7Address>>()
 }
 
 /// Solves AOC 2016 Day 07 Part 1 // Determines the number of the given "IPv7" addresses that
 /// support "TLS" (transport-layer snooping).
-fn solve_part1(ipv7_addresses: &[String]) -> usize {
+fn solve_part1(ipv7_addresses: &[Ipv7Address]) -> usize {
     ipv7_addresses
         .iter()
-        .filter(|addr| check_tls_support(addr))
+        .filter(|addr| addr.supports_tls())
         .count()
 }
 
 /// Solves AOC 2016 Day 07 Part 2 // Determines the number of the given "IPv7" addresses that
 /// support "SSL" (super-secret listening).
-fn solve_part2(ipv7_addresses: &[String]) -> usize {
+fn solve_part2(ipv7_addresses: &[Ipv7Address]) -> usize {
     ipv7_addresses
         .iter()
-        .filter(|addr| check_ssl_support(addr))
+        .filter(|addr| addr.supports_ssl())
         .count()
 }
 
-/// Checks if the given "IPv7" address supports "TLS" (transport-layer snooping).
-fn check_tls_support(ipv7_address: &str) -> bool {
-    let (supernets, hypernets) = extract_supernet_and_hypernet_sequences(ipv7_address);
-    // Check that one of the supernet sequences contains an ABBA
-    let mut supernet_check = false;
-    for supernet in supernets {
-        if let Ok(Some(caps)) = REGEX_ABBA.captures(&supernet) {
-            // Check that the first two characters of the ABBA are different
-            supernet_check = caps[1] != caps[2];
-            if supernet_check {
-                break;
-            }
-        }
-    }
-    if !supernet_check {
-        return false;
+/// Formats an explanation of the TLS and SSL checks for each address. Each line shows the ABBAs
+/// that caused the address to pass or fail the TLS check, and the ABA/BAB pairs that caused it to
+/// pass the SSL check.
+fn format_explanation(ipv7_addresses: &[Ipv7Address]) -> String {
+    let mut explanation = String::new();
+    for addr in ipv7_addresses {
+        let supernet_abbas = addr.supernet_abbas();
+        let hypernet_abbas = addr.hypernet_abbas();
+        let tls = if !hypernet_abbas.is_empty() {
+            format!("fail (hypernet ABBA {})", hypernet_abbas.join(", "))
+        } else if !supernet_abbas.is_empty() {
+            format!("pass (supernet ABBA {})", supernet_abbas.join(", "))
+        } else {
+            String::from("fail (no supernet ABBA)")
+        };
+        let aba_bab_pairs = addr.aba_bab_pairs();
+        let ssl = if aba_bab_pairs.is_empty() {
+            String::from("fail (no ABA/BAB pair)")
+        } else {
+            let pairs = aba_bab_pairs
+                .iter()
+                .map(|(aba, bab)| format!("{aba}/{bab}"))
+                .collect::<Vec<String>>();
+            format!("pass (ABA/BAB {})", pairs.join(", "))
+        };
+        explanation.push_str(&format!("{} | TLS: {tls} | SSL: {ssl}\n", addr.address()));
     }
-    // Check that none of the hypernet sequences contains an ABBA
-    for hypernet in hypernets {
-        if REGEX_ABBA.is_match(&hypernet).unwrap() {
-            return false;
-        }
-    }
-    true
-}
-
-/// Checks if the given "IPv7" address supports "SSL" (super-secret listening).
-fn check_ssl_support(ipv7_address: &str) -> bool {
-    let (supernets, hypernets) = extract_supernet_and_hypernet_sequences(ipv7_address);
-    // Find the possible BAB candidates
-    let mut bab_candidates: HashSet<String> = HashSet::new();
-    for supernet in supernets.iter() {
-        let supernet = supernet.chars().collect::<Vec<char>>();
-        for (i, c) in supernet.iter().enumerate().take(supernet.len() - 2) {
-            let c1 = supernet[i + 1];
-            let c2 = supernet[i + 2];
-            if *c == c2 && *c != c1 {
-                bab_candidates.insert(format!("{c1}{c}{c1}"));
-            }
-        }
-    }
-    // Check if any of the hypernets contain one of the BAB candidates
-    for hypernet in hypernets.iter() {
-        for bab in bab_candidates.iter() {
-            if hypernet.contains(bab) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
-/// Extracts the supernet and hypernet sequences from the given ipv7 address (assuming that there
-/// are no hypernet sequences nested within other hypernet sequences).
-fn extract_supernet_and_hypernet_sequences(ipv7_address: &str) -> (Vec<String>, Vec<String>) {
-    // Extract supernets
-    let supernets = REGEX_SUPERNET
-        .find_iter(ipv7_address)
-        .map(|cap| {
-            REGEX_SQUARE_BRACE
-                .replace_all(cap.unwrap().as_str(), "")
-                .to_string()
-        })
-        .collect::<Vec<String>>();
-    // Extract hypernets
-    let hypernets = REGEX_HYPERNET
-        .captures_iter(ipv7_address)
-        .map(|cap| cap.unwrap()[1].to_string())
-        .collect::<Vec<String>>();
-    (supernets, hypernets)
+    explanation
 }
 
 #[cfg(test)]
@@ -172,4 +145,25 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(231, solution);
     }
+
+    /// Tests the explanation of the TLS and SSL checks using examples from the problem
+    /// description.
+    #[test]
+    fn test_day07_explanation() {
+        let addresses = [
+            "abba[mnop]qrst",
+            "abcd[bddb]xyyx",
+            "aaaa[qwer]tyui",
+            "aba[bab]xyz",
+        ]
+        .iter()
+        .map(|addr| addr.parse::<Ipv7Address>().unwrap())
+        .collect::<Vec<Ipv7Address>>();
+        let expected = "\
+            abba[mnop]qrst | TLS: pass (supernet ABBA abba) | SSL: fail (no ABA/BAB pair)\n\
+            abcd[bddb]xyyx | TLS: fail (hypernet ABBA bddb) | SSL: fail (no ABA/BAB pair)\n\
+            aaaa[qwer]tyui | TLS: fail (no supernet ABBA) | SSL: fail (no ABA/BAB pair)\n\
+            aba[bab]xyz | TLS: fail (no supernet ABBA) | SSL: pass (ABA/BAB aba/bab)\n";
+        assert_eq!(expected, format_explanation(&addresses));
+    }
 }
@@ -1,8 +1,14 @@
 use std::collections::HashMap;
-use std::fs;
+use std::env;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufWriter;
 use std::time::Instant;
 
-use fancy_regex::Regex;
+use aoc2016::utils::animation;
+use aoc2016::utils::bespoke::{Screen, ScreenInstruction};
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::visualize::{self, Frame, FrameSink, Visualize};
 use itertools::iproduct;
 use lazy_static::lazy_static;
 
@@ -14,11 +20,18 @@ const SCREEN_WIDTH: usize = 50;
 const SCREEN_HEIGHT: usize = 6;
 const CHAR_WIDTH: usize = 5;
 
-lazy_static! {
-    static ref REGEX_RECT: Regex = Regex::new(r"^rect (\d+)x(\d+)$").unwrap();
-    static ref REGEX_ROTATE_ROW: Regex = Regex::new(r"^rotate row y=(\d+) by (\d+)$").unwrap();
-    static ref REGEX_ROTATE_COL: Regex = Regex::new(r"^rotate column x=(\d+) by (\d+)$").unwrap();
+/// Command line flag used to export the final screen state as an image to the file given after the
+/// flag. The image format (PNG or PPM) is determined by the file extension.
+const EXPORT_FLAG: &str = "--export";
+/// Default number of frames per second when animating the screen as each instruction is applied.
+const ANIMATION_FPS: u32 = 40;
+/// Number of image pixels drawn along each side of a screen pixel when exporting the screen.
+const EXPORT_PIXEL_SCALE: usize = 10;
+/// Command line flag used to load additional letter glyphs from the font file given after the flag,
+/// adding to (or replacing) the built-in glyphs when decoding the screen.
+const FONT_FLAG: &str = "--font";
 
+lazy_static! {
     /// Maps the binary representation of the screen characters (5px wide by 6px tall) to the
     /// corresponding character displayed on the screen.
     static ref SCREEN_CHARS: HashMap<u32, char> = HashMap::from([
@@ -51,162 +64,306 @@ lazy_static! {
     ]);
 }
 
-/// Represents a single instruction used to operate on the pixels of the screen.
-enum Instruction {
-    Rect { width: usize, height: usize },
-    RotateRow { row: usize, amount: usize },
-    RotateCol { col: usize, amount: usize },
-}
-
-impl Instruction {
-    /// Converts the given string into an Instruction. Returns None if the given string does not
-    /// match an expected format.
-    fn from_string(s: &str) -> Option<Instruction> {
-        if let Ok(Some(caps)) = REGEX_RECT.captures(s) {
-            let width = caps[1].parse::<usize>().unwrap();
-            let height = caps[2].parse::<usize>().unwrap();
-            return Some(Instruction::Rect { width, height });
-        } else if let Ok(Some(caps)) = REGEX_ROTATE_ROW.captures(s) {
-            let row = caps[1].parse::<usize>().unwrap();
-            let amount = caps[2].parse::<usize>().unwrap();
-            return Some(Instruction::RotateRow { row, amount });
-        } else if let Ok(Some(caps)) = REGEX_ROTATE_COL.captures(s) {
-            let col = caps[1].parse::<usize>().unwrap();
-            let amount = caps[2].parse::<usize>().unwrap();
-            return Some(Instruction::RotateCol { col, amount });
+/// Custom error type indicating that a font file of letter glyphs could not be parsed.
+#[derive(Debug, PartialEq, Eq)]
+enum FontFileError {
+    /// The first line of the glyph block is not a single character naming the letter
+    InvalidLetter { block: usize, text: String },
+    /// The glyph does not have one row for each row of the screen
+    WrongGlyphHeight { letter: char, rows: usize },
+    /// The glyph row is wider than a letter or contains a character other than '#' or '.'
+    InvalidGlyphRow { letter: char, row: usize },
+}
+
+impl fmt::Display for FontFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontFileError::InvalidLetter { block, text } => {
+                write!(
+                    f,
+                    "glyph {block}: expected a single letter, found \"{text}\""
+                )
+            }
+            FontFileError::WrongGlyphHeight { letter, rows } => write!(
+                f,
+                "glyph '{letter}': expected {SCREEN_HEIGHT} rows, found {rows}"
+            ),
+            FontFileError::InvalidGlyphRow { letter, row } => write!(
+                f,
+                "glyph '{letter}' row {row}: expected up to {CHAR_WIDTH} '#' or '.' characters"
+            ),
+        }
+    }
+}
+
+/// Letter glyph displayed on the screen that does not match any known letter.
+#[derive(Debug, PartialEq, Eq)]
+struct UnknownGlyph {
+    position: usize,
+    key: u32,
+}
+
+impl fmt::Display for UnknownGlyph {
+    /// Formats the glyph with its bit pattern, followed by its rows in the font file format.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "glyph {} (0x{:08X})", self.position, self.key)?;
+        for row in 0..SCREEN_HEIGHT {
+            let line = (0..CHAR_WIDTH)
+                .map(|col| {
+                    let power = CHAR_WIDTH * SCREEN_HEIGHT - 1 - (row * CHAR_WIDTH + col);
+                    if self.key & (1 << power) != 0 {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            write!(f, "\n{line}")?;
         }
-        None
+        Ok(())
     }
 }
 
 /// Processes the AOC 2016 Day 08 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let font = load_font();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, &font);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Render, animate or export the screen states if requested
+    let args = env::args().collect::<Vec<String>>();
+    let replay = ScreenReplay {
+        instructions: &input,
+    };
+    if visualize::is_requested() {
+        visualize::print_frames(&replay);
+    }
+    animation::run_if_requested(&replay, ANIMATION_FPS);
+    if let Some(i) = args.iter().position(|arg| arg == EXPORT_FLAG) {
+        let Some(filename) = args.get(i + 1) else {
+            panic!("Missing image file after {EXPORT_FLAG} flag!");
+        };
+        export_screen_image(&run_screen_instructions(&input), filename);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 08 input file in the format required by the solver functions.
 /// Returned value is vector of instructions given in the lines of the input file.
-fn process_input_file(filename: &str) -> Vec<Instruction> {
+fn process_input_file(filename: &str) -> Vec<ScreenInstruction> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
     raw_input
         .trim()
         .lines()
-        .filter_map(|line| Instruction::from_string(line.trim()))
-        .collect::<Vec<Instruction>>()
+        .filter_map(|line| ScreenInstruction::from_string(line.trim()))
+        .collect::<Vec<ScreenInstruction>>()
 }
 
-/// Solves AOC 2016 Day 08 Part 1 // Returns the number of pixels that are lit after processing the
-/// instructions for the 50px-by-6px screen starting with all pixels set to off.
-fn solve_part1(instructions: &[Instruction]) -> usize {
-    // Generate the initial screen and process the instructions
-    let mut screen: [[bool; SCREEN_WIDTH]; SCREEN_HEIGHT] = [[false; SCREEN_WIDTH]; SCREEN_HEIGHT];
-    process_screen_instructions(instructions, &mut screen);
-    // Count the number of pixels that are lit
-    screen
-        .iter()
-        .map(|row| row.iter().filter(|e| **e).count())
-        .sum()
+/// Loads the letter glyphs used to decode the screen: the built-in glyphs, plus the glyphs in the
+/// font file given after the font flag (if present).
+fn load_font() -> HashMap<u32, char> {
+    let mut font = SCREEN_CHARS.clone();
+    let args = env::args().collect::<Vec<String>>();
+    if let Some(i) = args.iter().position(|arg| arg == FONT_FLAG) {
+        let Some(filename) = args.get(i + 1) else {
+            panic!("Font file must be given after {FONT_FLAG} flag!");
+        };
+        let raw_font = match fs::read_to_string(filename) {
+            Ok(raw_font) => raw_font,
+            Err(e) => panic!("Failed to read font file! // {filename} // {e}"),
+        };
+        match parse_font(&raw_font) {
+            Ok(glyphs) => font.extend(glyphs),
+            Err(e) => panic!("Bad font file! // {filename} // {e}"),
+        }
+    }
+    font
 }
 
-/// Solves AOC 2016 Day 08 Part 2 // Determines the 10-letter sequence displayed on the 50px-by-6px
-/// screen after processing all of the instructions.
-fn solve_part2(instructions: &[Instruction]) -> String {
-    // Generate the initial screen and process the instructions
-    let mut screen: [[bool; SCREEN_WIDTH]; SCREEN_HEIGHT] = [[false; SCREEN_WIDTH]; SCREEN_HEIGHT];
-    process_screen_instructions(instructions, &mut screen);
-    // Decode the letter sequence displayed on the screen
-    decode_screen_letters(&screen)
-}
-
-/// Processes the instructions for the screen, updating the screen state by processing the
-/// instructions.
-fn process_screen_instructions(
-    instructions: &[Instruction],
-    screen: &mut [[bool; SCREEN_WIDTH]; SCREEN_HEIGHT],
-) {
-    for instruct in instructions.iter() {
-        match instruct {
-            Instruction::Rect { width, height } => {
-                for (y, x) in iproduct!(0..*height, 0..*width) {
-                    screen[y][x] = true;
-                }
-            }
-            Instruction::RotateRow { row, amount } => {
-                let mut row_buffer: [bool; SCREEN_WIDTH] = [false; SCREEN_WIDTH];
-                for (i, state) in screen[*row].iter().enumerate() {
-                    row_buffer[(i + amount) % SCREEN_WIDTH] = *state;
-                }
-                screen[*row] = row_buffer;
+/// Parses the letter glyphs from a font file. Glyphs are separated by blank lines, with each glyph
+/// given as the letter on its own line followed by one line for each row of the screen. Rows are
+/// drawn with '#' for lit pixels and '.' for unlit pixels, and rows narrower than a letter are
+/// padded with unlit pixels on the right. Returns hashmap mapping the binary representation of
+/// each glyph to its letter.
+fn parse_font(raw_font: &str) -> Result<HashMap<u32, char>, FontFileError> {
+    let mut font: HashMap<u32, char> = HashMap::new();
+    let lines = raw_font
+        .lines()
+        .map(|line| line.trim())
+        .collect::<Vec<&str>>();
+    for (block, glyph) in lines
+        .split(|line| line.is_empty())
+        .filter(|glyph| !glyph.is_empty())
+        .enumerate()
+    {
+        let mut chars = glyph[0].chars();
+        let (Some(letter), None) = (chars.next(), chars.next()) else {
+            return Err(FontFileError::InvalidLetter {
+                block: block + 1,
+                text: glyph[0].to_string(),
+            });
+        };
+        let rows = &glyph[1..];
+        if rows.len() != SCREEN_HEIGHT {
+            return Err(FontFileError::WrongGlyphHeight {
+                letter,
+                rows: rows.len(),
+            });
+        }
+        let mut key = 0;
+        for (row, line) in rows.iter().enumerate() {
+            if line.len() > CHAR_WIDTH || line.chars().any(|c| c != '#' && c != '.') {
+                return Err(FontFileError::InvalidGlyphRow {
+                    letter,
+                    row: row + 1,
+                });
             }
-            Instruction::RotateCol { col, amount } => {
-                let mut col_buffer: [bool; SCREEN_HEIGHT] = [false; SCREEN_HEIGHT];
-                for (i, row) in screen.iter().enumerate() {
-                    let state = row[*col];
-                    col_buffer[(i + amount) % SCREEN_HEIGHT] = state;
-                }
-                for (i, row) in screen.iter_mut().enumerate() {
-                    row[*col] = col_buffer[i];
+            for (col, c) in line.chars().enumerate() {
+                if c == '#' {
+                    key |= 1 << (CHAR_WIDTH * SCREEN_HEIGHT - 1 - (row * CHAR_WIDTH + col));
                 }
             }
         }
+        font.insert(key, letter);
+    }
+    Ok(font)
+}
+
+/// Solves AOC 2016 Day 08 Part 1 // Returns the number of pixels that are lit after processing the
+/// instructions for the 50px-by-6px screen starting with all pixels set to off.
+fn solve_part1(instructions: &[ScreenInstruction]) -> usize {
+    run_screen_instructions(instructions).lit_count()
+}
+
+/// Solves AOC 2016 Day 08 Part 2 // Determines the 10-letter sequence displayed on the 50px-by-6px
+/// screen after processing all of the instructions, using the given letter glyphs.
+fn solve_part2(instructions: &[ScreenInstruction], font: &HashMap<u32, char>) -> String {
+    match decode_screen_letters(&run_screen_instructions(instructions), font) {
+        Ok(letters) => letters,
+        Err(unknown_glyphs) => {
+            let glyphs = unknown_glyphs
+                .iter()
+                .map(|glyph| glyph.to_string())
+                .collect::<Vec<String>>();
+            panic!("Unknown glyphs on screen! //\n{}", glyphs.join("\n"));
+        }
+    }
+}
+
+/// Screen instructions applied one at a time, visualized as the screen after each instruction
+/// (starting from the initial screen with all pixels off).
+struct ScreenReplay<'a> {
+    instructions: &'a [ScreenInstruction],
+}
+
+impl Visualize for ScreenReplay<'_> {
+    fn visualize(&self, sink: &mut dyn FrameSink) {
+        let screens = Screen::replay(SCREEN_WIDTH, SCREEN_HEIGHT, self.instructions);
+        for (i, screen) in screens.enumerate() {
+            let caption = match i {
+                0 => String::from("initial screen"),
+                _ => self.instructions[i - 1].to_string(),
+            };
+            sink.frame(Frame::new(&caption, screen.to_string()));
+        }
+    }
+}
+
+/// Generates the initial 50px-by-6px screen (all pixels off) and processes the instructions.
+fn run_screen_instructions(instructions: &[ScreenInstruction]) -> Screen {
+    Screen::replay(SCREEN_WIDTH, SCREEN_HEIGHT, instructions)
+        .last()
+        .unwrap()
+}
+
+/// Writes the screen to the image file, using the PNG or PPM format depending on the file
+/// extension.
+fn export_screen_image(screen: &Screen, filename: &str) {
+    let result = if filename.ends_with(".png") {
+        File::create(filename).and_then(|f| screen.write_png(BufWriter::new(f), EXPORT_PIXEL_SCALE))
+    } else if filename.ends_with(".ppm") {
+        File::create(filename)
+            .and_then(|f| screen.write_ppm(&mut BufWriter::new(f), EXPORT_PIXEL_SCALE))
+    } else {
+        panic!("Image file must have a .png or .ppm extension! // {filename}");
+    };
+    if let Err(e) = result {
+        panic!("Failed to export screen image! // {filename} // {e}");
     }
 }
 
 /// Returns the letter sequence displayed by the screen by decoding the letters displayed by the
-/// letter pixel groups (5px wide and 6px tall).
-fn decode_screen_letters(screen: &[[bool; SCREEN_WIDTH]; SCREEN_HEIGHT]) -> String {
+/// letter pixel groups (5px wide and 6px tall) with the given letter glyphs. Returns the glyphs
+/// that do not match a known letter if any are found.
+fn decode_screen_letters(
+    screen: &Screen,
+    font: &HashMap<u32, char>,
+) -> Result<String, Vec<UnknownGlyph>> {
     let mut decoded = String::new();
-    for i in 0..(SCREEN_WIDTH / CHAR_WIDTH) {
+    let mut unknown_glyphs: Vec<UnknownGlyph> = vec![];
+    for i in 0..(screen.width() / CHAR_WIDTH) {
         let mut key = 0;
-        let mut power = (CHAR_WIDTH * SCREEN_HEIGHT) as u32;
-        for (y, x) in iproduct!(0..SCREEN_HEIGHT, (i * CHAR_WIDTH)..((i + 1) * CHAR_WIDTH)) {
+        let mut power = (CHAR_WIDTH * screen.height()) as u32;
+        for (y, x) in iproduct!(0..screen.height(), (i * CHAR_WIDTH)..((i + 1) * CHAR_WIDTH)) {
             power -= 1;
-            if screen[y][x] {
+            if screen.is_lit(x, y) {
                 key += u32::pow(2, power);
             }
         }
         // Get the letter displayed in the current window
-        if let Some(c) = SCREEN_CHARS.get(&key) {
-            decoded.push(*c);
-        } else {
-            decoded.push('#');
+        match font.get(&key) {
+            Some(c) => decoded.push(*c),
+            None => unknown_glyphs.push(UnknownGlyph { position: i, key }),
         }
     }
-    decoded
+    if unknown_glyphs.is_empty() {
+        Ok(decoded)
+    } else {
+        Err(unknown_glyphs)
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::visualize::FrameRecorder;
+
     use super::*;
 
     /// Tests the Day 08 Part 1 solver method against the actual problem solution.
@@ -221,7 +378,59 @@ mod test {
     #[test]
     fn test_day08_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, &SCREEN_CHARS);
         assert_eq!("AFBUPZBJPS", solution);
     }
+
+    /// Tests that the screen visualization has a frame for the initial screen and after each
+    /// instruction, ending with the final screen.
+    #[test]
+    fn test_day08_screen_replay_frames() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let mut recorder = FrameRecorder::default();
+        ScreenReplay {
+            instructions: &input,
+        }
+        .visualize(&mut recorder);
+        let frames = recorder.frames();
+        assert_eq!(input.len() + 1, frames.len());
+        assert_eq!("initial screen", frames[0].caption());
+        assert_eq!(input[0].to_string(), frames[1].caption());
+        assert_eq!(
+            run_screen_instructions(&input).to_string(),
+            frames.last().unwrap().text()
+        );
+    }
+
+    /// Tests decoding the screen with a glyph loaded from a font file, and the bit pattern reported
+    /// for a glyph that is not in the font.
+    #[test]
+    fn test_day08_font_file() {
+        let mut screen = Screen::new(10, SCREEN_HEIGHT);
+        screen.apply(&ScreenInstruction::Rect {
+            width: 1,
+            height: SCREEN_HEIGHT,
+        });
+        let font = parse_font("\nl\n#\n#\n#\n#\n#\n#\n\n").unwrap();
+        assert_eq!(HashMap::from([(0x21084210, 'l')]), font);
+        let Err(unknown_glyphs) = decode_screen_letters(&screen, &font) else {
+            panic!("Blank glyph should not be decoded!");
+        };
+        assert_eq!(
+            "glyph 1 (0x00000000)\n.....\n.....\n.....\n.....\n.....\n.....",
+            unknown_glyphs[0].to_string()
+        );
+        let font = parse_font("l\n#\n#\n#\n#\n#\n#\n\n_\n.\n.\n.\n.\n.\n.").unwrap();
+        assert_eq!(
+            Ok(String::from("l_")),
+            decode_screen_letters(&screen, &font)
+        );
+        assert_eq!(
+            Err(FontFileError::InvalidGlyphRow {
+                letter: 'l',
+                row: 2
+            }),
+            parse_font("l\n#\n#?\n#\n#\n#\n#\n")
+        );
+    }
 }
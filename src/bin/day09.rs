@@ -1,20 +1,28 @@
+use std::env;
 use std::fs;
+use std::io::{self, BufWriter, Write};
 use std::time::Instant;
 
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
+use aoc2016::utils::bespoke::{Decompressor, FormatVersion};
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Explosives in Cyberspace";
 const PROBLEM_INPUT_FILE: &str = "./input/day09.txt";
 const PROBLEM_DAY: u64 = 9;
 
-lazy_static! {
-    static ref REGEX_MARKER: Regex = Regex::new(r"\((\d+)x(\d+)\)").unwrap();
-}
+/// Command line flag used to write the decompressed input to stdout, using the format version
+/// ("v1" or "v2") given after the flag.
+const DECOMPRESS_FLAG: &str = "--decompress";
+/// Command line flag used to set the maximum number of bytes written when decompressing the input.
+const OUTPUT_LIMIT_FLAG: &str = "--output-limit";
+/// Default maximum number of bytes written when decompressing the input.
+const DEFAULT_OUTPUT_LIMIT: u64 = 100_000_000;
 
 /// Processes the AOC 2016 Day 09 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -24,25 +32,50 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Write the decompressed input if requested
+    let args = env::args().collect::<Vec<String>>();
+    if let Some(i) = args.iter().position(|arg| arg == DECOMPRESS_FLAG) {
+        let version = match args.get(i + 1).map(|arg| arg.as_str()) {
+            Some("v1") => FormatVersion::V1,
+            Some("v2") => FormatVersion::V2,
+            _ => panic!("Format version must be given after {DECOMPRESS_FLAG} flag (v1 or v2)!"),
+        };
+        let output_limit = match args.iter().position(|arg| arg == OUTPUT_LIMIT_FLAG) {
+            Some(j) => match args.get(j + 1).map(|arg| arg.parse::<u64>()) {
+                Some(Ok(limit)) => limit,
+                _ => panic!("Invalid output limit given after {OUTPUT_LIMIT_FLAG} flag!"),
+            },
+            None => DEFAULT_OUTPUT_LIMIT,
+        };
+        write_decompressed_input(&input, version, output_limit);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 09 input file in the format required by the solver functions.
@@ -56,61 +89,34 @@ fn process_input_file(filename: &str) -> String {
 
 /// Solves AOC 2016 Day 09 Part 1 // Determines the decompressed length of the input string, where
 /// nested marker sequences are not decompressed.
-fn solve_part1(input: &str) -> usize {
-    calculate_decompressed_length(input, false)
+fn solve_part1(input: &str) -> u64 {
+    calculate_decompressed_length(input, FormatVersion::V1)
 }
 
 /// Solves AOC 2016 Day 09 Part 2 // Determines the decompressed length of the input string, where
 /// nested marker sequences are decompressed (version two decompression).
-fn solve_part2(input: &str) -> usize {
-    calculate_decompressed_length(input, true)
+fn solve_part2(input: &str) -> u64 {
+    calculate_decompressed_length(input, FormatVersion::V2)
 }
 
-/// Calculates the decompressed length of the given string, using the length and number of repeats
-/// in marker sequences. Nested marker sequences are not decompressed unless the v2_decompression
-/// parameter is set to true.
-fn calculate_decompressed_length(s: &str, v2_decompression: bool) -> usize {
-    let mut decompressed_length = 0;
-    let mut index = 0;
-    let chars = s.chars().collect::<Vec<char>>();
-    while index < chars.len() {
-        // Look for index at start of marker sequence
-        if chars[index] != '(' {
-            index += 1;
-            decompressed_length += 1;
-            continue;
-        }
-        // Look for end of marker sequence
-        let mut index_la = index + 1;
-        while index_la < chars.len() {
-            if chars[index_la] == ')' {
-                break;
-            }
-            index_la += 1;
-        }
-        // Extract sequence length and number of repeats from the marker
-        let marker = chars[index..index_la + 1].iter().collect::<String>();
-        let (length, repeats) = if let Ok(Some(caps)) = REGEX_MARKER.captures(&marker) {
-            let length = caps[1].parse::<usize>().unwrap();
-            let repeats = caps[2].parse::<usize>().unwrap();
-            (length, repeats)
-        } else {
-            panic!("Bad marker format!");
-        };
-        // Calculate the decompressed length of the marker sequence
-        if !v2_decompression {
-            decompressed_length += length * repeats;
-        } else {
-            let sub_s = chars[index_la + 1..index_la + 1 + length]
-                .iter()
-                .collect::<String>();
-            let length = calculate_decompressed_length(&sub_s, v2_decompression);
-            decompressed_length += length * repeats;
-        }
-        // Update index position to next character after marker sequence
-        index = index_la + 1 + length;
+/// Calculates the decompressed length of the input string using the given format version. Panics
+/// if the input string contains a malformed marker.
+fn calculate_decompressed_length(input: &str, version: FormatVersion) -> u64 {
+    match Decompressor::new(version).decompressed_length(input) {
+        Ok(length) => length,
+        Err(e) => panic!("Bad compressed data in input file! // {e}"),
+    }
+}
+
+/// Writes the input string decompressed with the given format version to stdout, followed by a
+/// newline. Panics if the decompressed output would exceed the output limit.
+fn write_decompressed_input(input: &str, version: FormatVersion, output_limit: u64) {
+    let decompressor = Decompressor::new(version).with_output_limit(output_limit);
+    let mut writer = BufWriter::new(io::stdout().lock());
+    if let Err(e) = decompressor.decompress_to(input, &mut writer) {
+        panic!("Failed to decompress input! // {e}");
     }
-    decompressed_length
+    writeln!(writer).unwrap();
 }
 
 #[cfg(test)]
@@ -1,19 +1,40 @@
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::env;
 use std::fs;
 use std::time::Instant;
 
 use fancy_regex::Regex;
 
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "Balance Bots";
 const PROBLEM_INPUT_FILE: &str = "./input/day10.txt";
 const PROBLEM_DAY: u64 = 10;
 
-/// Represents a different part of the two-part solution.
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum SolutionPart {
-    PartOne,
-    PartTwo,
+/// Value of the low microchip compared by the bot to be found in Part 1 of the problem.
+const DEFAULT_TARGET_LOW: u64 = 17;
+/// Value of the high microchip compared by the bot to be found in Part 1 of the problem.
+const DEFAULT_TARGET_HIGH: u64 = 61;
+/// Command line flag used to override the value of the low target microchip.
+const TARGET_LOW_FLAG: &str = "--target-low";
+/// Command line flag used to override the value of the high target microchip.
+const TARGET_HIGH_FLAG: &str = "--target-high";
+
+/// Represents the pair of microchip values compared by the bot to be found in Part 1.
+#[derive(Copy, Clone)]
+struct TargetChips {
+    low: u64,
+    high: u64,
+}
+
+impl Default for TargetChips {
+    fn default() -> Self {
+        TargetChips {
+            low: DEFAULT_TARGET_LOW,
+            high: DEFAULT_TARGET_HIGH,
+        }
+    }
 }
 
 /// Represents a single entity that can receive microchips.
@@ -43,6 +64,21 @@ struct Instruction {
     high_id: u64,
 }
 
+/// Represents a single transfer of the low-value and high-value microchips held by a bot.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Transfer {
+    bot_id: u64,
+    low_value: u64,
+    high_value: u64,
+}
+
+/// Represents the complete record of a bot simulation: every transfer made by the bots (in the
+/// order they were made) and the microchips held in each output bin once all bots have finished.
+struct TransferLog {
+    transfers: Vec<Transfer>,
+    output_held: HashMap<u64, Vec<u64>>,
+}
+
 type ProblemInput = (
     HashMap<u64, Instruction>,
     HashMap<u64, Vec<u64>>,
@@ -52,34 +88,45 @@ type ProblemInput = (
 /// Processes the AOC 2016 Day 10 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let target = read_target_chips();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, target);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 10 input file in the format required by the solver functions.
@@ -89,6 +136,39 @@ fn process_input_file(filename: &str) -> ProblemInput {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
+    parse_bot_instructions(&raw_input)
+}
+
+/// Reads the target microchip values from the command line, using the default values for any not
+/// given. Panics if a value is invalid or the low value is not less than the high value.
+fn read_target_chips() -> TargetChips {
+    let mut target = TargetChips::default();
+    let args = env::args().collect::<Vec<String>>();
+    for (flag, value) in args.iter().zip(args.iter().skip(1)) {
+        if flag != TARGET_LOW_FLAG && flag != TARGET_HIGH_FLAG {
+            continue;
+        }
+        let Ok(value) = value.parse::<u64>() else {
+            panic!("Invalid microchip value given after {flag} flag! // {value}");
+        };
+        if flag == TARGET_LOW_FLAG {
+            target.low = value;
+        } else {
+            target.high = value;
+        }
+    }
+    if target.low >= target.high {
+        panic!(
+            "Low target microchip must be less than high target microchip! // {} {}",
+            target.low, target.high
+        );
+    }
+    target
+}
+
+/// Parses the value and bot instruction lines into the robot IDs mapped to instructions, initial
+/// state of robots and initial state of output bins.
+fn parse_bot_instructions(raw_input: &str) -> ProblemInput {
     let regex_bot =
         Regex::new(r"^bot (\d+) gives low to (bot|output) (\d+) and high to (bot|output) (\d+)$")
             .unwrap();
@@ -151,73 +231,86 @@ fn process_input_file(filename: &str) -> ProblemInput {
 }
 
 /// Solves AOC 2016 Day 10 Part 1 // Find the ID of the bot that is responsible for comparing
-/// value-17 microchips to value-17 microchips.
-fn solve_part1(input: &ProblemInput) -> u64 {
-    process_bot_instructions(input, SolutionPart::PartOne)
+/// the target microchips (value-61 microchips to value-17 microchips by default).
+fn solve_part1(input: &ProblemInput, target: TargetChips) -> u64 {
+    let log = simulate_bots(input);
+    match find_comparing_bot(&log, target) {
+        Some(bot_id) => bot_id,
+        None => panic!(
+            "No bot compares the target microchips! // {} {}",
+            target.low, target.high
+        ),
+    }
 }
 
 /// Solves AOC 2016 Day 10 Part 2 // Find the product of the values held in outputs 0, 1 and 2 when
 /// each contains one microchip.
 fn solve_part2(input: &ProblemInput) -> u64 {
-    process_bot_instructions(input, SolutionPart::PartTwo)
-}
-
-/// Processes the robot instructions based on the start state and returns the result required by the
-/// solution part.
-fn process_bot_instructions(input: &ProblemInput, solution_part: SolutionPart) -> u64 {
-    let bot_instructions = input.0.clone();
-    let mut bot_held = input.1.clone();
-    let mut output_held = input.2.clone();
-    loop {
-        let mut check_outputs = false;
-        for (id, instr) in bot_instructions.iter() {
-            // Check for Part Two solution
-            if solution_part == SolutionPart::PartTwo && check_outputs {
-                let output0 = output_held.get(&0).unwrap();
-                let output1 = output_held.get(&1).unwrap();
-                let output2 = output_held.get(&2).unwrap();
-                if output0.len() == 1 && output1.len() == 1 && output2.len() == 1 {
-                    return output0[0] * output1[0] * output2[0];
-                }
-                check_outputs = false;
-            }
-            if bot_held.get(id).unwrap().len() < 2 {
-                continue;
-            }
-            // Get the low and high microchip values held by the bot
-            let (bot_low, bot_high) = {
-                let bot_values = bot_held.get_mut(id).unwrap();
-                (bot_values[0], bot_values[1])
-            };
-            // Check for Part One solution
-            if solution_part == SolutionPart::PartOne && bot_low == 17 && bot_high == 61 {
-                return *id;
-            }
-            // Allocate the bot low-value and high-value microchips to a bot or output
-            match instr.low_target {
-                Entity::Output => {
-                    output_held.get_mut(&instr.low_id).unwrap().push(bot_low);
-                    check_outputs = true;
-                }
-                Entity::Robot => {
-                    bot_held.get_mut(&instr.low_id).unwrap().push(bot_low);
-                    bot_held.get_mut(&instr.low_id).unwrap().sort();
-                }
-            }
-            match instr.high_target {
-                Entity::Output => {
-                    output_held.get_mut(&instr.high_id).unwrap().push(bot_high);
-                    check_outputs = true;
-                }
+    let log = simulate_bots(input);
+    (0..3)
+        .map(
+            |id| match log.output_held.get(&id).map(|chips| chips.as_slice()) {
+                Some([value]) => *value,
+                _ => panic!("Output does not contain exactly one microchip! // {id}"),
+            },
+        )
+        .product()
+}
+
+/// Finds the ID of the first bot in the transfer log that compared the target microchips.
+fn find_comparing_bot(log: &TransferLog, target: TargetChips) -> Option<u64> {
+    log.transfers
+        .iter()
+        .find(|transfer| transfer.low_value == target.low && transfer.high_value == target.high)
+        .map(|transfer| transfer.bot_id)
+}
+
+/// Simulates the bots from the start state until no bot holds two microchips. Bots are processed
+/// from a work queue as soon as they hold two microchips, so each transfer happens exactly once.
+fn simulate_bots(input: &ProblemInput) -> TransferLog {
+    let (bot_instructions, bot_held, output_held) = input;
+    let mut bot_held = bot_held.clone();
+    let mut output_held = output_held.clone();
+    let mut transfers: Vec<Transfer> = vec![];
+    let mut ready_bots = bot_held
+        .iter()
+        .filter(|(_, values)| values.len() == 2)
+        .map(|(id, _)| *id)
+        .collect::<VecDeque<u64>>();
+    while let Some(bot_id) = ready_bots.pop_front() {
+        let Some(instr) = bot_instructions.get(&bot_id) else {
+            panic!("Bot holding two microchips has no instruction! // {bot_id}");
+        };
+        // Take the low and high microchip values held by the bot
+        let values = bot_held.insert(bot_id, vec![]).unwrap();
+        let low_value = values[0].min(values[1]);
+        let high_value = values[0].max(values[1]);
+        transfers.push(Transfer {
+            bot_id,
+            low_value,
+            high_value,
+        });
+        // Allocate the bot low-value and high-value microchips to a bot or output
+        for (target, target_id, value) in [
+            (instr.low_target, instr.low_id, low_value),
+            (instr.high_target, instr.high_id, high_value),
+        ] {
+            match target {
+                Entity::Output => output_held.get_mut(&target_id).unwrap().push(value),
                 Entity::Robot => {
-                    bot_held.get_mut(&instr.high_id).unwrap().push(bot_high);
-                    bot_held.get_mut(&instr.high_id).unwrap().sort();
+                    let target_held = bot_held.get_mut(&target_id).unwrap();
+                    target_held.push(value);
+                    if target_held.len() == 2 {
+                        ready_bots.push_back(target_id);
+                    }
                 }
             }
-            // Remove the microchips from the current bot and record the instruction for removal
-            bot_held.insert(*id, vec![]);
         }
     }
+    TransferLog {
+        transfers,
+        output_held,
+    }
 }
 
 #[cfg(test)]
@@ -228,7 +321,7 @@ mod test {
     #[test]
     fn test_day10_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, TargetChips::default());
         assert_eq!(98, solution);
     }
 
@@ -239,4 +332,32 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(4042, solution);
     }
+
+    /// Tests the transfer log of the bot simulation using the example from the problem description.
+    #[test]
+    fn test_day10_transfer_log() {
+        let input = parse_bot_instructions(
+            "value 5 goes to bot 2
+            bot 2 gives low to bot 1 and high to bot 0
+            value 3 goes to bot 1
+            bot 1 gives low to output 1 and high to bot 0
+            bot 0 gives low to output 2 and high to output 0
+            value 2 goes to bot 2",
+        );
+        let log = simulate_bots(&input);
+        let transfers = log
+            .transfers
+            .iter()
+            .map(|t| (t.bot_id, t.low_value, t.high_value))
+            .collect::<Vec<(u64, u64, u64)>>();
+        assert_eq!(vec![(2, 2, 5), (1, 2, 3), (0, 3, 5)], transfers);
+        assert_eq!(Some(&vec![5]), log.output_held.get(&0));
+        assert_eq!(Some(&vec![2]), log.output_held.get(&1));
+        assert_eq!(Some(&vec![3]), log.output_held.get(&2));
+        assert_eq!(2, solve_part1(&input, TargetChips { low: 2, high: 5 }));
+        assert_eq!(
+            None,
+            find_comparing_bot(&log, TargetChips { low: 2, high: 4 })
+        );
+    }
 }
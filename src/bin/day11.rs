@@ -1,82 +1,88 @@
-use std::collections::hash_map::DefaultHasher;
-use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::collections::BTreeSet;
+use std::env;
 use std::fs;
-use std::hash::{Hash, Hasher};
 use std::time::Instant;
 
-use fancy_regex::Regex;
-use itertools::Itertools;
+use aoc2016::utils::bespoke::{
+    parse_facility, Component, ComponentType, FacilitySearch, FacilityState,
+};
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::metrics;
+use aoc2016::utils::search::{self, SearchOutcome, SearchStrategy};
 
 const PROBLEM_NAME: &str = "Radioisotope Thermoelectric Generators";
 const PROBLEM_INPUT_FILE: &str = "./input/day11.txt";
 const PROBLEM_DAY: u64 = 11;
 
-/// Represents the two different types of Components found within the "Radioisotope Testing
-/// Facility".
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-enum ComponentType {
-    Generator,
-    Microchip,
-}
-
-/// Represents an individual Component found within the "Radioisotope Testing Facility".
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Component {
-    comp_type: ComponentType,
-    name: String,
-}
-
-impl Component {
-    pub fn new(comp_type: ComponentType, name: &str) -> Component {
-        Component {
-            comp_type,
-            name: name.to_string(),
-        }
-    }
-}
-
-/// Represents the current state of the "Radioisotope Testing Facility".
-#[derive(Clone, Hash)]
-struct FacilityState {
-    /// Number of moves taken so far
-    moves: usize,
-    /// Current floor of the elevator
-    elev_floor: usize,
-    /// State of the floor comps - floors need to be in sorted order to enable stable hashing
-    floor_comps: Vec<BTreeSet<Component>>,
-}
+/// Command line flag used to print the moves making up the minimal solution for each part.
+const SHOW_PATH_FLAG: &str = "--show-path";
+/// Command line flag used to print the number of states expanded by each search strategy.
+const COMPARE_SEARCH_FLAG: &str = "--compare-search";
 
 /// Processes the AOC 2016 Day 11 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let strategy = SearchStrategy::from_args();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, strategy);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, strategy);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the moves making up the minimal solutions if requested
+    if env::args().any(|arg| arg == SHOW_PATH_FLAG) {
+        let part2_input = add_part2_components(&input);
+        for (part, floor_comps) in [(1, &input), (2, &part2_input)] {
+            let outcome = calculate_minimum_moves_to_top_floor(floor_comps, strategy).unwrap();
+            println!("[Part {part}] Minimal solution:");
+            print!("{}", format_move_sequence(outcome.path()));
+        }
+    }
+    // Compare the search strategies if requested
+    if env::args().any(|arg| arg == COMPARE_SEARCH_FLAG) {
+        print_search_comparison(&input);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 11 input file in the format required by the solver functions.
@@ -86,222 +92,104 @@ fn process_input_file(filename: &str) -> Vec<BTreeSet<Component>> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    let mut floor_comps: Vec<BTreeSet<Component>> = vec![];
-    let regex_generator = Regex::new(r"([a-z]+) generator").unwrap();
-    let regex_microchip = Regex::new(r"([a-z]+)-compatible microchip").unwrap();
-    for line in raw_input.lines() {
-        // Ignore empty lines from input
-        let line = line.trim();
-        if line.is_empty() {
-            continue;
-        }
-        let mut floor: BTreeSet<Component> = BTreeSet::new();
-        // Find generators
-        for caps in regex_generator.captures_iter(line) {
-            let generator = &caps.unwrap()[1];
-            floor.insert(Component::new(ComponentType::Generator, generator));
-        }
-        // Find microchips
-        for caps in regex_microchip.captures_iter(line) {
-            let microchip = &caps.unwrap()[1];
-            floor.insert(Component::new(ComponentType::Microchip, microchip));
-        }
-        // Add floor to output
-        floor_comps.push(floor);
+    match parse_facility(&raw_input) {
+        Ok(floor_comps) => floor_comps,
+        Err(e) => panic!("Invalid facility description in input file! // {e}"),
     }
-    floor_comps
 }
 
 /// Solves AOC 2016 Day 11 Part 1 // Calculates the minimum number of moves required to move all
 /// the given Components to the top floor.
-fn solve_part1(floor_comps: &[BTreeSet<Component>]) -> usize {
-    calculate_minimum_moves_to_top_floor(floor_comps).unwrap()
+fn solve_part1(floor_comps: &[BTreeSet<Component>], strategy: SearchStrategy) -> usize {
+    calculate_minimum_moves_to_top_floor(floor_comps, strategy)
+        .unwrap()
+        .moves()
 }
 
 /// Solves AOC 2016 Day 11 Part 2 // Calculates the minimum number of moves required to move the
 /// given Components and the additional components (elerium and dilithium generator-microchip pairs
 /// starting on the first floor) to the top floor.
-fn solve_part2(floor_comps: &[BTreeSet<Component>]) -> usize {
-    // Add the additional components to the first floor
+fn solve_part2(floor_comps: &[BTreeSet<Component>], strategy: SearchStrategy) -> usize {
+    calculate_minimum_moves_to_top_floor(&add_part2_components(floor_comps), strategy)
+        .unwrap()
+        .moves()
+}
+
+/// Adds the additional components for Part 2 (elerium and dilithium generator-microchip pairs) to
+/// the first floor.
+fn add_part2_components(floor_comps: &[BTreeSet<Component>]) -> Vec<BTreeSet<Component>> {
     let mut floor_comps = floor_comps.to_owned();
     floor_comps[0].insert(Component::new(ComponentType::Generator, "elerium"));
     floor_comps[0].insert(Component::new(ComponentType::Microchip, "elerium"));
     floor_comps[0].insert(Component::new(ComponentType::Generator, "dilithium"));
     floor_comps[0].insert(Component::new(ComponentType::Microchip, "dilithium"));
-    // Calculate the minimum number of moves needed to move all items to the top floor
-    calculate_minimum_moves_to_top_floor(&floor_comps).unwrap()
-}
-
-/// Determines the minimum number of moves required to move all Components to the top floor.
-fn calculate_minimum_moves_to_top_floor(floor_comps: &[BTreeSet<Component>]) -> Option<usize> {
-    // Create the initial state of the facility
-    let initial_state = FacilityState {
-        moves: 0,
-        elev_floor: 0,
-        floor_comps: floor_comps.to_owned(),
-    };
-    // Enqueue the initial state and record the initial state as observed
-    let mut state_queue: VecDeque<FacilityState> = VecDeque::from([initial_state.clone()]);
-    let mut observed_states: HashSet<u64> =
-        HashSet::from([calculate_facility_state_hash(&initial_state)]);
-    while !state_queue.is_empty() {
-        let state = state_queue.pop_front().unwrap();
-        // Check if all components have been moved to the top floor
-        if state.elev_floor == floor_comps.len() - 1
-            && check_if_all_components_at_top_floor(&state.floor_comps)
-        {
-            return Some(state.moves);
-        }
-        // Find the possible next states and enqueue any states not already seen
-        for next_state in get_next_states(&state) {
-            let next_state_hash = calculate_facility_state_hash(&next_state);
-            if !observed_states.contains(&next_state_hash) {
-                observed_states.insert(next_state_hash);
-                state_queue.push_back(next_state);
-            }
-        }
-    }
-    None
+    floor_comps
 }
 
-/// Determines the next possible states from the given facility state.
-fn get_next_states(state: &FacilityState) -> Vec<FacilityState> {
-    let mut next_states: Vec<FacilityState> = vec![];
-    let move_options = itertools::chain(
-        state.floor_comps[state.elev_floor].iter().combinations(2),
-        state.floor_comps[state.elev_floor].iter().combinations(1),
-    );
-    let mut next_states_two_up: Vec<FacilityState> = vec![];
-    let mut next_states_one_up: Vec<FacilityState> = vec![];
-    let mut next_states_two_down: Vec<FacilityState> = vec![];
-    let mut next_states_one_down: Vec<FacilityState> = vec![];
-    for comps in move_options {
-        for floor_delta in [1, -1] {
-            // Skip move if at top or bottom floor and no floor to move to
-            if state.elev_floor == 0 && floor_delta == -1
-                || state.elev_floor == state.floor_comps.len() - 1 && floor_delta == 1
-            {
-                continue;
-            }
-            // Don't move one component up if two components can be moved up
-            if floor_delta == 1 && !next_states_two_up.is_empty() && comps.len() == 1 {
-                continue;
-            }
-            // Don't move two components down if one component can be moved down
-            if floor_delta == -1 && !next_states_one_down.is_empty() && comps.len() == 2 {
-                continue;
-            }
-            // Don't move down if all floors below are empty
-            if floor_delta == -1 {
-                let mut skip = true;
-                for floor in state.floor_comps.iter().take(state.elev_floor) {
-                    if !floor.is_empty() {
-                        skip = false;
-                        break;
-                    }
-                }
-                if skip {
-                    continue;
-                }
-            }
-            // Modify next floor
-            let mut next_state = state.clone();
-            next_state.elev_floor = (state.elev_floor as i64 + floor_delta) as usize;
-            next_state.moves += 1;
-            for comp in comps.iter() {
-                next_state.floor_comps[state.elev_floor].remove(comp);
-                next_state.floor_comps[next_state.elev_floor].insert((*comp).clone());
-            }
-            // Validate affected floors
-            if !validate_floor(&next_state.floor_comps[state.elev_floor])
-                || !validate_floor(&next_state.floor_comps[next_state.elev_floor])
-            {
-                continue;
-            }
-            // We have now found a valid next state
-            match floor_delta {
-                1 => match comps.len() {
-                    1 => next_states_one_up.push(next_state),
-                    2 => {
-                        next_states_one_up = vec![];
-                        next_states_two_up.push(next_state);
-                    }
-                    _ => (),
-                },
-                -1 => match comps.len() {
-                    1 => {
-                        next_states_two_down = vec![];
-                        next_states_one_down.push(next_state);
-                    }
-                    2 => next_states_two_down.push(next_state),
-                    _ => (),
-                },
-                _ => (),
-            }
-        }
-    }
-    // Combine the valid next states into single collection
-    next_states.append(&mut next_states_two_up);
-    next_states.append(&mut next_states_two_down);
-    next_states.append(&mut next_states_one_up);
-    next_states.append(&mut next_states_one_down);
-    next_states
+/// Determines the minimum number of moves required to move all Components to the top floor, using
+/// the given search strategy.
+fn calculate_minimum_moves_to_top_floor(
+    floor_comps: &[BTreeSet<Component>],
+    strategy: SearchStrategy,
+) -> Option<SearchOutcome<FacilityState>> {
+    let problem = FacilitySearch::new(floor_comps.to_owned());
+    search::search_explained(&problem, strategy)
 }
 
-/// Checks if the given floor represents a valid state. A floor is invalid if it contains a
-/// microchip without its matching generator in the presence of a mismatched generator
-fn validate_floor(floor: &BTreeSet<Component>) -> bool {
-    // Extract the names of the generators and microchips
-    let generators = floor
-        .iter()
-        .filter(|comp| comp.comp_type == ComponentType::Generator)
-        .map(|comp| &comp.name)
-        .collect::<HashSet<&String>>();
-    let microchips = floor
-        .iter()
-        .filter(|comp| comp.comp_type == ComponentType::Microchip)
-        .map(|comp| &comp.name)
-        .collect::<HashSet<&String>>();
-    // Valid if there is only one type of Component
-    if generators.is_empty() || microchips.is_empty() {
-        return true;
-    }
-    // Invalid if microchip is in the presence of a mismatched generator
-    for chip in microchips {
-        if !generators.contains(&chip) {
-            return false;
-        }
+/// Formats the moves between consecutive facility states as a numbered list, giving the components
+/// carried in the elevator and the floors (starting at 1) it moves between.
+fn format_move_sequence(path: &[FacilityState]) -> String {
+    let mut output = String::new();
+    for (i, (prev, next)) in path.iter().zip(path.iter().skip(1)).enumerate() {
+        let moved = prev.floor_comps()[prev.elev_floor()]
+            .difference(&next.floor_comps()[prev.elev_floor()])
+            .map(|comp| comp.to_string())
+            .collect::<Vec<String>>();
+        let direction = if next.elev_floor() > prev.elev_floor() {
+            "up"
+        } else {
+            "down"
+        };
+        output.push_str(&format!(
+            "{:>3}. take {} {direction} from floor {} to floor {}\n",
+            i + 1,
+            moved.join(" and "),
+            prev.elev_floor() + 1,
+            next.elev_floor() + 1
+        ));
     }
-    // Valid if all microchips have a matching generator
-    true
-}
-
-/// Calculates the hash of the given state of the floor components.
-fn calculate_facility_state_hash(state: &FacilityState) -> u64 {
-    let mut hasher = DefaultHasher::new();
-    state.hash(&mut hasher);
-    hasher.finish()
+    output
 }
 
-/// Checks if all components are at the top floor.
-fn check_if_all_components_at_top_floor(floor_items: &[BTreeSet<Component>]) -> bool {
-    for floor in floor_items.iter().take(floor_items.len() - 1) {
-        if !floor.is_empty() {
-            return false;
+/// Prints the minimum number of moves, number of states expanded and duration of each search
+/// strategy for both parts of the problem.
+fn print_search_comparison(floor_comps: &[BTreeSet<Component>]) {
+    let part2_floor_comps = add_part2_components(floor_comps);
+    for (part, comps) in [(1, floor_comps), (2, &part2_floor_comps)] {
+        for strategy in [SearchStrategy::BreadthFirst, SearchStrategy::AStar] {
+            let start = Instant::now();
+            let outcome = calculate_minimum_moves_to_top_floor(comps, strategy).unwrap();
+            let duration = Instant::now().duration_since(start);
+            println!(
+                "[Part {part}] {strategy:?}: {} moves, {} states expanded, {duration:.2?}",
+                outcome.moves(),
+                outcome.expanded()
+            );
         }
     }
-    true
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::bespoke::FacilityInputError;
+
     use super::*;
 
     /// Tests the Day 11 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day11_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, SearchStrategy::BreadthFirst);
         assert_eq!(47, solution);
     }
 
@@ -309,7 +197,73 @@ mod test {
     #[test]
     fn test_day11_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, SearchStrategy::BreadthFirst);
         assert_eq!(71, solution);
     }
+
+    /// Tests that A* search finds the same minimum number of moves as breadth-first search while
+    /// expanding fewer states.
+    #[test]
+    fn test_day11_a_star_search() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let bfs = calculate_minimum_moves_to_top_floor(&input, SearchStrategy::BreadthFirst);
+        let a_star = calculate_minimum_moves_to_top_floor(&input, SearchStrategy::AStar);
+        let (bfs, a_star) = (bfs.unwrap(), a_star.unwrap());
+        assert_eq!(47, a_star.moves());
+        assert!(a_star.expanded() < bfs.expanded());
+    }
+
+    /// Tests the solver with the example from the problem description, extended to a different
+    /// number of floors, and the errors returned for invalid facility descriptions.
+    #[test]
+    fn test_day11_facility_floors() {
+        let example = "\
+            The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.
+            The second floor contains a hydrogen generator.
+            The third floor contains a lithium generator.
+            The fourth floor contains nothing relevant.";
+        let floor_comps = parse_facility(example).unwrap();
+        assert_eq!(11, solve_part1(&floor_comps, SearchStrategy::AStar));
+        let outcome =
+            calculate_minimum_moves_to_top_floor(&floor_comps, SearchStrategy::BreadthFirst);
+        let moves = format_move_sequence(outcome.unwrap().path());
+        assert_eq!(11, moves.lines().count());
+        assert_eq!(
+            "  1. take hydrogen-compatible microchip up from floor 1 to floor 2",
+            moves.lines().next().unwrap()
+        );
+        let error = parse_facility(&example.replace("fourth", "fifth")).unwrap_err();
+        assert_eq!(
+            "line 4: expected fourth floor, found \"fifth\"",
+            error.to_string()
+        );
+        let five_floors = format!("{example}\nThe fifth floor contains nothing relevant.");
+        let floor_comps = parse_facility(&five_floors).unwrap();
+        assert_eq!(5, floor_comps.len());
+        assert_eq!(16, solve_part1(&floor_comps, SearchStrategy::BreadthFirst));
+        assert_eq!(16, solve_part1(&floor_comps, SearchStrategy::AStar));
+        let errors = [
+            ("", FacilityInputError::NoFloors),
+            (
+                "The first floor contains a cobalt-compatible microchip.",
+                FacilityInputError::UnpairedMicrochip {
+                    name: String::from("cobalt"),
+                },
+            ),
+            (
+                "The first floor contains a cobalt generator and a cobalt generator.",
+                FacilityInputError::DuplicateComponent {
+                    name: String::from("cobalt"),
+                },
+            ),
+            (
+                "The first floor contains a cobalt generator and a lithium-compatible microchip.\n\
+                The second floor contains a lithium generator and a cobalt-compatible microchip.",
+                FacilityInputError::UnsafeFloor { floor: 1 },
+            ),
+        ];
+        for (input, expected) in errors {
+            assert_eq!(Err(expected), parse_facility(input));
+        }
+    }
 }
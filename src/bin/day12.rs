@@ -1,15 +1,25 @@
 use std::fs;
 use std::time::Instant;
 
-use aoc2016::utils::bespoke::AssembunnyInterpreter;
+use aoc2016::utils::bespoke::{AssembunnyInterpreter, ExecutionBackend};
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Leonardo's Monorail";
 const PROBLEM_INPUT_FILE: &str = "./input/day12.txt";
 const PROBLEM_DAY: u64 = 12;
 
+/// Register holding the result once the program has halted.
+const RESULT_REGISTER: char = 'a';
+/// Register values seeded before running the program in Part 1.
+const PART1_SEEDS: [(char, isize); 0] = [];
+/// Register values seeded before running the program in Part 2.
+const PART2_SEEDS: [(char, isize); 1] = [('c', 1)];
+
 /// Processes the AOC 2016 Day 12 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -19,51 +29,59 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 12 input file in the format required by the solver functions.
-/// Returned value is Assembunny interpreter created from the instructions listed in the iput file.
+/// Returned value is Assembunny interpreter created from the instructions listed in the iput file,
+/// using the compiled execution backend.
 fn process_input_file(filename: &str) -> AssembunnyInterpreter {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    AssembunnyInterpreter::new(&raw_input).unwrap()
+    AssembunnyInterpreter::with_backend(&raw_input, ExecutionBackend::Compiled).unwrap()
 }
 
 /// Solves AOC 2016 Day 12 Part 1 // Returns the value held in register 'a' of the Assembunny
 /// interpreter after executing the program.
 fn solve_part1(interpreter: &AssembunnyInterpreter) -> isize {
-    let mut interpreter = interpreter.clone();
-    interpreter.execute().unwrap();
-    interpreter.get_register('a').unwrap()
+    interpreter
+        .run_seeded(&PART1_SEEDS, RESULT_REGISTER)
+        .unwrap()
 }
 
 /// Solves AOC 2016 Day 12 Part 2 // Returns the value held in register 'a' of the Assembunny
 /// interpreter after executing the program, with register 'c' initialised to 1.
 fn solve_part2(interpreter: &AssembunnyInterpreter) -> isize {
-    let mut interpreter = interpreter.clone();
-    interpreter.set_register('c', 1).unwrap();
-    interpreter.execute().unwrap();
-    interpreter.get_register('a').unwrap()
+    interpreter
+        .run_seeded(&PART2_SEEDS, RESULT_REGISTER)
+        .unwrap()
 }
 
 #[cfg(test)]
@@ -85,4 +103,20 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(9227657, solution);
     }
+
+    /// Tests that the compiled (optimised) and interpreted execution backends agree on the example
+    /// program from the problem description and on a program with an addition loop.
+    #[test]
+    fn test_day12_backends_agree() {
+        let programs = [
+            "cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a",
+            "cpy 5 b\ncpy 7 a\ninc a\ndec b\njnz b -2\ncpy 1 c",
+        ];
+        for (program, expected) in programs.iter().zip([42, 12]) {
+            for backend in [ExecutionBackend::Interpreted, ExecutionBackend::Compiled] {
+                let interpreter = AssembunnyInterpreter::with_backend(program, backend).unwrap();
+                assert_eq!(expected, solve_part1(&interpreter));
+            }
+        }
+    }
 }
@@ -4,6 +4,13 @@ use std::time::Instant;
 
 use lazy_static::lazy_static;
 
+use aoc2016::utils::animation;
+use aoc2016::utils::bespoke::{CubicleMaze, MazeSearch};
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::metrics;
+use aoc2016::utils::params::Params;
+use aoc2016::utils::search::{self, SearchStrategy};
+use aoc2016::utils::visualize::{self, Frame, FrameSink, Visualize};
 use aoc_utils::cartography::Point2D;
 
 const PROBLEM_NAME: &str = "A Maze of Twisty Little Cubicles";
@@ -12,42 +19,123 @@ const PROBLEM_DAY: u64 = 13;
 
 const PART2_TARGET_STEPS: usize = 50;
 
+/// Puzzle constants that can be overridden on the command line: the starting location and Part 1
+/// target location (both given as "x,y"), and the Part 2 step limit.
+const PARAM_KEYS: [&str; 3] = ["start", "target", "steps"];
+
+/// Number of extra rows and columns of the maze shown beyond the path when rendering the maze.
+const VIZ_MARGIN: i64 = 2;
+/// Default number of frames per second when animating the maze with the shortest path overlaid.
+const ANIMATION_FPS: u32 = 1;
+
 lazy_static! {
     static ref LOC_START: Point2D = Point2D::new(1, 1);
     static ref PART1_LOC_TARGET: Point2D = Point2D::new(31, 39);
 }
 
+/// Shortest path between two locations in the cubicle maze, visualized as the maze with the path
+/// overlaid.
+struct MazePath<'a> {
+    maze: &'a CubicleMaze,
+    path: Vec<Point2D>,
+}
+
+impl Visualize for MazePath<'_> {
+    fn visualize(&self, sink: &mut dyn FrameSink) {
+        let (Some(start), Some(target)) = (self.path.first(), self.path.last()) else {
+            return;
+        };
+        let caption = format!(
+            "shortest path from ({},{}) to ({},{}) in {} steps",
+            start.x(),
+            start.y(),
+            target.x(),
+            target.y(),
+            self.path.len() - 1
+        );
+        sink.frame(Frame::new(
+            &caption,
+            render_maze(self.maze, &self.path, VIZ_MARGIN),
+        ));
+    }
+}
+
 /// Processes the AOC 2016 Day 13 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let params = Params::from_args(&PARAM_KEYS);
+    let loc_start = params.get_with("start", *LOC_START, parse_location);
+    let loc_target = params.get_with("target", *PART1_LOC_TARGET, parse_location);
+    let target_steps = params.get("steps", PART2_TARGET_STEPS);
+    let strategy = SearchStrategy::from_args();
+    let maze = CubicleMaze::new(input);
+    for loc in [loc_start, loc_target] {
+        if maze.is_wall(&loc) {
+            panic!("Location is a wall in the maze! // {},{}", loc.x(), loc.y());
+        }
+    }
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let Some(p1_solution) = solve_part1(&input, &loc_start, &loc_target, strategy) else {
+        panic!(
+            "Target location cannot be reached from the start location! // {},{}",
+            loc_target.x(),
+            loc_target.y()
+        );
+    };
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, &loc_start, target_steps);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print, animate or export the maze with the shortest path overlaid if requested
+    if visualize::is_requested() || animation::is_requested() {
+        let path = find_shortest_path(&maze, &loc_start, &loc_target, strategy).unwrap();
+        let maze_path = MazePath { maze: &maze, path };
+        if visualize::is_requested() {
+            visualize::print_frames(&maze_path);
+        }
+        animation::run_if_requested(&maze_path, ANIMATION_FPS);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 13 input file in the format required by the solver functions.
@@ -59,114 +147,221 @@ fn process_input_file(filename: &str) -> i64 {
     raw_input.trim().parse::<i64>().unwrap()
 }
 
-/// Solves AOC 2016 Day 13 Part 1 // Determines the fewest number of steps required to reach (31,39)
-/// when starting at (1,1).
-fn solve_part1(seed: &i64) -> usize {
-    find_minimum_steps_to_target_location(*seed, &LOC_START, &PART1_LOC_TARGET).unwrap()
+/// Parses a location given as "x,y" with non-negative coordinates.
+fn parse_location(s: &str) -> Option<Point2D> {
+    let (x, y) = s.split_once(',')?;
+    let x = x.trim().parse::<i64>().ok()?;
+    let y = y.trim().parse::<i64>().ok()?;
+    if x < 0 || y < 0 {
+        return None;
+    }
+    Some(Point2D::new(x, y))
+}
+
+/// Solves AOC 2016 Day 13 Part 1 // Determines the fewest number of steps required to reach the
+/// target location (31,39 by default) when starting at the start location (1,1 by default). Returns
+/// None if the target location cannot be reached.
+fn solve_part1(
+    seed: &i64,
+    loc_start: &Point2D,
+    loc_target: &Point2D,
+    strategy: SearchStrategy,
+) -> Option<usize> {
+    let maze = CubicleMaze::new(*seed);
+    find_minimum_steps_to_target_location(&maze, loc_start, loc_target, strategy)
 }
 
 /// Solves AOC 2016 Day 13 Part 2 // Determines how many locations, including the starting location,
-/// can be reached in at most 50 steps.
-fn solve_part2(seed: &i64) -> usize {
-    find_reachable_locations_in_steps(*seed, &LOC_START, PART2_TARGET_STEPS).unwrap()
+/// can be reached in at most the given number of steps (50 by default).
+fn solve_part2(seed: &i64, loc_start: &Point2D, max_steps: usize) -> usize {
+    let maze = CubicleMaze::new(*seed);
+    find_reachable_locations_in_steps(&maze, loc_start, max_steps)
 }
 
 /// Finds the minimum number of steps to get from the starting location to the target location.
-///
-/// The seed value is used to dynamically determine if a particular location in the grid is a wall
-/// or open space.
+/// Returns None if the target location cannot be reached within the bounds of the maze search.
 fn find_minimum_steps_to_target_location(
-    seed: i64,
+    maze: &CubicleMaze,
     loc_start: &Point2D,
     loc_target: &Point2D,
+    strategy: SearchStrategy,
 ) -> Option<usize> {
-    let mut visit_queue: VecDeque<(Point2D, usize)> = VecDeque::from([(*loc_start, 0)]);
-    let mut visited: HashSet<Point2D> = HashSet::from([*loc_start]);
-    while !visit_queue.is_empty() {
-        // Check if the target location has been reached
-        let (loc, steps) = visit_queue.pop_front().unwrap();
-        if loc == *loc_target {
-            return Some(steps);
-        }
-        // Get the next locations to visit
-        for next_loc in get_next_locations(seed, &loc) {
-            if !visited.contains(&next_loc) {
-                visit_queue.push_back((next_loc, steps + 1));
-                visited.insert(next_loc);
-            }
+    find_shortest_path(maze, loc_start, loc_target, strategy).map(|path| path.len() - 1)
+}
+
+/// Finds the locations on a shortest path from the starting location to the target location,
+/// including both the starting and target locations.
+fn find_shortest_path(
+    maze: &CubicleMaze,
+    loc_start: &Point2D,
+    loc_target: &Point2D,
+    strategy: SearchStrategy,
+) -> Option<Vec<Point2D>> {
+    let problem = MazeSearch::new(maze, *loc_start, *loc_target);
+    search::search_explained(&problem, strategy).map(|outcome| outcome.path().to_vec())
+}
+
+/// Renders the area of the maze containing the path (plus the given margin of extra rows and
+/// columns) as text. Walls are shown as '#' and open space as '.', with the path overlaid as 'O'
+/// and the start and end of the path marked with 'S' and 'T'.
+fn render_maze(maze: &CubicleMaze, path: &[Point2D], margin: i64) -> String {
+    let max_x = path.iter().map(|loc| loc.x()).max().unwrap_or(0) + margin;
+    let max_y = path.iter().map(|loc| loc.y()).max().unwrap_or(0) + margin;
+    let path_locs = path.iter().copied().collect::<HashSet<Point2D>>();
+    let mut output = String::new();
+    for y in 0..=max_y {
+        for x in 0..=max_x {
+            let loc = Point2D::new(x, y);
+            let tile = if path.first() == Some(&loc) {
+                'S'
+            } else if path.last() == Some(&loc) {
+                'T'
+            } else if path_locs.contains(&loc) {
+                'O'
+            } else if maze.is_open(&loc) {
+                '.'
+            } else {
+                '#'
+            };
+            output.push(tile);
         }
+        output.push('\n');
     }
-    None
+    output
 }
 
-/// Finds the number of locations that can be reached in at most the target number of steps.
-///
-/// The seed value is used to dynamically determine if a particular location in the grid is a wall
-/// or open space.
+/// Finds the number of locations (including the starting location) that can be reached in at most
+/// the target number of steps. Locations are not expanded once the target number of steps is
+/// reached, so the count is also correct when the reachable area is enclosed by walls.
 fn find_reachable_locations_in_steps(
-    seed: i64,
+    maze: &CubicleMaze,
     loc_start: &Point2D,
     target_steps: usize,
-) -> Option<usize> {
+) -> usize {
     let mut visit_queue: VecDeque<(Point2D, usize)> = VecDeque::from([(*loc_start, 0)]);
     let mut visited: HashSet<Point2D> = HashSet::from([*loc_start]);
-    while !visit_queue.is_empty() {
-        // Check if the target steps have been exceeded
-        let (loc, steps) = visit_queue.pop_front().unwrap();
-        if steps > target_steps {
-            // Account for enqueued locations and the current location over target steps
-            return Some(visited.len() - visit_queue.len() - 1);
+    while let Some((loc, steps)) = visit_queue.pop_front() {
+        if steps == target_steps {
+            continue;
         }
         // Get the next locations to visit
-        for next_loc in get_next_locations(seed, &loc) {
-            if !visited.contains(&next_loc) {
+        for next_loc in maze.neighbours(&loc) {
+            if visited.insert(next_loc) {
                 visit_queue.push_back((next_loc, steps + 1));
-                visited.insert(next_loc);
             }
         }
     }
-    None
-}
-
-/// Gets the next locations that could be visited from the current location. Does not account for
-/// any points that have already been visited.
-fn get_next_locations(seed: i64, loc: &Point2D) -> Vec<Point2D> {
-    let mut next_locations: Vec<Point2D> = vec![];
-    for next_loc in loc.get_adjacent_points() {
-        if next_loc.x() < 0 || next_loc.y() < 0 {
-            continue;
-        }
-        if is_location_open(seed, &next_loc) {
-            next_locations.push(next_loc);
-        }
-    }
-    next_locations
-}
-
-/// Checks if the given location is open space. If not, it is a wall and cannot be visited.
-fn is_location_open(seed: i64, loc: &Point2D) -> bool {
-    let (x, y) = (loc.x(), loc.y());
-    let value = x * x + 3 * x + 2 * x * y + y + y * y + seed;
-    format!("{value:b}").chars().filter(|c| *c == '1').count() % 2 == 0
+    visited.len()
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::visualize::FrameRecorder;
+
     use super::*;
 
     /// Tests the Day 13 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day13_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
-        assert_eq!(90, solution);
+        let solution = solve_part1(
+            &input,
+            &LOC_START,
+            &PART1_LOC_TARGET,
+            SearchStrategy::BreadthFirst,
+        );
+        assert_eq!(Some(90), solution);
     }
 
     /// Tests the Day 13 Part 2 solver method against the actual problem solution.
     #[test]
     fn test_day13_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, &LOC_START, PART2_TARGET_STEPS);
         assert_eq!(135, solution);
     }
+
+    /// Tests the reachable location count using the example seed from the problem description,
+    /// including a step limit large enough to exhaust the reachable area.
+    #[test]
+    fn test_day13_reachable_locations() {
+        let maze = CubicleMaze::new(10);
+        assert_eq!(
+            Some(11),
+            find_minimum_steps_to_target_location(
+                &maze,
+                &LOC_START,
+                &Point2D::new(7, 4),
+                SearchStrategy::BreadthFirst
+            )
+        );
+        assert_eq!(1, find_reachable_locations_in_steps(&maze, &LOC_START, 0));
+        assert_eq!(3, find_reachable_locations_in_steps(&maze, &LOC_START, 1));
+        // Location (2, 0) is enclosed by walls on all sides within the grid
+        let enclosed = Point2D::new(2, 0);
+        assert_eq!(1, find_reachable_locations_in_steps(&maze, &enclosed, 100));
+        assert_eq!(
+            None,
+            solve_part1(&10, &LOC_START, &enclosed, SearchStrategy::AStar)
+        );
+    }
+
+    /// Tests the maze rendering using the example from the problem description.
+    #[test]
+    fn test_day13_render_maze() {
+        let maze = CubicleMaze::new(10);
+        let path = find_shortest_path(
+            &maze,
+            &LOC_START,
+            &Point2D::new(7, 4),
+            SearchStrategy::BreadthFirst,
+        )
+        .unwrap();
+        let expected = "\
+            .#.####.##\n\
+            .S#..#...#\n\
+            #OOO.##...\n\
+            ###O#.###.\n\
+            .##OO#OT#.\n\
+            ..##OOO.#.\n\
+            #...##.###\n\
+            .##..#.##.\n";
+        assert_eq!(expected, render_maze(&maze, &path, 2));
+        let mut recorder = FrameRecorder::default();
+        MazePath { maze: &maze, path }.visualize(&mut recorder);
+        assert_eq!(
+            "shortest path from (1,1) to (7,4) in 11 steps",
+            recorder.frames()[0].caption()
+        );
+    }
+
+    /// Tests that A* search finds paths of the same length as breadth-first search for the actual
+    /// problem input and for alternative start and target locations.
+    #[test]
+    fn test_day13_astar_matches_bfs() {
+        let maze = CubicleMaze::new(process_input_file(PROBLEM_INPUT_FILE));
+        let routes = [
+            (*LOC_START, *PART1_LOC_TARGET),
+            (Point2D::new(31, 39), Point2D::new(1, 1)),
+            (Point2D::new(1, 1), Point2D::new(17, 13)),
+        ];
+        for (loc_start, loc_target) in routes {
+            let bfs = find_minimum_steps_to_target_location(
+                &maze,
+                &loc_start,
+                &loc_target,
+                SearchStrategy::BreadthFirst,
+            );
+            let astar = find_minimum_steps_to_target_location(
+                &maze,
+                &loc_start,
+                &loc_target,
+                SearchStrategy::AStar,
+            );
+            assert!(bfs.is_some());
+            assert_eq!(bfs, astar);
+        }
+        assert_eq!(Some(Point2D::new(3, 7)), parse_location("3,7"));
+        assert_eq!(None, parse_location("-1,7"));
+    }
 }
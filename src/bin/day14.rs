@@ -1,65 +1,110 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::VecDeque;
 use std::fs;
 use std::time::Instant;
 
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::hashmining::{HashMiner, Md5};
+use aoc2016::utils::metrics;
+use aoc2016::utils::nibbles::{first_nibble_run, nibble_run_mask};
+use aoc2016::utils::params::Params;
 
 const PROBLEM_NAME: &str = "One-Time Pad";
 const PROBLEM_INPUT_FILE: &str = "./input/day14.txt";
 const PROBLEM_DAY: u64 = 14;
 
-/// We are looking for the 64th valid one-time pad key.
-const TARGET_OTP_ORD: usize = 64;
-const HASH_BUFFER_LEN: usize = 1000;
+/// Configuration used to find the one-time pad key for Part 1.
+const PART1_CONFIG: OtpKeyConfig = OtpKeyConfig {
+    nth_key: 64,
+    window_len: 1000,
+    stretch_rounds: 0,
+};
+/// Configuration used to find the one-time pad key for Part 2, with key stretching enabled.
+const PART2_CONFIG: OtpKeyConfig = OtpKeyConfig {
+    stretch_rounds: 2016,
+    ..PART1_CONFIG
+};
+/// Puzzle constants that can be overridden on the command line: the ordinal of the key to find and
+/// the window length (for both parts), and the number of key stretching rounds (for Part 2).
+const PARAM_KEYS: [&str; 3] = ["nth-key", "window", "stretch-rounds"];
 
-lazy_static! {
-    static ref REGEX_THREE_GROUP: Regex = Regex::new(r"([0-9a-f])\1\1").unwrap();
-    static ref REGEX_FIVE_GROUP: Regex = Regex::new(r"([0-9a-f])\1\1\1\1").unwrap();
+/// Represents the parameters used to generate and validate the one-time pad keys.
+#[derive(Clone, Copy)]
+struct OtpKeyConfig {
+    /// Ordinal of the valid key to find (e.g. 64 for the 64th key)
+    nth_key: usize,
+    /// Number of hashes following a candidate key that are checked for a matching group-of-five.
+    /// Must be at least 1.
+    window_len: usize,
+    /// Number of additional MD5 rounds applied to each hash for key stretching
+    stretch_rounds: usize,
 }
 
-/// Represents the details extracted from an MD5 hash, being the characters that are involved in any
-/// groups of the same character three-in-a-row or five-in-a-row.
+/// Represents the details extracted from an MD5 hash containing a group-of-three, being the
+/// hexadecimal digits that are involved in any groups of the same digit three-in-a-row or
+/// five-in-a-row.
 struct Md5HashDetails {
     /// Index of the MD5 hash
     index: usize,
-    /// First character in a group-of-three that the MD5 hash contains
-    three_group: Option<char>,
-    /// Any characters contained in group-of-five of same characters
-    five_groups: HashSet<char>,
+    /// First digit in a group-of-three that the MD5 hash contains
+    three_group: u8,
+    /// Bitmask of the digits contained in a group-of-five of the same digit
+    five_groups: u16,
 }
 
 /// Processes the AOC 2016 Day 14 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let (p1_config, p2_config) = match read_configs(&Params::from_args(&PARAM_KEYS)) {
+        Ok(configs) => configs,
+        Err(e) => panic!("Invalid one-time pad key config! // {e}"),
+    };
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, &p1_config);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, &p2_config);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 14 input file in the format required by the solver functions.
@@ -71,100 +116,102 @@ fn process_input_file(filename: &str) -> String {
     raw_input.trim().to_string()
 }
 
-/// Solves AOC 2016 Day 14 Part 1 // Determines the index that produces the 64th one-time pad key.
-fn solve_part1(salt: &str) -> usize {
-    find_index_of_target_ord_otp_key(salt, TARGET_OTP_ORD, false)
+/// Reads the configurations for Part 1 and Part 2, with any puzzle constants overridden on the
+/// command line replacing the values from the problem description. Returns an error if the key
+/// ordinal or window length is overridden with 0, as no key could then be found.
+fn read_configs(params: &Params) -> Result<(OtpKeyConfig, OtpKeyConfig), String> {
+    let p1_config = OtpKeyConfig {
+        nth_key: params.get("nth-key", PART1_CONFIG.nth_key),
+        window_len: params.get("window", PART1_CONFIG.window_len),
+        ..PART1_CONFIG
+    };
+    for (key, value) in [
+        ("nth-key", p1_config.nth_key),
+        ("window", p1_config.window_len),
+    ] {
+        if value == 0 {
+            return Err(format!("{key} must be at least 1"));
+        }
+    }
+    let p2_config = OtpKeyConfig {
+        stretch_rounds: params.get("stretch-rounds", PART2_CONFIG.stretch_rounds),
+        ..p1_config
+    };
+    Ok((p1_config, p2_config))
+}
+
+/// Solves AOC 2016 Day 14 Part 1 // Determines the index that produces the 64th one-time pad key
+/// (using the Part 1 config by default).
+fn solve_part1(salt: &str, config: &OtpKeyConfig) -> usize {
+    find_index_of_target_ord_otp_key(salt, config)
 }
 
 /// Solves AOC 2016 Day 14 Part 2 // Determines the index that produces the 64th one-time pad key,
-/// with key stretching enabled.
-fn solve_part2(salt: &str) -> usize {
-    find_index_of_target_ord_otp_key(salt, TARGET_OTP_ORD, true)
+/// with key stretching enabled (using the Part 2 config by default).
+fn solve_part2(salt: &str, config: &OtpKeyConfig) -> usize {
+    find_index_of_target_ord_otp_key(salt, config)
 }
 
-/// Determins the index of the one-time pad key that is the nth valid key.
-fn find_index_of_target_ord_otp_key(salt: &str, nth_key: usize, use_key_stretching: bool) -> usize {
+/// Determines the index of the one-time pad key that is the nth valid key, using the window length
+/// and number of key stretching rounds given in the config.
+///
+/// Only the hashes containing a group-of-three are mined, as every group-of-five is also a
+/// group-of-three. A candidate key is checked once a hash beyond the end of its window is mined.
+fn find_index_of_target_ord_otp_key(salt: &str, config: &OtpKeyConfig) -> usize {
+    assert!(config.nth_key > 0, "Key ordinal must be at least 1!");
+    assert!(config.window_len > 0, "Window length must be at least 1!");
+    let mut mined_hashes = HashMiner::new(
+        Md5::stretched(config.stretch_rounds),
+        salt.as_bytes(),
+        |digest: &[u8; 16]| first_nibble_run(digest, 3).is_some(),
+    )
+    .map(|mined| Md5HashDetails {
+        index: mined.index() as usize,
+        three_group: first_nibble_run(mined.digest(), 3).unwrap(),
+        five_groups: nibble_run_mask(mined.digest(), 5),
+    });
     let mut details_queue: VecDeque<Md5HashDetails> = VecDeque::new();
-    let mut five_groups_enqueued: HashSet<char> = HashSet::new();
-    // Initialise the buffer of MD5 hash details
-    for index in 0..HASH_BUFFER_LEN {
-        let md5_hash_details = calculate_md5_hash_details(salt, index, use_key_stretching);
-        five_groups_enqueued.extend(md5_hash_details.five_groups.iter());
-        details_queue.push_back(md5_hash_details);
-    }
     let mut valid_otp_keys_found = 0;
     loop {
-        // Pop key from front and adjust five-groups enqueued
-        let key_details = details_queue.pop_front().unwrap();
-        for c in key_details.five_groups {
-            five_groups_enqueued.remove(&c);
+        // Mine hashes until the window following the current key has been covered
+        while details_queue.len() < 2
+            || details_queue.back().unwrap().index <= details_queue[0].index + config.window_len
+        {
+            details_queue.push_back(mined_hashes.next().unwrap());
         }
-        // Generate next md5 hash details and adjust five-groups enqueue
-        let new_md5_hash_details = calculate_md5_hash_details(
-            salt,
-            key_details.index + HASH_BUFFER_LEN,
-            use_key_stretching,
-        );
-        five_groups_enqueued.extend(new_md5_hash_details.five_groups.iter());
-        details_queue.push_back(new_md5_hash_details);
-        // Check if the current key is a valid key
-        if let Some(c) = key_details.three_group {
-            if five_groups_enqueued.contains(&c) {
-                valid_otp_keys_found += 1;
-            }
-            if valid_otp_keys_found == nth_key {
+        let key_details = details_queue.pop_front().unwrap();
+        let window_end = key_details.index + config.window_len;
+        if details_queue
+            .iter()
+            .take_while(|details| details.index <= window_end)
+            .any(|details| details.five_groups & (1 << key_details.three_group) != 0)
+        {
+            valid_otp_keys_found += 1;
+            if valid_otp_keys_found == config.nth_key {
                 return key_details.index;
             }
         }
     }
 }
 
-/// Calculates the MD5 hash details for the given salt and index.
-fn calculate_md5_hash_details(
-    salt: &str,
-    index: usize,
-    use_key_stretching: bool,
-) -> Md5HashDetails {
-    // Calculate MD5 hash
-    let digest = calculate_md5_hexadecimal_digest(salt, index, use_key_stretching);
-    // Calculate three-groups and five-groups
-    let mut three_group: Option<char> = None;
-    let mut five_groups: HashSet<char> = HashSet::new();
-    if let Ok(Some(caps)) = REGEX_THREE_GROUP.captures(&digest) {
-        three_group = Some(caps[1].chars().next().unwrap());
-    }
-    for caps in REGEX_FIVE_GROUP.captures_iter(&digest) {
-        let caps = caps.unwrap();
-        five_groups.insert(caps[1].chars().next().unwrap());
-    }
-    Md5HashDetails {
-        index,
-        three_group,
-        five_groups,
-    }
-}
-
-/// Caluclates the MD5 hexadecimal digest for the given salt and index. Key stretching is applied
-/// if use_key_stretching is set to true.
-fn calculate_md5_hexadecimal_digest(salt: &str, index: usize, use_key_stretching: bool) -> String {
-    let mut digest = format!("{:x}", md5::compute(format!("{salt}{index}")));
-    if use_key_stretching {
-        for _ in 0..2016 {
-            digest = format!("{:x}", md5::compute(digest.as_bytes()));
-        }
-    }
-    digest
-}
-
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::params::PARAM_FLAG;
+
     use super::*;
 
+    #[cfg(feature = "count-allocations")]
+    use aoc2016::utils::alloccount::CountingAllocator;
+
+    #[cfg(feature = "count-allocations")]
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
     /// Tests the Day 14 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day14_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, &PART1_CONFIG);
         assert_eq!(25427, solution);
     }
 
@@ -172,7 +219,77 @@ mod test {
     #[test]
     fn test_day14_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, &PART2_CONFIG);
         assert_eq!(22045, solution);
     }
+
+    /// Tests the key search using the example salt from the problem description, with and without
+    /// key stretching, and with a reduced window length.
+    #[test]
+    fn test_day14_example_configs() {
+        assert_eq!(
+            22728,
+            find_index_of_target_ord_otp_key("abc", &PART1_CONFIG)
+        );
+        let first_key = OtpKeyConfig {
+            nth_key: 1,
+            ..PART1_CONFIG
+        };
+        assert_eq!(39, find_index_of_target_ord_otp_key("abc", &first_key));
+        let first_stretched_key = OtpKeyConfig {
+            nth_key: 1,
+            ..PART2_CONFIG
+        };
+        assert_eq!(
+            10,
+            find_index_of_target_ord_otp_key("abc", &first_stretched_key)
+        );
+        // The group-of-five confirming key 39 is at index 816, outside a window of 500
+        let short_window = OtpKeyConfig {
+            window_len: 500,
+            ..first_key
+        };
+        assert!(find_index_of_target_ord_otp_key("abc", &short_window) > 39);
+    }
+
+    /// Tests that overriding the key ordinal or window length with 0 is rejected, and that the
+    /// stretch rounds override only applies to Part 2.
+    #[test]
+    fn test_day14_read_configs() {
+        for (pair, expected) in [
+            ("nth-key=0", "nth-key must be at least 1"),
+            ("window=0", "window must be at least 1"),
+        ] {
+            let args = ["day14", PARAM_FLAG, pair].map(String::from);
+            let params = Params::parse(&args, &PARAM_KEYS).unwrap();
+            assert_eq!(Err(expected.to_string()), read_configs(&params).map(|_| ()));
+        }
+        let args = ["day14", PARAM_FLAG, "stretch-rounds=5"].map(String::from);
+        let params = Params::parse(&args, &PARAM_KEYS).unwrap();
+        let (p1_config, p2_config) = read_configs(&params).unwrap();
+        assert_eq!((0, 5), (p1_config.stretch_rounds, p2_config.stretch_rounds));
+    }
+
+    /// Tests that finding the first key for the example salt, with and without key stretching, does
+    /// not make more allocations than the recorded limits.
+    #[cfg(all(feature = "count-allocations", not(feature = "parallel")))]
+    #[test]
+    fn test_day14_allocations() {
+        use aoc2016::utils::alloccount::assert_allocations_within;
+
+        let first_key = OtpKeyConfig {
+            nth_key: 1,
+            ..PART1_CONFIG
+        };
+        assert_allocations_within("part 1", 200, || {
+            find_index_of_target_ord_otp_key("abc", &first_key)
+        });
+        let first_stretched_key = OtpKeyConfig {
+            nth_key: 1,
+            ..PART2_CONFIG
+        };
+        assert_allocations_within("part 2", 300, || {
+            find_index_of_target_ord_otp_key("abc", &first_stretched_key)
+        });
+    }
 }
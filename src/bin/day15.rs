@@ -1,14 +1,26 @@
 use std::fs;
 use std::time::Instant;
 
+use aoc2016::utils::algorithms::AlgorithmRegistry;
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::modular::chinese_remainder;
 use fancy_regex::Regex;
 
 const PROBLEM_NAME: &str = "Timing is Everything";
 const PROBLEM_INPUT_FILE: &str = "./input/day15.txt";
 const PROBLEM_DAY: u64 = 15;
 
+/// Number of positions on the additional disc added below the other discs in Part 2.
+const PART2_EXTRA_DISC_POSITIONS: u64 = 11;
+/// Starting position of the additional disc added below the other discs in Part 2.
+const PART2_EXTRA_DISC_START_POSITION: u64 = 0;
+
+/// Algorithm that finds the first time at which the ball could be dropped and still pass through the
+/// hole in each disc, returning None if there is no such time.
+type DropTimeFinder = fn(&[Disc]) -> Option<u64>;
+
 /// Represents a single disc containing multiple positions, one of which has the hole in it.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 struct Disc {
     id: u64,
     total_positions: u64,
@@ -30,41 +42,60 @@ impl Disc {
         if time + self.id < self.offset {
             return false;
         }
-        (time + self.id - self.offset) % self.total_positions == 0
+        (time + self.id - self.offset).is_multiple_of(self.total_positions)
+    }
+
+    /// Gets the congruence (residue, modulus) that the drop time must satisfy for the ball to fall
+    /// through the hole in the disc.
+    pub fn drop_time_congruence(&self) -> (i64, i64) {
+        let modulus = self.total_positions as i64;
+        let residue = (self.offset as i64 - self.id as i64).rem_euclid(modulus);
+        (residue, modulus)
     }
 }
 
 /// Processes the AOC 2016 Day 15 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let (_, algorithm) = drop_time_algorithms().select_from_args();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, algorithm);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, algorithm);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 15 input file in the format required by the solver functions.
@@ -73,6 +104,11 @@ fn process_input_file(filename: &str) -> Vec<Disc> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
+    parse_discs(&raw_input)
+}
+
+/// Parses the Discs specified by the lines of the given string.
+fn parse_discs(raw_input: &str) -> Vec<Disc> {
     let regex_disc =
         Regex::new(r"^Disc #(\d+) has (\d+) positions; at time=0, it is at position (\d+).$")
             .unwrap();
@@ -94,47 +130,71 @@ fn process_input_file(filename: &str) -> Vec<Disc> {
 
 /// Solves AOC 2016 Day 15 Part 1 // Determines the first time at which the ball could be dropped
 /// and still pass through the hole in each disc.
-fn solve_part1(discs: &[Disc]) -> u64 {
-    find_first_valid_drop_time(discs)
+fn solve_part1(discs: &[Disc], algorithm: DropTimeFinder) -> u64 {
+    algorithm(discs).unwrap()
 }
 
 /// Solves AOC 2016 Day 15 Part 2 // Determines the first time at which the ball could be dropped
 /// and still pass through the hole in each disc, with the additional disc added to the end.
-fn solve_part2(discs: &[Disc]) -> u64 {
+fn solve_part2(discs: &[Disc], algorithm: DropTimeFinder) -> u64 {
     let mut discs = discs.to_vec();
-    discs.push(Disc::new((discs.len() + 1) as u64, 11, 0));
-    find_first_valid_drop_time(&discs)
+    discs.push(Disc::new(
+        (discs.len() + 1) as u64,
+        PART2_EXTRA_DISC_POSITIONS,
+        PART2_EXTRA_DISC_START_POSITION,
+    ));
+    algorithm(&discs).unwrap()
+}
+
+/// Gets the algorithms that can be used to find the first valid drop time, with the Chinese
+/// Remainder Theorem solver as the default.
+fn drop_time_algorithms() -> AlgorithmRegistry<DropTimeFinder> {
+    AlgorithmRegistry::new(
+        "crt",
+        "solves the congruences on the drop time with the Chinese Remainder Theorem",
+        find_first_valid_drop_time as DropTimeFinder,
+    )
+    .register(
+        "brute-force",
+        "checks each drop time in turn until the discs return to their starting positions",
+        find_first_valid_drop_time_brute_force,
+    )
 }
 
 /// Finds the first time at which the ball could be dropped and still pass through the hole in each
-/// disc.
-fn find_first_valid_drop_time(discs: &[Disc]) -> u64 {
-    let mut time: u64 = 0;
-    loop {
-        let mut valid_time = true;
-        for disc in discs {
-            if !disc.validate_time(time) {
-                valid_time = false;
-                break;
-            }
-        }
-        if !valid_time {
-            time += 1;
-            continue;
-        }
-        return time;
-    }
+/// disc, by solving the congruences on the drop time for each disc with the Chinese Remainder
+/// Theorem. Returns None if there is no such time.
+fn find_first_valid_drop_time(discs: &[Disc]) -> Option<u64> {
+    let congruences = discs
+        .iter()
+        .map(|disc| disc.drop_time_congruence())
+        .collect::<Vec<(i64, i64)>>();
+    chinese_remainder(&congruences).map(|(time, _)| time as u64)
+}
+
+/// Finds the first time at which the ball could be dropped and still pass through the hole in each
+/// disc by checking each time in turn. Only times before the discs return to their starting
+/// positions are checked, after which the pattern repeats.
+fn find_first_valid_drop_time_brute_force(discs: &[Disc]) -> Option<u64> {
+    let period = discs
+        .iter()
+        .map(|disc| disc.total_positions)
+        .product::<u64>();
+    (0..period).find(|&time| discs.iter().all(|disc| disc.validate_time(time)))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[cfg(feature = "test-support")]
+    use aoc2016::test_support::disc_definitions;
+
     /// Tests the Day 15 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day15_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, find_first_valid_drop_time);
         assert_eq!(203660, solution);
     }
 
@@ -142,7 +202,60 @@ mod test {
     #[test]
     fn test_day15_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, find_first_valid_drop_time);
         assert_eq!(2408135, solution);
     }
+
+    /// Tests the drop time calculation using the example from the problem description, with the
+    /// discs both parsed and constructed directly.
+    #[test]
+    fn test_day15_example() {
+        let discs = parse_discs(
+            "Disc #1 has 5 positions; at time=0, it is at position 4.\n\
+            Disc #2 has 2 positions; at time=0, it is at position 1.\n",
+        );
+        assert_eq!(vec![Disc::new(1, 5, 4), Disc::new(2, 2, 1)], discs);
+        assert_eq!(Some(5), find_first_valid_drop_time(&discs));
+    }
+
+    /// Tests that every registered algorithm solves Part 1, and that the CRT solver agrees with the
+    /// brute force search for small discs, including discs with position counts that are not
+    /// coprime.
+    #[test]
+    fn test_day15_crt_matches_brute_force() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        for (name, algorithm) in drop_time_algorithms().iter() {
+            assert_eq!(203660, solve_part1(&input, algorithm), "{name}");
+        }
+        for (positions_a, positions_b) in [(2, 3), (4, 6), (5, 5), (3, 9)] {
+            for start_a in 0..positions_a {
+                for start_b in 0..positions_b {
+                    let discs = [
+                        Disc::new(1, positions_a, start_a),
+                        Disc::new(2, positions_b, start_b),
+                        Disc::new(3, 7, 3),
+                    ];
+                    assert_eq!(
+                        find_first_valid_drop_time_brute_force(&discs),
+                        find_first_valid_drop_time(&discs)
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "test-support")]
+    proptest::proptest! {
+        /// Tests that generated disc definitions parse into one disc per line, and that the first
+        /// valid drop time found from the congruences matches the brute force search.
+        #[test]
+        fn test_day15_generated_discs(definitions in disc_definitions(3)) {
+            let discs = parse_discs(&definitions);
+            proptest::prop_assert_eq!(definitions.lines().count(), discs.len());
+            proptest::prop_assert_eq!(
+                find_first_valid_drop_time_brute_force(&discs),
+                find_first_valid_drop_time(&discs)
+            );
+        }
+    }
 }
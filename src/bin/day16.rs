@@ -1,123 +1,256 @@
+use std::env;
 use std::fs;
 use std::time::Instant;
 
+use bitvec::vec::BitVec;
+
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "Dragon Checksum";
 const PROBLEM_INPUT_FILE: &str = "./input/day16.txt";
 const PROBLEM_DAY: u64 = 16;
 
+/// Size of the disk to be filled in Part 1.
+const PART1_DISK_LENGTH: usize = 272;
+/// Size of the disk to be filled in Part 2.
+const PART2_DISK_LENGTH: usize = 35651584;
+
+/// Command line flag used to calculate the checksums without generating the dragon curve data.
+const ANALYTIC_FLAG: &str = "--analytic";
+
+/// Represents the ways the checksum of the dragon curve data can be calculated.
+#[derive(Clone, Copy)]
+enum ChecksumMode {
+    /// Generates the full dragon curve data and reduces it to the checksum.
+    Materialized,
+    /// Calculates each checksum bit from the parity of the dragon curve data prefixes.
+    Analytic,
+}
+
+/// Calculates the parity (whether there are an odd number of 1 bits) of any prefix of the dragon
+/// curve data generated from a seed, without generating the data.
+///
+/// The dragon curve data consists of the seed (a) and its reversed and inverted copy (b) in
+/// alternating blocks (a, b, a, b, ...), each block followed by a joiner bit. The joiner bits form
+/// the dragon curve data generated from the seed "0".
+struct DragonCurveParity {
+    /// Parity of the first i bits of the seed, for i from 0 up to the seed length
+    seed_prefix_parity: Vec<bool>,
+}
+
+impl DragonCurveParity {
+    pub fn new(seed: &[u8]) -> DragonCurveParity {
+        let mut seed_prefix_parity = vec![false];
+        for &b in seed {
+            let parity = *seed_prefix_parity.last().unwrap() ^ (b == b'1');
+            seed_prefix_parity.push(parity);
+        }
+        DragonCurveParity { seed_prefix_parity }
+    }
+
+    /// Calculates the parity of the first len bits of the dragon curve data.
+    pub fn prefix_parity(&self, len: usize) -> bool {
+        let seed_len = self.seed_prefix_parity.len() - 1;
+        let seed_parity = self.seed_prefix_parity[seed_len];
+        // Each block of inverted seed bits has the seed parity flipped once per bit
+        let inverted_seed_parity = seed_parity ^ (seed_len % 2 == 1);
+        let full_blocks = len / (seed_len + 1);
+        let partial_len = len % (seed_len + 1);
+        let mut parity = false;
+        parity ^= seed_parity && full_blocks.div_ceil(2) % 2 == 1;
+        parity ^= inverted_seed_parity && full_blocks / 2 % 2 == 1;
+        parity ^= joiner_prefix_parity(full_blocks);
+        parity ^= if full_blocks.is_multiple_of(2) {
+            self.seed_prefix_parity[partial_len]
+        } else {
+            // The start of the inverted block is the end of the seed reversed and inverted
+            seed_parity ^ self.seed_prefix_parity[seed_len - partial_len] ^ (partial_len % 2 == 1)
+        };
+        parity
+    }
+}
+
+/// Calculates the parity of the first len joiner bits in the dragon curve data. Joiner k (counting
+/// from 1) is a 1 bit if the odd part of k leaves a remainder of 3 when divided by 4.
+fn joiner_prefix_parity(len: usize) -> bool {
+    let mut ones = 0;
+    let mut remaining = len;
+    while remaining > 0 {
+        ones += (remaining + 1) / 4;
+        remaining >>= 1;
+    }
+    ones % 2 == 1
+}
+
 /// Processes the AOC 2016 Day 16 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let mode = if env::args().any(|arg| arg == ANALYTIC_FLAG) {
+        ChecksumMode::Analytic
+    } else {
+        ChecksumMode::Materialized
+    };
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, mode);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, mode);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 16 input file in the format required by the solver functions.
-/// Returned value is seed sequence given in the input file.
-fn process_input_file(filename: &str) -> String {
+/// Returned value is seed sequence given in the input file, as the bytes of its "0" and "1"
+/// characters.
+fn process_input_file(filename: &str) -> Vec<u8> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    raw_input.trim().to_string()
+    parse_seed(raw_input.trim())
+}
+
+/// Parses the seed into the bytes of its characters. Panics if the seed contains a character other
+/// than "0" or "1".
+fn parse_seed(s: &str) -> Vec<u8> {
+    if let Some(c) = s.chars().find(|c| *c != '0' && *c != '1') {
+        panic!("Invalid character in seed! // {c}");
+    }
+    s.as_bytes().to_vec()
 }
 
 /// Solves AOC 2016 Day 16 Part 1 // Determines the checksum of the modified dragon curve data
 /// needed to fill a disk with size 272 units.
-fn solve_part1(seed: &str) -> String {
-    let blob = generate_dragon_curve_data(seed, 272);
-    generate_dragon_curve_checksum(&blob)
+fn solve_part1(seed: &[u8], mode: ChecksumMode) -> String {
+    calculate_disk_checksum(seed, PART1_DISK_LENGTH, mode)
 }
 
 /// Solves AOC 2016 Day 16 Part 2 // Determines the checksum of the modified dragon curve data
 /// needed to fill a disk with size 35651584 units.
-fn solve_part2(seed: &str) -> String {
-    let blob = generate_dragon_curve_data(seed, 35651584);
-    generate_dragon_curve_checksum(&blob)
+fn solve_part2(seed: &[u8], mode: ChecksumMode) -> String {
+    calculate_disk_checksum(seed, PART2_DISK_LENGTH, mode)
 }
 
-/// Processes the dragon curve data blob using the checksum calculation until the checksum has an
-/// off number of characters.
-fn generate_dragon_curve_checksum(blob: &str) -> String {
-    let mut checksum = blob.to_string();
-    while checksum.len() % 2 == 0 {
-        checksum = apply_checksum_iteration(&checksum);
+/// Calculates the checksum of the dragon curve data generated from the seed to fill a disk of the
+/// given length, using the given checksum mode.
+fn calculate_disk_checksum(seed: &[u8], disk_length: usize, mode: ChecksumMode) -> String {
+    match mode {
+        ChecksumMode::Materialized => {
+            let data = generate_dragon_curve_data(seed, disk_length);
+            generate_dragon_curve_checksum(data)
+        }
+        ChecksumMode::Analytic => calculate_dragon_curve_checksum_analytic(seed, disk_length),
     }
-    checksum
 }
 
-/// Applies a single iteration of the dragon curve checksum calculation to the dragon curve data
-/// blob.
-fn apply_checksum_iteration(blob: &str) -> String {
-    if blob.len() % 2 == 1 {
-        return blob.to_string();
-    }
-    let blob_chars = blob.chars().collect::<Vec<char>>();
-    let mut checksum = String::new();
-    for (i, c) in blob_chars.iter().enumerate().step_by(2) {
-        let c1 = blob_chars[i + 1];
-        match c.eq(&c1) {
-            true => checksum.push('1'),
-            false => checksum.push('0'),
-        }
+/// Calculates the checksum of the dragon curve data directly from the parity of the data prefixes.
+///
+/// Reducing a chunk of 2^k bits (k >= 1) down to a single checksum bit gives a 1 bit if the chunk
+/// contains an even number of 1 bits, so each checksum bit only depends on the parity of the chunk.
+/// The chunk length is the largest power of two that divides the disk length.
+fn calculate_dragon_curve_checksum_analytic(seed: &[u8], disk_length: usize) -> String {
+    if disk_length == 0 {
+        return String::new();
     }
-    checksum
+    let parity = DragonCurveParity::new(seed);
+    let chunk_len = 1 << disk_length.trailing_zeros();
+    let mut prev_parity = false;
+    (1..=disk_length / chunk_len)
+        .map(|i| {
+            let next_parity = parity.prefix_parity(i * chunk_len);
+            let chunk_parity = prev_parity ^ next_parity;
+            prev_parity = next_parity;
+            // An odd disk length has no reduction applied, so the checksum is the data itself
+            if chunk_parity ^ (chunk_len > 1) {
+                '1'
+            } else {
+                '0'
+            }
+        })
+        .collect::<String>()
 }
 
-/// Generates a blob of dragon curve data from the given seed that is the same length as the given
-/// disk length.
-fn generate_dragon_curve_data(seed: &str, disk_length: usize) -> String {
-    let mut blob = seed.to_string();
-    while blob.len() < disk_length {
-        blob = apply_dragon_curve_iteration(&blob);
+/// Processes the dragon curve data using the checksum calculation until the checksum has an odd
+/// number of bits. Each iteration is applied in place, with the pair checksums written over the
+/// front half of the data before it is truncated.
+fn generate_dragon_curve_checksum(mut data: BitVec) -> String {
+    while !data.is_empty() && data.len() % 2 == 0 {
+        let half_len = data.len() / 2;
+        for i in 0..half_len {
+            let same = data[2 * i] == data[2 * i + 1];
+            data.set(i, same);
+        }
+        data.truncate(half_len);
     }
-    blob.chars().take(disk_length).collect::<String>()
+    data.iter()
+        .map(|bit| if *bit { '1' } else { '0' })
+        .collect::<String>()
 }
 
-/// Generates a new dragon curve blob using the given blob as input to the iteration.
-fn apply_dragon_curve_iteration(blob: &str) -> String {
-    let b_half = blob
-        .chars()
-        .rev()
-        .map(|c| if c == '0' { '1' } else { '0' })
-        .collect::<String>();
-    format!("{blob}0{b_half}")
+/// Generates the dragon curve data from the given seed that is the same length as the given disk
+/// length. Each iteration appends a zero followed by the reversed and inverted copy of the existing
+/// data (or as much of the end of the copy as fits), stopping as soon as the disk length is reached.
+fn generate_dragon_curve_data(seed: &[u8], disk_length: usize) -> BitVec {
+    let mut data = seed.iter().map(|b| *b == b'1').collect::<BitVec>();
+    data.reserve(disk_length.saturating_sub(data.len()));
+    while data.len() < disk_length {
+        let len = data.len();
+        let copy_len = len.min(disk_length - len - 1);
+        let mut copy = data[len - copy_len..].to_bitvec();
+        copy.reverse();
+        data.push(false);
+        data.extend_from_bitslice(&!copy);
+    }
+    data.truncate(disk_length);
+    data
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[cfg(feature = "count-allocations")]
+    use aoc2016::utils::alloccount::CountingAllocator;
+
+    #[cfg(feature = "count-allocations")]
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
     /// Tests the Day 16 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day16_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, ChecksumMode::Materialized);
         assert_eq!("00000100100001100", solution);
     }
 
@@ -125,7 +258,59 @@ mod test {
     #[test]
     fn test_day16_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, ChecksumMode::Materialized);
         assert_eq!("00011010100010010", solution);
     }
+
+    /// Tests the dragon curve data generation and checksum calculation using the examples from the
+    /// problem description.
+    #[test]
+    fn test_day16_examples() {
+        let data = generate_dragon_curve_data(b"111100001010", 25);
+        let data_string = data
+            .iter()
+            .map(|bit| if *bit { '1' } else { '0' })
+            .collect::<String>();
+        assert_eq!("1111000010100101011110000", data_string);
+        let data = generate_dragon_curve_data(b"10000", 20);
+        assert_eq!("01100", generate_dragon_curve_checksum(data));
+    }
+
+    /// Tests that the analytic checksum calculation matches the checksum of the generated dragon
+    /// curve data for a range of seeds and disk lengths, including the actual problem input.
+    #[test]
+    fn test_day16_analytic_matches_materialized() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let seeds: [&[u8]; 7] = [b"", b"0", b"1", b"10000", b"110", b"111100001010", &input];
+        for seed in seeds {
+            for disk_length in (0..300).chain([272, 1024, 4000, 12288]) {
+                let data = generate_dragon_curve_data(seed, disk_length);
+                assert_eq!(
+                    generate_dragon_curve_checksum(data),
+                    calculate_dragon_curve_checksum_analytic(seed, disk_length),
+                    "seed {:?}, disk length {disk_length}",
+                    String::from_utf8_lossy(seed)
+                );
+            }
+        }
+        assert_eq!(
+            "00011010100010010",
+            solve_part2(&input, ChecksumMode::Analytic)
+        );
+    }
+
+    /// Tests that the Day 16 solver methods do not make more allocations than their recorded limits.
+    #[cfg(all(feature = "count-allocations", not(feature = "parallel")))]
+    #[test]
+    fn test_day16_allocations() {
+        use aoc2016::utils::alloccount::assert_allocations_within;
+
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        assert_allocations_within("part 1", 10, || {
+            solve_part1(&input, ChecksumMode::Materialized)
+        });
+        assert_allocations_within("part 2", 200, || {
+            solve_part2(&input, ChecksumMode::Materialized)
+        });
+    }
 }
@@ -1,58 +1,262 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
 use std::fs;
 use std::time::Instant;
 
-use lazy_static::lazy_static;
+use aoc2016::utils::events::EventStream;
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use aoc2016::utils::hashmining::{HashAlgorithm, Md5};
+use aoc2016::utils::md5batch::LANES;
+use aoc2016::utils::metrics;
+use aoc2016::utils::nibbles::nibbles;
+use aoc2016::utils::params::Params;
 use aoc_utils::cartography::Point2D;
 
 const PROBLEM_NAME: &str = "Two Steps Forward";
 const PROBLEM_INPUT_FILE: &str = "./input/day17.txt";
 const PROBLEM_DAY: u64 = 17;
 
-lazy_static! {
-    static ref OPEN_CHARS: HashSet<char> = HashSet::from(['b', 'c', 'd', 'e', 'f']);
-    static ref LOC_START: Point2D = Point2D::new(0, 0);
-    static ref LOC_TARGET: Point2D = Point2D::new(3, 3);
+/// Command line flag used to print statistics about all of the paths that reach the vault.
+const STATS_FLAG: &str = "--stats";
+/// Number of levels of the path space explored before the remaining search is split between
+/// threads.
+#[cfg(feature = "parallel")]
+const PARALLEL_SPLIT_DEPTH: usize = 4;
+
+/// Width and height of the grid of rooms leading to the vault.
+const GRID_SIZE: i64 = 4;
+/// Characters of the MD5 hexdigest that indicate a door is open.
+const OPEN_CHARS: &str = "bcdef";
+/// Puzzle constants that can be overridden on the command line: the width and height of the grid.
+const PARAM_KEYS: [&str; 1] = ["grid-size"];
+
+/// Represents the layout of the grid of rooms leading to the vault, with the start location in the
+/// top-left room and the vault in the bottom-right room.
+struct VaultLayout {
+    width: i64,
+    height: i64,
+    /// Whether a door is open for each nibble value of the MD5 digest
+    open_nibbles: [bool; 16],
+}
+
+impl Default for VaultLayout {
+    /// Creates the 4x4 layout described in the problem, with doors open for "b" to "f".
+    fn default() -> VaultLayout {
+        VaultLayout::new(GRID_SIZE, GRID_SIZE, OPEN_CHARS)
+    }
+}
+
+impl VaultLayout {
+    /// Creates a new layout with the given grid size. A door is open if the corresponding character
+    /// of the MD5 hexdigest is in the given set of open characters.
+    ///
+    /// Panics if any of the open characters is not a lowercase hexadecimal digit.
+    pub fn new(width: i64, height: i64, open_chars: &str) -> VaultLayout {
+        assert!(
+            width > 0 && height > 0,
+            "Grid must contain at least one room!"
+        );
+        let mut open_nibbles = [false; 16];
+        for c in open_chars.chars() {
+            match c.to_digit(16) {
+                Some(nibble) if !c.is_ascii_uppercase() => open_nibbles[nibble as usize] = true,
+                _ => panic!("Open door character must be a lowercase hex digit! // {c}"),
+            }
+        }
+        VaultLayout {
+            width,
+            height,
+            open_nibbles,
+        }
+    }
+
+    /// Checks if a door is open when the corresponding digit of the MD5 hexdigest has the given
+    /// nibble value.
+    pub fn is_door_open(&self, nibble: u8) -> bool {
+        self.open_nibbles[nibble as usize]
+    }
+
+    /// Gets the location of the starting room.
+    pub fn loc_start(&self) -> Point2D {
+        Point2D::new(0, 0)
+    }
+
+    /// Gets the location of the vault room.
+    pub fn loc_vault(&self) -> Point2D {
+        Point2D::new(self.width - 1, self.height - 1)
+    }
 }
 
-/// Represents the current state of navigating through the grid leading to the vault.
+/// Represents the current state of navigating through the grid leading to the vault. The path holds
+/// the ASCII direction characters ("U", "D", "L" and "R") of the steps taken.
 struct PathState {
     loc: Point2D,
-    path: String,
+    path: Vec<u8>,
+}
+
+/// Iterator over all of the paths that reach the vault location from the start location, yielded
+/// in order of increasing length. The rooms are explored breadth-first, with paths ending once the
+/// vault location is reached. Up to LANES states are expanded together, so the MD5 hashes of their
+/// paths can use the multi-lane MD5 backend.
+struct VaultPaths<'a> {
+    vault_code: &'a [u8],
+    layout: &'a VaultLayout,
+    state_queue: VecDeque<PathState>,
+}
+
+impl<'a> VaultPaths<'a> {
+    pub fn new(vault_code: &'a [u8], layout: &'a VaultLayout) -> VaultPaths<'a> {
+        let initial_state = PathState {
+            loc: layout.loc_start(),
+            path: vec![],
+        };
+        VaultPaths::from_state(vault_code, layout, initial_state)
+    }
+
+    /// Creates an iterator over the paths that reach the vault location by extending the path of
+    /// the given state.
+    pub fn from_state(
+        vault_code: &'a [u8],
+        layout: &'a VaultLayout,
+        state: PathState,
+    ) -> VaultPaths<'a> {
+        VaultPaths {
+            vault_code,
+            layout,
+            state_queue: VecDeque::from([state]),
+        }
+    }
+}
+
+impl Iterator for VaultPaths<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        while let Some(state) = self.state_queue.front() {
+            if state.loc == self.layout.loc_vault() {
+                let state = self.state_queue.pop_front().unwrap();
+                // Paths only ever hold the ASCII direction characters
+                return Some(String::from_utf8(state.path).unwrap());
+            }
+            // Expand the states ahead of the next state at the vault location
+            let batch_len = self
+                .state_queue
+                .iter()
+                .take(LANES)
+                .take_while(|state| state.loc != self.layout.loc_vault())
+                .count();
+            let batch = self
+                .state_queue
+                .drain(..batch_len)
+                .collect::<Vec<PathState>>();
+            self.state_queue
+                .extend(find_next_valid_states(self.vault_code, self.layout, &batch));
+        }
+        None
+    }
+}
+
+/// Represents statistics about the paths that reach the vault location.
+struct PathStatistics {
+    /// Number of paths that reach the vault
+    count: usize,
+    /// Shortest path that reaches the vault (the first found, if there are multiple)
+    shortest: Option<String>,
+    /// Length of the longest path that reaches the vault
+    longest_length: Option<usize>,
+    /// Number of paths that reach the vault with each path length
+    length_counts: BTreeMap<usize, usize>,
+}
+
+impl PathStatistics {
+    /// Calculates the statistics for the given paths in a single pass.
+    pub fn from_paths(paths: impl Iterator<Item = String>) -> PathStatistics {
+        let mut stats = PathStatistics {
+            count: 0,
+            shortest: None,
+            longest_length: None,
+            length_counts: BTreeMap::new(),
+        };
+        for path in paths {
+            stats.count += 1;
+            *stats.length_counts.entry(path.len()).or_insert(0) += 1;
+            stats.longest_length = stats.longest_length.max(Some(path.len()));
+            if stats.shortest.as_ref().is_none_or(|p| path.len() < p.len()) {
+                stats.shortest = Some(path);
+            }
+        }
+        stats
+    }
 }
 
 /// Processes the AOC 2016 Day 17 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let grid_size = Params::from_args(&PARAM_KEYS).get("grid-size", GRID_SIZE);
+    let layout = VaultLayout::new(grid_size, grid_size, OPEN_CHARS);
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, &layout);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, &layout);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the path statistics if requested
+    if env::args().any(|arg| arg == STATS_FLAG) {
+        let stats = PathStatistics::from_paths(VaultPaths::new(input.as_bytes(), &layout));
+        println!("[*] Paths reaching vault: {}", stats.count);
+        println!("[*] Shortest path: {}", stats.shortest.unwrap_or_default());
+        println!(
+            "[*] Longest path length: {}",
+            stats.longest_length.unwrap_or_default()
+        );
+        for (length, count) in stats.length_counts {
+            println!("[*] Paths of length {length}: {count}");
+        }
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 17 input file in the format required by the solver functions.
@@ -64,113 +268,96 @@ fn process_input_file(filename: &str) -> String {
     raw_input.trim().to_string()
 }
 
-/// Solves AOC 2016 Day 17 Part 1 // Determines the shortest path string to reach the vault.
-fn solve_part1(vault_code: &str) -> String {
-    find_shortest_path_to_vault(vault_code, &LOC_START, &LOC_TARGET).unwrap()
+/// Solves AOC 2016 Day 17 Part 1 // Determines the shortest path string to reach the vault in the
+/// given layout (4x4 by default).
+fn solve_part1(vault_code: &str, layout: &VaultLayout) -> String {
+    find_shortest_path_to_vault(vault_code.as_bytes(), layout).unwrap()
 }
 
 /// Solves AOC 2016 Day 17 Part 2 // Determines the length of the longest path that reaches the
-/// vault location from the start location.
-fn solve_part2(vault_code: &str) -> usize {
-    find_longest_path_length_to_vault(vault_code, &LOC_START, &LOC_TARGET).unwrap()
+/// vault location from the start location in the given layout (4x4 by default).
+fn solve_part2(vault_code: &str, layout: &VaultLayout) -> usize {
+    find_longest_path_length_to_vault(vault_code.as_bytes(), layout).unwrap()
 }
 
 /// Determines the shortest path string needed to go from the start location to the vault location.
 /// Uses a breadth-first search method.
-fn find_shortest_path_to_vault(
-    vault_code: &str,
-    loc_start: &Point2D,
-    loc_vault: &Point2D,
-) -> Option<String> {
-    // Create the state representing the starting point of the search
-    let initial_state = PathState {
-        loc: *loc_start,
-        path: String::new(),
-    };
-    // The initial state is the first to be visited
-    let mut state_queue: VecDeque<PathState> = VecDeque::from([initial_state]);
-    while !state_queue.is_empty() {
-        // Get the next state to be visited and check if the vault location has been reached
-        let state = state_queue.pop_front().unwrap();
-        if state.loc == *loc_vault {
-            return Some(state.path);
-        }
-        // Visit all open rooms from the current room
-        for next_state in find_next_valid_states(vault_code, &state) {
-            state_queue.push_back(next_state);
-        }
-    }
-    None
+fn find_shortest_path_to_vault(vault_code: &[u8], layout: &VaultLayout) -> Option<String> {
+    VaultPaths::new(vault_code, layout).next()
+}
+
+/// Determines the length of the longest path that reaches the vault location from the start
+/// location. Since the paths are yielded in order of increasing length, the last path is longest.
+#[cfg(not(feature = "parallel"))]
+fn find_longest_path_length_to_vault(vault_code: &[u8], layout: &VaultLayout) -> Option<usize> {
+    VaultPaths::new(vault_code, layout)
+        .last()
+        .map(|path| path.len())
 }
 
 /// Determines the length of the longest path that reaches the vault location from the start
-/// location. Uses depth-first search method.
-fn find_longest_path_length_to_vault(
-    vault_code: &str,
-    loc_start: &Point2D,
-    loc_vault: &Point2D,
-) -> Option<usize> {
-    // Create the state representing the starting point of the search
-    let initial_state = PathState {
-        loc: *loc_start,
-        path: String::new(),
-    };
-    // The initial state is the first to be visited
-    let mut state_stack: VecDeque<PathState> = VecDeque::from([initial_state]);
+/// location. The first few levels of the path space are explored sequentially, after which the
+/// longest path extending each state in the frontier is found in parallel.
+#[cfg(feature = "parallel")]
+fn find_longest_path_length_to_vault(vault_code: &[u8], layout: &VaultLayout) -> Option<usize> {
+    let mut frontier = vec![PathState {
+        loc: layout.loc_start(),
+        path: vec![],
+    }];
     let mut longest_path_length: Option<usize> = None;
-    while !state_stack.is_empty() {
-        // Get the next state to be visited and check if the vault location has been reached
-        let state = state_stack.pop_front().unwrap();
-        if state.loc == *loc_vault {
-            // Check if a new longest path length has been found
-            let path_length = state.path.len();
-            if longest_path_length.is_none() || longest_path_length.unwrap() < path_length {
-                longest_path_length = Some(path_length);
-            }
-            continue;
-        }
-        // Go to the first of the next open rooms, if vault location not yet reached
-        for next_state in find_next_valid_states(vault_code, &state) {
-            state_stack.push_front(next_state);
+    for _ in 0..PARALLEL_SPLIT_DEPTH {
+        let (at_vault, expanding): (Vec<PathState>, Vec<PathState>) = frontier
+            .into_iter()
+            .partition(|state| state.loc == layout.loc_vault());
+        for state in at_vault {
+            longest_path_length = longest_path_length.max(Some(state.path.len()));
         }
+        frontier = find_next_valid_states(vault_code, layout, &expanding);
     }
-    longest_path_length
+    frontier
+        .into_par_iter()
+        .filter_map(|state| {
+            VaultPaths::from_state(vault_code, layout, state)
+                .last()
+                .map(|path| path.len())
+        })
+        .max()
+        .max(longest_path_length)
 }
 
-/// Determines the next valid states from the current state. Fixed walls are taken into account,
-/// which limit the (x,y) values to a minimum of 0 and a maximum of 3 each.
-fn find_next_valid_states(vault_code: &str, state: &PathState) -> Vec<PathState> {
+/// Determines the next valid states from each of the given states, in order. Fixed walls are
+/// taken into account, which limit the (x,y) values to the bounds of the grid given in the layout.
+fn find_next_valid_states(
+    vault_code: &[u8],
+    layout: &VaultLayout,
+    states: &[PathState],
+) -> Vec<PathState> {
+    // Generate MD5 hash for each room and take first four nibbles of the digest
+    let messages = states
+        .iter()
+        .map(|state| [vault_code, &state.path].concat())
+        .collect::<Vec<Vec<u8>>>();
+    let digests = Md5::new().digest_batch(&messages);
     let mut valid_states: Vec<PathState> = vec![];
-    // Generate MD5 hash for current room and take first four characters of the hexdigest
-    let digest = md5::compute(format!("{vault_code}{}", state.path).as_bytes());
-    let check_chars = format!("{digest:x}").chars().take(4).collect::<Vec<char>>();
-    // UP - 'U'
-    if OPEN_CHARS.contains(&check_chars[0]) && state.loc.y() > 0 {
-        valid_states.push(PathState {
-            loc: state.loc.peek_shift(0, -1),
-            path: state.path.to_string() + "U",
-        });
-    }
-    // DOWN - 'D'
-    if OPEN_CHARS.contains(&check_chars[1]) && state.loc.y() < 3 {
-        valid_states.push(PathState {
-            loc: state.loc.peek_shift(0, 1),
-            path: state.path.to_string() + "D",
-        });
-    }
-    // LEFT - 'L'
-    if OPEN_CHARS.contains(&check_chars[2]) && state.loc.x() > 0 {
-        valid_states.push(PathState {
-            loc: state.loc.peek_shift(-1, 0),
-            path: state.path.to_string() + "L",
-        });
-    }
-    // RIGHT - 'R'
-    if OPEN_CHARS.contains(&check_chars[3]) && state.loc.x() < 3 {
-        valid_states.push(PathState {
-            loc: state.loc.peek_shift(1, 0),
-            path: state.path.to_string() + "R",
-        });
+    for (state, digest) in states.iter().zip(digests) {
+        let (x, y) = (state.loc.x(), state.loc.y());
+        let moves = [
+            (b'U', 0, -1, y > 0),
+            (b'D', 0, 1, y < layout.height - 1),
+            (b'L', -1, 0, x > 0),
+            (b'R', 1, 0, x < layout.width - 1),
+        ];
+        for (nibble, (direction, dx, dy, in_grid)) in nibbles(&digest[..2]).zip(moves) {
+            if layout.is_door_open(nibble) && in_grid {
+                let mut path = Vec::with_capacity(state.path.len() + 1);
+                path.extend_from_slice(&state.path);
+                path.push(direction);
+                valid_states.push(PathState {
+                    loc: state.loc.peek_shift(dx, dy),
+                    path,
+                });
+            }
+        }
     }
     valid_states
 }
@@ -179,11 +366,18 @@ fn find_next_valid_states(vault_code: &str, state: &PathState) -> Vec<PathState>
 mod test {
     use super::*;
 
+    #[cfg(feature = "count-allocations")]
+    use aoc2016::utils::alloccount::CountingAllocator;
+
+    #[cfg(feature = "count-allocations")]
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
     /// Tests the Day 17 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day17_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, &VaultLayout::default());
         assert_eq!("RLDRUDRDDR", solution);
     }
 
@@ -191,7 +385,58 @@ mod test {
     #[test]
     fn test_day17_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, &VaultLayout::default());
         assert_eq!(498, solution);
     }
+
+    /// Tests the path statistics using the examples from the problem description.
+    #[test]
+    fn test_day17_path_statistics() {
+        let examples = [
+            ("ihgpwlah", "DDRRRD", 370),
+            ("kglvqrro", "DDUDRLRRUDRD", 492),
+            ("ulqzkmiv", "DRURDRUDDLLDLUURRDULRLDUUDDDRR", 830),
+        ];
+        let layout = VaultLayout::default();
+        for (vault_code, shortest, longest_length) in examples {
+            let paths = VaultPaths::new(vault_code.as_bytes(), &layout);
+            let stats = PathStatistics::from_paths(paths);
+            assert_eq!(Some(shortest.to_string()), stats.shortest);
+            assert_eq!(Some(longest_length), stats.longest_length);
+            assert_eq!(stats.count, stats.length_counts.values().sum::<usize>());
+        }
+        assert_eq!(0, VaultPaths::new(b"hijkl", &layout).count());
+    }
+
+    /// Tests the path search with alternative grid sizes and sets of open door characters.
+    #[test]
+    fn test_day17_layouts() {
+        // All doors are open, so the shortest path only depends on the direction order
+        let all_open = VaultLayout::new(3, 2, "0123456789abcdef");
+        assert_eq!(
+            Some("DRR".to_string()),
+            find_shortest_path_to_vault(b"ihgpwlah", &all_open)
+        );
+        // The start room is the vault room in a single room grid
+        let single_room = VaultLayout::new(1, 1, OPEN_CHARS);
+        assert_eq!(
+            Some(0),
+            find_longest_path_length_to_vault(b"ihgpwlah", &single_room)
+        );
+        // No doors are ever open
+        let all_closed = VaultLayout::new(GRID_SIZE, GRID_SIZE, "");
+        assert_eq!(None, find_shortest_path_to_vault(b"ihgpwlah", &all_closed));
+    }
+
+    /// Tests that the Day 17 solver methods do not make more allocations than their recorded limits.
+    #[cfg(all(feature = "count-allocations", not(feature = "parallel")))]
+    #[test]
+    fn test_day17_allocations() {
+        use aoc2016::utils::alloccount::assert_allocations_within;
+
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let layout = VaultLayout::default();
+        assert_allocations_within("part 1", 200, || solve_part1(&input, &layout));
+        assert_allocations_within("part 2", 100_000, || solve_part2(&input, &layout));
+    }
 }
@@ -1,8 +1,14 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::fs;
+use std::iter;
 use std::time::Instant;
 
-use fancy_regex::Regex;
-use lazy_static::lazy_static;
+use aoc2016::utils::animation;
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::params::Params;
+use aoc2016::utils::visualize::{self, Frame, FrameSink, Visualize};
 
 const PROBLEM_NAME: &str = "Like a Rogue";
 const PROBLEM_INPUT_FILE: &str = "./input/day18.txt";
@@ -10,122 +16,283 @@ const PROBLEM_DAY: u64 = 18;
 
 const PART1_TOTAL_ROWS: usize = 40;
 const PART2_TOTAL_ROWS: usize = 400000;
+/// Puzzle constants that can be overridden on the command line: the number of rows counted for each
+/// part.
+const PARAM_KEYS: [&str; 2] = ["part1-rows", "part2-rows"];
 
-lazy_static! {
-    static ref REGEX_TRAP: Regex = Regex::new(r"\^\^\.|\.\^\^|\^\.\.|\.\.\^").unwrap();
+/// Command line flag used to count the safe tiles in a given number of rows (which can be far larger
+/// than the row counts in the problem) using cycle detection.
+const ROWS_FLAG: &str = "--rows";
+
+/// Default number of frames per second when animating the map of the tile rows for Part 1, adding
+/// one row per frame.
+const ANIMATION_FPS: u32 = 20;
+
+/// Maximum number of tiles in a row that can be held in the row bitmask.
+const MAX_ROW_WIDTH: usize = 128;
+/// Maximum number of rows remembered while looking for a cycle in the rows. Rows beyond this are
+/// counted as they are generated, without being remembered.
+const MAX_REMEMBERED_ROWS: usize = 1 << 20;
+
+/// Represents a row of tiles, with bit i of the bitmask set if tile i (from the left) is a trap.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct TrapRow {
+    traps: u128,
+    width: usize,
+}
+
+impl fmt::Display for TrapRow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.width {
+            let tile = if self.is_trap(i) { '^' } else { '.' };
+            write!(f, "{tile}")?;
+        }
+        Ok(())
+    }
+}
+
+impl TrapRow {
+    /// Creates a row from the given string of safe ('.') and trap ('^') tiles. Returns None if the
+    /// string contains any other characters or has more than 128 tiles.
+    pub fn from_string(s: &str) -> Option<TrapRow> {
+        if s.len() > MAX_ROW_WIDTH {
+            return None;
+        }
+        let mut traps: u128 = 0;
+        for (i, c) in s.chars().enumerate() {
+            match c {
+                '^' => traps |= 1 << i,
+                '.' => (),
+                _ => return None,
+            }
+        }
+        Some(TrapRow {
+            traps,
+            width: s.len(),
+        })
+    }
+
+    /// Checks if the tile at the given index is a trap.
+    pub fn is_trap(&self, index: usize) -> bool {
+        self.traps & (1 << index) != 0
+    }
+
+    /// Gets the number of safe tiles in the row.
+    pub fn safe_tiles(&self) -> usize {
+        self.width - self.traps.count_ones() as usize
+    }
+
+    /// Generates the next row. A tile is a trap if exactly one of the tiles to its left and right in
+    /// the current row is a trap, with tiles beyond the ends of the row treated as safe.
+    pub fn next_row(&self) -> TrapRow {
+        let mask = u128::MAX
+            .checked_shr((MAX_ROW_WIDTH - self.width) as u32)
+            .unwrap_or(0);
+        TrapRow {
+            traps: ((self.traps << 1) ^ (self.traps >> 1)) & mask,
+            width: self.width,
+        }
+    }
+}
+
+/// Map of the first rows of tiles, visualized with the running total of safe tiles after each row.
+/// Each frame adds the next row to the map.
+struct TrapMap {
+    first_row: TrapRow,
+    total_rows: usize,
+}
+
+impl Visualize for TrapMap {
+    fn visualize(&self, sink: &mut dyn FrameSink) {
+        let mut text = String::new();
+        for (i, line) in render_trap_map(&self.first_row, self.total_rows).enumerate() {
+            text += &line;
+            text.push('\n');
+            let caption = format!(
+                "row {} of {} with running safe tile count",
+                i + 1,
+                self.total_rows
+            );
+            sink.frame(Frame::new(&caption, text.clone()));
+        }
+    }
 }
 
 /// Processes the AOC 2016 Day 18 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let params = Params::from_args(&PARAM_KEYS);
+    let p1_total_rows = params.get("part1-rows", PART1_TOTAL_ROWS);
+    let p2_total_rows = params.get("part2-rows", PART2_TOTAL_ROWS);
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, p1_total_rows);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, p2_total_rows);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print or animate the map of the Part 1 rows if requested
+    let trap_map = TrapMap {
+        first_row: input,
+        total_rows: p1_total_rows,
+    };
+    if visualize::is_requested() {
+        visualize::print_frames(&trap_map);
+    }
+    animation::run_if_requested(&trap_map, ANIMATION_FPS);
+    // Count the safe tiles for the requested number of rows
+    if let Some(total_rows) = read_total_rows() {
+        let safe_tiles = calculate_total_safe_tiles_cyclic(&input, total_rows, MAX_REMEMBERED_ROWS);
+        println!("[*] Safe tiles in {total_rows} rows: {safe_tiles}");
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 18 input file in the format required by the solver functions.
-/// Returned value is string given in the input file.
-fn process_input_file(filename: &str) -> String {
+/// Returned value is the first row of tiles given in the input file.
+fn process_input_file(filename: &str) -> TrapRow {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    raw_input.trim().to_string()
+    let raw_input = raw_input.trim();
+    match TrapRow::from_string(raw_input) {
+        Some(row) => row,
+        None => panic!("Bad first row of tiles! // {raw_input}"),
+    }
 }
 
-/// Solves AOC 2016 Day 18 Part 1 // Determines how many safe tiles there are in the first 40 rows.
-fn solve_part1(first_row: &str) -> usize {
-    calculate_total_safe_tiles(first_row, PART1_TOTAL_ROWS)
+/// Reads the number of rows given after the rows flag on the command line, if present.
+fn read_total_rows() -> Option<usize> {
+    let args = env::args().collect::<Vec<String>>();
+    let i = args.iter().position(|arg| arg == ROWS_FLAG)?;
+    match args.get(i + 1).map(|arg| arg.parse::<usize>()) {
+        Some(Ok(total_rows)) => Some(total_rows),
+        _ => panic!("Number of rows must be given after {ROWS_FLAG} flag!"),
+    }
+}
+
+/// Solves AOC 2016 Day 18 Part 1 // Determines how many safe tiles there are in the first 40 rows
+/// (or the given number of rows).
+fn solve_part1(first_row: &TrapRow, total_rows: usize) -> usize {
+    calculate_total_safe_tiles(first_row, total_rows)
 }
 
 /// Solves AOC 2016 Day 18 Part 2 // Determines how many safe tiles there are in the first 400,000
-/// rows.
-fn solve_part2(first_row: &str) -> usize {
-    calculate_total_safe_tiles(first_row, PART2_TOTAL_ROWS)
+/// rows (or the given number of rows).
+fn solve_part2(first_row: &TrapRow, total_rows: usize) -> usize {
+    calculate_total_safe_tiles(first_row, total_rows)
+}
+
+/// Returns an iterator over the rows of tiles, starting with the given first row.
+fn trap_rows(first_row: &TrapRow) -> impl Iterator<Item = TrapRow> {
+    iter::successors(Some(*first_row), |row| Some(row.next_row()))
+}
+
+/// Renders the given number of rows as lines of trap ('^') and safe ('.') tiles, each followed by
+/// the running total of safe tiles up to and including that row.
+fn render_trap_map(first_row: &TrapRow, total_rows: usize) -> impl Iterator<Item = String> {
+    trap_rows(first_row)
+        .take(total_rows)
+        .scan(0, |safe_tiles, row| {
+            *safe_tiles += row.safe_tiles();
+            Some(format!("{row}  {safe_tiles:>6}"))
+        })
 }
 
 /// Calculates the number of safe tiles there are in the given number of rows, starting from the
 /// given first row.
-fn calculate_total_safe_tiles(first_row: &str, total_rows: usize) -> usize {
-    // Calculate first row safe tile count and have total_rows edge cases
-    let mut total_safe_tiles = first_row.chars().filter(|c| *c == '.').count();
-    if total_rows == 0 {
-        return 0;
-    } else if total_rows == 1 {
-        return total_safe_tiles;
-    }
-    // Consider each row after the first
-    let mut prior_row = first_row.chars().collect::<Vec<char>>();
-    for _ in 1..total_rows {
-        let mut next_row: Vec<char> = vec![];
-        // Determine new character for next row for each character in the prior row
-        for i in 0..prior_row.len() {
-            let header = generate_header(&prior_row, i).unwrap();
-            match REGEX_TRAP.is_match(&header).unwrap() {
-                true => next_row.push('^'),
-                false => next_row.push('.'),
-            }
-        }
-        // Update the prior row and the total safe tiles count
-        prior_row = next_row;
-        total_safe_tiles += prior_row.iter().filter(|c| **c == '.').count();
-    }
-    total_safe_tiles
+fn calculate_total_safe_tiles(first_row: &TrapRow, total_rows: usize) -> usize {
+    trap_rows(first_row)
+        .take(total_rows)
+        .map(|row| row.safe_tiles())
+        .sum()
 }
 
-/// Genenerates the string representing the three characters from the prior row, centred around the
-/// given index. Indices outside of the prior row are treated as safe tiles.
-fn generate_header(prior_row: &[char], index: usize) -> Option<String> {
-    // Handle edge cases for index at start or end of prior row, or out-of-bounds
-    if index >= prior_row.len() {
-        return None;
-    } else if index == 0 {
-        return Some(format!(".{}{}", prior_row[index], prior_row[index + 1]));
-    } else if index == prior_row.len() - 1 {
-        return Some(format!("{}{}.", prior_row[index - 1], prior_row[index]));
-    }
-    // Standard case - index is within bounds and not at start or end of prior row
-    Some(format!(
-        "{}{}{}",
-        prior_row[index - 1],
-        prior_row[index],
-        prior_row[index + 1]
-    ))
+/// Calculates the number of safe tiles there are in the given number of rows, starting from the
+/// given first row. Rows are generated until either the row count is reached or a row repeats. Once
+/// a row repeats, the rows cycle, so the remaining rows are counted using the safe tiles in each
+/// cycle instead of being generated.
+///
+/// At most the given number of rows are remembered while looking for the cycle. If no row repeats
+/// within those rows (as for wide rows, where the cycle can be astronomically long), the remaining
+/// rows are generated and counted one at a time using constant memory.
+fn calculate_total_safe_tiles_cyclic(
+    first_row: &TrapRow,
+    total_rows: usize,
+    max_remembered_rows: usize,
+) -> usize {
+    let mut row_indices: HashMap<TrapRow, usize> = HashMap::new();
+    // Total safe tiles in the first i rows, for each i
+    let mut prefix_safe_tiles: Vec<usize> = vec![0];
+    for (i, row) in trap_rows(first_row).enumerate() {
+        if i == total_rows {
+            return prefix_safe_tiles[i];
+        }
+        if i == max_remembered_rows {
+            return prefix_safe_tiles[i]
+                + trap_rows(&row)
+                    .take(total_rows - i)
+                    .map(|row| row.safe_tiles())
+                    .sum::<usize>();
+        }
+        if let Some(&cycle_start) = row_indices.get(&row) {
+            let cycle_len = i - cycle_start;
+            let cycle_safe_tiles = prefix_safe_tiles[i] - prefix_safe_tiles[cycle_start];
+            let remaining_rows = total_rows - i;
+            let partial_cycle_safe_tiles = prefix_safe_tiles
+                [cycle_start + remaining_rows % cycle_len]
+                - prefix_safe_tiles[cycle_start];
+            return prefix_safe_tiles[i]
+                + (remaining_rows / cycle_len) * cycle_safe_tiles
+                + partial_cycle_safe_tiles;
+        }
+        row_indices.insert(row, i);
+        prefix_safe_tiles.push(prefix_safe_tiles[i] + row.safe_tiles());
+    }
+    unreachable!("Row iterator is infinite")
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::visualize::FrameRecorder;
+
     use super::*;
 
     /// Tests the Day 18 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day18_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, PART1_TOTAL_ROWS);
         assert_eq!(1974, solution);
     }
 
@@ -133,7 +300,72 @@ mod test {
     #[test]
     fn test_day18_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, PART2_TOTAL_ROWS);
         assert_eq!(19991126, solution);
     }
+
+    /// Tests the row generation and safe tile count using the example from the problem description.
+    #[test]
+    fn test_day18_example() {
+        let first_row = TrapRow::from_string(".^^.^.^^^^").unwrap();
+        let rows = trap_rows(&first_row)
+            .take(3)
+            .map(|row| row.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(vec![".^^.^.^^^^", "^^^...^..^", "^.^^.^.^^."], rows);
+        assert_eq!(38, calculate_total_safe_tiles(&first_row, 10));
+        assert_eq!(0, calculate_total_safe_tiles(&first_row, 0));
+        assert!(TrapRow::from_string("..x").is_none());
+        let map = render_trap_map(&first_row, 10).collect::<Vec<String>>();
+        assert_eq!(".^^.^.^^^^       3", map[0]);
+        assert_eq!("^^.^^^..^^      38", map[9]);
+        let mut recorder = FrameRecorder::default();
+        TrapMap {
+            first_row,
+            total_rows: 10,
+        }
+        .visualize(&mut recorder);
+        assert_eq!(10, recorder.frames().len());
+        assert_eq!(map[0].clone() + "\n", recorder.frames()[0].text());
+        assert_eq!(map.join("\n") + "\n", recorder.frames()[9].text());
+    }
+
+    /// Tests the cycle detection against direct simulation for small row counts, and for a huge row
+    /// count where every row is safe.
+    #[test]
+    fn test_day18_cyclic_safe_tiles() {
+        for first_row in [".^^.^.^^^^", "..^^.", "^", "^.^.^.^"] {
+            let first_row = TrapRow::from_string(first_row).unwrap();
+            for total_rows in 0..300 {
+                let expected = calculate_total_safe_tiles(&first_row, total_rows);
+                for max_remembered_rows in [4, MAX_REMEMBERED_ROWS] {
+                    assert_eq!(
+                        expected,
+                        calculate_total_safe_tiles_cyclic(
+                            &first_row,
+                            total_rows,
+                            max_remembered_rows
+                        )
+                    );
+                }
+            }
+        }
+        let safe_row = TrapRow::from_string(".....").unwrap();
+        assert_eq!(
+            5_000_000_000_000,
+            calculate_total_safe_tiles_cyclic(&safe_row, 1_000_000_000_000, MAX_REMEMBERED_ROWS)
+        );
+    }
+
+    /// Tests that a row count far beyond the rows remembered while looking for a cycle finishes for
+    /// the actual problem input, whose rows do not repeat within the remembered rows.
+    #[test]
+    fn test_day18_cyclic_safe_tiles_uncapped_cycle() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let total_rows = 4_000_000;
+        assert_eq!(
+            calculate_total_safe_tiles(&input, total_rows),
+            calculate_total_safe_tiles_cyclic(&input, total_rows, 1_000)
+        );
+    }
 }
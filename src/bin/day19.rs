@@ -1,42 +1,84 @@
 use std::collections::VecDeque;
+use std::env;
 use std::fs;
 use std::time::Instant;
 
+use aoc2016::utils::algorithms::AlgorithmRegistry;
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "An Elephant Named Joseph";
 const PROBLEM_INPUT_FILE: &str = "./input/day19.txt";
 const PROBLEM_DAY: u64 = 19;
 
+/// Command line flag used to print each step of both games for the (small) number of elves given
+/// after the flag.
+const VERBOSE_FLAG: &str = "--verbose";
+/// Largest number of elves that the verbose simulation will print the steps for.
+const MAX_VERBOSE_ELVES: usize = 100;
+
+/// Algorithm that finds the winning elf in the steal-opposite game for the given number of elves.
+type StealOppositeSolver = fn(usize) -> usize;
+
+/// Represents the two variants of the gift exchange game.
+#[derive(Clone, Copy)]
+enum GameVariant {
+    /// Elves steal the presents from the elf on their left (Part 1).
+    StealLeft,
+    /// Elves steal the presents from the elf directly opposite them in the circle (Part 2).
+    StealOpposite,
+}
+
 /// Processes the AOC 2016 Day 19 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let (_, algorithm) = steal_opposite_algorithms().select_from_args();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, algorithm);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the steps of each game for the requested number of elves
+    if let Some(num_elves) = read_verbose_elves() {
+        for (part, variant) in [(1, GameVariant::StealLeft), (2, GameVariant::StealOpposite)] {
+            println!("[*] Part {part} game with {num_elves} elves:");
+            for line in simulate_game_verbose(num_elves, variant) {
+                println!("    {line}");
+            }
+        }
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 19 input file in the format required by the solver functions.
@@ -48,6 +90,33 @@ fn process_input_file(filename: &str) -> usize {
     raw_input.trim().parse::<usize>().unwrap()
 }
 
+/// Gets the algorithms that can be used to find the winning elf in the steal-opposite game (used for
+/// Part 2), with the closed-form solution as the default.
+fn steal_opposite_algorithms() -> AlgorithmRegistry<StealOppositeSolver> {
+    AlgorithmRegistry::new(
+        "closed-form",
+        "calculates the winner directly using the powers-of-3 closed-form solution",
+        solve_elf_steal_opposite_closed_form as StealOppositeSolver,
+    )
+    .register(
+        "naive",
+        "simulates each elf being removed from the circle",
+        solve_elf_steal_opposite,
+    )
+}
+
+/// Reads the number of elves given after the verbose flag on the command line, if present.
+fn read_verbose_elves() -> Option<usize> {
+    let args = env::args().collect::<Vec<String>>();
+    let i = args.iter().position(|arg| arg == VERBOSE_FLAG)?;
+    match args.get(i + 1).map(|arg| arg.parse::<usize>()) {
+        Some(Ok(n)) if (1..=MAX_VERBOSE_ELVES).contains(&n) => Some(n),
+        _ => panic!(
+            "Number of elves (1 to {MAX_VERBOSE_ELVES}) must be given after {VERBOSE_FLAG} flag!"
+        ),
+    }
+}
+
 /// Solves AOC 2016 Day 19 Part 1 // Determines which elf ends up with all of the presents when the
 /// gift exchange game ends (where elves in play steal the presents from the elf on their left).
 /// The game has been modelled on the Josephus problem with k=2
@@ -59,8 +128,8 @@ fn solve_part1(num_elves: &usize) -> usize {
 /// Solves AOC 2016 Day 19 Part 2 // Determines which elf ens up with all of the presents when the
 /// gift exchange game ends (where the elves in play steal the presents from the elf directly
 /// opposite them in the circle).
-fn solve_part2(num_elves: &usize) -> usize {
-    solve_elf_steal_opposite(*num_elves)
+fn solve_part2(num_elves: &usize, algorithm: StealOppositeSolver) -> usize {
+    algorithm(*num_elves)
 }
 
 /// Provides the number of the last remaining place when the Josephus problem is solved for n with
@@ -71,33 +140,82 @@ fn solve_josephus_k2(n: usize) -> usize {
 
 /// Determines the place number of the last elf remaining at the end of the gift exchange game,
 /// where elves steal gifts from the elf opposite them in the circle.
+///
+/// With p being the largest power of 3 below n, the winner is n - p when n is at most 2p (each
+/// steal moves the winner one place along) or 2n - 3p otherwise (the winner moves two places along
+/// per steal). When n is a power of 3, the last elf wins.
+///
+/// Panics if there are no elves in the game.
+fn solve_elf_steal_opposite_closed_form(n: usize) -> usize {
+    assert!(n > 0, "Gift exchange game must have at least one elf!");
+    let mut power = 1;
+    while power * 3 < n {
+        power *= 3;
+    }
+    if n == 1 || n == power * 3 {
+        n
+    } else if n <= 2 * power {
+        n - power
+    } else {
+        2 * n - 3 * power
+    }
+}
+
+/// Determines the place number of the last elf remaining at the end of the gift exchange game,
+/// where elves steal gifts from the elf opposite them in the circle. Simulates the game using two
+/// deques holding each half of the circle.
 fn solve_elf_steal_opposite(n: usize) -> usize {
-    // Create the left and right halves of the circle
-    let mut right = VecDeque::from_iter(1..n / 2);
-    let mut left = VecDeque::from_iter((n / 2..=n).rev());
-    while !right.is_empty() && !left.is_empty() {
-        // Steal present from elf
-        if right.len() > left.len() {
-            right.pop_back();
-            if right.is_empty() {
-                break;
-            }
-        } else {
+    // Elf at the front of the left half is taking presents, with the elf opposite at the boundary
+    let mut left = VecDeque::from_iter(1..=n / 2);
+    let mut right = VecDeque::from_iter(n / 2 + 1..=n);
+    while left.len() + right.len() > 1 {
+        // Steal present from the elf opposite (the left one of the two opposite for odd counts)
+        if left.len() > right.len() {
             left.pop_back();
-            if left.is_empty() {
-                break;
-            }
+        } else {
+            right.pop_front();
         }
-        // Rotate elves counter-clockwise to account of the removed elf
-        left.push_front(right.pop_front().unwrap());
-        right.push_back(left.pop_back().unwrap());
+        if left.len() + right.len() == 1 {
+            break;
+        }
+        // Rotate elves around the circle so the next elf in play is at the front of the left half
+        right.push_back(left.pop_front().unwrap());
+        left.push_back(right.pop_front().unwrap());
     }
-    // Return the place number of left remaining elf
-    if right.is_empty() {
-        left.pop_front().unwrap()
-    } else {
-        right.pop_front().unwrap()
+    // Return the place number of the remaining elf
+    left.pop_front().or_else(|| right.pop_front()).unwrap()
+}
+
+/// Simulates the given variant of the gift exchange game, recording each steal and the circle of
+/// elves left in play after it (starting from the elf whose turn is next). The circle is held in a
+/// ring buffer rotated so the elf taking their turn is always at the front.
+fn simulate_game_verbose(n: usize, variant: GameVariant) -> Vec<String> {
+    let mut circle = VecDeque::from_iter(1..=n);
+    let mut steps: Vec<String> = vec![format!("start: {}", format_circle(&circle))];
+    while circle.len() > 1 {
+        let victim_index = match variant {
+            GameVariant::StealLeft => 1,
+            GameVariant::StealOpposite => circle.len() / 2,
+        };
+        let victim = circle.remove(victim_index).unwrap();
+        let taker = circle[0];
+        circle.rotate_left(1);
+        steps.push(format!(
+            "elf {taker} takes from elf {victim}: {}",
+            format_circle(&circle)
+        ));
     }
+    steps.push(format!("elf {} wins", circle[0]));
+    steps
+}
+
+/// Formats the circle of elves as their place numbers separated by spaces.
+fn format_circle(circle: &VecDeque<usize>) -> String {
+    circle
+        .iter()
+        .map(|elf| elf.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
 }
 
 #[cfg(test)]
@@ -116,7 +234,73 @@ mod test {
     #[test]
     fn test_day19_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, solve_elf_steal_opposite_closed_form);
         assert_eq!(1407007, solution);
     }
+
+    /// Tests that every registered algorithm solves Part 2, and that the closed-form solution for the
+    /// steal-opposite game matches the simulation for a sample of the numbers of elves up to 10^5
+    /// (see the ignored full range test for every number).
+    #[test]
+    fn test_day19_closed_form_matches_naive() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        for (name, algorithm) in steal_opposite_algorithms().iter() {
+            assert_eq!(1407007, solve_part2(&input, algorithm), "{name}");
+        }
+        for n in (1..=1000).chain((1000..=100_000).step_by(997)) {
+            assert_eq!(
+                solve_elf_steal_opposite(n),
+                solve_elf_steal_opposite_closed_form(n),
+                "n = {n}"
+            );
+        }
+    }
+
+    /// Tests that the closed-form solution for the steal-opposite game matches the simulation for
+    /// every number of elves up to 10^5. Ignored by default as it runs the simulation 10^5 times.
+    #[test]
+    #[ignore]
+    fn test_day19_closed_form_matches_naive_full_range() {
+        for n in 1..=100_000 {
+            assert_eq!(
+                solve_elf_steal_opposite(n),
+                solve_elf_steal_opposite_closed_form(n),
+                "n = {n}"
+            );
+        }
+    }
+
+    /// Tests that the closed-form solution rejects a game without any elves.
+    #[test]
+    #[should_panic(expected = "Gift exchange game must have at least one elf!")]
+    fn test_day19_closed_form_no_elves() {
+        solve_elf_steal_opposite_closed_form(0);
+    }
+
+    /// Tests the verbose simulation of both games using the examples from the problem description.
+    #[test]
+    fn test_day19_verbose_simulation() {
+        let steal_left = simulate_game_verbose(5, GameVariant::StealLeft);
+        assert_eq!("elf 1 takes from elf 2: 3 4 5 1", steal_left[1]);
+        assert_eq!("elf 3 wins", steal_left.last().unwrap());
+        let steal_opposite = simulate_game_verbose(5, GameVariant::StealOpposite);
+        assert_eq!(
+            vec![
+                "start: 1 2 3 4 5",
+                "elf 1 takes from elf 3: 2 4 5 1",
+                "elf 2 takes from elf 5: 4 1 2",
+                "elf 4 takes from elf 1: 2 4",
+                "elf 2 takes from elf 4: 2",
+                "elf 2 wins",
+            ],
+            steal_opposite
+        );
+        for n in 1..=MAX_VERBOSE_ELVES {
+            let winner = simulate_game_verbose(n, GameVariant::StealOpposite)
+                .pop()
+                .unwrap();
+            let expected = solve_elf_steal_opposite_closed_form(n);
+            assert_eq!(format!("elf {expected} wins"), winner);
+        }
+    }
 }
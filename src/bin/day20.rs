@@ -1,14 +1,28 @@
+use std::env;
 use std::fs;
 use std::ops::RangeInclusive;
 use std::time::Instant;
 
+use aoc2016::utils::bespoke::{parse_ranges, Firewall};
+use aoc2016::utils::events::EventStream;
+
 const PROBLEM_NAME: &str = "Firewall Rules";
 const PROBLEM_INPUT_FILE: &str = "./input/day20.txt";
 const PROBLEM_DAY: u64 = 20;
 
+/// Highest value in the address space covered by the firewall rules in the problem.
+const ADDRESS_SPACE_MAX: u64 = u32::MAX as u64;
+/// Command line flag used to export the merged blocked ranges and the allowed ranges as JSON to the
+/// file given after the flag.
+const EXPORT_RANGES_FLAG: &str = "--export-ranges";
+/// Command line flag used to print the allowed ranges as the minimal list of CIDR blocks.
+const CIDR_FLAG: &str = "--cidr";
+
 /// Processes the AOC 2016 Day 20 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -18,119 +32,79 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the allowed ranges as CIDR blocks or export the blocked and allowed ranges if requested
+    let args = env::args().collect::<Vec<String>>();
+    if args.iter().any(|arg| arg == CIDR_FLAG) {
+        for block in Firewall::new(&input, ADDRESS_SPACE_MAX).allowed_cidr_blocks() {
+            println!("{block}");
+        }
+    }
+    if let Some(i) = args.iter().position(|arg| arg == EXPORT_RANGES_FLAG) {
+        let Some(filename) = args.get(i + 1) else {
+            panic!("Missing output file after {EXPORT_RANGES_FLAG} flag!");
+        };
+        let firewall = Firewall::new(&input, ADDRESS_SPACE_MAX);
+        fs::write(filename, serde_json::to_string_pretty(&firewall).unwrap()).unwrap();
+        println!(
+            "[*] Exported {} blocked and {} allowed ranges (0 to {}) to {filename}",
+            firewall.blocked_ranges().len(),
+            firewall.allowed_ranges().len(),
+            firewall.max_value()
+        );
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 20 input file in the format required by the solver functions.
 /// Returned value is sorted vector of inclusive ranges given in the lines of the input file.
-fn process_input_file(filename: &str) -> Vec<RangeInclusive<u32>> {
+fn process_input_file(filename: &str) -> Vec<RangeInclusive<u64>> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    let mut ranges = raw_input
-        .lines()
-        .map(|line| line.trim().to_string())
-        .filter(|line| !line.is_empty())
-        .map(|line| {
-            line.split('-')
-                .map(|item| item.to_string())
-                .collect::<Vec<String>>()
-        })
-        .map(|split| split[0].parse::<u32>().unwrap()..=split[1].parse::<u32>().unwrap())
-        .collect::<Vec<RangeInclusive<u32>>>();
-    ranges.sort_by(|a, b| a.start().cmp(b.start()));
-    ranges
+    match parse_ranges(&raw_input) {
+        Ok(ranges) => ranges,
+        Err(e) => panic!("Bad firewall rules! // {e}"),
+    }
 }
 
 /// Solves AOC 2016 Day 20 Part 1 // Determines the lowest value that is not included in the given
 /// ranges.
-fn solve_part1(ranges: &[RangeInclusive<u32>]) -> u32 {
-    find_lowest_value_not_included(ranges).unwrap()
+fn solve_part1(ranges: &[RangeInclusive<u64>]) -> u64 {
+    Firewall::new(ranges, ADDRESS_SPACE_MAX)
+        .lowest_allowed()
+        .unwrap()
 }
 
 /// Solves AOC 2016 Day 20 Part 2 // Determines the total number of values that are not covered by
 /// the ranges.
-fn solve_part2(ranges: &[RangeInclusive<u32>]) -> usize {
-    find_total_allowed_values(ranges)
-}
-
-/// Finds the lowest value (u32) that is not included in the given ranges (sorted by start value).
-/// Returns None if the entire range of u32 values is covered.
-fn find_lowest_value_not_included(ranges: &[RangeInclusive<u32>]) -> Option<u32> {
-    let mut highest_end: Option<u32> = None;
-    for r in ranges {
-        if highest_end.is_none() {
-            // Check if there is a gap at the start of the u32 value range
-            if *r.start() > 0 {
-                return Some(0);
-            }
-            highest_end = Some(*r.end());
-        } else {
-            // Check if there is a gap between the highest end value and the range start
-            if *r.start() > highest_end.unwrap()
-                && u32::abs_diff(*r.start(), highest_end.unwrap()) >= 2
-            {
-                break;
-            }
-            // New highest end value has been observed
-            if *r.end() > highest_end.unwrap() {
-                highest_end = Some(*r.end());
-            }
-        }
-    }
-    // Check if there is a gap in u32 value range at the end of the ranges
-    if let Some(value) = highest_end {
-        if value < u32::MAX {
-            return Some(highest_end.unwrap() + 1);
-        }
-    }
-    None
-}
-
-/// Finds the total number of u32 values that are not included in the given ranges (sorted by start
-/// value).
-fn find_total_allowed_values(ranges: &[RangeInclusive<u32>]) -> usize {
-    let mut allowed_count: usize = u32::MAX as usize + 1;
-    let mut lowest_start: Option<u32> = None;
-    let mut highest_end: Option<u32> = None;
-    for r in ranges {
-        if highest_end.is_none() {
-            lowest_start = Some(*r.start());
-            highest_end = Some(*r.end());
-        } else if *r.start() > highest_end.unwrap()
-            && u32::abs_diff(*r.start(), highest_end.unwrap()) >= 2
-        {
-            // Reduce the allowed count by the size of the surpassed range
-            allowed_count -=
-                u32::abs_diff(lowest_start.unwrap(), highest_end.unwrap()) as usize + 1;
-            // Update the start and end trackers per the current range
-            lowest_start = Some(*r.start());
-            highest_end = Some(*r.end());
-        } else if *r.end() > highest_end.unwrap() {
-            highest_end = Some(*r.end());
-        }
-    }
-    // Adjust the allowed count for the final section of overlapping range
-    allowed_count -= u32::abs_diff(lowest_start.unwrap(), highest_end.unwrap()) as usize + 1;
-    allowed_count
+fn solve_part2(ranges: &[RangeInclusive<u64>]) -> u128 {
+    Firewall::new(ranges, ADDRESS_SPACE_MAX).allowed_count()
 }
 
 #[cfg(test)]
@@ -1,38 +1,255 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::fmt;
 use std::fs;
 use std::time::Instant;
 
-use fancy_regex::Regex;
-
 use aoc_utils::cartography::Point2D;
 
+use aoc2016::utils::animation;
+use aoc2016::utils::bespoke::{convert_nodes_to_tiles, parse_nodes, NodeData, NodeType};
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::grid::Grid;
+use aoc2016::utils::metrics;
+use aoc2016::utils::search::{self, SearchOutcome, SearchProblem, SearchStrategy};
+use aoc2016::utils::visualize::{self, Frame, FrameSink, Visualize};
+
 const PROBLEM_NAME: &str = "Grid Computing";
 const PROBLEM_INPUT_FILE: &str = "./input/day22.txt";
 const PROBLEM_DAY: u64 = 22;
 
-/// Lower bound of used percentage for nodes considered as Wall tiles.
-const WALL_NODE_USED_PCT: usize = 90;
+/// Default lower bound of used percentage for nodes considered as Wall tiles.
+const DEFAULT_WALL_NODE_USED_PCT: usize = 90;
+/// Command line flag used to set the lower bound of used percentage for nodes considered as Wall
+/// tiles, in place of the default.
+const WALL_THRESHOLD_FLAG: &str = "--wall-threshold";
+/// Default number of frames per second when animating the grid of nodes for each move that brings
+/// the goal data to the target node.
+const ANIMATION_FPS: u32 = 40;
+/// Command line flag used to print the data transfers that bring the goal data to the target node.
+const PLAN_FLAG: &str = "--plan";
 
-/// Represents the details for data held in a single node.
-#[derive(Copy, Clone)]
-struct NodeData {
-    _size: usize,     // Terabytes
-    used: usize,      // Terabytes
-    available: usize, // Terabytes
-    used_pct: usize,
+/// A move of all of the data held by one node into an adjacent node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct DataTransfer {
+    from: Point2D,
+    to: Point2D,
 }
 
-/// Used to model the the nodes based on their used percentage.
-#[derive(Clone, Copy, PartialEq, Eq)]
-enum NodeType {
-    Empty,       // Visitable
-    PartialUsed, // Visitable
-    Wall,        // Not visitable
+impl fmt::Display for DataTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "node-x{}-y{} -> node-x{}-y{}",
+            self.from.x(),
+            self.from.y(),
+            self.to.x(),
+            self.to.y()
+        )
+    }
+}
+
+/// Custom error type indicating that a data transfer in a move plan cannot be made.
+#[derive(Debug)]
+enum DataTransferError {
+    /// The transfer refers to a node that is not in the grid
+    UnknownNode { step: usize, loc: Point2D },
+    /// The transfer is between nodes that are not directly next to each other
+    NotAdjacent { step: usize },
+    /// The receiving node does not have enough space available for the data being moved
+    InsufficientSpace {
+        step: usize,
+        used: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for DataTransferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataTransferError::UnknownNode { step, loc } => {
+                write!(f, "move {step}: no node at ({}, {})", loc.x(), loc.y())
+            }
+            DataTransferError::NotAdjacent { step } => {
+                write!(f, "move {step}: nodes are not adjacent")
+            }
+            DataTransferError::InsufficientSpace {
+                step,
+                used,
+                available,
+            } => write!(
+                f,
+                "move {step}: {used}T of data does not fit in {available}T available"
+            ),
+        }
+    }
+}
+
+/// Custom error type indicating that the grid of nodes does not have the structure needed to move
+/// the goal data to the target node.
+#[derive(Debug)]
+enum GridStructureError {
+    /// The grid does not contain any nodes
+    NoNodes,
+    /// No node is empty, so no data can be moved
+    NoEmptyNode,
+    /// More than one node is empty, which the move search does not model
+    MultipleEmptyNodes { count: usize },
+    /// The goal data is held by a wall node, so it is too large to be moved
+    ImmovableGoalData,
+    /// The goal data cannot be moved to the target node (for example, if walls block the way)
+    GoalDataUnreachable,
+}
+
+impl fmt::Display for GridStructureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GridStructureError::NoNodes => write!(f, "grid has no nodes"),
+            GridStructureError::NoEmptyNode => write!(f, "grid has no empty node"),
+            GridStructureError::MultipleEmptyNodes { count } => {
+                write!(f, "grid has {count} empty nodes, expected exactly one")
+            }
+            GridStructureError::ImmovableGoalData => {
+                write!(f, "goal data is held by a wall node and cannot be moved")
+            }
+            GridStructureError::GoalDataUnreachable => {
+                write!(f, "goal data cannot reach the target node")
+            }
+        }
+    }
+}
+
+/// Locations of the empty node and the goal data while the goal data is moved to the target node.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GridState {
+    loc_empty: Point2D,
+    loc_goal_data: Point2D,
+}
+
+/// Grid of node tiles that has been checked to have a single empty node and movable goal data,
+/// along with the initial locations of the empty node and goal data.
+struct NodeGrid {
+    node_tiles: Grid<NodeType>,
+    initial_state: GridState,
+    loc_target: Point2D,
+}
+
+impl NodeGrid {
+    /// Converts the nodes into a grid of node tiles, with nodes at or above the given used
+    /// percentage treated as walls. The goal data is held by the node with y=0 and the highest x
+    /// value, and the target node is (0, 0).
+    ///
+    /// Returns an error if the grid does not have exactly one empty node, or the goal data is held
+    /// by a wall node.
+    fn new(
+        nodes: &HashMap<Point2D, NodeData>,
+        wall_used_pct: usize,
+    ) -> Result<NodeGrid, GridStructureError> {
+        let Some(node_tiles) = convert_nodes_to_tiles(nodes, wall_used_pct) else {
+            return Err(GridStructureError::NoNodes);
+        };
+        let loc_goal_data = Point2D::new(node_tiles.width() as i64 - 1, 0);
+        if node_tiles.get(&loc_goal_data) != Some(&NodeType::PartialUsed) {
+            return Err(GridStructureError::ImmovableGoalData);
+        }
+        let empty_nodes = node_tiles
+            .iter()
+            .filter(|(_loc, tile)| **tile == NodeType::Empty)
+            .map(|(loc, _tile)| loc)
+            .collect::<Vec<Point2D>>();
+        let loc_empty = match empty_nodes.len() {
+            0 => return Err(GridStructureError::NoEmptyNode),
+            1 => empty_nodes[0],
+            count => return Err(GridStructureError::MultipleEmptyNodes { count }),
+        };
+        Ok(NodeGrid {
+            node_tiles,
+            initial_state: GridState {
+                loc_empty,
+                loc_goal_data,
+            },
+            loc_target: Point2D::new(0, 0),
+        })
+    }
+}
+
+impl Visualize for NodeGrid {
+    /// Renders the grid in the style of the problem description before and after each move of the
+    /// minimal plan that brings the goal data to the target node. Only the initial grid is rendered
+    /// if the goal data cannot reach the target node.
+    fn visualize(&self, sink: &mut dyn FrameSink) {
+        let Some(outcome) = search_goal_data_to_target(self) else {
+            sink.frame(Frame::new(
+                "goal data cannot reach the target node",
+                render_grid(&self.node_tiles, &self.initial_state),
+            ));
+            return;
+        };
+        for (i, state) in outcome.path().iter().enumerate() {
+            let caption = match i {
+                0 => String::from("initial grid"),
+                _ => format!("move {i} of {}", outcome.moves()),
+            };
+            sink.frame(Frame::new(&caption, render_grid(&self.node_tiles, state)));
+        }
+    }
+}
+
+/// Search problem for moving the goal data to the target node by moving the empty node around the
+/// grid.
+struct GoalDataSearch<'a> {
+    grid: &'a NodeGrid,
+}
+
+impl SearchProblem for GoalDataSearch<'_> {
+    type State = GridState;
+    type Key = GridState;
+
+    fn key(&self, state: &GridState) -> GridState {
+        *state
+    }
+
+    fn initial_state(&self) -> GridState {
+        self.grid.initial_state
+    }
+
+    fn is_goal(&self, state: &GridState) -> bool {
+        state.loc_goal_data == self.grid.loc_target
+    }
+
+    fn successors(&self, state: &GridState) -> Vec<GridState> {
+        get_next_valid_locations(&self.grid.node_tiles, &state.loc_empty)
+            .into_iter()
+            .map(|loc_next| GridState {
+                loc_empty: loc_next,
+                loc_goal_data: if loc_next == state.loc_goal_data {
+                    state.loc_empty
+                } else {
+                    state.loc_goal_data
+                },
+            })
+            .collect()
+    }
+
+    /// The goal data needs at least one move per step to the target, and the empty node must first
+    /// be moved next to the goal data.
+    fn heuristic(&self, state: &GridState) -> usize {
+        if state.loc_goal_data == self.grid.loc_target {
+            return 0;
+        }
+        let goal_to_target = state
+            .loc_goal_data
+            .get_manhattan_distance(&self.grid.loc_target);
+        let empty_to_goal = state.loc_empty.get_manhattan_distance(&state.loc_goal_data);
+        (goal_to_target + empty_to_goal - 1) as usize
+    }
 }
 
 /// Processes the AOC 2016 Day 22 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -42,25 +259,64 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let wall_used_pct = read_wall_threshold().unwrap_or(DEFAULT_WALL_NODE_USED_PCT);
+    let p2_solution = solve_part2(&input, wall_used_pct);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print or animate the grid of nodes if requested
+    let args = env::args().collect::<Vec<String>>();
+    let grid = NodeGrid::new(&input, wall_used_pct).unwrap();
+    if visualize::is_requested() {
+        visualize::print_frames(&grid);
+    }
+    animation::run_if_requested(&grid, ANIMATION_FPS);
+    if args.iter().any(|arg| arg == PLAN_FLAG) {
+        print_data_transfer_plan(&input, &grid);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
+}
+
+/// Reads the lower bound of used percentage for wall nodes given after the wall threshold flag on
+/// the command line, if the flag is present.
+fn read_wall_threshold() -> Option<usize> {
+    let args = env::args().collect::<Vec<String>>();
+    let i = args.iter().position(|arg| arg == WALL_THRESHOLD_FLAG)?;
+    match args.get(i + 1).map(|arg| arg.parse::<usize>()) {
+        Some(Ok(wall_used_pct)) => Some(wall_used_pct),
+        _ => panic!("Used percentage must be given after {WALL_THRESHOLD_FLAG} flag!"),
+    }
 }
 
 /// Processes the AOC 2016 Day 22 input file in the format required by the solver functions.
@@ -70,37 +326,7 @@ fn process_input_file(filename: &str) -> HashMap<Point2D, NodeData> {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    let regex_line =
-        Regex::new(r"^/dev/grid/node-x(\d+)-y(\d+)\s+(\d+)T\s+(\d+)T\s+(\d+)T\s+(\d+)%$").unwrap();
-    let mut output: HashMap<Point2D, NodeData> = HashMap::new();
-    for line in raw_input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .skip(2)
-    {
-        if let Ok(Some(caps)) = regex_line.captures(line) {
-            // Extract location and NodeData details from the input line
-            let x = caps[1].parse::<i64>().unwrap();
-            let y = caps[2].parse::<i64>().unwrap();
-            let size = caps[3].parse::<usize>().unwrap();
-            let used = caps[4].parse::<usize>().unwrap();
-            let available = caps[5].parse::<usize>().unwrap();
-            let used_pct = caps[6].parse::<usize>().unwrap();
-            // Create key and value
-            let loc = Point2D::new(x, y);
-            let node_data = NodeData {
-                _size: size,
-                used,
-                available,
-                used_pct,
-            };
-            output.insert(loc, node_data);
-        } else {
-            panic!("Bad format input line! // {line}");
-        }
-    }
-    output
+    parse_nodes(&raw_input)
 }
 
 /// Solves AOC 2016 Day 22 Part 1 // Determines the number of viable pairs of nodes.
@@ -110,8 +336,11 @@ fn solve_part1(nodes: &HashMap<Point2D, NodeData>) -> usize {
 
 /// Solves AOC 2016 Day 22 Part 2 // Determines the minimum number of moves required to move the
 /// data at the location with y=0 and the highest x value to the location (0, 0).
-fn solve_part2(nodes: &HashMap<Point2D, NodeData>) -> usize {
-    find_minimum_steps_from_goal_to_target(nodes)
+fn solve_part2(nodes: &HashMap<Point2D, NodeData>, wall_used_pct: usize) -> usize {
+    match find_minimum_steps_from_goal_to_target(nodes, wall_used_pct) {
+        Ok(steps) => steps,
+        Err(e) => panic!("Cannot move the goal data to the target node! // {e}"),
+    }
 }
 
 /// Determines the number of viable pairs of nodes.
@@ -119,12 +348,12 @@ fn count_viable_pairs(nodes: &HashMap<Point2D, NodeData>) -> usize {
     let mut viable_pairs = 0;
     for (a_loc, a_node_data) in nodes.iter() {
         // Pair is node viable is Node A is empty
-        if a_node_data.used == 0 {
+        if a_node_data.used() == 0 {
             continue;
         }
         // Check if Node B has enough available space to fit the Node A used space
         for (_, b_node_data) in nodes.iter().filter(|(k, _)| *k != a_loc) {
-            if b_node_data.available >= a_node_data.used {
+            if b_node_data.available() >= a_node_data.used() {
                 viable_pairs += 1;
             }
         }
@@ -133,114 +362,237 @@ fn count_viable_pairs(nodes: &HashMap<Point2D, NodeData>) -> usize {
 }
 
 /// Determines the minimum number of moves required to move the data from the goal node (y=0 and
-/// highest x value) to the target node (0, 0).
-fn find_minimum_steps_from_goal_to_target(nodes: &HashMap<Point2D, NodeData>) -> usize {
-    // Convert the node data map into the node tile map
-    let node_tiles = convert_nodes_to_tiles(nodes);
+/// highest x value) to the target node (0, 0), with nodes at or above the given used percentage
+/// treated as walls. Returns an error if the grid structure prevents the goal data from being moved
+/// to the target node.
+///
+/// When the grid has the structure of the puzzle input (no walls in the top two rows), the goal data
+/// is walked along the top row. Otherwise (or if the walk fails), the exact minimum is found by
+/// searching the space of empty node and goal data locations.
+fn find_minimum_steps_from_goal_to_target(
+    nodes: &HashMap<Point2D, NodeData>,
+    wall_used_pct: usize,
+) -> Result<usize, GridStructureError> {
+    let grid = NodeGrid::new(nodes, wall_used_pct)?;
+    has_walkable_structure(&grid)
+        .then(|| walk_goal_data_to_target(&grid))
+        .flatten()
+        .or_else(|| search_goal_data_to_target(&grid).map(|outcome| outcome.moves()))
+        .ok_or(GridStructureError::GoalDataUnreachable)
+}
+
+/// Checks if the grid has the structure that allows the goal data to be walked along the top row to
+/// the target: no wall nodes in the top two rows. With this structure, the empty node can always be
+/// moved around the goal data to the front of it.
+fn has_walkable_structure(grid: &NodeGrid) -> bool {
+    let top_wall = grid
+        .node_tiles
+        .iter()
+        .any(|(loc, tile)| loc.y() <= 1 && *tile == NodeType::Wall);
+    let single_row = grid.node_tiles.height() == 1;
+    !top_wall && !single_row
+}
+
+/// Determines the number of moves needed to walk the goal data along its shortest path to the
+/// target, by repeatedly moving the empty node to the next location on the path (without disturbing
+/// the goal data) and then moving the goal data into the empty node. This is only guaranteed to be
+/// the minimum number of moves for grids with the structure checked by has_walkable_structure.
+fn walk_goal_data_to_target(grid: &NodeGrid) -> Option<usize> {
+    let node_tiles = &grid.node_tiles;
+    let mut loc_goal_data = grid.initial_state.loc_goal_data;
+    let mut loc_empty = grid.initial_state.loc_empty;
     let mut steps: usize = 0;
     // Determine the shortest path between the goal data node and the target node
-    let max_x = node_tiles.keys().map(|loc| loc.x()).max().unwrap();
-    let mut loc_goal_data = Point2D::new(max_x, 0);
-    let loc_target = Point2D::new(0, 0);
-    let mut shortest_path =
-        find_shortest_path(&node_tiles, &loc_goal_data, &loc_target, None).unwrap();
+    let mut shortest_path = find_shortest_path(node_tiles, &loc_goal_data, &grid.loc_target, None)?;
     shortest_path.pop_front();
-    // Find the initial location of the empty node
-    let mut loc_empty = *node_tiles
-        .iter()
-        .filter(|(_loc, tile)| **tile == NodeType::Empty)
-        .map(|(loc, _tile)| loc)
-        .next()
-        .unwrap();
     // Keep moving the empty node to the next location in the goal shortest path to target
-    while !shortest_path.is_empty() {
+    while let Some(loc_next) = shortest_path.pop_front() {
         // Find the shortest path between the empty location and next location on goal shortest path
-        let sp_empty_to_goal = find_shortest_path(
-            &node_tiles,
-            &loc_empty,
-            &shortest_path.pop_front().unwrap(),
-            Some(&loc_goal_data),
-        )
-        .unwrap();
+        let sp_empty_to_goal =
+            find_shortest_path(node_tiles, &loc_empty, &loc_next, Some(&loc_goal_data))?;
         // Move the goal data into the empty location, and update empty location
         loc_empty = loc_goal_data;
         loc_goal_data = *sp_empty_to_goal.back().unwrap();
         // Increase steps for empty node moving in front of goal, and goal moving into empty loc
         steps += sp_empty_to_goal.len();
     }
-    // Move goal node to next location on shortest path
-    steps
+    Some(steps)
 }
 
-/// Converts the node data map into a node tile map.
-fn convert_nodes_to_tiles(nodes: &HashMap<Point2D, NodeData>) -> HashMap<Point2D, NodeType> {
-    let mut output: HashMap<Point2D, NodeType> = HashMap::new();
-    for (&loc, &node_data) in nodes.iter() {
-        if node_data.used_pct == 0 {
-            output.insert(loc, NodeType::Empty);
-        } else if node_data.used_pct < WALL_NODE_USED_PCT {
-            output.insert(loc, NodeType::PartialUsed);
-        } else {
-            output.insert(loc, NodeType::Wall);
+/// Searches for the minimum sequence of moves that brings the goal data to the target node. Each
+/// move swaps the empty node with an adjacent non-wall node, moving the goal data if it is held by
+/// that node. Returns None if the target cannot be reached.
+fn search_goal_data_to_target(grid: &NodeGrid) -> Option<SearchOutcome<GridState>> {
+    search::search_explained(&GoalDataSearch { grid }, SearchStrategy::AStar)
+}
+
+/// Plans the minimal sequence of data transfers that brings the goal data to the target node.
+/// Returns None if the goal data cannot be moved to the target node.
+fn plan_data_transfers(grid: &NodeGrid) -> Option<Vec<DataTransfer>> {
+    let outcome = search_goal_data_to_target(grid)?;
+    // Each move fills the empty node with the data from the node it swaps with
+    let transfers = outcome
+        .path()
+        .windows(2)
+        .map(|states| DataTransfer {
+            from: states[1].loc_empty,
+            to: states[0].loc_empty,
+        })
+        .collect();
+    Some(transfers)
+}
+
+/// Replays the data transfers against the nodes, checking that each transfer is between adjacent
+/// nodes and that the receiving node has enough space available. Returns the final location of the
+/// goal data (initially held by the node with y=0 and the highest x value).
+fn replay_data_transfers(
+    nodes: &HashMap<Point2D, NodeData>,
+    transfers: &[DataTransfer],
+) -> Result<Point2D, DataTransferError> {
+    let mut nodes = nodes.clone();
+    let max_x = nodes.keys().map(|loc| loc.x()).max().unwrap_or(0);
+    let mut loc_goal_data = Point2D::new(max_x, 0);
+    for (i, transfer) in transfers.iter().enumerate() {
+        let step = i + 1;
+        for loc in [transfer.from, transfer.to] {
+            if !nodes.contains_key(&loc) {
+                return Err(DataTransferError::UnknownNode { step, loc });
+            }
+        }
+        if transfer.from.get_manhattan_distance(&transfer.to) != 1 {
+            return Err(DataTransferError::NotAdjacent { step });
         }
+        let (sender, receiver) = (nodes[&transfer.from], nodes[&transfer.to]);
+        let used = sender.used();
+        if receiver.size() - receiver.used() < used {
+            return Err(DataTransferError::InsufficientSpace {
+                step,
+                used,
+                available: receiver.size() - receiver.used(),
+            });
+        }
+        nodes.insert(
+            transfer.to,
+            NodeData::new(receiver.size(), receiver.used() + used),
+        );
+        nodes.insert(transfer.from, NodeData::new(sender.size(), 0));
+        if transfer.from == loc_goal_data {
+            loc_goal_data = transfer.to;
+        }
+    }
+    Ok(loc_goal_data)
+}
+
+/// Prints the data transfers that bring the goal data to the target node, after checking them by
+/// replaying the transfers against the node capacities.
+fn print_data_transfer_plan(nodes: &HashMap<Point2D, NodeData>, grid: &NodeGrid) {
+    let Some(transfers) = plan_data_transfers(grid) else {
+        println!("[!] Goal data cannot be moved to the target node");
+        return;
+    };
+    for (i, transfer) in transfers.iter().enumerate() {
+        println!("{:>4}: {transfer}", i + 1);
     }
-    output
+    match replay_data_transfers(nodes, &transfers) {
+        Ok(loc) => println!(
+            "[*] Plan verified: goal data reaches ({}, {}) in {} moves",
+            loc.x(),
+            loc.y(),
+            transfers.len()
+        ),
+        Err(e) => println!("[!] Plan failed verification: {e}"),
+    }
+}
+
+/// Renders the grid of nodes in the style of the problem description, with one character per node:
+/// "G" for the goal data, "_" for the empty node, "#" for wall nodes and "." for the other nodes.
+fn render_grid(node_tiles: &Grid<NodeType>, state: &GridState) -> String {
+    node_tiles.render(|loc, tile| {
+        if loc == state.loc_goal_data {
+            'G'
+        } else if loc == state.loc_empty {
+            '_'
+        } else {
+            match tile {
+                NodeType::Wall => '#',
+                NodeType::Empty | NodeType::PartialUsed => '.',
+            }
+        }
+    })
 }
 
 /// Finds the shorted path between the start and end locations. Any nodes locations that are equal
-/// to the exclude node or are wall tiles cannot be visited.
+/// to the exclude node or are wall tiles cannot be visited. The path holds only the start location
+/// if it is also the end location.
+///
+/// The location each node was first reached from is recorded in a grid the same size as the node
+/// tiles, so the path is only built once the end location has been reached.
 fn find_shortest_path(
-    node_tiles: &HashMap<Point2D, NodeType>,
+    node_tiles: &Grid<NodeType>,
     loc_start: &Point2D,
     loc_end: &Point2D,
     exclude: Option<&Point2D>,
 ) -> Option<VecDeque<Point2D>> {
-    let mut visit_queue: VecDeque<VecDeque<Point2D>> =
-        VecDeque::from([VecDeque::from([*loc_start])]);
-    let mut visited: HashSet<Point2D> = HashSet::from([*loc_start]);
-    while !visit_queue.is_empty() {
-        let path = visit_queue.pop_front().unwrap();
-        for next_loc in get_next_valid_locations(node_tiles, path.back().unwrap()) {
+    if loc_start == loc_end {
+        return Some(VecDeque::from([*loc_start]));
+    }
+    let mut visit_queue: VecDeque<Point2D> = VecDeque::from([*loc_start]);
+    let mut predecessors: Grid<Option<Point2D>> =
+        Grid::new(node_tiles.width(), node_tiles.height(), None);
+    *predecessors.get_mut(loc_start)? = Some(*loc_start);
+    while let Some(loc) = visit_queue.pop_front() {
+        for next_loc in get_next_valid_locations(node_tiles, &loc) {
             // Don't visit node already visited or the excluded node
-            if visited.contains(&next_loc) || exclude.is_some() && *exclude.unwrap() == next_loc {
+            if predecessors[next_loc].is_some() || exclude == Some(&next_loc) {
                 continue;
             }
-            // Create new path and check if the end location has been reached
-            let mut new_path = path.clone();
-            new_path.push_back(next_loc);
+            // Record the next location as visited and check if the end location has been reached
+            predecessors[next_loc] = Some(loc);
             if next_loc == *loc_end {
-                return Some(new_path);
+                let mut path = VecDeque::from([next_loc]);
+                while path[0] != *loc_start {
+                    path.push_front(predecessors[path[0]].unwrap());
+                }
+                return Some(path);
             }
-            // Record the next location as visited
-            visited.insert(next_loc);
-            visit_queue.push_back(new_path);
+            visit_queue.push_back(next_loc);
         }
     }
     None
 }
 
 /// Gets the next valid locations when conducting BFS of node tile map.
-fn get_next_valid_locations(
-    node_tiles: &HashMap<Point2D, NodeType>,
-    loc: &Point2D,
-) -> Vec<Point2D> {
-    let mut output: Vec<Point2D> = vec![];
+fn get_next_valid_locations(node_tiles: &Grid<NodeType>, loc: &Point2D) -> Vec<Point2D> {
     // Record Empty and PartialUsed locations as valid for visiting
-    for next_loc in loc.get_adjacent_points() {
-        if let Some(tile) = node_tiles.get(&next_loc) {
-            match tile {
-                NodeType::Empty | NodeType::PartialUsed => output.push(next_loc),
-                NodeType::Wall => (),
-            }
-        }
-    }
-    output
+    node_tiles
+        .neighbours(loc)
+        .into_iter()
+        .filter(|next_loc| node_tiles[*next_loc] != NodeType::Wall)
+        .collect()
 }
 
 #[cfg(test)]
 mod test {
+    use aoc2016::utils::visualize::FrameRecorder;
+
     use super::*;
 
+    #[cfg(feature = "test-support")]
+    use aoc2016::test_support::node_table;
+
+    /// Output of the "df" command for the example grid from the problem description.
+    const EXAMPLE_INPUT: &str = "root@ebhq-gridcenter# df -h\n\
+        Filesystem            Size  Used  Avail  Use%\n\
+        /dev/grid/node-x0-y0   10T    8T     2T   80%\n\
+        /dev/grid/node-x0-y1   11T    6T     5T   54%\n\
+        /dev/grid/node-x0-y2   32T   28T     4T   87%\n\
+        /dev/grid/node-x1-y0    9T    7T     2T   77%\n\
+        /dev/grid/node-x1-y1    8T    0T     8T    0%\n\
+        /dev/grid/node-x1-y2   11T    7T     4T   63%\n\
+        /dev/grid/node-x2-y0   10T    6T     4T   60%\n\
+        /dev/grid/node-x2-y1    9T    8T     1T   88%\n\
+        /dev/grid/node-x2-y2    9T    6T     3T   66%\n";
+
     /// Tests the Day 22 Part 1 solver method against the actual problem solution.
     #[test]
     fn test_day22_part1_actual() {
@@ -253,7 +605,164 @@ mod test {
     #[test]
     fn test_day22_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, DEFAULT_WALL_NODE_USED_PCT);
         assert_eq!(225, solution);
     }
+
+    /// Tests the Day 22 Part 2 solver against the example from the problem description.
+    #[test]
+    fn test_day22_part2_example() {
+        let nodes = parse_nodes(EXAMPLE_INPUT);
+        let steps = find_minimum_steps_from_goal_to_target(&nodes, DEFAULT_WALL_NODE_USED_PCT);
+        assert_eq!(7, steps.unwrap());
+        let grid = NodeGrid::new(&nodes, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        assert_eq!(7, search_goal_data_to_target(&grid).unwrap().moves());
+    }
+
+    /// Tests that the example grid assembled directly from node sizes and used space matches the
+    /// parsed example, and that moving the empty node changes the number of steps needed.
+    #[test]
+    fn test_day22_assembled_nodes() {
+        let build_nodes = |columns: &[[(usize, usize); 3]]| {
+            let mut nodes: HashMap<Point2D, NodeData> = HashMap::new();
+            for (x, column) in columns.iter().enumerate() {
+                for (y, &(size, used)) in column.iter().enumerate() {
+                    nodes.insert(Point2D::new(x as i64, y as i64), NodeData::new(size, used));
+                }
+            }
+            nodes
+        };
+        let mut columns = [
+            [(10, 8), (11, 6), (32, 28)],
+            [(9, 7), (8, 0), (11, 7)],
+            [(10, 6), (9, 8), (9, 6)],
+        ];
+        assert_eq!(parse_nodes(EXAMPLE_INPUT), build_nodes(&columns));
+        // Swap the empty node with its neighbour to the left
+        columns[1][1] = (8, 6);
+        columns[0][1] = (11, 0);
+        let nodes = build_nodes(&columns);
+        let steps = find_minimum_steps_from_goal_to_target(&nodes, DEFAULT_WALL_NODE_USED_PCT);
+        assert_eq!(8, steps.unwrap());
+    }
+
+    /// Tests walking the goal data when the empty node starts directly in front of it, using a
+    /// variation of the example from the problem description.
+    #[test]
+    fn test_day22_empty_node_next_to_goal_data() {
+        let mut nodes = parse_nodes(EXAMPLE_INPUT);
+        nodes.insert(Point2D::new(1, 0), NodeData::new(9, 0));
+        nodes.insert(Point2D::new(1, 1), NodeData::new(8, 7));
+        let grid = NodeGrid::new(&nodes, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        assert!(has_walkable_structure(&grid));
+        assert_eq!(Some(6), walk_goal_data_to_target(&grid));
+        assert_eq!(6, search_goal_data_to_target(&grid).unwrap().moves());
+        let steps = find_minimum_steps_from_goal_to_target(&nodes, DEFAULT_WALL_NODE_USED_PCT);
+        assert_eq!(6, steps.unwrap());
+    }
+
+    /// Tests that grids without a single empty node or with goal data that cannot reach the target
+    /// node are reported as errors, using variations of the example from the problem description.
+    #[test]
+    fn test_day22_grid_structure_errors() {
+        let mut nodes = parse_nodes(EXAMPLE_INPUT);
+        // The goal data node (60% used) becomes a wall
+        assert!(matches!(
+            find_minimum_steps_from_goal_to_target(&nodes, 50),
+            Err(GridStructureError::ImmovableGoalData)
+        ));
+        // The target node (80% used) becomes a wall
+        assert!(matches!(
+            find_minimum_steps_from_goal_to_target(&nodes, 70),
+            Err(GridStructureError::GoalDataUnreachable)
+        ));
+        nodes.insert(Point2D::new(0, 1), NodeData::new(11, 0));
+        assert!(matches!(
+            find_minimum_steps_from_goal_to_target(&nodes, DEFAULT_WALL_NODE_USED_PCT),
+            Err(GridStructureError::MultipleEmptyNodes { count: 2 })
+        ));
+        nodes.insert(Point2D::new(0, 1), NodeData::new(11, 6));
+        nodes.insert(Point2D::new(1, 1), NodeData::new(8, 4));
+        assert!(matches!(
+            find_minimum_steps_from_goal_to_target(&nodes, DEFAULT_WALL_NODE_USED_PCT),
+            Err(GridStructureError::NoEmptyNode)
+        ));
+    }
+
+    /// Tests that the move plan for the example grid replays within the node capacities, and that
+    /// replaying an invalid plan fails.
+    #[test]
+    fn test_day22_data_transfer_plan() {
+        let nodes = parse_nodes(EXAMPLE_INPUT);
+        let grid = NodeGrid::new(&nodes, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        let transfers = plan_data_transfers(&grid).unwrap();
+        assert_eq!(7, transfers.len());
+        assert_eq!(
+            DataTransfer {
+                from: Point2D::new(1, 0),
+                to: Point2D::new(1, 1)
+            },
+            transfers[0]
+        );
+        assert_eq!(
+            Point2D::new(0, 0),
+            replay_data_transfers(&nodes, &transfers).unwrap()
+        );
+        let bad_transfer = DataTransfer {
+            from: Point2D::new(0, 2),
+            to: Point2D::new(1, 2),
+        };
+        assert!(matches!(
+            replay_data_transfers(&nodes, &[bad_transfer]),
+            Err(DataTransferError::InsufficientSpace { step: 1, .. })
+        ));
+    }
+
+    /// Tests the rendering of the grid for the example from the problem description, modified so the
+    /// bottom-left node is a wall node.
+    #[test]
+    fn test_day22_render_grid() {
+        let mut nodes = parse_nodes(EXAMPLE_INPUT);
+        nodes.insert(Point2D::new(0, 2), NodeData::new(32, 31));
+        let node_tiles = convert_nodes_to_tiles(&nodes, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        let state = GridState {
+            loc_empty: Point2D::new(1, 1),
+            loc_goal_data: Point2D::new(2, 0),
+        };
+        assert_eq!("..G\n._.\n#..\n", render_grid(&node_tiles, &state));
+        let grid = NodeGrid::new(&nodes, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        let mut recorder = FrameRecorder::default();
+        grid.visualize(&mut recorder);
+        let frames = recorder.frames();
+        assert_eq!(8, frames.len());
+        assert_eq!("..G\n._.\n#..\n", frames[0].text());
+        assert_eq!("move 7 of 7", frames[7].caption());
+        assert_eq!("G_.\n...\n#..\n", frames[7].text());
+    }
+
+    /// Tests that the exact search agrees with walking the goal data for the actual problem input,
+    /// which has the structure needed for the walk to be minimal.
+    #[test]
+    fn test_day22_exact_search_matches_walk() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let grid = NodeGrid::new(&input, DEFAULT_WALL_NODE_USED_PCT).unwrap();
+        assert!(has_walkable_structure(&grid));
+        let outcome = search_goal_data_to_target(&grid).unwrap();
+        assert_eq!(walk_goal_data_to_target(&grid), Some(outcome.moves()));
+    }
+
+    #[cfg(feature = "test-support")]
+    proptest::proptest! {
+        /// Tests that generated node tables parse into one node per line after the header lines,
+        /// with the used and available space of each node adding up to its size.
+        #[test]
+        fn test_day22_generated_node_table(table in node_table(6, 5)) {
+            let nodes = parse_nodes(&table);
+            proptest::prop_assert_eq!(table.lines().count() - 2, nodes.len());
+            for node in nodes.values() {
+                proptest::prop_assert_eq!(node.size(), node.used() + node.available());
+            }
+            proptest::prop_assert!(count_viable_pairs(&nodes) < nodes.len().max(1) * nodes.len());
+        }
+    }
 }
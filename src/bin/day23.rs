@@ -1,15 +1,55 @@
+use std::env;
+use std::fmt;
 use std::fs;
 use std::time::Instant;
 
-use aoc2016::utils::bespoke::AssembunnyInterpreter;
+use aoc2016::utils::bespoke::{AssembunnyInterpreter, LoopEffect};
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Safe Cracking";
 const PROBLEM_INPUT_FILE: &str = "./input/day23.txt";
 const PROBLEM_DAY: u64 = 23;
 
+/// Register holding the value to send to the safe once the program has halted.
+const RESULT_REGISTER: char = 'a';
+/// Register values seeded before running the program in Part 1.
+const PART1_SEEDS: [(char, isize); 1] = [('a', 7)];
+/// Register values seeded before running the program in Part 2.
+const PART2_SEEDS: [(char, isize); 1] = [('a', 12)];
+/// Command line flag used to print the analysis of the loops in the program and the closed-form
+/// expression for the value it sends to the safe.
+const ANALYSIS_FLAG: &str = "--analyse";
+
+/// Closed-form expression for the value left in register "a" by the safe program: the factorial of
+/// the initial value of register "a", plus the product of the two constants in the final loop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct SafeClosedForm {
+    factor_x: isize,
+    factor_y: isize,
+}
+
+impl SafeClosedForm {
+    /// Evaluates the closed-form expression for the given initial value of register "a". Returns
+    /// None if the value overflows, which happens for any initial value above 20 (as 21! is beyond
+    /// the range of a 64-bit register).
+    fn evaluate(&self, seed: isize) -> Option<isize> {
+        (1..=seed)
+            .try_fold(1_isize, |product, n| product.checked_mul(n))?
+            .checked_add(self.factor_x.checked_mul(self.factor_y)?)
+    }
+}
+
+impl fmt::Display for SafeClosedForm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a! + {}*{}", self.factor_x, self.factor_y)
+    }
+}
+
 /// Processes the AOC 2016 Day 23 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -19,25 +59,37 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the analysis of the program if requested
+    if env::args().any(|arg| arg == ANALYSIS_FLAG) {
+        print_program_analysis(&input);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 23 input file in the format required by the solver functions.
@@ -53,20 +105,91 @@ fn process_input_file(filename: &str) -> AssembunnyInterpreter {
 /// register "a" initialised to 7 (all others initialised to 0) and returns the value saved to
 /// register "a" (the value that should be sent to the safe).
 fn solve_part1(interpreter: &AssembunnyInterpreter) -> isize {
-    let mut interpreter = interpreter.clone();
-    interpreter.set_register('a', 7).unwrap();
-    interpreter.execute().unwrap();
-    interpreter.get_register('a').unwrap()
+    interpreter
+        .run_seeded(&PART1_SEEDS, RESULT_REGISTER)
+        .unwrap()
 }
 
 /// Solves AOC 2016 Day 23 Part 2 // Runs the program in the assembunny code interpreter with
 /// register "a" initialised to 12 (all others initialised to 0) and returns the value saved to
 /// register "a" (the value that should be sent to the safe).
 fn solve_part2(interpreter: &AssembunnyInterpreter) -> isize {
-    let mut interpreter = interpreter.clone();
-    interpreter.set_register('a', 12).unwrap();
-    interpreter.execute().unwrap();
-    interpreter.get_register('a').unwrap()
+    interpreter
+        .run_seeded(&PART2_SEEDS, RESULT_REGISTER)
+        .unwrap()
+}
+
+/// Prints the loops found in the program along with their arithmetic effect, followed by the
+/// closed-form expression for the value sent to the safe if the program has the expected structure.
+fn print_program_analysis(interpreter: &AssembunnyInterpreter) {
+    for analysis in interpreter.analyse_loops() {
+        let effect = match analysis.effect() {
+            Some(effect) => effect.to_string(),
+            None => String::from("unrecognised loop"),
+        };
+        let toggled = if analysis.may_be_toggled() {
+            " [may be toggled]"
+        } else {
+            ""
+        };
+        println!(
+            "[*] Loop {:>3}..={:<3} {effect}{toggled}",
+            analysis.range().start(),
+            analysis.range().end()
+        );
+    }
+    match derive_closed_form(interpreter) {
+        Some(closed_form) => {
+            println!("[*] Closed form: a = {closed_form}");
+            for (_, seed) in PART1_SEEDS.iter().chain(PART2_SEEDS.iter()) {
+                match closed_form.evaluate(*seed) {
+                    Some(value) => println!("[*] a = {seed} -> {value}"),
+                    None => println!("[!] a = {seed} -> value overflows"),
+                }
+            }
+        }
+        None => println!("[!] Program does not have the expected structure for a closed form"),
+    }
+}
+
+/// Derives the closed-form expression for the value the program leaves in register "a", by
+/// checking for the structure shared by the safe programs.
+///
+/// The program repeatedly multiplies register "a" by the decreasing value of register "b" (using a
+/// multiplication loop), toggling instructions in the final loop on each pass. Once toggled, the
+/// final loop adds the product of the constants in its first instruction and the instruction before
+/// it into register "a". Returns None if the program does not have this structure.
+fn derive_closed_form(interpreter: &AssembunnyInterpreter) -> Option<SafeClosedForm> {
+    let loops = interpreter.analyse_loops();
+    let factorial_step = LoopEffect::MulAdd {
+        target: 'a',
+        factor: String::from("b"),
+        counter: 'd',
+        scratch: 'c',
+    };
+    if !loops
+        .iter()
+        .any(|analysis| analysis.effect() == Some(&factorial_step))
+    {
+        return None;
+    }
+    // Final loop ends at the last instruction of the program
+    let final_loop = loops
+        .iter()
+        .find(|analysis| *analysis.range().end() == interpreter.instruction_count() - 1)?;
+    let start = *final_loop.range().start();
+    let factor_x = read_constant(&interpreter.instruction(start.checked_sub(1)?)?, "cpy", 'c')?;
+    let factor_y = read_constant(&interpreter.instruction(start)?, "jnz", 'd')?;
+    Some(SafeClosedForm { factor_x, factor_y })
+}
+
+/// Reads the constant from an instruction of the form "{mnemonic} {constant} {register}".
+fn read_constant(instruction: &str, mnemonic: &str, register: char) -> Option<isize> {
+    let tokens = instruction.split_whitespace().collect::<Vec<&str>>();
+    match tokens[..] {
+        [m, constant, r] if m == mnemonic && r == register.to_string() => constant.parse().ok(),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -88,4 +211,19 @@ mod test {
         let solution = solve_part2(&input);
         assert_eq!(479008890, solution);
     }
+
+    /// Tests that the closed form derived from the actual problem input gives the same values as
+    /// running the program.
+    #[test]
+    fn test_day23_closed_form() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        let closed_form = derive_closed_form(&input).unwrap();
+        assert_eq!("a! + 90*81", closed_form.to_string());
+        assert_eq!(Some(12330), closed_form.evaluate(7));
+        assert_eq!(Some(479008890), closed_form.evaluate(12));
+        assert_eq!(Some(2432902008176647290), closed_form.evaluate(20));
+        assert_eq!(None, closed_form.evaluate(21));
+        let program = AssembunnyInterpreter::new("cpy 7 a\ninc a\n").unwrap();
+        assert_eq!(None, derive_closed_form(&program));
+    }
 }
@@ -1,209 +1,330 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::env;
 use std::fs;
+use std::iter;
 use std::time::Instant;
 
-use itertools::Itertools;
-
+use aoc2016::utils::algorithms::AlgorithmRegistry;
+use aoc2016::utils::bespoke::{parse_duct_map, DistanceMatrix, DuctMap};
+use aoc2016::utils::events::EventStream;
+use aoc2016::utils::{animation, metrics, visualize};
 use aoc_utils::cartography::Point2D;
+use itertools::Itertools;
 
 const PROBLEM_NAME: &str = "Air Duct Spelunking";
 const PROBLEM_INPUT_FILE: &str = "./input/day24.txt";
 const PROBLEM_DAY: u64 = 24;
 
-/// Represents the different types of tiles that can exist in the grid.
-enum TileType {
-    Open,
-    Wall,
+/// Command line flag used to print the full route through the air ducts for both parts.
+const ROUTE_FLAG: &str = "--route";
+/// Default number of frames per second when animating the map of the air ducts.
+const ANIMATION_FPS: u32 = 1;
+
+/// Algorithm that finds the minimum distance required to visit all of the waypoints from the minimum
+/// distances between each pair of waypoints, optionally returning to the '0' waypoint.
+type WaypointDistanceSolver = fn(&DistanceMatrix, bool) -> Option<u64>;
+
+/// Single leg of a route through the air ducts between two waypoints, with the total movement cost
+/// of the tiles entered on the leg.
+struct RouteLeg {
+    from: char,
+    to: char,
+    path: Vec<Point2D>,
+    cost: u64,
 }
 
-type ProblemInput = (HashMap<Point2D, TileType>, HashMap<u64, Point2D>);
+impl RouteLeg {
+    /// Gets the number of steps taken on the leg.
+    fn steps(&self) -> usize {
+        self.path.len() - 1
+    }
+}
 
 /// Processes the AOC 2016 Day 24 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
+    let (_, algorithm) = waypoint_distance_algorithms().select_from_args();
     let input_parser_timestamp = Instant::now();
     let input_parser_duration = input_parser_timestamp.duration_since(start);
     // Solve part 1
-    let p1_solution = solve_part1(&input);
+    let p1_solution = solve_part1(&input, algorithm);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
-    let p2_solution = solve_part2(&input);
+    let p2_solution = solve_part2(&input, algorithm);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print, animate or export the map and print the routes if requested
+    if visualize::is_requested() {
+        visualize::print_frames(&input);
+    }
+    animation::run_if_requested(&input, ANIMATION_FPS);
+    if env::args().any(|arg| arg == ROUTE_FLAG) {
+        for return_to_zero in [false, true] {
+            print_route(&input, return_to_zero);
+        }
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        metrics::print_metrics();
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
+    );
+    metrics::write_json_if_requested(
+        PROBLEM_DAY,
+        &[
+            ("input", input_parser_duration),
+            ("part1", p1_duration),
+            ("part2", p2_duration),
+        ],
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 24 input file in the format required by the solver functions.
-/// Returned value is tuple containing: hashmap mapping location to grid tile type, and hashmap
-/// mapping number to its location in the grid.
-fn process_input_file(filename: &str) -> ProblemInput {
+/// Returned value is the map of the air ducts, including the minimum distances between each pair of
+/// waypoints.
+fn process_input_file(filename: &str) -> DuctMap {
     // Read contents of problem input file
     let raw_input = fs::read_to_string(filename).unwrap();
     // Process input file contents into data structure
-    let mut grid: HashMap<Point2D, TileType> = HashMap::new();
-    let mut numbered_locations: HashMap<u64, Point2D> = HashMap::new();
-    for (y, line) in raw_input
-        .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
-        .enumerate()
-    {
-        for (x, c) in line.chars().enumerate() {
-            let loc = Point2D::new(x as i64, y as i64);
-            match c {
-                '#' => _ = grid.insert(loc, TileType::Wall),
-                '.' => _ = grid.insert(loc, TileType::Open),
-                '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
-                    grid.insert(loc, TileType::Open);
-                    _ = numbered_locations.insert(c.to_digit(10).unwrap() as u64, loc);
-                }
-                _ => panic!("Bad character in input file! // x:{x}, y:{y} // char: {c}"),
-            }
-        }
-    }
-    (grid, numbered_locations)
+    parse_duct_map(&raw_input)
 }
 
 /// Solves AOC 2016 Day 24 Part 1 // Determines the minimum number of steps required to visit every
-/// non-0 number marked on the map at least once.
-fn solve_part1(input: &ProblemInput) -> u64 {
-    let (grid, numbered_locations) = input;
-    determine_min_steps_to_visit_all_numbers(grid, numbered_locations, false).unwrap()
+/// waypoint marked on the map at least once, starting at '0'.
+fn solve_part1(duct_map: &DuctMap, algorithm: WaypointDistanceSolver) -> u64 {
+    algorithm(duct_map.distances(), false).unwrap()
 }
 
 /// Solves AOC 2016 Day 24 Part 2 // Determines the minimum number of steps required to visit every
-/// non-0 number marked on the map at least once and return to the '0' location.
-fn solve_part2(input: &ProblemInput) -> u64 {
-    let (grid, numbered_locations) = input;
-    determine_min_steps_to_visit_all_numbers(grid, numbered_locations, true).unwrap()
+/// waypoint marked on the map at least once and return to the '0' waypoint.
+fn solve_part2(duct_map: &DuctMap, algorithm: WaypointDistanceSolver) -> u64 {
+    algorithm(duct_map.distances(), true).unwrap()
+}
+
+/// Gets the algorithms that can be used to find the minimum distance to visit all of the waypoints,
+/// with the Held-Karp solver as the default.
+fn waypoint_distance_algorithms() -> AlgorithmRegistry<WaypointDistanceSolver> {
+    AlgorithmRegistry::new(
+        "held-karp",
+        "dynamic programming over the subsets of waypoints visited",
+        find_min_distance_held_karp as WaypointDistanceSolver,
+    )
+    .register(
+        "permutations",
+        "checks every order the waypoints can be visited in",
+        find_min_distance_permutations,
+    )
+}
+
+/// Determines the order to visit all of the waypoints in (starting at '0') that takes the minimum
+/// distance, using the minimum distances between each pair of waypoints. Includes the distance
+/// required to travel from the last waypoint back to the '0' waypoint if option is given as true
+/// (with the final '0' included in the order). Returns the order and the distance, or None if there
+/// is no '0' waypoint or the waypoints cannot all be reached.
+///
+/// Uses the Held-Karp dynamic programming algorithm over the subsets of non-0 waypoints visited, taking O(2^n * n^2) time rather than the O(n!) time needed to check every order.
+fn find_best_waypoint_order(
+    minimum_distances: &DistanceMatrix,
+    return_to_zero: bool,
+) -> Option<(Vec<char>, u64)> {
+    // Index the waypoints, with the '0' waypoint first
+    let mut labels = minimum_distances
+        .keys()
+        .copied()
+        .filter(|k| *k != '0')
+        .collect::<Vec<char>>();
+    labels.sort();
+    minimum_distances.get(&'0')?;
+    labels.insert(0, '0');
+    let dist = |from: usize, to: usize| minimum_distances[&labels[from]].get(&labels[to]).copied();
+    // min_steps[visited][last] is the minimum steps to visit the set of non-0 locations (bit i-1
+    // set for location i) starting at '0', ending at the location with index last, along with the
+    // index of the location visited before last
+    let waypoints = labels.len() - 1;
+    let mut min_steps: Vec<Vec<Option<(u64, usize)>>> =
+        vec![vec![None; labels.len()]; 1 << waypoints];
+    min_steps[0][0] = Some((0, 0));
+    for visited in 0..(1_usize << waypoints) {
+        for last in 0..labels.len() {
+            let Some((steps, _)) = min_steps[visited][last] else {
+                continue;
+            };
+            // Move to each location that has not been visited yet
+            for next in (1..labels.len()).filter(|next| visited & (1 << (next - 1)) == 0) {
+                let Some(leg) = dist(last, next) else {
+                    continue;
+                };
+                let entry = &mut min_steps[visited | (1 << (next - 1))][next];
+                if entry.is_none_or(|(current, _)| current > steps + leg) {
+                    *entry = Some((steps + leg, last));
+                }
+            }
+        }
+    }
+    // Find the best last location, including the return to '0' if required
+    let all_visited = (1 << waypoints) - 1;
+    let (steps, mut last) = (0..labels.len())
+        .filter_map(|last| {
+            let (steps, _) = min_steps[all_visited][last]?;
+            if return_to_zero && last != 0 {
+                Some((steps + dist(last, 0)?, last))
+            } else {
+                Some((steps, last))
+            }
+        })
+        .min()?;
+    // Follow the previous locations back to '0' to recover the order
+    let mut order: Vec<char> = if return_to_zero && last != 0 {
+        vec!['0']
+    } else {
+        vec![]
+    };
+    let mut visited = all_visited;
+    while last != 0 {
+        order.push(labels[last]);
+        let (_, prev) = min_steps[visited][last].unwrap();
+        visited &= !(1 << (last - 1));
+        last = prev;
+    }
+    order.push('0');
+    order.reverse();
+    Some((order, steps))
+}
+
+/// Determines the minimum distance required to visit all of the waypoints, using the Held-Karp
+/// solver that also finds the order to visit them in.
+fn find_min_distance_held_karp(
+    minimum_distances: &DistanceMatrix,
+    return_to_zero: bool,
+) -> Option<u64> {
+    find_best_waypoint_order(minimum_distances, return_to_zero).map(|(_, steps)| steps)
 }
 
-/// Determines the minimum number of steps required to visit all of the numbered locations. Includes
-/// the distance required to travel from the last location back to the '0' location if option is
-/// given as true.
-fn determine_min_steps_to_visit_all_numbers(
-    grid: &HashMap<Point2D, TileType>,
-    numbered_locations: &HashMap<u64, Point2D>,
+/// Determines the minimum distance required to visit all of the waypoints (starting at '0') by
+/// checking every order the waypoints can be visited in, taking O(n!) time. Includes the distance
+/// required to travel from the last waypoint back to the '0' waypoint if option is given as true.
+/// Returns None if the waypoints cannot all be reached.
+fn find_min_distance_permutations(
+    minimum_distances: &DistanceMatrix,
     return_to_zero: bool,
 ) -> Option<u64> {
-    // Determine the minimum distance between each pair of numbered locations
-    let minimum_distances =
-        determine_min_distances_between_numbered_locations(numbered_locations, grid);
-    // Determine the possible orders in which the non-0 numbered locations can be visited in
+    // Determine the possible orders in which the non-0 waypoints can be visited in
     let orders = minimum_distances
         .keys()
-        .filter(|k| **k != 0)
+        .filter(|k| **k != '0')
         .permutations(minimum_distances.len() - 1);
     // Calculate distance for each location order and check if distance is new overall minimum
     let mut min_steps: Option<u64> = None;
     for ord in orders {
-        let ord = ord.into_iter().copied().collect::<Vec<u64>>();
-        // Calculate distance required to visited all numbered locations in order, starting with '0'
-        let mut current_steps = *minimum_distances.get(&0).unwrap().get(&ord[0]).unwrap();
-        for i in 1..ord.len() {
-            current_steps += minimum_distances
-                .get(&ord[i - 1])
-                .unwrap()
-                .get(&ord[i])
-                .unwrap();
-        }
-        // Include the distance for returning to '0' location if required
+        // Visit all waypoints in order, starting with '0'
+        let mut route = iter::once('0')
+            .chain(ord.into_iter().copied())
+            .collect::<Vec<char>>();
+        // Include the distance for returning to '0' waypoint if required
         if return_to_zero {
-            current_steps += minimum_distances
-                .get(ord.last().unwrap())
-                .unwrap()
-                .get(&0)
-                .unwrap();
+            route.push('0');
         }
+        // Skip the order if any leg of the route is unreachable
+        let Some(current_steps) = route
+            .windows(2)
+            .map(|leg| minimum_distances.get(&leg[0])?.get(&leg[1]))
+            .sum::<Option<u64>>()
+        else {
+            continue;
+        };
         // Check if a new minimum distance has been found
-        if min_steps.is_none() || min_steps.unwrap() > current_steps {
+        if min_steps.is_none_or(|min_steps| min_steps > current_steps) {
             min_steps = Some(current_steps);
         }
     }
     min_steps
 }
 
-/// For each numbered location, determines the minimum distance to each other numbered location.
-/// Returns hashmap mapping the numbered location to hashmap containing destination location mapped
-/// to distance in steps.
-fn determine_min_distances_between_numbered_locations(
-    numbered_locations: &HashMap<u64, Point2D>,
-    grid: &HashMap<Point2D, TileType>,
-) -> HashMap<u64, HashMap<u64, u64>> {
-    let mut minimum_distances: HashMap<u64, HashMap<u64, u64>> = HashMap::new();
-    // Find min distance between each different pair of numbered locations
-    for (num_from, loc_start) in numbered_locations {
-        let mut minimum_distances_from_num: HashMap<u64, u64> = HashMap::new();
-        for (num_to, loc_end) in numbered_locations.iter().filter(|(k, _)| *k != num_from) {
-            let min_dist = find_min_distance_between_locations(grid, loc_start, loc_end).unwrap();
-            minimum_distances_from_num.insert(*num_to, min_dist);
-        }
-        minimum_distances.insert(*num_from, minimum_distances_from_num);
-    }
-    minimum_distances
-}
-
-/// Determines the minimum distance between the start location and the end location in the grid.
-/// Returns none if start or end locations are not in the grid, or if the end location is not
-/// reachable from the start location.
-fn find_min_distance_between_locations(
-    grid: &HashMap<Point2D, TileType>,
-    loc_start: &Point2D,
-    loc_end: &Point2D,
-) -> Option<u64> {
-    // Check if the start or end location is not contained in the grid
-    if !grid.contains_key(loc_start) || !grid.contains_key(loc_end) {
-        return None;
-    }
-    let mut visit_queue: VecDeque<(Point2D, u64)> = VecDeque::from([(*loc_start, 0)]);
-    let mut visited: HashSet<Point2D> = HashSet::from([*loc_start]);
-    while !visit_queue.is_empty() {
-        let (loc, steps) = visit_queue.pop_front().unwrap();
-        if loc == *loc_end {
-            return Some(steps);
-        }
-        for next_loc in determine_next_reachable_locations(grid, &loc) {
-            if visited.contains(&next_loc) {
-                continue;
+/// Reconstructs the full route through the air ducts that visits the waypoints in the given order,
+/// as the shortest path for each leg between consecutive waypoints. Returns None if a waypoint in
+/// the order does not exist or cannot be reached.
+fn reconstruct_route(duct_map: &DuctMap, order: &[char]) -> Option<Vec<RouteLeg>> {
+    let mut legs: Vec<RouteLeg> = vec![];
+    for pair in order.windows(2) {
+        let (from, to) = (pair[0], pair[1]);
+        // The highest labelled waypoint has no search tree if the map has no movement costs, in
+        // which case the path from the other end of the leg is reversed
+        let path = match duct_map.search_tree(from) {
+            Some(search_tree) => search_tree.path_to(duct_map.waypoints().get(&to)?)?,
+            None => {
+                let loc_from = duct_map.waypoints().get(&from)?;
+                let mut path = duct_map.search_tree(to)?.path_to(loc_from)?;
+                path.reverse();
+                path
             }
-            visited.insert(next_loc);
-            visit_queue.push_back((next_loc, steps + 1));
-        }
+        };
+        let cost = duct_map.path_cost(&path);
+        legs.push(RouteLeg {
+            from,
+            to,
+            path,
+            cost,
+        });
     }
-    None
+    Some(legs)
 }
 
-/// Determines the locations that can be reached in the grid from the current location.
-fn determine_next_reachable_locations(
-    grid: &HashMap<Point2D, TileType>,
-    loc: &Point2D,
-) -> Vec<Point2D> {
-    let mut output: Vec<Point2D> = vec![];
-    for next_loc in loc.get_adjacent_points() {
-        match grid.get(&next_loc) {
-            Some(TileType::Open) => output.push(next_loc),
-            _ => continue,
-        }
+/// Prints the best route through the air ducts, with the number of steps for each leg (and its
+/// movement cost, if the map has movement costs) followed by the locations visited on the leg. The
+/// total for the route is its distance, which is the total movement cost if the map has movement
+/// costs.
+fn print_route(duct_map: &DuctMap, return_to_zero: bool) {
+    let Some((order, steps)) = find_best_waypoint_order(duct_map.distances(), return_to_zero)
+    else {
+        println!("[!] The waypoints cannot all be reached");
+        return;
+    };
+    let order_text = order
+        .iter()
+        .map(|label| label.to_string())
+        .collect::<Vec<String>>();
+    println!("[*] Route {} (distance {steps}):", order_text.join(" -> "));
+    for leg in reconstruct_route(duct_map, &order).unwrap() {
+        let locations = leg
+            .path
+            .iter()
+            .map(|loc| format!("({},{})", loc.x(), loc.y()))
+            .collect::<Vec<String>>();
+        let cost_text = match duct_map.tile_costs() {
+            Some(_) => format!(", cost {}", leg.cost),
+            None => String::new(),
+        };
+        println!(
+            "[+] {} -> {} ({} steps{cost_text}): {}",
+            leg.from,
+            leg.to,
+            leg.steps(),
+            locations.join(" ")
+        );
     }
-    output
 }
 
 #[cfg(test)]
@@ -214,7 +335,7 @@ mod test {
     #[test]
     fn test_day24_part1_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part1(&input);
+        let solution = solve_part1(&input, find_min_distance_held_karp);
         assert_eq!(442, solution);
     }
 
@@ -222,7 +343,143 @@ mod test {
     #[test]
     fn test_day24_part2_actual() {
         let input = process_input_file(PROBLEM_INPUT_FILE);
-        let solution = solve_part2(&input);
+        let solution = solve_part2(&input, find_min_distance_held_karp);
         assert_eq!(660, solution);
     }
+
+    /// Tests the Day 24 solvers against the example from the problem description.
+    #[test]
+    fn test_day24_example() {
+        let duct_map = parse_duct_map(
+            "###########\n\
+            #0.1.....2#\n\
+            #.#######.#\n\
+            #4.......3#\n\
+            ###########\n",
+        );
+        assert_eq!(Some(&2), duct_map.distances()[&'0'].get(&'1'));
+        assert_eq!(Some(&10), duct_map.distances()[&'3'].get(&'0'));
+        assert_eq!(14, solve_part1(&duct_map, find_min_distance_held_karp));
+        assert_eq!(20, solve_part2(&duct_map, find_min_distance_held_karp));
+        assert_eq!(
+            "###########\n#0.1.....2#\n#.#######.#\n#4.......3#\n###########\n",
+            duct_map.render()
+        );
+    }
+
+    /// Tests the route reconstructed for the example from the problem description, checking that
+    /// each step of the route is between adjacent open tiles.
+    #[test]
+    fn test_day24_reconstruct_route() {
+        let duct_map = parse_duct_map(
+            "###########\n\
+            #0.1.....2#\n\
+            #.#######.#\n\
+            #4.......3#\n\
+            ###########\n",
+        );
+        let (order, steps) = find_best_waypoint_order(duct_map.distances(), false).unwrap();
+        assert_eq!((vec!['0', '4', '1', '2', '3'], 14), (order.clone(), steps));
+        let legs = reconstruct_route(&duct_map, &order).unwrap();
+        let leg_steps = legs.iter().map(|leg| leg.steps()).collect::<Vec<usize>>();
+        assert_eq!(vec![2, 4, 6, 2], leg_steps);
+        assert!(legs.iter().all(|leg| leg.cost == leg.steps() as u64));
+        for leg in &legs {
+            assert_eq!(duct_map.waypoints()[&leg.from], leg.path[0]);
+            assert_eq!(duct_map.waypoints()[&leg.to], *leg.path.last().unwrap());
+            assert!(leg
+                .path
+                .windows(2)
+                .all(|step| step[0].get_manhattan_distance(&step[1]) == 1));
+        }
+        let (order, _) = find_best_waypoint_order(duct_map.distances(), true).unwrap();
+        assert_eq!((Some(&'0'), Some(&'0')), (order.first(), order.last()));
+    }
+
+    /// Tests that every registered algorithm (including checking every visiting order) agrees with
+    /// the dynamic programming solver for the actual problem input.
+    #[test]
+    fn test_day24_held_karp_matches_brute_force() {
+        let duct_map = process_input_file(PROBLEM_INPUT_FILE);
+        for (name, algorithm) in waypoint_distance_algorithms().iter() {
+            assert_eq!(
+                (442, 660),
+                (
+                    solve_part1(&duct_map, algorithm),
+                    solve_part2(&duct_map, algorithm)
+                ),
+                "{name}"
+            );
+        }
+    }
+
+    /// Tests a map with more than ten waypoints, using letters after the digits.
+    #[test]
+    fn test_day24_letter_waypoints() {
+        let duct_map = parse_duct_map("###############\n#0123456789abZ#\n###############\n");
+        assert_eq!(13, duct_map.waypoints().len());
+        assert_eq!(12, solve_part1(&duct_map, find_min_distance_held_karp));
+        assert_eq!(24, solve_part2(&duct_map, find_min_distance_held_karp));
+        let (order, _) = find_best_waypoint_order(duct_map.distances(), false).unwrap();
+        assert_eq!("0123456789abZ", order.into_iter().collect::<String>());
+    }
+
+    /// Tests a map with movement costs, where the direct path between the waypoints is made
+    /// expensive enough that the route goes around it.
+    #[test]
+    fn test_day24_weighted_tiles() {
+        let duct_map = parse_duct_map(
+            "#####\n\
+            #0..#\n\
+            #...#\n\
+            #..1#\n\
+            #####\n\
+            \n\
+            .....\n\
+            ..9..\n\
+            .....\n\
+            .....\n\
+            .....\n",
+        );
+        assert_eq!(4, solve_part1(&duct_map, find_min_distance_held_karp));
+        assert_eq!(8, solve_part2(&duct_map, find_min_distance_held_karp));
+        let legs = reconstruct_route(&duct_map, &['0', '1']).unwrap();
+        assert!(!legs[0].path.contains(&Point2D::new(2, 1)));
+        assert_eq!(4, legs[0].steps());
+        assert_eq!(4, legs[0].cost);
+    }
+
+    /// Tests a map where the waypoint tiles have unequal movement costs, so the distance between a
+    /// pair of waypoints depends on the direction travelled.
+    #[test]
+    fn test_day24_asymmetric_tile_costs() {
+        let duct_map = parse_duct_map(
+            "#######\n\
+            #0.1.2#\n\
+            #######\n\
+            \n\
+            .......\n\
+            .1.5.9.\n\
+            .......\n",
+        );
+        for (name, algorithm) in waypoint_distance_algorithms().iter() {
+            assert_eq!(
+                (16, 24),
+                (
+                    solve_part1(&duct_map, algorithm),
+                    solve_part2(&duct_map, algorithm)
+                ),
+                "{name}"
+            );
+        }
+        for return_to_zero in [false, true] {
+            let (order, steps) =
+                find_best_waypoint_order(duct_map.distances(), return_to_zero).unwrap();
+            let legs = reconstruct_route(&duct_map, &order).unwrap();
+            for leg in &legs {
+                assert_eq!(duct_map.distances()[&leg.from][&leg.to], leg.cost);
+            }
+            assert_eq!(steps, legs.iter().map(|leg| leg.cost).sum::<u64>());
+        }
+    }
 }
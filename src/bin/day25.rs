@@ -1,17 +1,27 @@
+use std::env;
 use std::fs;
 use std::time::Instant;
 
-use aoc2016::utils::bespoke::AssembunnyInterpreter;
+use aoc2016::utils::bespoke::{AssembunnyInterpreter, LoopEffect};
+use aoc2016::utils::events::EventStream;
 
 const PROBLEM_NAME: &str = "Clock Signal";
 const PROBLEM_INPUT_FILE: &str = "./input/day25.txt";
 const PROBLEM_DAY: u64 = 25;
 
-const TONE_SEQUENCE_LENGTH_TARGET: usize = 50;
+/// Register initialised with the seed value before running the program.
+const SEED_REGISTER: char = 'a';
+/// Maximum number of instructions each seed candidate may execute before it is rejected.
+const SEED_INSTRUCTION_LIMIT: u64 = 1000000;
+/// Command line flag used to print the seed derived arithmetically from the constants in the
+/// program, cross-checked against the seed found by simulation.
+const ANALYTIC_FLAG: &str = "--analytic";
 
 /// Processes the AOC 2016 Day 25 input file and solves both parts of the problem. Solutions are
 /// printed to stdout.
 pub fn main() {
+    let events = EventStream::from_args();
+    events.run_started(PROBLEM_DAY, PROBLEM_NAME);
     let start = Instant::now();
     // Input processing
     let input = process_input_file(PROBLEM_INPUT_FILE);
@@ -21,25 +31,37 @@ pub fn main() {
     let p1_solution = solve_part1(&input);
     let p1_timestamp = Instant::now();
     let p1_duration = p1_timestamp.duration_since(input_parser_timestamp);
+    events.part_finished(PROBLEM_DAY, 1, &p1_solution, p1_duration);
     // Solve part 2
     let p2_solution = solve_part2(&input);
     let p2_timestamp = Instant::now();
     let p2_duration = p2_timestamp.duration_since(p1_timestamp);
-    // Print results
-    println!("==================================================");
-    println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
-    println!("[+] Part 1: {p1_solution}");
-    println!("[+] Part 2: {p2_solution}");
-    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
-    println!("Execution times:");
-    println!("[+] Input:  {input_parser_duration:.2?}");
-    println!("[+] Part 1: {p1_duration:.2?}");
-    println!("[+] Part 2: {p2_duration:.2?}");
-    println!(
-        "[*] TOTAL:  {:.2?}",
-        input_parser_duration + p1_duration + p2_duration
+    events.part_finished(PROBLEM_DAY, 2, &p2_solution, p2_duration);
+    // Print the analytic seed derivation if requested
+    if env::args().any(|arg| arg == ANALYTIC_FLAG) {
+        print_analytic_seed(&input, p1_solution);
+    }
+    // Print results, unless streaming events in their place
+    if !events.is_enabled() {
+        println!("==================================================");
+        println!("AOC 2016 Day {PROBLEM_DAY} - \"{PROBLEM_NAME}\"");
+        println!("[+] Part 1: {p1_solution}");
+        println!("[+] Part 2: {p2_solution}");
+        println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+        println!("Execution times:");
+        println!("[+] Input:  {input_parser_duration:.2?}");
+        println!("[+] Part 1: {p1_duration:.2?}");
+        println!("[+] Part 2: {p2_duration:.2?}");
+        println!(
+            "[*] TOTAL:  {:.2?}",
+            input_parser_duration + p1_duration + p2_duration
+        );
+        println!("==================================================");
+    }
+    events.run_completed(
+        PROBLEM_DAY,
+        input_parser_duration + p1_duration + p2_duration,
     );
-    println!("==================================================");
 }
 
 /// Processes the AOC 2016 Day 25 input file in the format required by the solver functions.
@@ -56,30 +78,9 @@ fn process_input_file(filename: &str) -> AssembunnyInterpreter {
 /// register needs to be initialised to in order for the interpreter to produce the required clock
 /// signal (indefinitely alternating sequence of 0 and 1).
 fn solve_part1(interpreter: &AssembunnyInterpreter) -> isize {
-    let mut seed = 0;
-    'outer: loop {
-        // Initialise the interpreter with the new seed value
-        seed += 1;
-        let mut interpreter = interpreter.clone();
-        interpreter.set_register('a', seed).unwrap();
-        let mut expected_tones = [0isize, 1isize].iter().cycle();
-        // Check for sequence of good tones
-        'inner: for _ in 0..TONE_SEQUENCE_LENGTH_TARGET {
-            // Resume execution of the program and check that interpreter has not halted
-            interpreter.execute().unwrap();
-            if interpreter.is_halted() {
-                continue 'outer;
-            }
-            // Check if next tone is expected value in 0/1 sequence
-            if let Some(tone) = interpreter.get_next_transmit_value() {
-                if tone == *expected_tones.next().unwrap() {
-                    continue 'inner;
-                }
-            }
-            continue 'outer;
-        }
-        return seed;
-    }
+    (1..)
+        .find(|&seed| produces_clock_signal(interpreter, seed))
+        .unwrap()
 }
 
 /// Solves AOC 2016 Day 25 Part 2 // Christmas has been saved for 2016!
@@ -87,6 +88,88 @@ fn solve_part2(_interpreter: &AssembunnyInterpreter) -> bool {
     true
 }
 
+/// Checks if the program produces the clock signal (alternating sequence of 0 and 1, starting with
+/// 0) forever when the seed register is initialised with the given value. The signal is proven to
+/// continue forever by finding the point where the machine state repeats, and checking that the
+/// repeating part of the signal has an even length. Seeds where the program halts or runs away
+/// without repeating are rejected.
+fn produces_clock_signal(interpreter: &AssembunnyInterpreter, seed: isize) -> bool {
+    let mut interpreter = interpreter.seeded(&[(SEED_REGISTER, seed)]).unwrap();
+    interpreter.set_instruction_limit(Some(SEED_INSTRUCTION_LIMIT));
+    let Ok(Some(output)) = interpreter.find_output_cycle() else {
+        return false;
+    };
+    output.cycle().len() % 2 == 0
+        && (0..output.prefix().len() + output.cycle().len())
+            .all(|i| output.output(i) == (i % 2) as isize)
+}
+
+/// Prints the offset added to the seed by the program and the seed derived from it, checking the
+/// derived seed against the seed found by simulation.
+fn print_analytic_seed(interpreter: &AssembunnyInterpreter, simulated_seed: isize) {
+    let Some(offset) = derive_seed_offset(interpreter) else {
+        println!("[!] Program does not have the expected structure to derive the seed");
+        return;
+    };
+    let seed = find_minimum_clock_seed(offset);
+    println!("[*] Clock signal is the binary digits of a + {offset}");
+    if seed == simulated_seed {
+        println!("[*] Analytic seed: {seed} (matches simulation)");
+    } else {
+        println!("[!] Analytic seed: {seed} (simulation found {simulated_seed})");
+    }
+}
+
+/// Derives the offset the program adds to the seed before sending the binary digits of the sum as
+/// the clock signal (least significant digit first, repeating forever).
+///
+/// The program starts by copying the seed register into a target register, then adds the product
+/// of two constants into the target with a multiplication loop: the counter constant is copied in
+/// before the loop and the factor constant is copied in at the top of the loop. Returns None if the
+/// program does not have this structure.
+fn derive_seed_offset(interpreter: &AssembunnyInterpreter) -> Option<isize> {
+    let loops = interpreter.analyse_loops();
+    let mul_loop = loops.iter().find(|analysis| {
+        matches!(analysis.effect(), Some(LoopEffect::MulAdd { .. })) && !analysis.may_be_toggled()
+    })?;
+    let Some(LoopEffect::MulAdd {
+        target,
+        factor,
+        counter,
+        ..
+    }) = mul_loop.effect()
+    else {
+        return None;
+    };
+    let start = *mul_loop.range().start();
+    if interpreter.instruction(start.checked_sub(2)?)? != format!("cpy {SEED_REGISTER} {target}") {
+        return None;
+    }
+    let count = read_constant(&interpreter.instruction(start - 1)?, "cpy", *counter)?;
+    let factor = factor.parse::<isize>().ok()?;
+    Some(count * factor)
+}
+
+/// Reads the constant from an instruction of the form "{mnemonic} {constant} {register}".
+fn read_constant(instruction: &str, mnemonic: &str, register: char) -> Option<isize> {
+    let tokens = instruction.split_whitespace().collect::<Vec<&str>>();
+    match tokens[..] {
+        [m, constant, r] if m == mnemonic && r == register.to_string() => constant.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Finds the minimum positive seed that produces the clock signal when the given offset is added
+/// to it. The sum must have binary digits alternating 0 and 1 from the least significant digit and
+/// ending with a 1 (of the form 0b1010...10), so the candidates are 2, 10, 42, 170 and so on.
+fn find_minimum_clock_seed(offset: isize) -> isize {
+    let mut target = 2;
+    while target - offset < 1 {
+        target = target * 4 + 2;
+    }
+    target - offset
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -106,4 +189,16 @@ mod test {
         let solution = solve_part2(&input);
         assert!(solution);
     }
+
+    /// Tests that the seed derived from the constants in the actual problem input matches the seed
+    /// found by simulation.
+    #[test]
+    fn test_day25_analytic_seed() {
+        let input = process_input_file(PROBLEM_INPUT_FILE);
+        assert_eq!(Some(2548), derive_seed_offset(&input));
+        assert_eq!(182, find_minimum_clock_seed(2548));
+        assert_eq!(8, find_minimum_clock_seed(2));
+        let program = AssembunnyInterpreter::new("cpy 7 a\nout a\n").unwrap();
+        assert_eq!(None, derive_seed_offset(&program));
+    }
 }
@@ -1 +1,3 @@
+#[cfg(feature = "test-support")]
+pub mod test_support;
 pub mod utils;
@@ -1,3 +1,177 @@
+use std::env;
+use std::path::Path;
+use std::process::{self, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use aoc2016::utils::stats::TimingSummary;
+
+/// Command used to run every day's solver and rank the days by runtime.
+const BENCH_ALL_COMMAND: &str = "bench-all";
+/// Command line flag used to give a total runtime budget (in milliseconds) for the bench-all
+/// command, which fails if the days take longer than the budget in total.
+const BUDGET_FLAG: &str = "--budget";
+/// Command line flag used to give the number of timed runs of each day for the bench-all command,
+/// which are summarised after rejecting outliers.
+const RUNS_FLAG: &str = "--runs";
+/// Command line flag used to give the number of untimed warm-up runs of each day for the bench-all
+/// command, made before the timed runs.
+const WARMUP_FLAG: &str = "--warmup";
+/// Number of days in the event.
+const DAYS: u64 = 25;
+
+/// Runtime of a single day's solver over the timed runs, including reading its input file and
+/// process startup.
+struct DayRuntime {
+    day: u64,
+    summary: TimingSummary,
+}
+
+impl DayRuntime {
+    /// Gets the median runtime of the day, used to rank the days.
+    fn duration(&self) -> Duration {
+        self.summary.median()
+    }
+}
+
+/// Runs the command given on the command line. The bench-all command runs the solver binary for
+/// each day, which must already be built next to this binary (for example, with
+/// "cargo build --release --bins"). Each day is run the given number of warm-up times before its
+/// timed runs.
 fn main() {
-    println!("Hello, world! This is the default binary.");
+    let args = env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) != Some(BENCH_ALL_COMMAND) {
+        println!(
+            "Usage: aoc2016 {BENCH_ALL_COMMAND} [{BUDGET_FLAG} <milliseconds>] \
+            [{WARMUP_FLAG} <runs>] [{RUNS_FLAG} <runs>]"
+        );
+        return;
+    }
+    let budget = read_flag_value(&args, BUDGET_FLAG).map(Duration::from_millis);
+    let warmup = read_flag_value(&args, WARMUP_FLAG).unwrap_or(0);
+    let runs = read_flag_value(&args, RUNS_FLAG).unwrap_or(1).max(1);
+    let exe = env::current_exe().unwrap();
+    let bin_dir = exe.parent().unwrap();
+    let mut runtimes: Vec<DayRuntime> = vec![];
+    for day in 1..=DAYS {
+        let samples = (0..warmup + runs)
+            .map(|_| run_day(bin_dir, day))
+            .skip(warmup as usize)
+            .collect::<Result<Vec<Duration>, String>>();
+        match samples {
+            Ok(samples) => runtimes.push(DayRuntime {
+                day,
+                summary: TimingSummary::from_samples(&samples).unwrap(),
+            }),
+            Err(e) => {
+                eprintln!("[!] Day {day:02} failed: {e}");
+                process::exit(1);
+            }
+        }
+    }
+    print!("{}", format_leaderboard(&mut runtimes));
+    let total = runtimes
+        .iter()
+        .map(|runtime| runtime.duration())
+        .sum::<Duration>();
+    if let Some(budget) = budget.filter(|budget| total > *budget) {
+        eprintln!("[!] Total runtime {total:.2?} exceeds the budget of {budget:.2?}");
+        process::exit(1);
+    }
+}
+
+/// Reads the number given after the flag on the command line. Returns None if the flag is not
+/// given.
+///
+/// Panics if the flag is not followed by a valid number.
+fn read_flag_value(args: &[String], flag: &str) -> Option<u64> {
+    let i = args.iter().position(|arg| arg == flag)?;
+    match args.get(i + 1).and_then(|value| value.parse::<u64>().ok()) {
+        Some(value) => Some(value),
+        None => panic!("Missing or invalid number after {flag} flag!"),
+    }
+}
+
+/// Runs the solver binary for the given day from the directory holding the binaries, discarding its
+/// output. Returns the wall-clock time taken, or an error if the binary could not be run or exited
+/// unsuccessfully.
+fn run_day(bin_dir: &Path, day: u64) -> Result<Duration, String> {
+    let bin_path = bin_dir.join(format!("day{day:02}{}", env::consts::EXE_SUFFIX));
+    let start = Instant::now();
+    let status = Command::new(&bin_path)
+        .stdout(Stdio::null())
+        .status()
+        .map_err(|e| format!("cannot run {} ({e})", bin_path.display()))?;
+    let duration = start.elapsed();
+    if !status.success() {
+        return Err(format!("{} exited with {status}", bin_path.display()));
+    }
+    Ok(duration)
+}
+
+/// Formats the day runtimes as a leaderboard, ranked by median runtime from slowest to fastest. Each
+/// day is given with the 95% confidence interval of its mean runtime (if there were enough timed
+/// runs), its share of the total and the number of runs rejected as outliers, followed by the total
+/// of the median runtimes.
+fn format_leaderboard(runtimes: &mut [DayRuntime]) -> String {
+    runtimes.sort_by(|a, b| b.duration().cmp(&a.duration()).then(a.day.cmp(&b.day)));
+    let total = runtimes
+        .iter()
+        .map(|runtime| runtime.duration())
+        .sum::<Duration>();
+    let mut output = String::new();
+    for (i, runtime) in runtimes.iter().enumerate() {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            100.0 * runtime.duration().as_secs_f64() / total.as_secs_f64()
+        };
+        let ci95 = match runtime.summary.ci95() {
+            Some(ci95) => format!("±{ci95:.2?}"),
+            None => String::from("-"),
+        };
+        output += &format!(
+            "{:>3}. Day {:02} {:>12} {ci95:>11} {share:>6.1}%",
+            i + 1,
+            runtime.day,
+            format!("{:.2?}", runtime.duration())
+        );
+        if runtime.summary.outliers() > 0 {
+            output += &format!(" ({} outliers)", runtime.summary.outliers());
+        }
+        output.push('\n');
+    }
+    output += &format!("[*] TOTAL: {total:.2?}\n");
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that the days are ranked by median runtime from slowest to fastest, with ties broken by
+    /// day and outliers reported, and that the flag values are read from the command line.
+    #[test]
+    fn test_bench_all_leaderboard() {
+        let samples: [(u64, &[u64]); 3] = [(3, &[10]), (1, &[29, 30, 31, 90]), (2, &[10])];
+        let mut runtimes = samples.map(|(day, millis)| DayRuntime {
+            day,
+            summary: TimingSummary::from_samples(
+                &millis
+                    .iter()
+                    .map(|&millis| Duration::from_millis(millis))
+                    .collect::<Vec<Duration>>(),
+            )
+            .unwrap(),
+        });
+        assert_eq!(
+            "  1. Day 01      30.00ms     ±2.48ms   60.0% (1 outliers)\n  \
+            2. Day 02      10.00ms           -   20.0%\n  \
+            3. Day 03      10.00ms           -   20.0%\n\
+            [*] TOTAL: 50.00ms\n",
+            format_leaderboard(&mut runtimes)
+        );
+        let args = ["aoc2016", BENCH_ALL_COMMAND, BUDGET_FLAG, "1000"].map(String::from);
+        assert_eq!(Some(1000), read_flag_value(&args, BUDGET_FLAG));
+        assert_eq!(None, read_flag_value(&args, RUNS_FLAG));
+    }
 }
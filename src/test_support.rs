@@ -0,0 +1,157 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/// Letters of the passwords that generated scramble programs are valid for.
+pub const SCRAMBLE_LETTERS: &str = "abcdefgh";
+
+/// Generates a line of a AOC 2016 Day 21 scrambling program. Positions and letters are drawn from
+/// the 8-letter password "abcdefgh", so every generated operation can be applied to it.
+pub fn scramble_operation_line() -> impl Strategy<Value = String> {
+    let pos = || 0..SCRAMBLE_LETTERS.len();
+    let letter = || select(SCRAMBLE_LETTERS.chars().collect::<Vec<char>>());
+    prop_oneof![
+        (pos(), pos()).prop_map(|(x, y)| format!("swap position {x} with position {y}")),
+        (letter(), letter()).prop_map(|(x, y)| format!("swap letter {x} with letter {y}")),
+        (0..20_usize).prop_map(|steps| format!("rotate left {steps} steps")),
+        (0..20_usize).prop_map(|steps| format!("rotate right {steps} steps")),
+        letter().prop_map(|x| format!("rotate based on position of letter {x}")),
+        (pos(), pos()).prop_map(|(x, y)| format!(
+            "reverse positions {} through {}",
+            x.min(y),
+            x.max(y)
+        )),
+        (pos(), pos()).prop_map(|(x, y)| format!("move position {x} to position {y}")),
+    ]
+}
+
+/// Generates a AOC 2016 Day 21 scrambling program with up to the given number of operations, one
+/// per line.
+pub fn scramble_program(max_operations: usize) -> impl Strategy<Value = String> {
+    vec(scramble_operation_line(), 0..=max_operations).prop_map(|lines| lines.join("\n"))
+}
+
+/// Generates a AOC 2016 Day 7 IPv7 address, made of supernet sequences separated by hypernet
+/// sequences in square brackets. Sequences are non-empty runs of lowercase letters, and the
+/// address may start or end with a hypernet sequence.
+pub fn ipv7_address() -> impl Strategy<Value = String> {
+    let sequence = || "[a-z]{1,8}";
+    (
+        proptest::option::of(sequence()),
+        vec((sequence(), sequence()), 0..4),
+        proptest::option::of(sequence()),
+    )
+        .prop_map(|(first, pairs, last)| {
+            let mut address = first.unwrap_or_default();
+            for (hypernet, supernet) in pairs {
+                address += &format!("[{hypernet}]{supernet}");
+            }
+            if let Some(hypernet) = last {
+                address += &format!("[{hypernet}]");
+            }
+            address
+        })
+        .prop_filter("address must not be empty", |address| !address.is_empty())
+}
+
+/// Generates the output of the "df" command for a AOC 2016 Day 22 grid of storage nodes with up to
+/// the given width and height, including the command and header lines. The used and available
+/// space of each node always adds up to its size.
+pub fn node_table(max_width: usize, max_height: usize) -> impl Strategy<Value = String> {
+    (1..=max_width, 1..=max_height)
+        .prop_flat_map(|(width, height)| {
+            (
+                Just(height),
+                vec((1..=500_usize, 0..=100_usize), width * height),
+            )
+        })
+        .prop_map(|(height, nodes)| {
+            let mut table = String::from("root@ebhq-gridcenter# df -h\n");
+            table += "Filesystem              Size  Used  Avail  Use%\n";
+            for (i, (size, used_pct)) in nodes.into_iter().enumerate() {
+                let (x, y) = (i / height, i % height);
+                let used = size * used_pct / 100;
+                table += &format!(
+                    "/dev/grid/node-x{x}-y{y}   {size}T   {used}T   {}T   {}%\n",
+                    size - used,
+                    used * 100 / size
+                );
+            }
+            table
+        })
+}
+
+/// Generates the AOC 2016 Day 15 definitions of up to the given number of discs, numbered from 1
+/// in order, one per line.
+pub fn disc_definitions(max_discs: usize) -> impl Strategy<Value = String> {
+    vec(
+        (1..=23_u64).prop_flat_map(|positions| (Just(positions), 0..positions)),
+        0..=max_discs,
+    )
+    .prop_map(|discs| {
+        discs
+            .iter()
+            .enumerate()
+            .map(|(i, (positions, start))| {
+                format!(
+                    "Disc #{} has {positions} positions; at time=0, it is at position {start}.",
+                    i + 1
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::utils::bespoke::{AssembunnyInterpreter, Ipv7Address, ScrambleOperation, Scrambler};
+
+    proptest! {
+        /// Tests that the parsers return an error rather than panicking on arbitrary input, or on
+        /// input that follows the grammar but has numbers too large for the parsed values.
+        #[test]
+        fn test_parsers_never_panic(
+            s in "\\PC{0,64}",
+            number in "[0-9]{1,30}",
+            line in scramble_operation_line(),
+        ) {
+            let _ = ScrambleOperation::from_str(&s);
+            let _ = Scrambler::from_str(&s);
+            let _ = Ipv7Address::from_str(&s);
+            let _ = AssembunnyInterpreter::new(&s);
+            let oversized_line = line.replace(|c: char| c.is_ascii_digit(), &number);
+            let _ = ScrambleOperation::from_str(&oversized_line);
+            let _ = AssembunnyInterpreter::new(&format!("cpy {number} a\njnz a -{number}"));
+        }
+
+        /// Tests that generated scramble programs parse into one operation per line, and that
+        /// unscrambling reverses scrambling for the password the programs are generated for.
+        #[test]
+        fn test_scramble_program_round_trip(program in scramble_program(12)) {
+            let scrambler = Scrambler::from_str(&program).unwrap();
+            prop_assert_eq!(program.lines().count(), scrambler.operations().len());
+            scrambler.assert_round_trip(SCRAMBLE_LETTERS);
+        }
+
+        /// Tests that generated IPv7 addresses parse into sequences that make up the whole address.
+        #[test]
+        fn test_ipv7_address_sequences(address in ipv7_address()) {
+            let ipv7 = Ipv7Address::from_str(&address).unwrap();
+            prop_assert_eq!(address.matches('[').count(), ipv7.hypernets().len());
+            let letters = address.chars().filter(|c| c.is_ascii_lowercase()).count();
+            let sequence_letters = ipv7
+                .supernets()
+                .iter()
+                .chain(ipv7.hypernets())
+                .map(|sequence| sequence.len())
+                .sum::<usize>();
+            prop_assert_eq!(letters, sequence_letters);
+        }
+    }
+}
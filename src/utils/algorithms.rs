@@ -0,0 +1,163 @@
+use std::env;
+
+/// Command line flag shared by the solvers with alternate algorithms, used to select the algorithm
+/// by name in place of the default.
+pub const ALGORITHM_FLAG: &str = "--algorithm";
+
+/// Algorithm registered under a name, with a short description of how it works.
+struct NamedAlgorithm<F> {
+    name: &'static str,
+    description: &'static str,
+    solve: F,
+}
+
+/// Registry of the alternate implementations of a solver function (such as a naive simulation and
+/// a closed-form solution), each registered under a name so it can be selected on the command line,
+/// benchmarked and checked against the others. The first algorithm registered is the default.
+///
+/// The algorithms are usually function pointers sharing the same signature.
+pub struct AlgorithmRegistry<F: Copy> {
+    algorithms: Vec<NamedAlgorithm<F>>,
+}
+
+impl<F: Copy> AlgorithmRegistry<F> {
+    /// Creates a registry holding the default algorithm.
+    pub fn new(name: &'static str, description: &'static str, solve: F) -> AlgorithmRegistry<F> {
+        AlgorithmRegistry {
+            algorithms: vec![NamedAlgorithm {
+                name,
+                description,
+                solve,
+            }],
+        }
+    }
+
+    /// Adds the algorithm to the registry under the given name.
+    ///
+    /// Panics if an algorithm is already registered under the name.
+    pub fn register(
+        mut self,
+        name: &'static str,
+        description: &'static str,
+        solve: F,
+    ) -> AlgorithmRegistry<F> {
+        assert!(
+            self.get(name).is_none(),
+            "Duplicate algorithm name! // {name}"
+        );
+        self.algorithms.push(NamedAlgorithm {
+            name,
+            description,
+            solve,
+        });
+        self
+    }
+
+    /// Gets the algorithm registered under the name. Returns None if there is no such algorithm.
+    pub fn get(&self, name: &str) -> Option<F> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.name == name)
+            .map(|algorithm| algorithm.solve)
+    }
+
+    /// Gets the name and the algorithm of the default algorithm.
+    pub fn default_algorithm(&self) -> (&'static str, F) {
+        (self.algorithms[0].name, self.algorithms[0].solve)
+    }
+
+    /// Returns an iterator over the names and algorithms in the order they were registered,
+    /// starting with the default.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, F)> + '_ {
+        self.algorithms
+            .iter()
+            .map(|algorithm| (algorithm.name, algorithm.solve))
+    }
+
+    /// Lists the registered algorithms with their descriptions, one per line.
+    pub fn describe(&self) -> String {
+        self.algorithms
+            .iter()
+            .map(|algorithm| format!("{}: {}\n", algorithm.name, algorithm.description))
+            .collect()
+    }
+
+    /// Selects the algorithm named after the algorithm flag in the arguments, or the default
+    /// algorithm if the flag is not given. Returns an error listing the registered algorithms if the
+    /// name is missing or unknown.
+    pub fn select(&self, args: &[String]) -> Result<(&'static str, F), String> {
+        let Some(i) = args.iter().position(|arg| arg == ALGORITHM_FLAG) else {
+            return Ok(self.default_algorithm());
+        };
+        args.get(i + 1)
+            .and_then(|name| {
+                self.algorithms
+                    .iter()
+                    .find(|algorithm| algorithm.name == name)
+            })
+            .map(|algorithm| (algorithm.name, algorithm.solve))
+            .ok_or_else(|| {
+                let names = self
+                    .algorithms
+                    .iter()
+                    .map(|algorithm| algorithm.name)
+                    .collect::<Vec<&str>>();
+                format!(
+                    "Algorithm must be given after {ALGORITHM_FLAG} flag ({})",
+                    names.join(", ")
+                )
+            })
+    }
+
+    /// Selects the algorithm named on the command line, or the default algorithm if none is named.
+    ///
+    /// Panics if the name is missing or unknown, listing the registered algorithms.
+    pub fn select_from_args(&self) -> (&'static str, F) {
+        let args = env::args().collect::<Vec<String>>();
+        match self.select(&args) {
+            Ok(selected) => selected,
+            Err(e) => panic!("{e}!\n{}", self.describe()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Sums the numbers from 1 to n with the triangle number formula.
+    fn sum_formula(n: u64) -> u64 {
+        n * (n + 1) / 2
+    }
+
+    /// Sums the numbers from 1 to n by adding each in turn.
+    fn sum_loop(n: u64) -> u64 {
+        (1..=n).sum()
+    }
+
+    /// Tests that algorithms are selected by name from the arguments, falling back to the default,
+    /// and that every registered algorithm can be run for differential testing.
+    #[test]
+    fn test_algorithm_registry() {
+        let registry = AlgorithmRegistry::new(
+            "formula",
+            "sums with the triangle formula",
+            sum_formula as fn(u64) -> u64,
+        )
+        .register("loop", "adds each number in turn", sum_loop);
+        let args = ["day00", ALGORITHM_FLAG, "loop"].map(String::from);
+        assert_eq!("loop", registry.select(&args).unwrap().0);
+        assert_eq!("formula", registry.select(&args[..1]).unwrap().0);
+        assert_eq!(
+            Err(String::from(
+                "Algorithm must be given after --algorithm flag (formula, loop)"
+            )),
+            registry.select(&args[..2]).map(|(name, _)| name)
+        );
+        assert!(registry.iter().all(|(_, solve)| solve(100) == 5050));
+        assert_eq!(
+            "formula: sums with the triangle formula\nloop: adds each number in turn\n",
+            registry.describe()
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// Number of allocations made on the current thread since it started.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Global allocator that passes allocations on to the system allocator, counting the allocations
+/// (including reallocations) made on each thread. Only counts allocations once installed with the
+/// #[global_allocator] attribute, which the solvers do in their tests when the "count-allocations"
+/// feature is enabled.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        record_allocation();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Adds an allocation to the count for the current thread. Allocations made while the thread is
+/// being torn down are not counted.
+fn record_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+/// Runs the function and counts the allocations it made on the current thread. Allocations made on
+/// other threads (such as the rayon thread pool) are not counted.
+pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let start = ALLOCATIONS.with(|count| count.get());
+    let result = f();
+    (result, ALLOCATIONS.with(|count| count.get()) - start)
+}
+
+/// Runs the function and checks that it made no more than the given number of allocations on the
+/// current thread.
+///
+/// Panics if the limit is exceeded, reporting the label and the number of allocations made.
+pub fn assert_allocations_within<R>(label: &str, limit: usize, f: impl FnOnce() -> R) -> R {
+    let (result, allocations) = count_allocations(f);
+    assert!(
+        allocations <= limit,
+        "Allocation count regression! // {label} made {allocations} allocations (limit {limit})"
+    );
+    result
+}
+
+#[cfg(all(test, feature = "count-allocations"))]
+mod test {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Tests that allocations and reallocations are counted, and that the limit check panics once
+    /// the limit is exceeded.
+    #[test]
+    fn test_count_allocations() {
+        let (sum, allocations) = count_allocations(|| (0..10).sum::<u64>());
+        assert_eq!((45, 0), (sum, allocations));
+        let (_, allocations) = count_allocations(|| {
+            let mut values = Vec::with_capacity(1);
+            values.extend([1, 2, 3]);
+            values
+        });
+        assert_eq!(2, allocations);
+        let result = std::panic::catch_unwind(|| {
+            assert_allocations_within("boxed value", 0, || Box::new(1));
+        });
+        assert!(result.is_err());
+    }
+}
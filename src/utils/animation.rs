@@ -0,0 +1,271 @@
+use std::env;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::utils::visualize::{Frame, FrameSink, Visualize};
+
+/// Command line flag shared by the solvers with a visualization, used to play the frames of the
+/// visualization back in the terminal.
+pub const ANIMATE_FLAG: &str = "--animate";
+/// Command line flag used to set the number of frames played back or exported per second, in place
+/// of the solver's default.
+pub const FPS_FLAG: &str = "--fps";
+/// Command line flag used to export the frames of the visualization to the animation file given
+/// after the flag. The format (GIF, or APNG for ".png" and ".apng" files) is determined by the file
+/// extension.
+pub const EXPORT_ANIMATION_FLAG: &str = "--export-animation";
+/// Number of image pixels drawn along each side of a text cell when exporting an animation.
+pub const EXPORT_CELL_SCALE: usize = 4;
+
+/// RGB colours of the exported animations, indexed by the cell colour index.
+const PALETTE: [u8; 9] = [
+    0x10, 0x10, 0x20, // background (spaces and open tiles)
+    0xd0, 0xd0, 0xd0, // solid cells (walls, traps and lit pixels)
+    0xff, 0xc0, 0x00, // markers (paths, labels and counts)
+];
+
+/// Frame sink that records the frames of a visualization, so they can be played back in the
+/// terminal or encoded as an animated GIF or PNG.
+#[derive(Default)]
+pub struct Animation {
+    frames: Vec<Frame>,
+}
+
+impl FrameSink for Animation {
+    fn frame(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+}
+
+impl Animation {
+    /// Records the frames of the visualization.
+    pub fn record<V: Visualize>(value: &V) -> Animation {
+        let mut animation = Animation::default();
+        value.visualize(&mut animation);
+        animation
+    }
+
+    /// Gets the recorded frames, in order.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// Plays the frames back in the terminal at the given number of frames per second, clearing the
+    /// terminal before drawing each frame along with its number and caption.
+    pub fn play(&self, fps: u32) {
+        let frame_delay = Duration::from_secs_f64(1.0 / fps.max(1) as f64);
+        for (i, frame) in self.frames.iter().enumerate() {
+            // Clear the terminal and move the cursor to the top-left before drawing the frame
+            print!(
+                "\x1b[2J\x1b[H[{}/{}] {}\n{}",
+                i + 1,
+                self.frames.len(),
+                frame.caption(),
+                frame.text()
+            );
+            io::stdout().flush().unwrap();
+            thread::sleep(frame_delay);
+        }
+    }
+
+    /// Writes the frames as a looping animated PNG, with each character of the frame text drawn as
+    /// a square of the given number of pixels along each side.
+    pub fn write_apng<W: Write>(&self, writer: W, fps: u32, scale: usize) -> io::Result<()> {
+        let (width, height) = self.image_size(scale);
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(&PALETTE[..]);
+        encoder.set_animated(self.frames.len().max(1) as u32, 0)?;
+        encoder.set_frame_delay(1, fps.clamp(1, u16::MAX as u32) as u16)?;
+        let mut png_writer = encoder.write_header()?;
+        for frame in self.frames.iter() {
+            png_writer.write_image_data(&rasterize(frame, width, height, scale))?;
+        }
+        if self.frames.is_empty() {
+            png_writer.write_image_data(&vec![0; width * height])?;
+        }
+        png_writer.finish()?;
+        Ok(())
+    }
+
+    /// Writes the frames as a looping animated GIF, with each character of the frame text drawn as
+    /// a square of the given number of pixels along each side. GIF frame delays are in hundredths of a second, so the
+    /// frame rate is capped at 100 frames per second.
+    #[cfg(feature = "gif")]
+    pub fn write_gif<W: Write>(&self, writer: W, fps: u32, scale: usize) -> io::Result<()> {
+        let (width, height) = self.image_size(scale);
+        let (Ok(width), Ok(height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frames are too large for a GIF",
+            ));
+        };
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &PALETTE).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        for frame in self.frames.iter() {
+            let pixels = rasterize(frame, width as usize, height as usize, scale);
+            let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+            gif_frame.delay = (100 / fps.clamp(1, 100)) as u16;
+            encoder.write_frame(&gif_frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    /// Calculates the size (in pixels) of the exported image, which is large enough to hold the
+    /// widest and tallest frames. Each side is at least one cell.
+    fn image_size(&self, scale: usize) -> (usize, usize) {
+        let width = self
+            .frames
+            .iter()
+            .flat_map(|frame| frame.text().lines())
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let height = self
+            .frames
+            .iter()
+            .map(|frame| frame.text().lines().count())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        (width * scale, height * scale)
+    }
+}
+
+/// Converts the text of the frame into image data of colour indices with the given size (in
+/// pixels), drawing each character as a square of the given number of pixels along each side. Any
+/// area not covered by the text is filled with the background colour.
+fn rasterize(frame: &Frame, width: usize, height: usize, scale: usize) -> Vec<u8> {
+    let mut data = vec![0; width * height];
+    for (row, line) in frame.text().lines().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let colour = cell_colour(c);
+            for y in row * scale..((row + 1) * scale).min(height) {
+                let start = (col * scale).min(width);
+                let end = ((col + 1) * scale).min(width);
+                data[y * width + start..y * width + end].fill(colour);
+            }
+        }
+    }
+    data
+}
+
+/// Gets the palette index of the colour used to draw the given character of a frame.
+fn cell_colour(c: char) -> u8 {
+    match c {
+        ' ' | '.' => 0,
+        '#' | '█' | '^' => 1,
+        _ => 2,
+    }
+}
+
+/// Checks if the animate or export animation flag was given on the command line.
+pub fn is_requested() -> bool {
+    env::args().any(|arg| arg == ANIMATE_FLAG || arg == EXPORT_ANIMATION_FLAG)
+}
+
+/// Plays back or exports the frames of the visualization if requested on the command line, using
+/// the given number of frames per second unless another is given after the FPS flag. Does nothing
+/// if neither the animate nor export animation flag is given.
+///
+/// Panics if the FPS or export animation flags are not followed by a valid value, or the animation
+/// file cannot be written.
+pub fn run_if_requested<V: Visualize>(value: &V, default_fps: u32) {
+    if !is_requested() {
+        return;
+    }
+    let args = env::args().collect::<Vec<String>>();
+    let export_index = args.iter().position(|arg| arg == EXPORT_ANIMATION_FLAG);
+    let fps = read_fps(&args).unwrap_or(default_fps);
+    let animation = Animation::record(value);
+    if args.iter().any(|arg| arg == ANIMATE_FLAG) {
+        animation.play(fps);
+    }
+    if let Some(i) = export_index {
+        let Some(filename) = args.get(i + 1) else {
+            panic!("Missing animation file after {EXPORT_ANIMATION_FLAG} flag!");
+        };
+        match export(&animation, filename, fps) {
+            Ok(()) => println!(
+                "[*] Exported {} frames to {filename}",
+                animation.frames().len()
+            ),
+            Err(e) => panic!("Failed to export animation! // {filename}: {e}"),
+        }
+    }
+}
+
+/// Reads the number of frames per second given after the FPS flag on the command line. Returns None
+/// if the flag is not given.
+fn read_fps(args: &[String]) -> Option<u32> {
+    let i = args.iter().position(|arg| arg == FPS_FLAG)?;
+    match args.get(i + 1).and_then(|fps| fps.parse::<u32>().ok()) {
+        Some(fps) if fps > 0 => Some(fps),
+        _ => panic!("Missing or invalid frame rate after {FPS_FLAG} flag!"),
+    }
+}
+
+/// Writes the animation to the file, using the APNG or GIF format depending on the file extension.
+fn export(animation: &Animation, filename: &str, fps: u32) -> io::Result<()> {
+    let writer = BufWriter::new(File::create(filename)?);
+    if filename.ends_with(".png") || filename.ends_with(".apng") {
+        return animation.write_apng(writer, fps, EXPORT_CELL_SCALE);
+    }
+    #[cfg(feature = "gif")]
+    if filename.ends_with(".gif") {
+        return animation.write_gif(writer, fps, EXPORT_CELL_SCALE);
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "unsupported animation format",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Visualization of a bar growing by one cell per frame, with a marker at its end.
+    struct GrowingBar(usize);
+
+    impl Visualize for GrowingBar {
+        fn visualize(&self, sink: &mut dyn FrameSink) {
+            for i in 0..self.0 {
+                let text = format!("{}O\n#", "#".repeat(i));
+                sink.frame(Frame::new(&format!("length {i}"), text));
+            }
+        }
+    }
+
+    /// Tests that frames of different sizes are padded to the largest frame when rasterized, and
+    /// that the recorded frames are encoded as animated images.
+    #[test]
+    fn test_animation_export() {
+        let animation = Animation::record(&GrowingBar(3));
+        assert_eq!(3, animation.frames().len());
+        assert_eq!((6, 4), animation.image_size(2));
+        let data = rasterize(&animation.frames()[1], 6, 4, 2);
+        assert_eq!(vec![1, 1, 2, 2, 0, 0], data[..6].to_vec());
+        assert_eq!(vec![1, 1, 0, 0, 0, 0], data[18..].to_vec());
+        let mut apng_data: Vec<u8> = vec![];
+        animation.write_apng(&mut apng_data, 10, 2).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &apng_data[..8]);
+        assert!(apng_data.windows(4).any(|chunk| chunk == b"acTL"));
+        #[cfg(feature = "gif")]
+        {
+            let mut gif_data: Vec<u8> = vec![];
+            animation.write_gif(&mut gif_data, 10, 2).unwrap();
+            assert_eq!(b"GIF89a", &gif_data[..6]);
+        }
+        let args = ["day08", ANIMATE_FLAG, FPS_FLAG, "30"].map(String::from);
+        assert_eq!(Some(30), read_fps(&args));
+        assert_eq!(None, read_fps(&args[..2]));
+    }
+}
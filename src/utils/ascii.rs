@@ -0,0 +1,35 @@
+/// Maximum number of decimal digits in a u64 value.
+pub const MAX_DECIMAL_DIGITS: usize = 20;
+
+/// Encodes the value as ASCII decimal digits into the end of the output buffer, and returns the
+/// slice of the buffer holding the digits. Avoids the allocation made when formatting the value
+/// into a String, which matters when appending an increasing index to a hash input.
+pub fn encode_decimal(mut value: u64, output: &mut [u8; MAX_DECIMAL_DIGITS]) -> &[u8] {
+    let mut start = MAX_DECIMAL_DIGITS;
+    loop {
+        start -= 1;
+        output[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    &output[start..]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests the decimal encoding of zero, a typical index and the largest u64 value.
+    #[test]
+    fn test_encode_decimal() {
+        let mut buffer = [0; MAX_DECIMAL_DIGITS];
+        assert_eq!(b"0", encode_decimal(0, &mut buffer));
+        assert_eq!(b"3231929", encode_decimal(3231929, &mut buffer));
+        assert_eq!(
+            u64::MAX.to_string().as_bytes(),
+            encode_decimal(u64::MAX, &mut buffer)
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use super::assembunnyinterpreter::{OpArgument, Operation};
+
+/// Arithmetic effect of a loop in an Assembunny program that can be expressed in closed form.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoopEffect {
+    /// The source register is added into the target register, leaving the source register at zero.
+    Add { target: char, source: char },
+    /// The product of the factor (a register or value) and the counter register is added into the
+    /// target register, leaving the counter and scratch registers at zero.
+    MulAdd {
+        target: char,
+        factor: String,
+        counter: char,
+        scratch: char,
+    },
+}
+
+impl fmt::Display for LoopEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoopEffect::Add { target, source } => {
+                write!(f, "{source} added into {target} ({source} cleared)")
+            }
+            LoopEffect::MulAdd {
+                target,
+                factor,
+                counter,
+                scratch,
+            } => write!(
+                f,
+                "{factor}*{counter} added into {target} ({scratch} and {counter} cleared)"
+            ),
+        }
+    }
+}
+
+/// Result of analysing a loop formed by a backwards jump with a constant offset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoopAnalysis {
+    range: RangeInclusive<usize>,
+    effect: Option<LoopEffect>,
+    may_be_toggled: bool,
+}
+
+impl LoopAnalysis {
+    /// Gets the range of instruction indices covered by the loop, from the jump target to the jump.
+    pub fn range(&self) -> &RangeInclusive<usize> {
+        &self.range
+    }
+
+    /// Gets the arithmetic effect of the loop, or None if the loop is not a recognised pattern.
+    pub fn effect(&self) -> Option<&LoopEffect> {
+        self.effect.as_ref()
+    }
+
+    /// Checks if a toggle operation could modify an instruction in the loop, in which case the
+    /// effect only holds until the loop is modified.
+    pub fn may_be_toggled(&self) -> bool {
+        self.may_be_toggled
+    }
+}
+
+/// Finds the loops in the program formed by backwards jumps with a constant offset, in order of the
+/// jump index, and determines the arithmetic effect of each. Nested loops are reported separately.
+pub(super) fn analyse_loops(
+    operations: &[Operation],
+    register_bounds: &BTreeMap<char, RangeInclusive<isize>>,
+) -> Vec<LoopAnalysis> {
+    let targets = tgl_targets(operations, register_bounds);
+    let add_loops = find_add_loops(operations);
+    let mut loops: Vec<LoopAnalysis> = vec![];
+    for (i, op) in operations.iter().enumerate() {
+        let delta = match *op {
+            // A jump that checks a zero value is never taken
+            Operation::Jnz {
+                check: OpArgument::Value { value: 0 },
+                ..
+            } => continue,
+            Operation::Jnz {
+                delta: OpArgument::Value { value },
+                ..
+            } => value,
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp {
+                delta: OpArgument::Value { value },
+            } => value,
+            _ => continue,
+        };
+        let Some(start) = i.checked_add_signed(delta).filter(|&start| start < i) else {
+            continue;
+        };
+        let effect = if let Some(&(_, target, source)) = add_loops
+            .iter()
+            .find(|(pc, _, _)| *pc == start && pc + 2 == i)
+        {
+            Some(LoopEffect::Add { target, source })
+        } else {
+            find_mul_add_loop(operations, start, i, &add_loops)
+        };
+        loops.push(LoopAnalysis {
+            range: start..=i,
+            effect,
+            may_be_toggled: targets.range(start..=i).next().is_some(),
+        });
+    }
+    loops
+}
+
+/// Checks if the loop covering the given instruction indices is a multiplication loop. A
+/// multiplication loop copies the factor into a scratch register, adds the scratch register into the
+/// target register with an addition loop, then decrements the counter register and jumps back to
+/// the copy while the counter is non-zero.
+fn find_mul_add_loop(
+    operations: &[Operation],
+    start: usize,
+    end: usize,
+    add_loops: &[(usize, char, char)],
+) -> Option<LoopEffect> {
+    if end != start + 5 {
+        return None;
+    }
+    let Operation::Cpy {
+        arg: factor,
+        register: OpArgument::Register { register: scratch },
+    } = operations[start]
+    else {
+        return None;
+    };
+    let &(_, target, _) = add_loops
+        .iter()
+        .find(|(pc, _, source)| *pc == start + 1 && *source == scratch)?;
+    let Operation::Dec {
+        register: OpArgument::Register { register: counter },
+    } = operations[start + 4]
+    else {
+        return None;
+    };
+    let Operation::Jnz {
+        check: OpArgument::Register { register: check },
+        ..
+    } = operations[end]
+    else {
+        return None;
+    };
+    // The factor must not be changed by the loop
+    let registers = [target, scratch, counter];
+    if check != counter
+        || counter == target
+        || counter == scratch
+        || matches!(factor, OpArgument::Register { register } if registers.contains(&register))
+    {
+        return None;
+    }
+    Some(LoopEffect::MulAdd {
+        target,
+        factor: factor.to_string(),
+        counter,
+        scratch,
+    })
+}
+
+/// Finds the addition loops in the program, returning the index of the first operation of each.
+/// An addition loop is an increment and decrement of two different registers (in either order)
+/// followed by a jump back to the first operation while the decremented register is non-zero.
+pub(super) fn find_add_loops(operations: &[Operation]) -> Vec<(usize, char, char)> {
+    let mut add_loops: Vec<(usize, char, char)> = vec![];
+    for (i, window) in operations.windows(3).enumerate() {
+        let (target, source) = match (window[0], window[1]) {
+            (
+                Operation::Inc {
+                    register: OpArgument::Register { register: target },
+                },
+                Operation::Dec {
+                    register: OpArgument::Register { register: source },
+                },
+            )
+            | (
+                Operation::Dec {
+                    register: OpArgument::Register { register: source },
+                },
+                Operation::Inc {
+                    register: OpArgument::Register { register: target },
+                },
+            ) => (target, source),
+            _ => continue,
+        };
+        if target == source {
+            continue;
+        }
+        if let Operation::Jnz {
+            check: OpArgument::Register { register: check },
+            delta: OpArgument::Value { value: -2 },
+        } = window[2]
+        {
+            if check == source {
+                add_loops.push((i, target, source));
+            }
+        }
+    }
+    add_loops
+}
+
+/// Determines the instruction indices that could be the target of a toggle operation, given the
+/// range of values that each register can hold when a toggle operation is executed. Toggle
+/// operations with a register-held offset are conservatively treated as able to target any
+/// instruction if no value range is given for the register.
+pub(super) fn tgl_targets(
+    operations: &[Operation],
+    register_bounds: &BTreeMap<char, RangeInclusive<isize>>,
+) -> BTreeSet<usize> {
+    let mut targets: BTreeSet<usize> = BTreeSet::new();
+    let last_index = operations.len() as isize - 1;
+    for (i, op) in operations.iter().enumerate() {
+        let Operation::Tgl { delta } = op else {
+            continue;
+        };
+        let deltas = match delta {
+            OpArgument::Value { value } => *value..=*value,
+            OpArgument::Register { register } => match register_bounds.get(register) {
+                Some(bounds) => bounds.clone(),
+                None => return (0..operations.len()).collect(),
+            },
+        };
+        // Clamp the targets to the instruction space
+        let start = (i as isize).saturating_add(*deltas.start()).max(0);
+        let end = (i as isize).saturating_add(*deltas.end()).min(last_index);
+        if start <= end {
+            targets.extend(start as usize..=end as usize);
+        }
+    }
+    targets
+}
@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+use super::assembunnyinterpreter::{
+    parse_operation, Operation, ParseAssembunnyError, DEFAULT_REGISTERS,
+};
+use super::AssembunnyInterpreter;
+
+lazy_static! {
+    static ref REGEX_LABEL: Regex = Regex::new(r"^([A-Za-z_][A-Za-z0-9_]+):\s*(.*)$").unwrap();
+    static ref REGEX_CONST: Regex =
+        Regex::new(r"^const\s+([A-Za-z_][A-Za-z0-9_]+)\s+(-?\d+)$").unwrap();
+}
+
+/// Represents a single operation line from the assembly source, prior to labels and constants being
+/// resolved.
+struct SourceOperation {
+    line: usize,
+    column: usize,
+    text: String,
+}
+
+impl AssembunnyInterpreter {
+    /// Assembles the given Assembunny assembly source into an interpreter with the default
+    /// registers (a, b, c and d).
+    ///
+    /// The assembly source extends the Assembunny syntax with:
+    /// - Comments starting with ';' and running to the end of the line
+    /// - Labels declared as `name:`, either on their own line or preceding an operation
+    /// - Named constants declared as `const NAME value`
+    /// - Labels given as the jump offset for `jnz`, `tgl` and `jmp` (resolved to relative offsets)
+    /// - Named constants given in place of any value argument
+    ///
+    /// Label and constant names must be at least two characters long, so they cannot be confused
+    /// with register names.
+    pub fn from_assembly(source: &str) -> Result<AssembunnyInterpreter, Vec<ParseAssembunnyError>> {
+        let mut errors: Vec<ParseAssembunnyError> = vec![];
+        let mut labels: HashMap<String, usize> = HashMap::new();
+        let mut constants: HashMap<String, String> = HashMap::new();
+        let mut source_ops: Vec<SourceOperation> = vec![];
+        // First pass - record labels and constants, and collect the operation lines
+        for (i, raw_line) in source.lines().enumerate() {
+            let uncommented = raw_line.split(';').next().unwrap();
+            let mut column = uncommented.len() - uncommented.trim_start().len() + 1;
+            let mut line = uncommented.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Ok(Some(caps)) = REGEX_CONST.captures(line) {
+                if constants
+                    .insert(caps[1].to_string(), caps[2].to_string())
+                    .is_some()
+                {
+                    errors.push(ParseAssembunnyError::new(i + 1, column, &caps[1]));
+                }
+                continue;
+            }
+            if let Ok(Some(caps)) = REGEX_LABEL.captures(line) {
+                if labels
+                    .insert(caps[1].to_string(), source_ops.len())
+                    .is_some()
+                {
+                    errors.push(ParseAssembunnyError::new(i + 1, column, &caps[1]));
+                }
+                let rest = caps.get(2).unwrap();
+                column += rest.start();
+                line = rest.as_str();
+                if line.is_empty() {
+                    continue;
+                }
+            }
+            source_ops.push(SourceOperation {
+                line: i + 1,
+                column,
+                text: line.to_string(),
+            });
+        }
+        // Second pass - resolve the labels and constants, and parse the resulting operations
+        let mut operations: Vec<Operation> = vec![];
+        for (index, source_op) in source_ops.iter().enumerate() {
+            let tokens = source_op.text.split_whitespace().collect::<Vec<&str>>();
+            let mut resolved = vec![tokens[0].to_string()];
+            for (arg_pos, token) in tokens.iter().enumerate().skip(1) {
+                // Jump offsets are the second argument of jnz and the first argument of tgl and jmp
+                let is_offset =
+                    matches!((tokens[0], arg_pos), ("jnz", 2) | ("tgl", 1) | ("jmp", 1));
+                if let Some(value) = constants.get(*token) {
+                    resolved.push(value.to_string());
+                } else if let (true, Some(&target)) = (is_offset, labels.get(*token)) {
+                    resolved.push((target as isize - index as isize).to_string());
+                } else {
+                    resolved.push(token.to_string());
+                }
+            }
+            match parse_operation(&resolved.join(" "), &DEFAULT_REGISTERS) {
+                Ok(op) => operations.push(op),
+                Err(_) => errors.push(ParseAssembunnyError::new(
+                    source_op.line,
+                    source_op.column,
+                    &source_op.text,
+                )),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(AssembunnyInterpreter::from_operations(
+            operations,
+            &DEFAULT_REGISTERS,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that an assembly program using labels, constants and comments produces the same result
+    /// as the equivalent plain Assembunny program.
+    #[test]
+    fn test_assemble_labels_and_constants() {
+        let source = "\
+            const COUNT 4    ; number of loop iterations\n\
+            cpy COUNT b\n\
+            loop: inc a\n\
+            inc a\n\
+            dec b\n\
+            jnz b loop\n\
+            jnz 1 end\n\
+            inc c\n\
+            end:\n";
+        let mut interpreter = AssembunnyInterpreter::from_assembly(source).unwrap();
+        interpreter.execute().unwrap();
+        assert_eq!(8, interpreter.get_register('a').unwrap());
+        assert_eq!(0, interpreter.get_register('c').unwrap());
+        let errors = AssembunnyInterpreter::from_assembly("jnz a nowhere\n")
+            .err()
+            .unwrap();
+        assert_eq!(1, errors.len());
+        assert_eq!(1, errors[0].line());
+    }
+}
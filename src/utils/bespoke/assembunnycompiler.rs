@@ -0,0 +1,280 @@
+use std::collections::{HashSet, VecDeque};
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use super::assembunnyanalysis::find_add_loops;
+use super::assembunnyinterpreter::{OpArgument, Operation};
+
+/// Represents the different backends that the Assembunny interpreter can use to execute programs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ExecutionBackend {
+    /// Executes each operation by interpreting it directly.
+    #[default]
+    Interpreted,
+    /// Compiles the program into a table of closures operating on a flat register array, with
+    /// addition loops fused into a single operation. Programs where a toggle operation could
+    /// target an optimised region fall back to being interpreted.
+    Compiled,
+}
+
+/// Outcome from executing a single compiled operation.
+pub(super) enum Step {
+    /// Continue execution at the given program counter.
+    Jump(usize),
+    /// Transmit the given value, then continue execution at the next operation.
+    Out(isize),
+    /// Toggle the operation at the given index, then continue execution at the next operation.
+    Toggle(usize),
+    /// Halt execution after jumping to the left of the instruction space.
+    Halt,
+}
+
+/// Operation compiled into a closure that takes the register array and returns the next step.
+pub(super) type CompiledOp = Box<dyn Fn(&mut [isize]) -> Step>;
+
+/// Operation argument compiled into a closure that reads its value from the register array.
+type CompiledArg = Box<dyn Fn(&[isize]) -> isize>;
+
+/// Compiled program held by the interpreter between executions, so the program is only compiled
+/// again after its operations are changed from outside of the compiled backend. The cache is
+/// emptied (rather than copied) when the interpreter is cloned.
+#[derive(Default)]
+pub(super) struct CompiledCache {
+    program: Option<Vec<CompiledOp>>,
+}
+
+impl Clone for CompiledCache {
+    fn clone(&self) -> Self {
+        CompiledCache::default()
+    }
+}
+
+impl CompiledCache {
+    /// Takes the cached program out of the cache, compiling the operations if nothing is cached.
+    pub(super) fn take_or_compile(
+        &mut self,
+        operations: &[Operation],
+        register_names: &[char],
+    ) -> Vec<CompiledOp> {
+        self.program
+            .take()
+            .unwrap_or_else(|| compile(operations, register_names))
+    }
+
+    /// Caches the compiled program, which must match the current operations.
+    pub(super) fn store(&mut self, program: Vec<CompiledOp>) {
+        self.program = Some(program);
+    }
+
+    /// Empties the cache after the operations have changed.
+    pub(super) fn invalidate(&mut self) {
+        self.program = None;
+    }
+
+    /// Checks if a compiled program is cached.
+    #[cfg(test)]
+    pub(super) fn is_cached(&self) -> bool {
+        self.program.is_some()
+    }
+}
+
+/// Checks if a toggle operation could be reached from the given program counter. Jumps with a
+/// register-held offset are conservatively treated as able to reach any operation.
+pub(super) fn is_tgl_reachable(operations: &[Operation], pc: usize) -> bool {
+    let mut visit_queue: VecDeque<usize> = VecDeque::from([pc]);
+    let mut visited: HashSet<usize> = HashSet::from([pc]);
+    while let Some(i) = visit_queue.pop_front() {
+        if i >= operations.len() {
+            continue;
+        }
+        let mut next: Vec<usize> = vec![i + 1];
+        match operations[i] {
+            Operation::Tgl { .. } => return true,
+            Operation::Jnz { delta, .. } => match delta {
+                OpArgument::Value { value } => {
+                    if let Some(target) = i.checked_add_signed(value) {
+                        next.push(target);
+                    }
+                }
+                OpArgument::Register { .. } => {
+                    return operations
+                        .iter()
+                        .any(|op| matches!(op, Operation::Tgl { .. }))
+                }
+            },
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp { delta } => match delta {
+                OpArgument::Value { value } => {
+                    next.clear();
+                    if let Some(target) = i.checked_add_signed(value) {
+                        next.push(target);
+                    }
+                }
+                OpArgument::Register { .. } => {
+                    return operations
+                        .iter()
+                        .any(|op| matches!(op, Operation::Tgl { .. }))
+                }
+            },
+            _ => (),
+        }
+        for j in next {
+            if visited.insert(j) {
+                visit_queue.push_back(j);
+            }
+        }
+    }
+    false
+}
+
+/// Gets the regions of the program that are replaced by a fused operation when compiled.
+pub(super) fn optimised_regions(operations: &[Operation]) -> Vec<RangeInclusive<usize>> {
+    find_add_loops(operations)
+        .into_iter()
+        .map(|(i, _, _)| i..=i + 2)
+        .collect()
+}
+
+/// Compiles the operations into closures that operate on an array of register values, where the
+/// index of each register is its position in the given register names. The first operation of
+/// each addition loop is compiled into a closure that performs the entire loop at once.
+pub(super) fn compile(operations: &[Operation], register_names: &[char]) -> Vec<CompiledOp> {
+    let mut program = operations
+        .iter()
+        .enumerate()
+        .map(|(pc, op)| compile_operation(op, pc, operations.len(), register_names))
+        .collect::<Vec<CompiledOp>>();
+    for (pc, target, source) in find_add_loops(operations) {
+        let t = register_names.iter().position(|&c| c == target).unwrap();
+        let s = register_names.iter().position(|&c| c == source).unwrap();
+        let unfused = compile_operation(&operations[pc], pc, operations.len(), register_names);
+        program[pc] = Box::new(move |regs| {
+            // Loop only terminates normally if the decremented register is positive
+            if regs[s] <= 0 {
+                return unfused(regs);
+            }
+            regs[t] = regs[t].wrapping_add(regs[s]);
+            regs[s] = 0;
+            Step::Jump(pc + 3)
+        });
+    }
+    program
+}
+
+/// Compiles a single operation at the given program counter into a closure. The program length is
+/// used to skip toggle operations that target outside of the instruction space.
+pub(super) fn compile_operation(
+    op: &Operation,
+    pc: usize,
+    program_length: usize,
+    register_names: &[char],
+) -> CompiledOp {
+    let index = |register: char| register_names.iter().position(|&c| c == register).unwrap();
+    let next = pc + 1;
+    match *op {
+        Operation::Cpy { arg, register } => match (arg, register) {
+            (OpArgument::Value { value }, OpArgument::Register { register }) => {
+                let r = index(register);
+                Box::new(move |regs| {
+                    regs[r] = value;
+                    Step::Jump(next)
+                })
+            }
+            (OpArgument::Register { register: src }, OpArgument::Register { register }) => {
+                let (s, r) = (index(src), index(register));
+                Box::new(move |regs| {
+                    regs[r] = regs[s];
+                    Step::Jump(next)
+                })
+            }
+            // Invalid instruction is skipped
+            (_, OpArgument::Value { .. }) => Box::new(move |_| Step::Jump(next)),
+        },
+        Operation::Inc { register } => match register {
+            OpArgument::Register { register } => {
+                let r = index(register);
+                Box::new(move |regs| {
+                    regs[r] = regs[r].wrapping_add(1);
+                    Step::Jump(next)
+                })
+            }
+            OpArgument::Value { .. } => Box::new(move |_| Step::Jump(next)),
+        },
+        Operation::Dec { register } => match register {
+            OpArgument::Register { register } => {
+                let r = index(register);
+                Box::new(move |regs| {
+                    regs[r] = regs[r].wrapping_sub(1);
+                    Step::Jump(next)
+                })
+            }
+            OpArgument::Value { .. } => Box::new(move |_| Step::Jump(next)),
+        },
+        Operation::Jnz { check, delta } => {
+            let check = compile_argument(check, register_names);
+            let delta = compile_argument(delta, register_names);
+            Box::new(move |regs| {
+                if check(regs) == 0 {
+                    return Step::Jump(next);
+                }
+                match pc.checked_add_signed(delta(regs)) {
+                    Some(target) => Step::Jump(target),
+                    None => Step::Halt,
+                }
+            })
+        }
+        Operation::Out { signal } => {
+            let signal = compile_argument(signal, register_names);
+            Box::new(move |regs| Step::Out(signal(regs)))
+        }
+        #[cfg(feature = "extended-instructions")]
+        Operation::Add { arg, register } | Operation::Mul { arg, register } => {
+            let OpArgument::Register { register } = register else {
+                // Invalid instruction is skipped
+                return Box::new(move |_| Step::Jump(next));
+            };
+            let r = index(register);
+            let arg = compile_argument(arg, register_names);
+            if matches!(op, Operation::Add { .. }) {
+                Box::new(move |regs| {
+                    regs[r] = regs[r].wrapping_add(arg(regs));
+                    Step::Jump(next)
+                })
+            } else {
+                Box::new(move |regs| {
+                    regs[r] = regs[r].wrapping_mul(arg(regs));
+                    Step::Jump(next)
+                })
+            }
+        }
+        #[cfg(feature = "extended-instructions")]
+        Operation::Nop => Box::new(move |_| Step::Jump(next)),
+        #[cfg(feature = "extended-instructions")]
+        Operation::Jmp { delta } => {
+            let delta = compile_argument(delta, register_names);
+            Box::new(move |regs| match pc.checked_add_signed(delta(regs)) {
+                Some(target) => Step::Jump(target),
+                None => Step::Halt,
+            })
+        }
+        Operation::Tgl { delta } => {
+            let delta = compile_argument(delta, register_names);
+            Box::new(move |regs| match pc.checked_add_signed(delta(regs)) {
+                Some(target) if target < program_length => Step::Toggle(target),
+                _ => Step::Jump(next),
+            })
+        }
+    }
+}
+
+/// Compiles an operation argument into a closure that reads its value from the register array.
+fn compile_argument(arg: OpArgument, register_names: &[char]) -> CompiledArg {
+    match arg {
+        OpArgument::Value { value } => Box::new(move |_| value),
+        OpArgument::Register { register } => {
+            let r = register_names.iter().position(|&c| c == register).unwrap();
+            Box::new(move |regs| regs[r])
+        }
+    }
+}
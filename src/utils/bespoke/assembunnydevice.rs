@@ -0,0 +1,121 @@
+/// Represents a device that can be attached to the Assembunny interpreter to handle its I/O.
+pub trait Device {
+    /// Receives a value transmitted by the interpreter. Returns true if the interpreter should
+    /// continue execution, or false if execution should be paused.
+    fn receive(&mut self, value: isize) -> bool;
+
+    /// Optionally provides an input value to the interpreter. Devices that do not provide input
+    /// return None.
+    fn provide(&mut self) -> Option<isize> {
+        None
+    }
+}
+
+/// Device that validates the clock signal described in AOC 2016 Day 25 - an alternating sequence
+/// of 0 and 1 starting with 0. Execution is paused as soon as a bad tone is received or the target
+/// number of good tones has been received.
+pub struct ClockSignalValidator {
+    target_length: usize,
+    good_tones: usize,
+    failed: bool,
+}
+
+impl ClockSignalValidator {
+    pub fn new(target_length: usize) -> ClockSignalValidator {
+        ClockSignalValidator {
+            target_length,
+            good_tones: 0,
+            failed: false,
+        }
+    }
+
+    /// Checks if the target number of tones have been received in the expected 0/1 sequence.
+    pub fn is_valid(&self) -> bool {
+        !self.failed && self.good_tones >= self.target_length
+    }
+}
+
+impl Device for ClockSignalValidator {
+    fn receive(&mut self, value: isize) -> bool {
+        // Check if the tone is the next expected value in the 0/1 sequence
+        if value != (self.good_tones % 2) as isize {
+            self.failed = true;
+            return false;
+        }
+        self.good_tones += 1;
+        self.good_tones < self.target_length
+    }
+}
+
+/// Device that prints each value received from the interpreter to stdout.
+#[derive(Default)]
+pub struct Printer;
+
+impl Device for Printer {
+    fn receive(&mut self, value: isize) -> bool {
+        println!("{value}");
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::AssembunnyInterpreter;
+    use super::*;
+
+    /// Tests that the clock signal validator pauses execution once the target number of good tones
+    /// is received, or as soon as a bad tone is received.
+    #[test]
+    fn test_clock_signal_validator() {
+        let mut validator = ClockSignalValidator::new(4);
+        assert!(validator.receive(0));
+        assert!(validator.receive(1));
+        assert!(validator.receive(0));
+        assert!(!validator.is_valid());
+        assert!(!validator.receive(1));
+        assert!(validator.is_valid());
+        let mut validator = ClockSignalValidator::new(4);
+        assert!(validator.receive(0));
+        assert!(!validator.receive(0));
+        assert!(!validator.is_valid());
+        // Attached to a program transmitting an endless 0/1 sequence
+        let raw_input = "cpy 0 a\nout a\ninc a\nout a\ndec a\njnz 1 -4\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        let mut validator = ClockSignalValidator::new(10);
+        interpreter.execute_with_device(&mut validator).unwrap();
+        assert!(validator.is_valid());
+        assert!(!interpreter.is_halted());
+    }
+
+    /// Tests that the printer never pauses execution, so the program runs until it halts.
+    #[test]
+    fn test_printer() {
+        let mut printer = Printer;
+        assert!(printer.receive(-3));
+        let raw_input = "cpy 3 a\nout a\ndec a\njnz a -2\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        interpreter.execute_with_device(&mut printer).unwrap();
+        assert!(interpreter.is_halted());
+    }
+
+    /// Tests that the built-in devices provide no input by default, while a device can override
+    /// the input hook to provide values.
+    #[test]
+    fn test_device_provide() {
+        struct Counter(isize);
+        impl Device for Counter {
+            fn receive(&mut self, _value: isize) -> bool {
+                true
+            }
+            fn provide(&mut self) -> Option<isize> {
+                self.0 += 1;
+                Some(self.0)
+            }
+        }
+        assert_eq!(None, ClockSignalValidator::new(4).provide());
+        assert_eq!(None, Printer.provide());
+        let mut counter = Counter(0);
+        assert_eq!(Some(1), counter.provide());
+        assert_eq!(Some(2), counter.provide());
+    }
+}
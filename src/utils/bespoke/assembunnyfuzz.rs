@@ -0,0 +1,161 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{AssembunnyInterpreter, ExecutionBackend};
+
+/// Maximum number of lines in a generated program.
+const MAX_PROGRAM_LINES: usize = 32;
+/// Operation mnemonics used when generating programs, with the kind of each argument taken by the
+/// operation ('r' for a register, or 'v' for a register or value).
+const MNEMONICS: [(&str, &str); 6] = [
+    ("cpy", "vr"),
+    ("inc", "r"),
+    ("dec", "r"),
+    ("jnz", "vv"),
+    ("tgl", "v"),
+    ("out", "v"),
+];
+/// Mnemonics and argument kinds of the extended instructions, which are also used when generating
+/// programs if the "extended-instructions" feature is enabled.
+#[cfg(feature = "extended-instructions")]
+const EXTENDED_MNEMONICS: [(&str, &str); 4] =
+    [("add", "vr"), ("mul", "vr"), ("nop", ""), ("jmp", "v")];
+/// Register names used when generating programs.
+const REGISTERS: [&str; 4] = ["a", "b", "c", "d"];
+/// Values used when generating programs that are likely to trigger edge cases.
+const EDGE_VALUES: [isize; 4] = [isize::MIN, isize::MAX, -1, 0];
+
+/// Randomly generated Assembunny program used for fuzzing the parser and interpreter. Most
+/// programs only contain valid Assembunny operations (with some extreme values), so that they can
+/// be executed. The rest have near-valid lines mixed in (unknown registers and missing or extra
+/// arguments) to exercise the parser.
+#[derive(Debug)]
+pub struct ArbitraryProgram {
+    lines: Vec<String>,
+    near_valid: bool,
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryProgram {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mnemonics = MNEMONICS.iter();
+        #[cfg(feature = "extended-instructions")]
+        let mnemonics = mnemonics.chain(EXTENDED_MNEMONICS.iter());
+        let mnemonics = mnemonics.collect::<Vec<&(&str, &str)>>();
+        let near_valid = u.ratio(1, 4)?;
+        let mut lines: Vec<String> = vec![];
+        for _ in 0..u.int_in_range(0..=MAX_PROGRAM_LINES)? {
+            let (mnemonic, mut arg_kinds) = **u.choose(&mnemonics)?;
+            // Occasionally give the wrong number of arguments
+            if near_valid && u.ratio(1, 20)? {
+                arg_kinds = &"vvv"[..u.int_in_range(0..=3)?];
+            }
+            let mut tokens = vec![mnemonic.to_string()];
+            for kind in arg_kinds.chars() {
+                // Only near-valid programs give a value where a register is required
+                if kind == 'r' && !near_valid {
+                    tokens.push(u.choose(&REGISTERS)?.to_string());
+                } else {
+                    tokens.push(arbitrary_argument(u, near_valid)?);
+                }
+            }
+            lines.push(tokens.join(" "));
+        }
+        Ok(ArbitraryProgram { lines, near_valid })
+    }
+}
+
+/// Generates an operation argument that is either a register name or a value, favouring small
+/// values so that jumps and toggles land inside the program. Register "e" does not exist in the
+/// default interpreter, so is occasionally used if generating a near-valid program.
+fn arbitrary_argument(u: &mut Unstructured, near_valid: bool) -> Result<String> {
+    Ok(match u.int_in_range(0..=19)? {
+        0..=9 => u.choose(&REGISTERS)?.to_string(),
+        10..=17 => u.int_in_range(-8_isize..=8)?.to_string(),
+        18 => u.choose(&EDGE_VALUES)?.to_string(),
+        _ if near_valid => String::from("e"),
+        _ => u.choose(&REGISTERS)?.to_string(),
+    })
+}
+
+impl ArbitraryProgram {
+    /// Gets the Assembunny source code for the generated program.
+    pub fn source(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// Parses and runs the generated program with each execution backend, stopping after the given
+    /// number of instructions. Panics if a program without near-valid lines fails to parse, or the
+    /// backends disagree on the final register values of a program that halts.
+    pub fn run(&self, instruction_limit: u64) {
+        let source = self.source();
+        let _ = AssembunnyInterpreter::from_assembly(&source);
+        if !self.near_valid {
+            assert!(AssembunnyInterpreter::new(&source).is_ok(), "{source}");
+        }
+        let mut results: Vec<Vec<isize>> = vec![];
+        for backend in [ExecutionBackend::Interpreted, ExecutionBackend::Compiled] {
+            let Ok(mut interpreter) = AssembunnyInterpreter::with_backend(&source, backend) else {
+                return;
+            };
+            interpreter.set_instruction_limit(Some(instruction_limit));
+            interpreter.outputs().for_each(drop);
+            if !interpreter.is_halted() {
+                return;
+            }
+            results.push(
+                ['a', 'b', 'c', 'd']
+                    .iter()
+                    .map(|&register| interpreter.get_register(register).unwrap())
+                    .collect(),
+            );
+        }
+        assert_eq!(results[0], results[1]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Generates a program from pseudo-random bytes produced by a linear congruential generator
+    /// with the given seed.
+    fn generate_program(seed: u64) -> ArbitraryProgram {
+        let mut state = seed;
+        let bytes = (0..1024)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 56) as u8
+            })
+            .collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&bytes);
+        ArbitraryProgram::arbitrary(&mut u).unwrap()
+    }
+
+    /// Tests that programs generated from a range of fixed byte seeds can be run without panicking.
+    #[test]
+    fn test_run_arbitrary_programs() {
+        for seed in 0..200_u64 {
+            generate_program(seed).run(10000);
+        }
+    }
+
+    /// Tests that the generated programs without near-valid lines parse with the enabled
+    /// instruction set, and that most generated programs are of this kind.
+    #[test]
+    fn test_arbitrary_programs_parse() {
+        let mut valid_programs = 0;
+        for seed in 0..200_u64 {
+            let program = generate_program(seed);
+            if !program.near_valid {
+                assert!(
+                    AssembunnyInterpreter::new(&program.source()).is_ok(),
+                    "{}",
+                    program.source()
+                );
+                valid_programs += 1;
+            }
+        }
+        assert!(valid_programs > 100);
+    }
+}
@@ -1,17 +1,39 @@
-use std::collections::hash_map::Entry;
-use std::collections::{HashMap, VecDeque};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::iter;
+use std::ops::{Range, RangeInclusive};
 use std::str::FromStr;
 
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 
+use super::assembunnyanalysis::{analyse_loops, tgl_targets, LoopAnalysis};
+use super::assembunnycompiler::{
+    compile_operation, is_tgl_reachable, optimised_regions, CompiledCache, ExecutionBackend, Step,
+};
+use super::assembunnydevice::Device;
+use super::assembunnytrace::{TraceEvent, TraceSink};
+
+/// Names of the registers used by the Assembunny interpreter unless otherwise specified.
+pub(super) const DEFAULT_REGISTERS: [char; 4] = ['a', 'b', 'c', 'd'];
+
+lazy_static! {
+    static ref REGEX_CPY: Regex = Regex::new(r"^cpy ([a-zA-Z]|-?\d+) ([a-zA-Z])$").unwrap();
+    static ref REGEX_INC: Regex = Regex::new(r"^inc ([a-zA-Z])$").unwrap();
+    static ref REGEX_DEC: Regex = Regex::new(r"^dec ([a-zA-Z])$").unwrap();
+    static ref REGEX_JNZ: Regex = Regex::new(r"^jnz ([a-zA-Z]|-?\d+) ([a-zA-Z]|-?\d+)$").unwrap();
+    static ref REGEX_TGL: Regex = Regex::new(r"^tgl ([a-zA-Z]|-?\d+)$").unwrap();
+    static ref REGEX_OUT: Regex = Regex::new(r"^out ([a-zA-Z]|-?\d+)$").unwrap();
+}
+
+#[cfg(feature = "extended-instructions")]
 lazy_static! {
-    static ref REGEX_CPY: Regex = Regex::new(r"^cpy ([abcd]|-?\d+) ([abcd])$").unwrap();
-    static ref REGEX_INC: Regex = Regex::new(r"^inc ([abcd])$").unwrap();
-    static ref REGEX_DEC: Regex = Regex::new(r"^dec ([abcd])$").unwrap();
-    static ref REGEX_JNZ: Regex = Regex::new(r"^jnz ([abcd]|-?\d+) ([abcd]|-?\d+)$").unwrap();
-    static ref REGEX_TGL: Regex = Regex::new(r"^tgl ([abcd]|-?\d+)$").unwrap();
-    static ref REGEX_OUT: Regex = Regex::new(r"^out ([abcd]|-?\d+)$").unwrap();
+    static ref REGEX_ADD: Regex = Regex::new(r"^add ([a-zA-Z]|-?\d+) ([a-zA-Z])$").unwrap();
+    static ref REGEX_MUL: Regex = Regex::new(r"^mul ([a-zA-Z]|-?\d+) ([a-zA-Z])$").unwrap();
+    static ref REGEX_NOP: Regex = Regex::new(r"^nop$").unwrap();
+    static ref REGEX_JMP: Regex = Regex::new(r"^jmp ([a-zA-Z]|-?\d+)$").unwrap();
 }
 
 /// Custom error type indicating that a specified register does not exist in the Assembunny
@@ -19,24 +41,109 @@ lazy_static! {
 #[derive(Debug)]
 pub struct RegisterDoesNotExist;
 
-/// Custom error type indicating that parsing of Assembunny code has failed.
+/// Custom error type indicating that parsing of Assembunny code has failed, recording where in the
+/// source the failure occurred and the offending text.
+///
+/// Examples of situations where this error could occur:
+/// - Converting an unrecognised line of raw input into an assembunny operation
+/// - An operation argument referring to a register not held by the interpreter
+/// - An invalid register name given when constructing the interpreter (line and column are 0)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseAssembunnyError {
+    line: usize,
+    column: usize,
+    text: String,
+}
+
+impl ParseAssembunnyError {
+    pub(super) fn new(line: usize, column: usize, text: &str) -> ParseAssembunnyError {
+        ParseAssembunnyError {
+            line,
+            column,
+            text: text.to_string(),
+        }
+    }
+
+    /// Gets the line number (starting at 1) of the raw input where the failure occurred.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Gets the column number (starting at 1) of the raw input where the failure occurred.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Gets a reference to the offending text that could not be parsed.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl fmt::Display for ParseAssembunnyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: cannot parse \"{}\"",
+            self.line, self.column, self.text
+        )
+    }
+}
+
+/// Custom error type indicating that an OpArgument could not be parsed from a string.
+#[derive(Debug)]
+pub(super) struct ParseOpArgumentError;
+
+/// Custom error type indicating that a patch could not be applied to the program loaded into the
+/// Assembunny interpreter.
 ///
 /// Examples of situations where this error could occur:
-/// - Converting invalid raw input into an assembunny operation
-/// - Trying to decode an OpArgument register that is a Value variant
+/// - Patching an instruction index outside of the program instruction space
+/// - Replacing an instruction with a line that is not valid Assembunny code
+#[derive(Debug)]
+pub struct PatchProgramError;
+
+/// Custom error type indicating that the Assembunny interpreter has executed the maximum number of
+/// instructions allowed by its instruction limit without halting.
+#[derive(Debug)]
+pub struct InstructionLimitExceeded;
+
+/// Custom error type indicating that a seeded run of the program loaded into the Assembunny
+/// interpreter has failed.
 #[derive(Debug)]
-pub struct ParseAssembunnyError;
+pub enum RunProgramError {
+    /// A seeded register or the result register does not exist in the interpreter
+    RegisterDoesNotExist,
+    /// The instruction limit was reached before the program halted
+    InstructionLimitExceeded,
+}
+
+impl From<RegisterDoesNotExist> for RunProgramError {
+    fn from(_: RegisterDoesNotExist) -> Self {
+        RunProgramError::RegisterDoesNotExist
+    }
+}
+
+impl From<InstructionLimitExceeded> for RunProgramError {
+    fn from(_: InstructionLimitExceeded) -> Self {
+        RunProgramError::InstructionLimitExceeded
+    }
+}
+
+/// Custom error type indicating that an Assembunny interpreter snapshot could not be restored.
+#[derive(Debug)]
+pub struct RestoreSnapshotError;
 
 /// Represents an argument for an Assembunny operation that could be either a register-held value or
 /// a raw value.
-#[derive(Copy, Clone)]
-enum OpArgument {
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(super) enum OpArgument {
     Register { register: char },
     Value { value: isize },
 }
 
 impl FromStr for OpArgument {
-    type Err = ParseAssembunnyError;
+    type Err = ParseOpArgumentError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if let Ok(value) = s.parse::<isize>() {
@@ -44,14 +151,23 @@ impl FromStr for OpArgument {
         } else if let Some(register) = s.chars().next() {
             return Ok(OpArgument::Register { register });
         }
-        Err(ParseAssembunnyError)
+        Err(ParseOpArgumentError)
+    }
+}
+
+impl fmt::Display for OpArgument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OpArgument::Register { register } => write!(f, "{register}"),
+            OpArgument::Value { value } => write!(f, "{value}"),
+        }
     }
 }
 
 /// Represents a single Assembunny operation with arguments that could be register-held values or
 /// raw values.
-#[derive(Copy, Clone)]
-enum Operation {
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(super) enum Operation {
     /// Copy
     Cpy {
         arg: OpArgument,
@@ -70,59 +186,539 @@ enum Operation {
     Tgl { delta: OpArgument },
     /// Out
     Out { signal: OpArgument },
+    /// Add (extended instruction set)
+    #[cfg(feature = "extended-instructions")]
+    Add {
+        arg: OpArgument,
+        register: OpArgument,
+    },
+    /// Multiply (extended instruction set)
+    #[cfg(feature = "extended-instructions")]
+    Mul {
+        arg: OpArgument,
+        register: OpArgument,
+    },
+    /// No operation (extended instruction set)
+    #[cfg(feature = "extended-instructions")]
+    Nop,
+    /// Unconditional jump (extended instruction set)
+    #[cfg(feature = "extended-instructions")]
+    Jmp { delta: OpArgument },
+}
+
+impl Operation {
+    /// Operation that has no effect when executed ("jnz 0 0").
+    pub(super) const NOP: Operation = Operation::Jnz {
+        check: OpArgument::Value { value: 0 },
+        delta: OpArgument::Value { value: 0 },
+    };
+
+    /// Returns the operation resulting from the operation being toggled by a "tgl" operation.
+    pub(super) fn toggled(&self) -> Operation {
+        match *self {
+            Operation::Cpy { arg, register } => Operation::Jnz {
+                check: arg,
+                delta: register,
+            },
+            Operation::Inc { register } => Operation::Dec { register },
+            Operation::Dec { register } => Operation::Inc { register },
+            Operation::Jnz { check, delta } => Operation::Cpy {
+                arg: check,
+                register: delta,
+            },
+            Operation::Tgl { delta } => Operation::Inc { register: delta },
+            Operation::Out { signal } => Operation::Inc { register: signal },
+            #[cfg(feature = "extended-instructions")]
+            Operation::Add { arg, register } | Operation::Mul { arg, register } => Operation::Jnz {
+                check: arg,
+                delta: register,
+            },
+            #[cfg(feature = "extended-instructions")]
+            Operation::Nop => Operation::Nop,
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp { delta } => Operation::Inc { register: delta },
+        }
+    }
+
+    /// Gets the arguments of the operation.
+    fn arguments(&self) -> Vec<OpArgument> {
+        match *self {
+            Operation::Cpy { arg, register } => vec![arg, register],
+            Operation::Inc { register } | Operation::Dec { register } => vec![register],
+            Operation::Jnz { check, delta } => vec![check, delta],
+            Operation::Tgl { delta } => vec![delta],
+            Operation::Out { signal } => vec![signal],
+            #[cfg(feature = "extended-instructions")]
+            Operation::Add { arg, register } | Operation::Mul { arg, register } => {
+                vec![arg, register]
+            }
+            #[cfg(feature = "extended-instructions")]
+            Operation::Nop => vec![],
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp { delta } => vec![delta],
+        }
+    }
+
+    /// Gets a mutable reference to the jump offset held by the operation. This includes the
+    /// destination of a copy holding a raw value, which becomes the offset if the copy is toggled
+    /// back into a jump.
+    fn jump_offset_mut(&mut self) -> Option<&mut OpArgument> {
+        match self {
+            Operation::Jnz { delta, .. } => Some(delta),
+            Operation::Cpy { register, .. } if matches!(register, OpArgument::Value { .. }) => {
+                Some(register)
+            }
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp { delta } => Some(delta),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Cpy { arg, register } => write!(f, "cpy {arg} {register}"),
+            Operation::Inc { register } => write!(f, "inc {register}"),
+            Operation::Dec { register } => write!(f, "dec {register}"),
+            Operation::Jnz { check, delta } => write!(f, "jnz {check} {delta}"),
+            Operation::Tgl { delta } => write!(f, "tgl {delta}"),
+            Operation::Out { signal } => write!(f, "out {signal}"),
+            #[cfg(feature = "extended-instructions")]
+            Operation::Add { arg, register } => write!(f, "add {arg} {register}"),
+            #[cfg(feature = "extended-instructions")]
+            Operation::Mul { arg, register } => write!(f, "mul {arg} {register}"),
+            #[cfg(feature = "extended-instructions")]
+            Operation::Nop => write!(f, "nop"),
+            #[cfg(feature = "extended-instructions")]
+            Operation::Jmp { delta } => write!(f, "jmp {delta}"),
+        }
+    }
+}
+
+/// Parses the OpArgument from the given string, checking that any register referred to is one of
+/// the given register names.
+fn parse_op_argument(s: &str, register_names: &[char]) -> Result<OpArgument, ParseOpArgumentError> {
+    let arg = OpArgument::from_str(s)?;
+    if let OpArgument::Register { register } = arg {
+        if !register_names.contains(&register) {
+            return Err(ParseOpArgumentError);
+        }
+    }
+    Ok(arg)
+}
+
+/// Parses the operation argument held in the given capture group. On failure, returns the byte
+/// offset of the capture group within the line and the text that could not be parsed.
+fn parse_capture_arg(
+    caps: &fancy_regex::Captures,
+    i: usize,
+    register_names: &[char],
+) -> Result<OpArgument, (usize, String)> {
+    let m = caps.get(i).unwrap();
+    parse_op_argument(m.as_str(), register_names).map_err(|_| (m.start(), m.as_str().to_string()))
+}
+
+/// Parses a single (trimmed) line of Assembunny code into an Operation. On failure, returns the
+/// byte offset within the line and the text that could not be parsed.
+pub(super) fn parse_operation(
+    line: &str,
+    register_names: &[char],
+) -> Result<Operation, (usize, String)> {
+    let parse_arg =
+        |caps: &fancy_regex::Captures, i: usize| parse_capture_arg(caps, i, register_names);
+    if let Ok(Some(caps)) = REGEX_CPY.captures(line) {
+        let arg = parse_arg(&caps, 1)?;
+        let register = parse_arg(&caps, 2)?;
+        Ok(Operation::Cpy { arg, register })
+    } else if let Ok(Some(caps)) = REGEX_INC.captures(line) {
+        let register = parse_arg(&caps, 1)?;
+        Ok(Operation::Inc { register })
+    } else if let Ok(Some(caps)) = REGEX_DEC.captures(line) {
+        let register = parse_arg(&caps, 1)?;
+        Ok(Operation::Dec { register })
+    } else if let Ok(Some(caps)) = REGEX_JNZ.captures(line) {
+        let check = parse_arg(&caps, 1)?;
+        let delta = parse_arg(&caps, 2)?;
+        Ok(Operation::Jnz { check, delta })
+    } else if let Ok(Some(caps)) = REGEX_TGL.captures(line) {
+        let delta = parse_arg(&caps, 1)?;
+        Ok(Operation::Tgl { delta })
+    } else if let Ok(Some(caps)) = REGEX_OUT.captures(line) {
+        let signal = parse_arg(&caps, 1)?;
+        Ok(Operation::Out { signal })
+    } else {
+        #[cfg(feature = "extended-instructions")]
+        if let Some(result) = parse_extended_operation(line, register_names) {
+            return result;
+        }
+        Err((0, line.to_string()))
+    }
+}
+
+/// Parses a single (trimmed) line of Assembunny code into an Operation from the extended
+/// instruction set. Returns None if the line is not an extended instruction.
+#[cfg(feature = "extended-instructions")]
+fn parse_extended_operation(
+    line: &str,
+    register_names: &[char],
+) -> Option<Result<Operation, (usize, String)>> {
+    let parse_arg =
+        |caps: &fancy_regex::Captures, i: usize| parse_capture_arg(caps, i, register_names);
+    if let Ok(Some(caps)) = REGEX_ADD.captures(line) {
+        Some(parse_arg(&caps, 1).and_then(|arg| {
+            let register = parse_arg(&caps, 2)?;
+            Ok(Operation::Add { arg, register })
+        }))
+    } else if let Ok(Some(caps)) = REGEX_MUL.captures(line) {
+        Some(parse_arg(&caps, 1).and_then(|arg| {
+            let register = parse_arg(&caps, 2)?;
+            Ok(Operation::Mul { arg, register })
+        }))
+    } else if let Ok(true) = REGEX_NOP.is_match(line) {
+        Some(Ok(Operation::Nop))
+    } else if let Ok(Some(caps)) = REGEX_JMP.captures(line) {
+        Some(parse_arg(&caps, 1).map(|delta| Operation::Jmp { delta }))
+    } else {
+        None
+    }
+}
+
+/// Execution statistics collected by the Assembunny interpreter while running a program.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ExecutionStats {
+    /// Total number of instructions executed (including skipped invalid instructions)
+    total_cycles: u64,
+    /// Number of times the instruction at each index has been executed
+    instruction_counts: Vec<u64>,
+    /// Number of times each backwards jump (loop) has been taken, keyed by target then source index
+    loop_counts: BTreeMap<usize, BTreeMap<usize, u64>>,
+}
+
+impl ExecutionStats {
+    fn new(program_length: usize) -> ExecutionStats {
+        ExecutionStats {
+            total_cycles: 0,
+            instruction_counts: vec![0; program_length],
+            loop_counts: BTreeMap::new(),
+        }
+    }
+
+    /// Gets the total number of instructions executed.
+    pub fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    /// Gets the number of times the instruction at each index has been executed.
+    pub fn instruction_counts(&self) -> &[u64] {
+        &self.instruction_counts
+    }
+
+    /// Gets the instruction ranges covered by the most frequently taken backwards jumps, in
+    /// descending order of the number of times the jump was taken. At most n ranges are returned.
+    pub fn hottest_loops(&self, n: usize) -> Vec<(RangeInclusive<usize>, u64)> {
+        let mut loops = self
+            .loop_counts
+            .iter()
+            .flat_map(|(&start, sources)| {
+                sources
+                    .iter()
+                    .map(move |(&end, &count)| (start..=end, count))
+            })
+            .collect::<Vec<(RangeInclusive<usize>, u64)>>();
+        loops.sort_by_key(|(range, count)| (u64::MAX - count, *range.start(), *range.end()));
+        loops.into_iter().take(n).collect()
+    }
+
+    /// Prints the execution statistics report to stdout, including the n hottest loop ranges.
+    pub fn print_report(&self, n: usize) {
+        println!("[+] Total cycles: {}", self.total_cycles);
+        for (i, count) in self.instruction_counts.iter().enumerate() {
+            println!("[+] Instruction {i:>3}: {count}");
+        }
+        for (range, count) in self.hottest_loops(n) {
+            println!(
+                "[*] Loop {:>3}..={:<3} taken {count} times",
+                range.start(),
+                range.end()
+            );
+        }
+    }
+}
+
+/// Full machine state of the interpreter: the program counter, register values and program.
+type MachineState = (usize, BTreeMap<char, isize>, Vec<Operation>);
+
+/// Values transmitted by a program whose output has been proven to repeat forever. The output is
+/// the prefix followed by the cycle repeated indefinitely.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputCycle {
+    prefix: Vec<isize>,
+    cycle: Vec<isize>,
+}
+
+impl OutputCycle {
+    /// Gets the values transmitted before the output starts repeating.
+    pub fn prefix(&self) -> &[isize] {
+        &self.prefix
+    }
+
+    /// Gets the values that are transmitted repeatedly once the prefix has been transmitted.
+    pub fn cycle(&self) -> &[isize] {
+        &self.cycle
+    }
+
+    /// Gets the value transmitted at the given (zero-based) position in the infinite output.
+    pub fn output(&self, index: usize) -> isize {
+        match index.checked_sub(self.prefix.len()) {
+            Some(i) => self.cycle[i % self.cycle.len()],
+            None => self.prefix[index],
+        }
+    }
 }
 
 /// Interpreter for the Assembunny code described in AOC 2016 Day 12, Day 23 and Day 25.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AssembunnyInterpreter {
-    registers: HashMap<char, isize>,
+    registers: BTreeMap<char, isize>,
     pc: usize,
     operations: Vec<Operation>,
     halted: bool,
     transmit_buffer: VecDeque<isize>,
+    stats: ExecutionStats,
+    instruction_limit: Option<u64>,
+    backend: ExecutionBackend,
+    #[serde(default)]
+    register_bounds: BTreeMap<char, RangeInclusive<isize>>,
+    #[serde(skip)]
+    compiled: CompiledCache,
 }
 
 impl AssembunnyInterpreter {
-    pub fn new(raw_input: &str) -> Result<AssembunnyInterpreter, ParseAssembunnyError> {
+    /// Creates a new Assembunny interpreter with the default registers (a, b, c and d) and loads
+    /// the program given in the raw input.
+    pub fn new(raw_input: &str) -> Result<AssembunnyInterpreter, Vec<ParseAssembunnyError>> {
+        AssembunnyInterpreter::with_registers(raw_input, &DEFAULT_REGISTERS)
+    }
+
+    /// Creates a new Assembunny interpreter with the given register names and loads the program
+    /// given in the raw input. Register names must be ASCII letters, and the program can only
+    /// refer to the given registers.
+    ///
+    /// All failures encountered while parsing the raw input are returned, rather than only the
+    /// first failure.
+    pub fn with_registers(
+        raw_input: &str,
+        register_names: &[char],
+    ) -> Result<AssembunnyInterpreter, Vec<ParseAssembunnyError>> {
+        let mut errors: Vec<ParseAssembunnyError> = register_names
+            .iter()
+            .filter(|c| !c.is_ascii_alphabetic())
+            .map(|c| ParseAssembunnyError::new(0, 0, &c.to_string()))
+            .collect();
         // Parse raw input into Assembunny operations
         let mut operations: Vec<Operation> = vec![];
-        for line in raw_input.lines() {
-            let line = line.trim();
+        for (i, raw_line) in raw_input.lines().enumerate() {
+            let line = raw_line.trim();
             if line.is_empty() {
                 continue;
             }
-            if let Ok(Some(caps)) = REGEX_CPY.captures(line) {
-                let arg = OpArgument::from_str(&caps[1])?;
-                let register = OpArgument::from_str(&caps[2])?;
-                operations.push(Operation::Cpy { arg, register });
-            } else if let Ok(Some(caps)) = REGEX_INC.captures(line) {
-                let register = OpArgument::from_str(&caps[1])?;
-                operations.push(Operation::Inc { register });
-            } else if let Ok(Some(caps)) = REGEX_DEC.captures(line) {
-                let register = OpArgument::from_str(&caps[1])?;
-                operations.push(Operation::Dec { register });
-            } else if let Ok(Some(caps)) = REGEX_JNZ.captures(line) {
-                let check = OpArgument::from_str(&caps[1])?;
-                let delta = OpArgument::from_str(&caps[2])?;
-                operations.push(Operation::Jnz { check, delta });
-            } else if let Ok(Some(caps)) = REGEX_TGL.captures(line) {
-                let delta = OpArgument::from_str(&caps[1])?;
-                operations.push(Operation::Tgl { delta });
-            } else if let Ok(Some(caps)) = REGEX_OUT.captures(line) {
-                let signal = OpArgument::from_str(&caps[1])?;
-                operations.push(Operation::Out { signal });
-            } else {
-                return Err(ParseAssembunnyError);
+            match parse_operation(line, register_names) {
+                Ok(op) => operations.push(op),
+                Err((offset, text)) => {
+                    // Account for leading whitespace trimmed from the line
+                    let indent = raw_line.len() - raw_line.trim_start().len();
+                    errors.push(ParseAssembunnyError::new(i + 1, indent + offset + 1, &text));
+                }
             }
         }
-        // Construct the Assembunny interpreter
-        Ok(AssembunnyInterpreter {
-            registers: HashMap::from([('a', 0), ('b', 0), ('c', 0), ('d', 0)]),
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(AssembunnyInterpreter::from_operations(
+            operations,
+            register_names,
+        ))
+    }
+
+    /// Constructs the Assembunny interpreter from already-parsed operations.
+    pub(super) fn from_operations(
+        operations: Vec<Operation>,
+        register_names: &[char],
+    ) -> AssembunnyInterpreter {
+        let stats = ExecutionStats::new(operations.len());
+        AssembunnyInterpreter {
+            registers: register_names.iter().map(|&c| (c, 0)).collect(),
             pc: 0,
             operations,
             halted: false,
             transmit_buffer: VecDeque::new(),
-        })
+            stats,
+            instruction_limit: None,
+            backend: ExecutionBackend::Interpreted,
+            register_bounds: BTreeMap::new(),
+            compiled: CompiledCache::default(),
+        }
+    }
+
+    /// Creates a new Assembunny interpreter with the default registers (a, b, c and d) that uses
+    /// the given backend to execute the program given in the raw input.
+    pub fn with_backend(
+        raw_input: &str,
+        backend: ExecutionBackend,
+    ) -> Result<AssembunnyInterpreter, Vec<ParseAssembunnyError>> {
+        let mut interpreter = AssembunnyInterpreter::new(raw_input)?;
+        interpreter.backend = backend;
+        Ok(interpreter)
+    }
+
+    /// Creates a snapshot of the full interpreter state (registers, program counter, operations,
+    /// pending output and execution statistics) encoded as a JSON string.
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Restores an interpreter from a snapshot previously created by the snapshot method.
+    pub fn restore(snapshot: &str) -> Result<AssembunnyInterpreter, RestoreSnapshotError> {
+        let interpreter: AssembunnyInterpreter =
+            serde_json::from_str(snapshot).map_err(|_| RestoreSnapshotError)?;
+        // Check that the statistics tracked are consistent with the restored program
+        if interpreter.stats.instruction_counts.len() != interpreter.operations.len() {
+            return Err(RestoreSnapshotError);
+        }
+        // Check that the program counter is within the program, or one past its end once halted
+        if interpreter.pc > interpreter.operations.len() {
+            return Err(RestoreSnapshotError);
+        }
+        // Check that the register names are valid, and that the program and register bounds only
+        // refer to the restored registers
+        let registers = &interpreter.registers;
+        if !registers.keys().all(|c| c.is_ascii_alphabetic())
+            || !interpreter
+                .register_bounds
+                .keys()
+                .all(|c| registers.contains_key(c))
+            || !interpreter
+                .operations
+                .iter()
+                .flat_map(|op| op.arguments())
+                .all(|arg| match arg {
+                    OpArgument::Register { register } => registers.contains_key(&register),
+                    OpArgument::Value { .. } => true,
+                })
+        {
+            return Err(RestoreSnapshotError);
+        }
+        Ok(interpreter)
+    }
+
+    /// Gets the number of instructions in the program loaded into the interpreter.
+    pub fn instruction_count(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Replaces the instruction at the given index with the instruction parsed from the given line
+    /// of Assembunny code.
+    pub fn replace_instruction(
+        &mut self,
+        index: usize,
+        line: &str,
+    ) -> Result<(), PatchProgramError> {
+        if index >= self.operations.len() {
+            return Err(PatchProgramError);
+        }
+        let register_names = self.registers.keys().copied().collect::<Vec<char>>();
+        let op = parse_operation(line.trim(), &register_names).map_err(|_| PatchProgramError)?;
+        self.set_operation(index, op);
+        Ok(())
+    }
+
+    /// Toggles the instruction at the given index, as if it had been the target of a "tgl"
+    /// operation.
+    pub fn toggle_instruction(&mut self, index: usize) -> Result<(), PatchProgramError> {
+        if index >= self.operations.len() {
+            return Err(PatchProgramError);
+        }
+        self.set_operation(index, self.operations[index].toggled());
+        Ok(())
+    }
+
+    /// Overwrites the instructions in the given index range with no-op instructions. The length of
+    /// the program is unchanged, so the offsets of jumps over the range remain valid. Use the
+    /// insert_nops method to pad the program with no-ops instead.
+    pub fn replace_with_nops(&mut self, indices: Range<usize>) -> Result<(), PatchProgramError> {
+        if indices.end > self.operations.len() {
+            return Err(PatchProgramError);
+        }
+        for i in indices {
+            self.set_operation(i, Operation::NOP);
+        }
+        Ok(())
+    }
+
+    /// Inserts the given number of no-op instructions before the instruction at the given index, so
+    /// the instructions from the index onwards follow the no-ops. The offsets of the jumps crossing
+    /// the insertion point are adjusted so that the program behaves as before, and the program
+    /// counter and execution statistics are moved with their instructions.
+    ///
+    /// An error is returned if the index is past the end of the program, a jump offset is held in a
+    /// register, or the program contains a toggle (either of which could send a jump across the
+    /// insertion point without its offset being adjusted).
+    pub fn insert_nops(&mut self, index: usize, count: usize) -> Result<(), PatchProgramError> {
+        if index > self.operations.len() {
+            return Err(PatchProgramError);
+        }
+        let mut operations = self.operations.clone();
+        let shift = |i: isize| {
+            if i >= index as isize {
+                i + count as isize
+            } else {
+                i
+            }
+        };
+        for (i, op) in operations.iter_mut().enumerate() {
+            if matches!(op, Operation::Tgl { .. }) {
+                return Err(PatchProgramError);
+            }
+            match op.jump_offset_mut() {
+                Some(OpArgument::Value { value }) => {
+                    let from = i as isize;
+                    *value = shift(from + *value) - shift(from);
+                }
+                Some(OpArgument::Register { .. }) => return Err(PatchProgramError),
+                None => (),
+            }
+        }
+        operations.splice(index..index, iter::repeat_n(Operation::NOP, count));
+        self.operations = operations;
+        self.compiled.invalidate();
+        // Move the program counter and statistics along with their instructions
+        if self.pc >= index {
+            self.pc += count;
+        }
+        let shift = |i: usize| if i >= index { i + count } else { i };
+        self.stats
+            .instruction_counts
+            .splice(index..index, iter::repeat_n(0, count));
+        self.stats.loop_counts = self
+            .stats
+            .loop_counts
+            .iter()
+            .map(|(&start, sources)| {
+                let sources = sources
+                    .iter()
+                    .map(|(&end, &n)| (shift(end), n))
+                    .collect::<BTreeMap<usize, u64>>();
+                (shift(start), sources)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Sets the operation at the given index, discarding the cached compiled program.
+    fn set_operation(&mut self, index: usize, op: Operation) {
+        self.operations[index] = op;
+        self.compiled.invalidate();
     }
 
     /// Gets the value held in the specified register.
@@ -150,26 +746,156 @@ impl AssembunnyInterpreter {
         }
     }
 
+    /// Creates a copy of the interpreter with each of the given registers set to its seed value.
+    pub fn seeded(
+        &self,
+        seeds: &[(char, isize)],
+    ) -> Result<AssembunnyInterpreter, RegisterDoesNotExist> {
+        let mut interpreter = self.clone();
+        for &(register, value) in seeds {
+            interpreter.set_register(register, value)?;
+        }
+        Ok(interpreter)
+    }
+
+    /// Runs a copy of the loaded program to completion with each of the given registers set to its
+    /// seed value, and returns the value left in the result register. The interpreter itself is
+    /// left unchanged.
+    pub fn run_seeded(
+        &self,
+        seeds: &[(char, isize)],
+        result_register: char,
+    ) -> Result<isize, RunProgramError> {
+        let mut interpreter = self.seeded(seeds)?;
+        interpreter.execute()?;
+        Ok(interpreter.get_register(result_register)?)
+    }
+
+    /// Sets the range of values that the specified register is known to hold whenever a toggle
+    /// operation is executed. The bounds are used to narrow down the instructions that could be
+    /// targeted by toggle operations, and must hold for the compiled backend to be correct.
+    pub fn set_register_bounds(
+        &mut self,
+        register: char,
+        bounds: RangeInclusive<isize>,
+    ) -> Result<(), RegisterDoesNotExist> {
+        if !self.registers.contains_key(&register) {
+            return Err(RegisterDoesNotExist);
+        }
+        self.register_bounds.insert(register, bounds);
+        Ok(())
+    }
+
+    /// Gets the indices of the instructions that could be targeted by a toggle operation, given
+    /// the register bounds set for the interpreter.
+    pub fn tgl_targets(&self) -> BTreeSet<usize> {
+        tgl_targets(&self.operations, &self.register_bounds)
+    }
+
+    /// Analyses the loops formed by backwards jumps with a constant offset in the loaded program,
+    /// determining the arithmetic effect of the loops that match a known pattern. Loops are flagged
+    /// if a toggle operation could modify them, given the register bounds set for the interpreter.
+    pub fn analyse_loops(&self) -> Vec<LoopAnalysis> {
+        analyse_loops(&self.operations, &self.register_bounds)
+    }
+
+    /// Gets the Assembunny code for the instruction at the given index, or None if the index is
+    /// outside of the program.
+    pub fn instruction(&self, index: usize) -> Option<String> {
+        self.operations.get(index).map(|op| op.to_string())
+    }
+
+    /// Checks if the program can be executed by the compiled backend. The program cannot modify
+    /// itself in a way that invalidates the compiled program if toggle operations cannot be
+    /// reached, or if no toggle operation can target an optimised region.
+    fn can_execute_compiled(&self) -> bool {
+        if !is_tgl_reachable(&self.operations, self.pc) {
+            return true;
+        }
+        let targets = self.tgl_targets();
+        optimised_regions(&self.operations)
+            .into_iter()
+            .all(|region| targets.range(region).next().is_none())
+    }
+
+    /// Sets the maximum number of instructions the interpreter is allowed to execute over its
+    /// lifetime. A limit of None allows the interpreter to run indefinitely.
+    pub fn set_instruction_limit(&mut self, limit: Option<u64>) {
+        self.instruction_limit = limit;
+    }
+
     /// Executes the program loaded into the Assembunny interpreter. Halts when the program counter
     /// is outside of the program instruction space.
-    pub fn execute(&mut self) -> Result<(), ParseAssembunnyError> {
+    ///
+    /// Returns an InstructionLimitExceeded error if the instruction limit is reached before the
+    /// program halts or transmits a value.
+    pub fn execute(&mut self) -> Result<(), InstructionLimitExceeded> {
         if self.halted {
             return Ok(());
         }
+        // Use the compiled backend if selected and optimised regions are never toggled
+        if self.backend == ExecutionBackend::Compiled && self.can_execute_compiled() {
+            return self.execute_compiled();
+        }
+        self.execute_interpreted(None)
+    }
+
+    /// Executes the program with the given trace sink attached, emitting an event to the sink
+    /// before each operation is executed. The program is always interpreted when being traced,
+    /// regardless of the selected backend.
+    pub fn execute_traced(
+        &mut self,
+        sink: &mut dyn TraceSink,
+    ) -> Result<(), InstructionLimitExceeded> {
+        if self.halted {
+            return Ok(());
+        }
+        self.execute_interpreted(Some(sink))
+    }
+
+    /// Executes the program by interpreting each operation directly, emitting trace events to the
+    /// sink if one is given.
+    fn execute_interpreted(
+        &mut self,
+        mut sink: Option<&mut dyn TraceSink>,
+    ) -> Result<(), InstructionLimitExceeded> {
         loop {
             // Check if the program has halted
             if self.halted || self.pc >= self.operations.len() {
                 self.halted = true;
+                if let Some(sink) = sink {
+                    sink.halted();
+                }
                 return Ok(());
             }
+            // Check if the instruction limit has been reached
+            if let Some(limit) = self.instruction_limit {
+                if self.stats.total_cycles >= limit {
+                    if let Some(sink) = sink {
+                        sink.failed();
+                    }
+                    return Err(InstructionLimitExceeded);
+                }
+            }
+            // Emit the trace event for the current operation
+            if let Some(sink) = sink.as_mut() {
+                sink.record(TraceEvent::new(
+                    self.pc,
+                    self.operations[self.pc].to_string(),
+                    self.registers.clone(),
+                ));
+            }
+            // Record the execution of the current operation
+            self.stats.total_cycles += 1;
+            self.stats.instruction_counts[self.pc] += 1;
             // Process the current operation
             match self.operations[self.pc] {
                 Operation::Cpy { arg, register } => {
                     let value = self.get_op_argument_value(&arg);
                     // Skip invalid instruction
                     let register = match self.get_op_argument_register(&register) {
-                        Ok(register) => register,
-                        Err(ParseAssembunnyError) => {
+                        Some(register) => register,
+                        None => {
                             self.pc += 1;
                             continue;
                         }
@@ -179,74 +905,44 @@ impl AssembunnyInterpreter {
                 Operation::Inc { register } => {
                     // Skip invalid instruction
                     let register = match self.get_op_argument_register(&register) {
-                        Ok(register) => register,
-                        Err(ParseAssembunnyError) => {
+                        Some(register) => register,
+                        None => {
                             self.pc += 1;
                             continue;
                         }
                     };
-                    *self.registers.get_mut(&register).unwrap() += 1;
+                    let current = self.registers.get_mut(&register).unwrap();
+                    *current = current.wrapping_add(1);
                 }
                 Operation::Dec { register } => {
                     // Skip invalid instruction
                     let register = match self.get_op_argument_register(&register) {
-                        Ok(register) => register,
-                        Err(ParseAssembunnyError) => {
+                        Some(register) => register,
+                        None => {
                             self.pc += 1;
                             continue;
                         }
                     };
-                    *self.registers.get_mut(&register).unwrap() -= 1;
+                    let current = self.registers.get_mut(&register).unwrap();
+                    *current = current.wrapping_sub(1);
                 }
                 Operation::Jnz { check, delta } => {
                     let check = self.get_op_argument_value(&check);
                     let delta = self.get_op_argument_value(&delta);
                     if check != 0 {
-                        // Check if jump would move program counter to left of instruction space
-                        if delta < 0 && delta.unsigned_abs() > self.pc {
-                            self.pc = 0;
-                            self.halted = true;
-                            continue;
-                        }
-                        // Adjust program counter by jump
-                        if delta < 0 {
-                            self.pc -= delta.unsigned_abs();
-                        } else {
-                            self.pc += delta.unsigned_abs();
-                        }
-                        // Check if program counter is to right of instruction space
-                        if self.pc >= self.operations.len() {
-                            self.halted = true;
-                            continue;
-                        }
-                        // Compensate for post instruction program counter increment
-                        self.pc -= 1;
+                        self.jump(delta);
+                        continue;
                     }
                 }
                 Operation::Tgl { delta } => {
                     let delta = self.get_op_argument_value(&delta);
-                    // Check if the toggle delta points outside of the interpreter instruction space
-                    if delta.is_negative() && delta.unsigned_abs() > self.pc
-                        || delta.is_positive()
-                            && (delta.unsigned_abs() + self.pc >= self.operations.len())
+                    // Skip toggle if the delta points outside of the interpreter instruction space
+                    if let Some(i_toggle) = self
+                        .pc
+                        .checked_add_signed(delta)
+                        .filter(|&i| i < self.operations.len())
                     {
-                        self.pc += 1;
-                        continue;
-                    }
-                    let i_toggle = delta.unsigned_abs() + self.pc;
-                    self.operations[i_toggle] = match self.operations[i_toggle] {
-                        Operation::Cpy { arg, register } => Operation::Jnz {
-                            check: arg,
-                            delta: register,
-                        },
-                        Operation::Inc { register } => Operation::Dec { register },
-                        Operation::Dec { register } => Operation::Inc { register },
-                        Operation::Jnz { check, delta } => Operation::Cpy {
-                            arg: check,
-                            register: delta,
-                        },
-                        Operation::Tgl { delta } => Operation::Inc { register: delta },
-                        Operation::Out { signal } => Operation::Inc { register: signal },
+                        self.set_operation(i_toggle, self.operations[i_toggle].toggled());
                     }
                 }
                 Operation::Out { signal } => {
@@ -255,17 +951,195 @@ impl AssembunnyInterpreter {
                     self.pc += 1;
                     return Ok(());
                 }
+                #[cfg(feature = "extended-instructions")]
+                Operation::Add { arg, register } => {
+                    let value = self.get_op_argument_value(&arg);
+                    // Skip invalid instruction
+                    if let Some(register) = self.get_op_argument_register(&register) {
+                        let current = self.registers.get_mut(&register).unwrap();
+                        *current = current.wrapping_add(value);
+                    }
+                }
+                #[cfg(feature = "extended-instructions")]
+                Operation::Mul { arg, register } => {
+                    let value = self.get_op_argument_value(&arg);
+                    // Skip invalid instruction
+                    if let Some(register) = self.get_op_argument_register(&register) {
+                        let current = self.registers.get_mut(&register).unwrap();
+                        *current = current.wrapping_mul(value);
+                    }
+                }
+                #[cfg(feature = "extended-instructions")]
+                Operation::Nop => (),
+                #[cfg(feature = "extended-instructions")]
+                Operation::Jmp { delta } => {
+                    let delta = self.get_op_argument_value(&delta);
+                    self.jump(delta);
+                    continue;
+                }
             }
             // Go to the next instruction
             self.pc += 1;
         }
     }
 
+    /// Moves the program counter by the given jump delta, recording backwards jumps as loops over
+    /// the instructions jumped over. Halts if the program counter is moved outside of the program
+    /// instruction space.
+    fn jump(&mut self, delta: isize) {
+        let Some(target) = self.pc.checked_add_signed(delta) else {
+            self.pc = 0;
+            self.halted = true;
+            return;
+        };
+        if delta <= 0 {
+            *self
+                .stats
+                .loop_counts
+                .entry(target)
+                .or_default()
+                .entry(self.pc)
+                .or_insert(0) += 1;
+        }
+        self.pc = target;
+        if self.pc >= self.operations.len() {
+            self.halted = true;
+        }
+    }
+
+    /// Executes the program using the compiled backend. Loops taken are not recorded in the
+    /// execution statistics when using this backend, and each fused addition loop is recorded as a
+    /// single executed instruction.
+    fn execute_compiled(&mut self) -> Result<(), InstructionLimitExceeded> {
+        let register_names = self.registers.keys().copied().collect::<Vec<char>>();
+        let mut registers = self.registers.values().copied().collect::<Vec<isize>>();
+        let mut program = self
+            .compiled
+            .take_or_compile(&self.operations, &register_names);
+        let result = loop {
+            // Check if the program has halted
+            if self.pc >= program.len() {
+                self.halted = true;
+                break Ok(());
+            }
+            // Check if the instruction limit has been reached
+            if let Some(limit) = self.instruction_limit {
+                if self.stats.total_cycles >= limit {
+                    break Err(InstructionLimitExceeded);
+                }
+            }
+            // Record the execution of and process the current operation
+            self.stats.total_cycles += 1;
+            self.stats.instruction_counts[self.pc] += 1;
+            match program[self.pc](&mut registers) {
+                Step::Jump(pc) => self.pc = pc,
+                Step::Out(value) => {
+                    self.transmit_buffer.push_back(value);
+                    self.pc += 1;
+                    break Ok(());
+                }
+                Step::Toggle(target) => {
+                    self.operations[target] = self.operations[target].toggled();
+                    program[target] = compile_operation(
+                        &self.operations[target],
+                        target,
+                        program.len(),
+                        &register_names,
+                    );
+                    self.pc += 1;
+                }
+                Step::Halt => {
+                    self.pc = 0;
+                    self.halted = true;
+                    break Ok(());
+                }
+            }
+        };
+        // Write the register values back to the interpreter, and keep the program (updated by any
+        // toggles) for the next execution
+        for (register, value) in register_names.into_iter().zip(registers) {
+            self.registers.insert(register, value);
+        }
+        self.compiled.store(program);
+        result
+    }
+
+    /// Executes the program with the given device attached, passing each transmitted value to the
+    /// device. Execution continues until the program halts or the device requests a pause.
+    pub fn execute_with_device(
+        &mut self,
+        device: &mut dyn Device,
+    ) -> Result<(), InstructionLimitExceeded> {
+        loop {
+            self.execute()?;
+            // Pass the transmitted values to the device
+            while let Some(value) = self.get_next_transmit_value() {
+                if !device.receive(value) {
+                    return Ok(());
+                }
+            }
+            if self.halted {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Returns an iterator that lazily executes the program, yielding each value transmitted by
+    /// the program. The iterator ends when the program halts or the instruction limit is reached.
+    pub fn outputs(&mut self) -> impl Iterator<Item = isize> + '_ {
+        iter::from_fn(move || loop {
+            if let Some(value) = self.get_next_transmit_value() {
+                return Some(value);
+            }
+            if self.halted || self.execute().is_err() {
+                return None;
+            }
+        })
+    }
+
+    /// Runs the program until its output is proven to repeat forever. This is the case once the
+    /// machine state (program counter, registers and program) after a value is transmitted matches
+    /// the state after an earlier transmitted value, as execution from that state is deterministic.
+    /// Returns None if the program halts instead.
+    ///
+    /// Returns an InstructionLimitExceeded error if the instruction limit is reached first. Programs
+    /// whose state never repeats (such as those transmitting a counter) need an instruction limit
+    /// to be set for this method to return.
+    pub fn find_output_cycle(&mut self) -> Result<Option<OutputCycle>, InstructionLimitExceeded> {
+        let mut outputs = self.transmit_buffer.drain(..).collect::<Vec<isize>>();
+        let mut seen_states: HashMap<MachineState, usize> = HashMap::new();
+        while !self.halted {
+            self.execute()?;
+            let Some(value) = self.get_next_transmit_value() else {
+                continue;
+            };
+            outputs.push(value);
+            // Check if the state after the value was transmitted has been seen before
+            let state = (self.pc, self.registers.clone(), self.operations.clone());
+            match seen_states.get(&state) {
+                Some(&start) => {
+                    let cycle = outputs.split_off(start);
+                    return Ok(Some(OutputCycle {
+                        prefix: outputs,
+                        cycle,
+                    }));
+                }
+                None => _ = seen_states.insert(state, outputs.len()),
+            }
+        }
+        Ok(None)
+    }
+
     /// Gets the next value in the transmit buffer.
     pub fn get_next_transmit_value(&mut self) -> Option<isize> {
         self.transmit_buffer.pop_front()
     }
 
+    /// Gets the execution statistics collected while running the program.
+    pub fn stats(&self) -> &ExecutionStats {
+        &self.stats
+    }
+
     /// Checks if the interpreter has halted execution.
     pub fn is_halted(&self) -> bool {
         self.halted
@@ -280,10 +1154,339 @@ impl AssembunnyInterpreter {
     }
 
     /// Gets the register held in the OpArgument.
-    fn get_op_argument_register(&self, arg: &OpArgument) -> Result<char, ParseAssembunnyError> {
+    fn get_op_argument_register(&self, arg: &OpArgument) -> Option<char> {
         match arg {
-            OpArgument::Register { register } => Ok(*register),
-            OpArgument::Value { value: _ } => Err(ParseAssembunnyError),
+            OpArgument::Register { register } => Some(*register),
+            OpArgument::Value { value: _ } => None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::super::assembunnyanalysis::LoopEffect;
+    use super::super::assembunnytrace::TraceRecorder;
+    use super::*;
+
+    /// Tests that the execution statistics count each executed instruction and the loop taken by
+    /// the backwards jump.
+    #[test]
+    fn test_execution_stats() {
+        let mut interpreter = AssembunnyInterpreter::new("cpy 3 a\ndec a\njnz a -1\n").unwrap();
+        interpreter.execute().unwrap();
+        let stats = interpreter.stats();
+        assert_eq!(7, stats.total_cycles());
+        assert_eq!(&[1, 3, 3], stats.instruction_counts());
+        assert_eq!(vec![(1..=2, 2)], stats.hottest_loops(5));
+    }
+
+    /// Tests that a non-terminating program returns an error once the instruction limit is reached.
+    #[test]
+    fn test_instruction_limit_exceeded() {
+        let mut interpreter = AssembunnyInterpreter::new("inc a\njnz 1 -1\n").unwrap();
+        interpreter.set_instruction_limit(Some(100));
+        assert!(interpreter.execute().is_err());
+        assert_eq!(100, interpreter.stats().total_cycles());
+        assert!(!interpreter.is_halted());
+    }
+
+    /// Tests that the loop analysis identifies addition and multiplication loops, and flags loops
+    /// that a toggle operation could modify.
+    #[test]
+    fn test_analyse_loops() {
+        let mut interpreter = AssembunnyInterpreter::new(
+            "cpy 0 a\ncpy b c\ninc a\ndec c\njnz c -2\ndec d\njnz d -5\ndec b\njnz b -1\ntgl 1\n",
+        )
+        .unwrap();
+        let loops = interpreter.analyse_loops();
+        assert_eq!(3, loops.len());
+        assert_eq!(&(2..=4), loops[0].range());
+        assert_eq!(
+            Some(&LoopEffect::Add {
+                target: 'a',
+                source: 'c'
+            }),
+            loops[0].effect()
+        );
+        assert_eq!(
+            "b*d added into a (c and d cleared)",
+            loops[1].effect().unwrap().to_string()
+        );
+        assert_eq!(&(7..=8), loops[2].range());
+        assert_eq!(None, loops[2].effect());
+        assert!(loops.iter().all(|analysis| !analysis.may_be_toggled()));
+        assert_eq!(Some(String::from("tgl 1")), interpreter.instruction(9));
+        interpreter.replace_instruction(9, "tgl -5").unwrap();
+        let loops = interpreter.analyse_loops();
+        assert!(loops[0].may_be_toggled() && loops[1].may_be_toggled());
+        assert!(!loops[2].may_be_toggled());
+    }
+
+    /// Tests that a repeating output is detected from the machine state, and that halting and
+    /// never-repeating programs are not reported as repeating.
+    #[test]
+    fn test_find_output_cycle() {
+        let mut interpreter = AssembunnyInterpreter::new("out 0\nout 1\njnz 1 -2\n").unwrap();
+        let output = interpreter.find_output_cycle().unwrap().unwrap();
+        assert_eq!((&[0][..], &[1, 0][..]), (output.prefix(), output.cycle()));
+        assert_eq!(
+            vec![0, 1, 0, 1, 0],
+            (0..5).map(|i| output.output(i)).collect::<Vec<_>>()
+        );
+        let mut interpreter = AssembunnyInterpreter::new("out 0\nout 1\n").unwrap();
+        assert_eq!(None, interpreter.find_output_cycle().unwrap());
+        let mut interpreter = AssembunnyInterpreter::new("inc a\nout a\njnz 1 -2\n").unwrap();
+        interpreter.set_instruction_limit(Some(1000));
+        assert!(interpreter.find_output_cycle().is_err());
+    }
+
+    /// Tests that an interpreter restored from a mid-execution snapshot has the same machine state
+    /// and produces the same result as the original interpreter.
+    #[test]
+    fn test_snapshot_restore() {
+        let raw_input = "cpy 2 a\nout a\ndec a\njnz a -2\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        interpreter.execute().unwrap();
+        let snapshot = interpreter.snapshot();
+        let mut restored = AssembunnyInterpreter::restore(&snapshot).unwrap();
+        assert_eq!(snapshot, restored.snapshot());
+        interpreter.execute().unwrap();
+        restored.execute().unwrap();
+        assert_eq!(interpreter.snapshot(), restored.snapshot());
+        assert!(AssembunnyInterpreter::restore("{}").is_err());
+        // Snapshots with a bad register name or program counter are rejected
+        for (from, to) in [("\"pc\":2", "\"pc\":5"), ("\"d\":0", "\"1\":0")] {
+            assert!(snapshot.contains(from));
+            let tampered = snapshot.replacen(from, to, 1);
+            assert!(AssembunnyInterpreter::restore(&tampered).is_err());
+        }
+        // Snapshots with a program referring to a register that is not restored are rejected
+        let raw_input = "cpy 2 a\nout a\ndec d\n";
+        let snapshot = AssembunnyInterpreter::new(raw_input).unwrap().snapshot();
+        let tampered = snapshot.replacen("\"d\":0", "\"e\":0", 1);
+        assert!(AssembunnyInterpreter::restore(&tampered).is_err());
+    }
+
+    /// Tests that the interpreter can be constructed with custom register names, and that programs
+    /// referring to registers outside of the register file are rejected.
+    #[test]
+    fn test_custom_registers() {
+        let raw_input = "cpy 5 x\ncpy x y\ninc y\n";
+        let mut interpreter =
+            AssembunnyInterpreter::with_registers(raw_input, &['x', 'y']).unwrap();
+        interpreter.execute().unwrap();
+        assert_eq!(6, interpreter.get_register('y').unwrap());
+        assert!(interpreter.get_register('a').is_err());
+        assert!(AssembunnyInterpreter::new(raw_input).is_err());
+    }
+
+    /// Tests that patching the program replaces, toggles and nops out the expected instructions.
+    #[test]
+    fn test_patch_program() {
+        let raw_input = "cpy 2 a\ncpy a b\ndec b\ninc a\njnz b -2\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        assert_eq!(5, interpreter.instruction_count());
+        interpreter.replace_instruction(0, "cpy 5 a").unwrap();
+        interpreter.toggle_instruction(3).unwrap();
+        interpreter.replace_with_nops(4..5).unwrap();
+        interpreter.execute().unwrap();
+        assert_eq!(4, interpreter.get_register('a').unwrap());
+        assert_eq!(4, interpreter.get_register('b').unwrap());
+        assert!(interpreter.replace_instruction(5, "inc a").is_err());
+        assert!(interpreter.replace_instruction(0, "div a b").is_err());
+        assert!(interpreter.replace_with_nops(3..6).is_err());
+    }
+
+    /// Tests that inserting no-ops anywhere in the program, including partway through execution,
+    /// leaves the program output unchanged with both execution backends.
+    #[test]
+    fn test_insert_nops() {
+        let raw_input = "cpy 3 a\nout a\ndec a\njnz a -2\ncpy 4 b\njnz b 2\nout 7\nout b\n";
+        let expected = vec![3, 2, 1, 4];
+        for backend in [ExecutionBackend::Interpreted, ExecutionBackend::Compiled] {
+            for index in 0..=8 {
+                let mut interpreter =
+                    AssembunnyInterpreter::with_backend(raw_input, backend).unwrap();
+                interpreter.insert_nops(index, 2).unwrap();
+                assert_eq!(10, interpreter.instruction_count());
+                assert_eq!(expected, interpreter.outputs().collect::<Vec<isize>>());
+                // Insert after the first value has been transmitted
+                let mut interpreter =
+                    AssembunnyInterpreter::with_backend(raw_input, backend).unwrap();
+                interpreter.execute().unwrap();
+                interpreter.insert_nops(index, 3).unwrap();
+                assert_eq!(expected, interpreter.outputs().collect::<Vec<isize>>());
+                assert_eq!(11, interpreter.stats().instruction_counts().len());
+            }
+        }
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        assert!(interpreter.insert_nops(9, 1).is_err());
+        let mut interpreter = AssembunnyInterpreter::new("cpy 2 a\njnz 1 a\n").unwrap();
+        assert!(interpreter.insert_nops(1, 1).is_err());
+        let mut interpreter = AssembunnyInterpreter::new("tgl 1\ninc a\n").unwrap();
+        assert!(interpreter.insert_nops(1, 1).is_err());
+    }
+
+    /// Tests that the toggle targets are narrowed by the register bounds, and that the compiled
+    /// backend produces the same result as the interpreter for a self-modifying program.
+    #[test]
+    fn test_tgl_targets() {
+        let raw_input = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        assert_eq!(
+            (0..7).collect::<BTreeSet<usize>>(),
+            interpreter.tgl_targets()
+        );
+        interpreter.set_register_bounds('a', 2..=2).unwrap();
+        assert_eq!(BTreeSet::from([3, 4, 5]), interpreter.tgl_targets());
+        let mut compiled =
+            AssembunnyInterpreter::with_backend(raw_input, ExecutionBackend::Compiled).unwrap();
+        compiled.set_register_bounds('a', 2..=2).unwrap();
+        interpreter.execute().unwrap();
+        compiled.execute().unwrap();
+        assert_eq!(3, interpreter.get_register('a').unwrap());
+        assert_eq!(3, compiled.get_register('a').unwrap());
+    }
+
+    /// Tests that the compiled backend produces the same machine state as the interpreted backend.
+    #[test]
+    fn test_compiled_backend_matches_interpreted() {
+        let raw_input = "cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\ncpy a b\ndec b\njnz b -1\n";
+        let mut interpreted = AssembunnyInterpreter::new(raw_input).unwrap();
+        let mut compiled =
+            AssembunnyInterpreter::with_backend(raw_input, ExecutionBackend::Compiled).unwrap();
+        interpreted.execute().unwrap();
+        compiled.execute().unwrap();
+        for register in DEFAULT_REGISTERS {
+            assert_eq!(
+                interpreted.get_register(register).unwrap(),
+                compiled.get_register(register).unwrap()
+            );
+        }
+        assert_eq!(
+            interpreted.stats().total_cycles(),
+            compiled.stats().total_cycles()
+        );
+    }
+
+    /// Tests that the compiled program is kept between executions (including after a toggle made by
+    /// the compiled backend), and is compiled again after the program is patched.
+    #[test]
+    fn test_compiled_program_cached() {
+        let raw_input = "cpy 2 a\nout a\ntgl 1\nout b\ndec a\njnz a -4\n";
+        let mut compiled =
+            AssembunnyInterpreter::with_backend(raw_input, ExecutionBackend::Compiled).unwrap();
+        compiled.execute().unwrap();
+        assert!(compiled.compiled.is_cached());
+        let mut interpreted = AssembunnyInterpreter::new(raw_input).unwrap();
+        assert_eq!(
+            interpreted.outputs().collect::<Vec<isize>>(),
+            compiled.outputs().collect::<Vec<isize>>()
+        );
+        assert!(compiled.compiled.is_cached());
+        compiled.replace_instruction(0, "cpy 3 a").unwrap();
+        assert!(!compiled.compiled.is_cached());
+        assert!(!compiled.clone().compiled.is_cached());
+    }
+
+    /// Tests that the outputs iterator yields the transmitted values until the program halts.
+    #[test]
+    fn test_outputs() {
+        let raw_input = "cpy 3 a\nout a\ndec a\njnz a -2\nout 9\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        assert_eq!(
+            vec![3, 2, 1, 9],
+            interpreter.outputs().collect::<Vec<isize>>()
+        );
+        assert!(interpreter.is_halted());
+    }
+
+    /// Tests that a hand-optimised program using the extended instruction set produces the same
+    /// result as the original program, with both execution backends.
+    #[cfg(feature = "extended-instructions")]
+    #[test]
+    fn test_extended_instructions() {
+        let original = "cpy 6 a\ncpy 7 b\ncpy b c\ninc d\ndec c\njnz c -2\ndec a\njnz a -5\n";
+        let optimised =
+            "cpy 6 a\ncpy 7 b\ncpy b c\nmul a c\nadd c d\ncpy 0 c\ncpy 0 a\njmp 2\ninc d\nnop\n";
+        let mut interpreter = AssembunnyInterpreter::new(original).unwrap();
+        interpreter.execute().unwrap();
+        for backend in [ExecutionBackend::Interpreted, ExecutionBackend::Compiled] {
+            let mut optimised_interpreter =
+                AssembunnyInterpreter::with_backend(optimised, backend).unwrap();
+            optimised_interpreter.execute().unwrap();
+            for register in DEFAULT_REGISTERS {
+                assert_eq!(
+                    interpreter.get_register(register).unwrap(),
+                    optimised_interpreter.get_register(register).unwrap()
+                );
+            }
+        }
+    }
+
+    /// Tests that the trace recorder retains the last events emitted before the program halts, and
+    /// dumps them under a heading saying how execution ended.
+    #[test]
+    fn test_execute_traced() {
+        let raw_input = "cpy 2 a\ndec a\njnz a -1\ninc b\n";
+        let mut interpreter = AssembunnyInterpreter::new(raw_input).unwrap();
+        let mut recorder = TraceRecorder::new(3);
+        interpreter.execute_traced(&mut recorder).unwrap();
+        let events = recorder.events().collect::<Vec<&TraceEvent>>();
+        assert_eq!(3, events.len());
+        assert_eq!(
+            vec![1, 2, 3],
+            events.iter().map(|e| e.pc()).collect::<Vec<usize>>()
+        );
+        assert_eq!("jnz a -1", events[1].operation());
+        assert_eq!(Some(&0), events[2].registers().get(&'a'));
+        let dump = recorder.to_string();
+        assert!(dump.starts_with("Program halted - last 3 events:\n"));
+        assert_eq!(4, dump.lines().count());
+    }
+
+    /// Tests that all parse failures are reported with their line and column numbers.
+    #[test]
+    fn test_parse_errors_reported() {
+        let raw_input = "cpy 1 a\n  inc x\ndiv a b\njnz a q\n";
+        let errors = AssembunnyInterpreter::new(raw_input).err().unwrap();
+        let expected = vec![
+            ParseAssembunnyError::new(2, 7, "x"),
+            ParseAssembunnyError::new(3, 1, "div a b"),
+            ParseAssembunnyError::new(4, 7, "q"),
+        ];
+        assert_eq!(expected, errors);
+    }
+
+    /// Tests running the program with seeded registers, including the errors returned for unknown
+    /// registers and runaway programs.
+    #[test]
+    fn test_run_seeded() {
+        let interpreter = AssembunnyInterpreter::new("cpy c a\ninc a\ninc a").unwrap();
+        assert_eq!(2, interpreter.run_seeded(&[], 'a').unwrap());
+        assert_eq!(7, interpreter.run_seeded(&[('c', 5)], 'a').unwrap());
+        assert_eq!(
+            5,
+            interpreter
+                .seeded(&[('c', 5)])
+                .unwrap()
+                .get_register('c')
+                .unwrap()
+        );
+        assert_eq!(0, interpreter.get_register('c').unwrap());
+        assert!(matches!(
+            interpreter.run_seeded(&[('e', 1)], 'a'),
+            Err(RunProgramError::RegisterDoesNotExist)
+        ));
+        assert!(matches!(
+            interpreter.run_seeded(&[], 'e'),
+            Err(RunProgramError::RegisterDoesNotExist)
+        ));
+        let mut interpreter = AssembunnyInterpreter::new("jnz 1 0").unwrap();
+        interpreter.set_instruction_limit(Some(100));
+        assert!(matches!(
+            interpreter.run_seeded(&[], 'a'),
+            Err(RunProgramError::InstructionLimitExceeded)
+        ));
+    }
+}
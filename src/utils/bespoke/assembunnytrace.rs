@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+
+/// Event recorded by the Assembunny interpreter immediately before it executes an operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    pc: usize,
+    operation: String,
+    registers: BTreeMap<char, isize>,
+}
+
+impl TraceEvent {
+    pub(super) fn new(
+        pc: usize,
+        operation: String,
+        registers: BTreeMap<char, isize>,
+    ) -> TraceEvent {
+        TraceEvent {
+            pc,
+            operation,
+            registers,
+        }
+    }
+
+    /// Gets the program counter of the operation about to be executed.
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Gets the Assembunny code of the operation about to be executed.
+    pub fn operation(&self) -> &str {
+        &self.operation
+    }
+
+    /// Gets the register values held before the operation is executed.
+    pub fn registers(&self) -> &BTreeMap<char, isize> {
+        &self.registers
+    }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{:>4}] {:<12}", self.pc, self.operation)?;
+        for (register, value) in self.registers.iter() {
+            write!(f, " {register}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents a sink that receives the trace events emitted by the Assembunny interpreter.
+pub trait TraceSink {
+    /// Receives the event for the operation about to be executed.
+    fn record(&mut self, event: TraceEvent);
+
+    /// Called when the program halts.
+    fn halted(&mut self) {}
+
+    /// Called when execution stops due to an error (such as the instruction limit being reached).
+    fn failed(&mut self) {}
+}
+
+/// Trace sink that prints each event to stdout as it is received.
+#[derive(Default)]
+pub struct TracePrinter;
+
+impl TraceSink for TracePrinter {
+    fn record(&mut self, event: TraceEvent) {
+        println!("{event}");
+    }
+}
+
+/// Trace sink that retains the last N events received in a ring buffer. The recorder is displayed
+/// as a dump of the retained events, headed by how execution ended, for the caller to print once
+/// the program halts or execution fails.
+pub struct TraceRecorder {
+    capacity: usize,
+    events: VecDeque<TraceEvent>,
+    ending: Option<&'static str>,
+}
+
+impl TraceRecorder {
+    pub fn new(capacity: usize) -> TraceRecorder {
+        TraceRecorder {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+            ending: None,
+        }
+    }
+
+    /// Gets the retained events, from oldest to newest.
+    pub fn events(&self) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter()
+    }
+}
+
+impl TraceSink for TraceRecorder {
+    fn record(&mut self, event: TraceEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    fn halted(&mut self) {
+        self.ending = Some("Program halted");
+    }
+
+    fn failed(&mut self) {
+        self.ending = Some("Execution failed");
+    }
+}
+
+impl fmt::Display for TraceRecorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let heading = self.ending.unwrap_or("Program running");
+        write!(f, "{heading} - last {} events:", self.events.len())?;
+        for event in self.events.iter() {
+            write!(f, "\n{event}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use aoc_utils::cartography::Point2D;
+
+use crate::utils::search::SearchProblem;
+
+/// Represents the procedurally generated office maze described in the AOC 2016 Day 13 problem
+/// (https://adventofcode.com/2016/day/13). The maze extends infinitely in the positive x and y
+/// directions, with each location being a wall or open space as determined by the seed value.
+///
+/// Wall decisions are memoized as locations are queried, so repeated searches and renders over the
+/// same area of the maze do not recompute the wall formula.
+pub struct CubicleMaze {
+    seed: i64,
+    open_cache: RefCell<HashMap<Point2D, bool>>,
+}
+
+impl CubicleMaze {
+    /// Creates a new maze generated from the given seed value (the office designer's favourite
+    /// number).
+    pub fn new(seed: i64) -> CubicleMaze {
+        CubicleMaze {
+            seed,
+            open_cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the seed value used to generate the maze.
+    pub fn seed(&self) -> i64 {
+        self.seed
+    }
+
+    /// Checks if the given location is open space. Locations with a negative coordinate are outside
+    /// of the maze and are treated as walls.
+    pub fn is_open(&self, loc: &Point2D) -> bool {
+        if loc.x() < 0 || loc.y() < 0 {
+            return false;
+        }
+        *self
+            .open_cache
+            .borrow_mut()
+            .entry(*loc)
+            .or_insert_with(|| is_open_space(self.seed, loc.x(), loc.y()))
+    }
+
+    /// Checks if the given location is a wall.
+    pub fn is_wall(&self, loc: &Point2D) -> bool {
+        !self.is_open(loc)
+    }
+
+    /// Gets the open locations directly above, below, left and right of the given location.
+    pub fn neighbours(&self, loc: &Point2D) -> Vec<Point2D> {
+        loc.get_adjacent_points()
+            .into_iter()
+            .filter(|next_loc| self.is_open(next_loc))
+            .collect()
+    }
+
+    /// Gets the number of locations whose wall or open space decision has been memoized.
+    pub fn cached_locations(&self) -> usize {
+        self.open_cache.borrow().len()
+    }
+}
+
+/// Represents the search for the shortest path between two locations in the cubicle maze, using
+/// the Manhattan distance to the target location as the A* heuristic.
+///
+/// The maze is infinite, so the search is bounded to keep it finite when the target location is
+/// enclosed by walls. Only locations with coordinates no greater than those of the start or target
+/// location plus the Manhattan distance between them are searched, so a path detouring further than
+/// this is not found.
+pub struct MazeSearch<'a> {
+    maze: &'a CubicleMaze,
+    loc_start: Point2D,
+    loc_target: Point2D,
+    loc_bound: Point2D,
+}
+
+impl MazeSearch<'_> {
+    /// Creates the search for the shortest path from the start location to the target location.
+    pub fn new(maze: &CubicleMaze, loc_start: Point2D, loc_target: Point2D) -> MazeSearch<'_> {
+        let margin = loc_start.get_manhattan_distance(&loc_target) as i64;
+        let loc_bound = Point2D::new(
+            loc_start.x().max(loc_target.x()) + margin,
+            loc_start.y().max(loc_target.y()) + margin,
+        );
+        MazeSearch {
+            maze,
+            loc_start,
+            loc_target,
+            loc_bound,
+        }
+    }
+}
+
+impl SearchProblem for MazeSearch<'_> {
+    type State = Point2D;
+    type Key = Point2D;
+
+    fn key(&self, state: &Point2D) -> Point2D {
+        *state
+    }
+
+    fn initial_state(&self) -> Point2D {
+        self.loc_start
+    }
+
+    fn is_goal(&self, state: &Point2D) -> bool {
+        *state == self.loc_target
+    }
+
+    fn successors(&self, state: &Point2D) -> Vec<Point2D> {
+        self.maze
+            .neighbours(state)
+            .into_iter()
+            .filter(|loc| loc.x() <= self.loc_bound.x() && loc.y() <= self.loc_bound.y())
+            .collect()
+    }
+
+    /// Manhattan distance to the target location, which never overestimates the number of steps
+    /// remaining since each step moves one unit horizontally or vertically.
+    fn heuristic(&self, state: &Point2D) -> usize {
+        state.get_manhattan_distance(&self.loc_target) as usize
+    }
+}
+
+/// Applies the wall formula to the location with the given coordinates. The location is open space
+/// if the sum of the formula result and seed value has an even number of bits set.
+fn is_open_space(seed: i64, x: i64, y: i64) -> bool {
+    let value = x * x + 3 * x + 2 * x * y + y + y * y + seed;
+    value.count_ones().is_multiple_of(2)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::search::{search, SearchStrategy};
+
+    use super::*;
+
+    /// Tests the wall layout and neighbours using the example from the problem description.
+    #[test]
+    fn test_cubicle_maze_example() {
+        let maze = CubicleMaze::new(10);
+        let expected = [
+            ".#.####.##",
+            "..#..#...#",
+            "#....##...",
+            "###.#.###.",
+            ".##..#..#.",
+            "..##....#.",
+            "#...##.###",
+        ];
+        for (y, row) in expected.iter().enumerate() {
+            for (x, tile) in row.chars().enumerate() {
+                let loc = Point2D::new(x as i64, y as i64);
+                assert_eq!(tile == '.', maze.is_open(&loc));
+            }
+        }
+        assert_eq!(70, maze.cached_locations());
+        assert!(maze.is_wall(&Point2D::new(-1, 0)));
+        let mut neighbours = maze.neighbours(&Point2D::new(1, 1));
+        neighbours.sort_by_key(|loc| (loc.x(), loc.y()));
+        assert_eq!(vec![Point2D::new(0, 1), Point2D::new(1, 2)], neighbours);
+    }
+
+    /// Tests that the maze search finds the shortest path using the example from the problem
+    /// description, and ends without a path when the target location is enclosed by walls.
+    #[test]
+    fn test_maze_search() {
+        let maze = CubicleMaze::new(10);
+        for strategy in [SearchStrategy::BreadthFirst, SearchStrategy::AStar] {
+            let problem = MazeSearch::new(&maze, Point2D::new(1, 1), Point2D::new(7, 4));
+            assert_eq!(Some(11), search(&problem, strategy).map(|o| o.moves()));
+            let problem = MazeSearch::new(&maze, Point2D::new(1, 1), Point2D::new(2, 0));
+            assert_eq!(None, search(&problem, strategy));
+        }
+    }
+}
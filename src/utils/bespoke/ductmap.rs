@@ -0,0 +1,450 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use aoc_utils::cartography::Point2D;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::utils::grid::Grid;
+use crate::utils::metrics::Counter;
+use crate::utils::visualize::{Frame, FrameSink, Visualize};
+
+/// Represents the different types of tiles that can exist in the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileType {
+    Open,
+    Wall,
+}
+
+/// Minimum distance between each pair of waypoints, keyed by the label of the start waypoint then
+/// the label of the end waypoint.
+pub type DistanceMatrix = HashMap<char, HashMap<char, u64>>;
+
+/// Result of a search of the grid from a start location, recording the minimum distance to each
+/// reachable location and the location it was reached from on the shortest path.
+pub struct SearchTree {
+    distances: Grid<Option<u64>>,
+    predecessors: Grid<Option<Point2D>>,
+}
+
+impl SearchTree {
+    /// Gets the minimum distance from the start location of the search to the given location.
+    /// Returns None if the location was not reached.
+    pub fn distance_to(&self, loc: &Point2D) -> Option<u64> {
+        self.distances.get(loc).copied().flatten()
+    }
+
+    /// Gets the shortest path from the start location of the search to the given location,
+    /// including both ends. Returns None if the location was not reached.
+    pub fn path_to(&self, loc: &Point2D) -> Option<Vec<Point2D>> {
+        self.distance_to(loc)?;
+        let mut path = vec![*loc];
+        while let Some(prev) = self.predecessors[*path.last().unwrap()] {
+            path.push(prev);
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Map of the air ducts as described in the AOC 2016 Day 24 problem
+/// (https://adventofcode.com/2016/day/24), along with the search trees from the waypoints and the
+/// distances between the waypoints.
+///
+/// Waypoints are the tiles marked with a digit or letter, with the start waypoint marked '0'. If the
+/// map has movement costs, distances are the minimum total cost of the tiles entered rather than
+/// the minimum number of steps.
+pub struct DuctMap {
+    grid: Grid<TileType>,
+    tile_costs: Option<Grid<u64>>,
+    waypoints: HashMap<char, Point2D>,
+    search_trees: HashMap<char, SearchTree>,
+    distances: DistanceMatrix,
+}
+
+impl DuctMap {
+    /// Gets a reference to the "grid" field.
+    pub fn grid(&self) -> &Grid<TileType> {
+        &self.grid
+    }
+
+    /// Gets a reference to the "waypoints" field, mapping the label of each waypoint to its
+    /// location.
+    pub fn waypoints(&self) -> &HashMap<char, Point2D> {
+        &self.waypoints
+    }
+
+    /// Gets a reference to the "tile_costs" field, holding the cost of moving onto each location if
+    /// the map has movement costs.
+    pub fn tile_costs(&self) -> Option<&Grid<u64>> {
+        self.tile_costs.as_ref()
+    }
+
+    /// Gets the total cost of the tiles entered along the path, not including its first location.
+    /// Each tile costs 1 if the map has no movement costs.
+    pub fn path_cost(&self, path: &[Point2D]) -> u64 {
+        path.iter()
+            .skip(1)
+            .map(|loc| match &self.tile_costs {
+                Some(tile_costs) => tile_costs.get(loc).copied().unwrap_or(1),
+                None => 1,
+            })
+            .sum()
+    }
+
+    /// Gets the search tree from the waypoint with the given label. Every waypoint has a search tree,
+    /// except the highest labelled if the map has no movement costs. Returns None if there is no
+    /// search tree for the label.
+    pub fn search_tree(&self, label: char) -> Option<&SearchTree> {
+        self.search_trees.get(&label)
+    }
+
+    /// Gets a reference to the "distances" field, holding the minimum distance between each pair of
+    /// waypoints that can reach each other.
+    pub fn distances(&self) -> &DistanceMatrix {
+        &self.distances
+    }
+
+    /// Renders the map of the air ducts in the format of the input, with the waypoints shown by their
+    /// labels.
+    pub fn render(&self) -> String {
+        let labels = self
+            .waypoints
+            .iter()
+            .map(|(&label, &loc)| (loc, label))
+            .collect::<HashMap<Point2D, char>>();
+        self.grid.render(|loc, tile| match labels.get(&loc) {
+            Some(&label) => label,
+            None => match tile {
+                TileType::Open => '.',
+                TileType::Wall => '#',
+            },
+        })
+    }
+}
+
+impl Visualize for DuctMap {
+    fn visualize(&self, sink: &mut dyn FrameSink) {
+        let caption = format!("map with {} waypoints", self.waypoints.len());
+        sink.frame(Frame::new(&caption, self.render()));
+    }
+}
+
+/// Parses the map of the air ducts from the given string, and determines the minimum distances
+/// between each pair of waypoints.
+///
+/// The map can optionally be followed by a blank line and a grid of movement costs the same shape
+/// as the map, where a digit from '1' to '9' gives the cost of moving onto the tile and any other
+/// character gives a cost of 1. Distances are found by Dijkstra's algorithm if movement costs are
+/// given, and by breadth-first search otherwise.
+///
+/// Panics if the map is empty, contains a character other than a wall, open tile or waypoint label,
+/// or has a duplicate waypoint label, or if a movement cost is 0 or the grid of movement costs is
+/// not the same shape as the map.
+pub fn parse_duct_map(raw_input: &str) -> DuctMap {
+    let mut sections = raw_input.trim().split("\n\n").map(|section| {
+        section
+            .lines()
+            .map(|line| line.trim())
+            .collect::<Vec<&str>>()
+    });
+    let map_lines = sections.next().unwrap();
+    let tile_costs = sections
+        .next()
+        .and_then(|cost_lines| parse_tile_costs(&cost_lines, &map_lines));
+    if sections.next().is_some() {
+        panic!("Too many sections in input file!");
+    }
+    let mut tiles: Vec<(Point2D, TileType)> = vec![];
+    let mut waypoints: HashMap<char, Point2D> = HashMap::new();
+    for (y, line) in map_lines.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            let loc = Point2D::new(x as i64, y as i64);
+            match c {
+                '#' => tiles.push((loc, TileType::Wall)),
+                '.' => tiles.push((loc, TileType::Open)),
+                _ if c.is_ascii_alphanumeric() => {
+                    tiles.push((loc, TileType::Open));
+                    if waypoints.insert(c, loc).is_some() {
+                        panic!("Duplicate waypoint in input file! // x:{x}, y:{y} // char: {c}");
+                    }
+                }
+                _ => panic!("Bad character in input file! // x:{x}, y:{y} // char: {c}"),
+            }
+        }
+    }
+    // Short rows are padded with walls
+    let Some(grid) = Grid::from_points(tiles, TileType::Wall) else {
+        panic!("Empty map in input file!");
+    };
+    let search_trees = build_search_trees(&waypoints, &grid, tile_costs.as_ref());
+    let distances =
+        determine_min_distances_between_waypoints(&waypoints, &search_trees, tile_costs.is_none());
+    DuctMap {
+        grid,
+        tile_costs,
+        waypoints,
+        search_trees,
+        distances,
+    }
+}
+
+/// Parses the grid of movement costs that can follow the map in the input. Returns the grid of the
+/// cost of moving onto each location, or None if there are no costs.
+///
+/// Panics if a movement cost is 0, or the costs do not have the same number of lines as the map
+/// with each line the same length as the matching line of the map.
+fn parse_tile_costs(cost_lines: &[&str], map_lines: &[&str]) -> Option<Grid<u64>> {
+    if cost_lines.len() != map_lines.len() {
+        panic!(
+            "Movement costs must be the same shape as the map! // {} lines of costs, {} lines of map",
+            cost_lines.len(),
+            map_lines.len()
+        );
+    }
+    let mut tile_costs: Vec<(Point2D, u64)> = vec![];
+    for (y, (line, map_line)) in cost_lines.iter().zip(map_lines).enumerate() {
+        if line.chars().count() != map_line.chars().count() {
+            panic!("Movement costs must be the same shape as the map! // y:{y}");
+        }
+        for (x, c) in line.chars().enumerate() {
+            let cost = match c.to_digit(10) {
+                Some(0) => panic!("Movement cost must be at least 1! // x:{x}, y:{y}"),
+                Some(cost) => cost as u64,
+                None => 1,
+            };
+            tile_costs.push((Point2D::new(x as i64, y as i64), cost));
+        }
+    }
+    Grid::from_points(tile_costs, 1)
+}
+
+/// Searches the grid from each waypoint. Without movement costs the highest labelled waypoint is
+/// skipped, since the other searches are enough to find the shortest path between each unordered
+/// pair of waypoints. Returns hashmap mapping the label of the start waypoint to the search tree.
+fn build_search_trees(
+    waypoints: &HashMap<char, Point2D>,
+    grid: &Grid<TileType>,
+    tile_costs: Option<&Grid<u64>>,
+) -> HashMap<char, SearchTree> {
+    let mut labels = waypoints.keys().copied().collect::<Vec<char>>();
+    labels.sort();
+    if tile_costs.is_none() {
+        labels.pop();
+    }
+    let sources = labels
+        .iter()
+        .map(|label| waypoints[label])
+        .collect::<Vec<Point2D>>();
+    labels
+        .into_iter()
+        .zip(search_from_sources(grid, tile_costs, &sources))
+        .collect()
+}
+
+/// For each waypoint, determines the minimum distance to each other waypoint. Returns hashmap
+/// mapping the waypoint label to hashmap containing destination label mapped to distance.
+/// Unreachable waypoints are left out of the inner hashmap.
+///
+/// Without movement costs, distances are the same in both directions, so each unordered pair of
+/// waypoints is only computed once, using the search tree from the lower labelled waypoint. With
+/// movement costs, the cost of a path includes the tile at its end but not its start, so the
+/// distance in each direction is taken from the search tree of the start waypoint.
+fn determine_min_distances_between_waypoints(
+    waypoints: &HashMap<char, Point2D>,
+    search_trees: &HashMap<char, SearchTree>,
+    symmetric: bool,
+) -> DistanceMatrix {
+    let mut minimum_distances: DistanceMatrix = waypoints
+        .keys()
+        .map(|&label| (label, HashMap::new()))
+        .collect();
+    for (&label_from, search_tree) in search_trees {
+        for (&label_to, loc_to) in waypoints.iter() {
+            if label_to == label_from || (symmetric && label_to < label_from) {
+                continue;
+            }
+            if let Some(dist) = search_tree.distance_to(loc_to) {
+                minimum_distances
+                    .get_mut(&label_from)
+                    .unwrap()
+                    .insert(label_to, dist);
+                if symmetric {
+                    minimum_distances
+                        .get_mut(&label_to)
+                        .unwrap()
+                        .insert(label_from, dist);
+                }
+            }
+        }
+    }
+    minimum_distances
+}
+
+/// Searches the grid from each of the source locations, returning the search trees in the same
+/// order as the source locations.
+#[cfg(not(feature = "parallel"))]
+fn search_from_sources(
+    grid: &Grid<TileType>,
+    tile_costs: Option<&Grid<u64>>,
+    sources: &[Point2D],
+) -> Vec<SearchTree> {
+    sources
+        .iter()
+        .map(|loc| search_from_location(grid, tile_costs, loc))
+        .collect()
+}
+
+/// Searches the grid from each of the source locations, returning the search trees in the same
+/// order as the source locations. The search from each source is run on the rayon thread pool.
+#[cfg(feature = "parallel")]
+fn search_from_sources(
+    grid: &Grid<TileType>,
+    tile_costs: Option<&Grid<u64>>,
+    sources: &[Point2D],
+) -> Vec<SearchTree> {
+    sources
+        .par_iter()
+        .map(|loc| search_from_location(grid, tile_costs, loc))
+        .collect()
+}
+
+/// Total number of locations visited by the searches of the duct maps.
+static CELLS_VISITED: Counter = Counter::new("duct map cells visited");
+
+/// Searches the grid from the start location, recording the minimum distance to every location
+/// reachable from it and the location each was reached from on the shortest path. Uses Dijkstra's
+/// algorithm if movement costs are given (tiles without a cost are given a cost of 1), or a
+/// breadth-first search otherwise. The search tree is empty if the start location is not in the
+/// grid.
+fn search_from_location(
+    grid: &Grid<TileType>,
+    tile_costs: Option<&Grid<u64>>,
+    loc_start: &Point2D,
+) -> SearchTree {
+    let mut search_tree = SearchTree {
+        distances: Grid::new(grid.width(), grid.height(), None),
+        predecessors: Grid::new(grid.width(), grid.height(), None),
+    };
+    // Check if the start location is not contained in the grid
+    if !grid.contains(loc_start) {
+        return search_tree;
+    }
+    search_tree.distances[*loc_start] = Some(0);
+    let Some(tile_costs) = tile_costs else {
+        let mut visit_queue: VecDeque<(Point2D, u64)> = VecDeque::from([(*loc_start, 0)]);
+        let mut visited = 0;
+        while let Some((loc, steps)) = visit_queue.pop_front() {
+            visited += 1;
+            for next_loc in determine_next_reachable_locations(grid, &loc) {
+                if search_tree.distances[next_loc].is_none() {
+                    search_tree.distances[next_loc] = Some(steps + 1);
+                    search_tree.predecessors[next_loc] = Some(loc);
+                    visit_queue.push_back((next_loc, steps + 1));
+                }
+            }
+        }
+        CELLS_VISITED.add(visited);
+        return search_tree;
+    };
+    // Queue entries are ordered by distance, then by coordinates to break ties
+    let mut visit_queue: BinaryHeap<Reverse<(u64, i64, i64)>> =
+        BinaryHeap::from([Reverse((0, loc_start.x(), loc_start.y()))]);
+    let mut visited = 0;
+    while let Some(Reverse((dist, x, y))) = visit_queue.pop() {
+        let loc = Point2D::new(x, y);
+        // Skip stale queue entries for locations already reached by a shorter path
+        if search_tree.distances[loc].is_some_and(|current| current < dist) {
+            continue;
+        }
+        visited += 1;
+        for next_loc in determine_next_reachable_locations(grid, &loc) {
+            let next_dist = dist + tile_costs.get(&next_loc).copied().unwrap_or(1);
+            if search_tree.distances[next_loc].is_none_or(|current| current > next_dist) {
+                search_tree.distances[next_loc] = Some(next_dist);
+                search_tree.predecessors[next_loc] = Some(loc);
+                visit_queue.push(Reverse((next_dist, next_loc.x(), next_loc.y())));
+            }
+        }
+    }
+    CELLS_VISITED.add(visited);
+    search_tree
+}
+
+/// Determines the locations that can be reached in the grid from the current location.
+fn determine_next_reachable_locations(grid: &Grid<TileType>, loc: &Point2D) -> Vec<Point2D> {
+    grid.neighbours(loc)
+        .into_iter()
+        .filter(|next_loc| grid[*next_loc] == TileType::Open)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    /// Tests the distances between the waypoints for the actual problem input against the recorded
+    /// snapshot, with one row per start waypoint and one column per end waypoint.
+    #[test]
+    fn test_duct_map_distances_snapshot() {
+        let duct_map = parse_duct_map(&fs::read_to_string("./input/day24.txt").unwrap());
+        let mut labels = duct_map.distances().keys().copied().collect::<Vec<char>>();
+        labels.sort();
+        let mut table = format!(
+            "  {}",
+            labels.iter().map(|l| format!("{l:>5}")).collect::<String>()
+        );
+        for from in labels.iter() {
+            table += &format!("\n{from} ");
+            for to in labels.iter() {
+                match duct_map.distances()[from].get(to) {
+                    Some(dist) => table += &format!("{dist:>5}"),
+                    None => table += "    -",
+                }
+            }
+        }
+        insta::assert_snapshot!(table);
+    }
+
+    /// Tests that the movement costs are kept with the map and used to find the cost of a path.
+    #[test]
+    fn test_duct_map_tile_costs() {
+        let duct_map = parse_duct_map("#####\n#0.1#\n#####\n\n.....\n..5..\n.....\n");
+        let tile_costs = duct_map.tile_costs().unwrap();
+        assert_eq!((5, 3), (tile_costs.width(), tile_costs.height()));
+        let path = duct_map
+            .search_tree('0')
+            .unwrap()
+            .path_to(&Point2D::new(3, 1))
+            .unwrap();
+        assert_eq!(6, duct_map.path_cost(&path));
+        assert_eq!(Some(&6), duct_map.distances()[&'0'].get(&'1'));
+        let duct_map = parse_duct_map("#####\n#0.1#\n#####\n");
+        assert!(duct_map.tile_costs().is_none());
+        assert_eq!(2, duct_map.path_cost(&path));
+    }
+
+    /// Tests that the distances between waypoints on tiles with unequal movement costs depend on the
+    /// direction, since the cost of the tile at the end of the path is included but not the start.
+    #[test]
+    fn test_duct_map_asymmetric_tile_costs() {
+        let duct_map = parse_duct_map("#####\n#0.1#\n#####\n\n.....\n.3.7.\n.....\n");
+        assert_eq!(Some(&8), duct_map.distances()[&'0'].get(&'1'));
+        assert_eq!(Some(&4), duct_map.distances()[&'1'].get(&'0'));
+        let path = duct_map
+            .search_tree('1')
+            .unwrap()
+            .path_to(&Point2D::new(1, 1))
+            .unwrap();
+        assert_eq!(4, duct_map.path_cost(&path));
+    }
+
+    /// Tests that a grid of movement costs with a different shape from the map is rejected.
+    #[test]
+    #[should_panic(expected = "Movement costs must be the same shape as the map!")]
+    fn test_duct_map_tile_costs_shape() {
+        parse_duct_map("#####\n#0.1#\n#####\n\n.....\n..5.\n.....\n");
+    }
+}
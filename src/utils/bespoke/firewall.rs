@@ -0,0 +1,303 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use serde::Serialize;
+
+/// Represents a CIDR block, covering the addresses that share the first prefix_len bits of the base
+/// address. Addresses in a 32-bit address space are displayed in IPv4 dotted-decimal notation.
+#[derive(Debug, PartialEq)]
+pub struct CidrBlock {
+    base: u64,
+    prefix_len: u32,
+    address_bits: u32,
+}
+
+impl fmt::Display for CidrBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.address_bits == 32 {
+            let octets = (self.base as u32).to_be_bytes();
+            write!(
+                f,
+                "{}.{}.{}.{}/{}",
+                octets[0], octets[1], octets[2], octets[3], self.prefix_len
+            )
+        } else {
+            write!(f, "{}/{}", self.base, self.prefix_len)
+        }
+    }
+}
+
+/// Custom error type indicating that a line of the firewall rules is not an inclusive range given as
+/// "start-end" with start no greater than end.
+#[derive(Debug, PartialEq)]
+pub struct ParseRangeError {
+    line: String,
+}
+
+impl fmt::Display for ParseRangeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "bad range: \"{}\"", self.line)
+    }
+}
+
+/// Represents a firewall blocking the values in a set of ranges, within an address space from 0 up
+/// to an inclusive upper bound, as described in the AOC 2016 Day 20 problem
+/// (https://adventofcode.com/2016/day/20). The blocked ranges are held merged, sorted and clipped
+/// to the address space.
+#[derive(Serialize)]
+pub struct Firewall {
+    max_value: u64,
+    blocked: Vec<RangeInclusive<u64>>,
+    allowed: Vec<RangeInclusive<u64>>,
+}
+
+impl Firewall {
+    /// Creates a new firewall blocking the given ranges (in any order) within the address space from
+    /// 0 up to max_value (inclusive).
+    pub fn new(ranges: &[RangeInclusive<u64>], max_value: u64) -> Firewall {
+        let blocked = merge_ranges(ranges)
+            .into_iter()
+            .filter(|r| *r.start() <= max_value)
+            .map(|r| *r.start()..=(*r.end()).min(max_value))
+            .collect::<Vec<RangeInclusive<u64>>>();
+        let mut allowed: Vec<RangeInclusive<u64>> = vec![];
+        let mut next_unblocked = Some(0);
+        for r in blocked.iter() {
+            if let Some(start) = next_unblocked.filter(|start| start < r.start()) {
+                allowed.push(start..=*r.start() - 1);
+            }
+            next_unblocked = r.end().checked_add(1);
+        }
+        if let Some(start) = next_unblocked.filter(|start| *start <= max_value) {
+            allowed.push(start..=max_value);
+        }
+        Firewall {
+            max_value,
+            blocked,
+            allowed,
+        }
+    }
+
+    /// Gets the inclusive upper bound of the address space.
+    pub fn max_value(&self) -> u64 {
+        self.max_value
+    }
+
+    /// Gets the merged, non-overlapping ranges of blocked values in order.
+    pub fn blocked_ranges(&self) -> &[RangeInclusive<u64>] {
+        &self.blocked
+    }
+
+    /// Gets the ranges of allowed values (those not blocked) in order.
+    pub fn allowed_ranges(&self) -> &[RangeInclusive<u64>] {
+        &self.allowed
+    }
+
+    /// Gets the lowest allowed value. Returns None if the entire address space is blocked.
+    pub fn lowest_allowed(&self) -> Option<u64> {
+        self.allowed.first().map(|r| *r.start())
+    }
+
+    /// Converts the allowed ranges into the minimal list of CIDR blocks covering exactly the allowed
+    /// values. The address width is the number of bits needed to hold the address space bound.
+    pub fn allowed_cidr_blocks(&self) -> Vec<CidrBlock> {
+        let address_bits = u64::BITS - self.max_value.leading_zeros();
+        self.allowed
+            .iter()
+            .flat_map(|r| range_to_cidr_blocks(r, address_bits))
+            .collect()
+    }
+
+    /// Gets the total number of allowed values. The count is a u128, as every value in an address
+    /// space ending at u64::MAX can be allowed.
+    pub fn allowed_count(&self) -> u128 {
+        self.allowed
+            .iter()
+            .map(|r| u128::from(r.end() - r.start()) + 1)
+            .sum()
+    }
+}
+
+/// Parses the inclusive ranges given as "start-end" in the lines of the string, sorted by start
+/// value. Returns an error for the first non-empty line that is not a valid range.
+pub fn parse_ranges(raw_input: &str) -> Result<Vec<RangeInclusive<u64>>, ParseRangeError> {
+    let mut ranges = raw_input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let bad_range = || ParseRangeError {
+                line: line.to_string(),
+            };
+            let (start, end) = line.split_once('-').ok_or_else(bad_range)?;
+            let start = start.parse::<u64>().map_err(|_| bad_range())?;
+            let end = end.parse::<u64>().map_err(|_| bad_range())?;
+            if start > end {
+                return Err(bad_range());
+            }
+            Ok(start..=end)
+        })
+        .collect::<Result<Vec<RangeInclusive<u64>>, ParseRangeError>>()?;
+    ranges.sort_by(|a, b| a.start().cmp(b.start()));
+    Ok(ranges)
+}
+
+/// Splits the range into the minimal list of CIDR blocks. Each block is the largest aligned
+/// power-of-two sized block starting at the lowest value not yet covered that fits within the range.
+fn range_to_cidr_blocks(range: &RangeInclusive<u64>, address_bits: u32) -> Vec<CidrBlock> {
+    let mut blocks: Vec<CidrBlock> = vec![];
+    let mut start = *range.start() as u128;
+    let end = *range.end() as u128;
+    while start <= end {
+        // Block size is limited by the alignment of the start and the values left in the range
+        let mut size_bits = start.trailing_zeros().min(address_bits);
+        while start + (1 << size_bits) - 1 > end {
+            size_bits -= 1;
+        }
+        blocks.push(CidrBlock {
+            base: start as u64,
+            prefix_len: address_bits - size_bits,
+            address_bits,
+        });
+        start += 1 << size_bits;
+    }
+    blocks
+}
+
+/// Merges the given ranges (in any order) into the minimal list of non-overlapping ranges covering
+/// the same values, sorted by start value. Ranges that overlap or are directly adjacent are
+/// combined.
+pub fn merge_ranges(ranges: &[RangeInclusive<u64>]) -> Vec<RangeInclusive<u64>> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_by(|a, b| a.start().cmp(b.start()));
+    let mut merged: Vec<RangeInclusive<u64>> = vec![];
+    for r in sorted {
+        match merged.last_mut() {
+            Some(last) if *r.start() <= last.end().saturating_add(1) => {
+                if r.end() > last.end() {
+                    *last = *last.start()..=*r.end();
+                }
+            }
+            _ => merged.push(r),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    /// Highest value in the address space covered by the firewall rules in the problem.
+    const ADDRESS_SPACE_MAX: u64 = u32::MAX as u64;
+
+    /// Tests the range merging and firewall using the example from the problem description, with
+    /// address spaces ending at 9 (as in the example) and at the end of the u32 value range.
+    #[test]
+    fn test_firewall_example() {
+        let ranges = parse_ranges("5-8\n0-2\n4-7\n").unwrap();
+        assert_eq!(vec![0..=2, 4..=8], merge_ranges(&ranges));
+        assert_eq!(vec![0..=2, 4..=8], merge_ranges(&[5..=8, 0..=2, 4..=7]));
+        assert_eq!(
+            &[3..=3, 9..=9],
+            Firewall::new(&[5..=8, 0..=2, 4..=7], 9).allowed_ranges()
+        );
+        let firewall = Firewall::new(&ranges, 9);
+        assert_eq!(9, firewall.max_value());
+        assert_eq!(&[0..=2, 4..=8], firewall.blocked_ranges());
+        assert_eq!(&[3..=3, 9..=9], firewall.allowed_ranges());
+        assert_eq!(Some(3), firewall.lowest_allowed());
+        assert_eq!(2, firewall.allowed_count());
+        let firewall = Firewall::new(&ranges, ADDRESS_SPACE_MAX);
+        assert_eq!(
+            u128::from(ADDRESS_SPACE_MAX) + 1 - 8,
+            firewall.allowed_count()
+        );
+        // Ranges extending past the end of the address space are clipped
+        let firewall = Firewall::new(&[0..=2, 5..=u64::MAX], 6);
+        assert_eq!(&[0..=2, 5..=6], firewall.blocked_ranges());
+        assert_eq!(&[3..=4], firewall.allowed_ranges());
+        assert_eq!(
+            None,
+            Firewall::new(&[0..=u64::MAX], u64::MAX).lowest_allowed()
+        );
+        assert_eq!(
+            u128::from(u64::MAX),
+            Firewall::new(&[0..=0], u64::MAX).allowed_count()
+        );
+        assert_eq!(1 << 64, Firewall::new(&[], u64::MAX).allowed_count());
+    }
+
+    /// Tests that lines that are not valid ranges are reported as errors.
+    #[test]
+    fn test_firewall_parse_errors() {
+        for line in ["5", "5-x", "8-5", "-3", "1-2-3"] {
+            assert_eq!(
+                Err(ParseRangeError {
+                    line: line.to_string()
+                }),
+                parse_ranges(&format!("0-2\n{line}\n"))
+            );
+        }
+        assert_eq!(
+            "bad range: \"8-5\"",
+            parse_ranges("8-5").unwrap_err().to_string()
+        );
+    }
+
+    /// Tests the conversion of allowed ranges into the minimal list of CIDR blocks.
+    #[test]
+    fn test_firewall_cidr_blocks() {
+        let firewall = Firewall::new(&[0..=9, 16..=ADDRESS_SPACE_MAX - 256], ADDRESS_SPACE_MAX);
+        let blocks = firewall
+            .allowed_cidr_blocks()
+            .iter()
+            .map(|block| block.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            vec!["0.0.0.10/31", "0.0.0.12/30", "255.255.255.0/24"],
+            blocks
+        );
+        let everything = Firewall::new(&[], ADDRESS_SPACE_MAX).allowed_cidr_blocks();
+        assert_eq!("0.0.0.0/0", everything[0].to_string());
+        let example = Firewall::new(&parse_ranges("5-8\n0-2\n4-7\n").unwrap(), 15);
+        assert_eq!(
+            vec![
+                CidrBlock {
+                    base: 3,
+                    prefix_len: 4,
+                    address_bits: 4
+                },
+                CidrBlock {
+                    base: 9,
+                    prefix_len: 4,
+                    address_bits: 4
+                },
+                CidrBlock {
+                    base: 10,
+                    prefix_len: 3,
+                    address_bits: 4
+                },
+                CidrBlock {
+                    base: 12,
+                    prefix_len: 2,
+                    address_bits: 4
+                },
+            ],
+            example.allowed_cidr_blocks()
+        );
+    }
+
+    /// Tests the merged blocked ranges for the actual problem input against the recorded snapshot.
+    #[test]
+    fn test_firewall_merged_ranges_snapshot() {
+        let ranges = parse_ranges(&fs::read_to_string("./input/day20.txt").unwrap()).unwrap();
+        let merged = merge_ranges(&ranges)
+            .iter()
+            .map(|r| format!("{}-{}", r.start(), r.end()))
+            .collect::<Vec<String>>();
+        insta::assert_snapshot!(merged.join("\n"));
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Custom error type indicating that an IPv7 address could not be parsed, recording where in the
+/// address the failure occurred and the reason.
+///
+/// Examples of situations where this error could occur:
+/// - An opening square bracket within a hypernet sequence (nested hypernet sequences)
+/// - A closing square bracket without a matching opening square bracket
+/// - A hypernet sequence that is empty or never closed
+/// - A character other than a lowercase letter or square bracket
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseIpv7AddressError {
+    column: usize,
+    reason: &'static str,
+}
+
+impl ParseIpv7AddressError {
+    fn new(column: usize, reason: &'static str) -> ParseIpv7AddressError {
+        ParseIpv7AddressError { column, reason }
+    }
+
+    /// Gets the column number (starting at 1) of the address where the failure occurred.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Gets the reason the address could not be parsed.
+    pub fn reason(&self) -> &str {
+        self.reason
+    }
+}
+
+impl fmt::Display for ParseIpv7AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.reason)
+    }
+}
+
+/// Represents an "IPv7" address as described in the AOC 2016 Day 7 problem
+/// (https://adventofcode.com/2016/day/7), split into its supernet sequences (outside square
+/// brackets) and hypernet sequences (inside square brackets).
+pub struct Ipv7Address {
+    address: String,
+    supernets: Vec<String>,
+    hypernets: Vec<String>,
+}
+
+impl FromStr for Ipv7Address {
+    type Err = ParseIpv7AddressError;
+
+    /// Splits the address into its supernet and hypernet sequences with a single pass over the
+    /// address. Hypernet sequences cannot be nested within other hypernet sequences.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseIpv7AddressError::new(0, "empty address"));
+        }
+        let mut supernets: Vec<String> = vec![];
+        let mut hypernets: Vec<String> = vec![];
+        let mut in_hypernet = false;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            match c {
+                'a'..='z' => (),
+                '[' => {
+                    if in_hypernet {
+                        return Err(ParseIpv7AddressError::new(i + 1, "nested hypernet"));
+                    }
+                    if i > start {
+                        supernets.push(s[start..i].to_string());
+                    }
+                    in_hypernet = true;
+                    start = i + 1;
+                }
+                ']' => {
+                    if !in_hypernet {
+                        return Err(ParseIpv7AddressError::new(i + 1, "unmatched \"]\""));
+                    }
+                    if i == start {
+                        return Err(ParseIpv7AddressError::new(i + 1, "empty hypernet"));
+                    }
+                    hypernets.push(s[start..i].to_string());
+                    in_hypernet = false;
+                    start = i + 1;
+                }
+                _ => return Err(ParseIpv7AddressError::new(i + 1, "invalid character")),
+            }
+        }
+        if in_hypernet {
+            return Err(ParseIpv7AddressError::new(s.len() + 1, "unclosed hypernet"));
+        }
+        if s.len() > start {
+            supernets.push(s[start..].to_string());
+        }
+        Ok(Ipv7Address {
+            address: s.to_string(),
+            supernets,
+            hypernets,
+        })
+    }
+}
+
+impl Ipv7Address {
+    /// Gets a reference to the "address" field.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Gets the supernet sequences (outside square brackets) of the address.
+    pub fn supernets(&self) -> &[String] {
+        &self.supernets
+    }
+
+    /// Gets the hypernet sequences (inside square brackets) of the address.
+    pub fn hypernets(&self) -> &[String] {
+        &self.hypernets
+    }
+
+    /// Checks if the address supports "TLS" (transport-layer snooping) - one of the supernet
+    /// sequences contains an ABBA and none of the hypernet sequences contains an ABBA.
+    pub fn supports_tls(&self) -> bool {
+        self.supernets
+            .iter()
+            .any(|supernet| contains_abba(supernet))
+            && !self
+                .hypernets
+                .iter()
+                .any(|hypernet| contains_abba(hypernet))
+    }
+
+    /// Checks if the address supports "SSL" (super-secret listening) - one of the supernet
+    /// sequences contains an ABA with the corresponding BAB in one of the hypernet sequences.
+    pub fn supports_ssl(&self) -> bool {
+        // Find the possible BAB candidates
+        let mut bab_candidates: HashSet<[u8; 3]> = HashSet::new();
+        for supernet in self.supernets.iter() {
+            for window in supernet.as_bytes().windows(3) {
+                if is_aba(window) {
+                    bab_candidates.insert([window[1], window[0], window[1]]);
+                }
+            }
+        }
+        // Check if any of the hypernets contain one of the BAB candidates
+        self.hypernets.iter().any(|hypernet| {
+            hypernet
+                .as_bytes()
+                .windows(3)
+                .any(|window| bab_candidates.contains(window))
+        })
+    }
+
+    /// Gets the ABBAs found in the supernet sequences of the address, in order of occurrence.
+    pub fn supernet_abbas(&self) -> Vec<&str> {
+        self.supernets.iter().flat_map(|s| find_abbas(s)).collect()
+    }
+
+    /// Gets the ABBAs found in the hypernet sequences of the address, in order of occurrence.
+    pub fn hypernet_abbas(&self) -> Vec<&str> {
+        self.hypernets.iter().flat_map(|s| find_abbas(s)).collect()
+    }
+
+    /// Gets the ABA (from a supernet sequence) and BAB (from a hypernet sequence) pairs that make
+    /// the address support SSL. Each distinct pair is only included once.
+    pub fn aba_bab_pairs(&self) -> Vec<(&str, &str)> {
+        let mut pairs: Vec<(&str, &str)> = vec![];
+        for supernet in self.supernets.iter() {
+            for i in 0..supernet.len().saturating_sub(2) {
+                let aba = &supernet[i..i + 3];
+                if !is_aba(aba.as_bytes()) {
+                    continue;
+                }
+                let bab = aba.as_bytes();
+                let bab = [bab[1], bab[0], bab[1]];
+                for hypernet in self.hypernets.iter() {
+                    if let Some(j) = hypernet.as_bytes().windows(3).position(|w| w == bab) {
+                        let pair = (aba, &hypernet[j..j + 3]);
+                        if !pairs.contains(&pair) {
+                            pairs.push(pair);
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        pairs
+    }
+}
+
+/// Checks if the window of three characters is an ABA - two of the same character with a different
+/// character between them.
+fn is_aba(window: &[u8]) -> bool {
+    window[0] == window[2] && window[0] != window[1]
+}
+
+/// Finds all ABBAs in the sequence, in order of occurrence.
+fn find_abbas(sequence: &str) -> impl Iterator<Item = &str> {
+    (0..sequence.len().saturating_sub(3))
+        .filter(|&i| is_abba(&sequence.as_bytes()[i..i + 4]))
+        .map(|i| &sequence[i..i + 4])
+}
+
+/// Checks if the window of four characters is an ABBA - a pair of two different characters
+/// followed by the reverse of that pair.
+fn is_abba(w: &[u8]) -> bool {
+    w[0] == w[3] && w[1] == w[2] && w[0] != w[1]
+}
+
+/// Checks if the sequence contains an ABBA.
+fn contains_abba(sequence: &str) -> bool {
+    sequence.as_bytes().windows(4).any(is_abba)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests TLS support using the examples from the problem description.
+    #[test]
+    fn test_supports_tls() {
+        let check = |addr: &str| addr.parse::<Ipv7Address>().unwrap().supports_tls();
+        assert!(check("abba[mnop]qrst"));
+        assert!(!check("abcd[bddb]xyyx"));
+        assert!(!check("aaaa[qwer]tyui"));
+        assert!(check("ioxxoj[asdfgh]zxcvbn"));
+        assert!(check("aaaabba[qwer]tyui"));
+    }
+
+    /// Tests SSL support using the examples from the problem description.
+    #[test]
+    fn test_supports_ssl() {
+        let check = |addr: &str| addr.parse::<Ipv7Address>().unwrap().supports_ssl();
+        assert!(check("aba[bab]xyz"));
+        assert!(!check("xyx[xyx]xyx"));
+        assert!(check("aaa[kek]eke"));
+        assert!(check("zazbz[bzb]cdb"));
+        let address = "ab[cd]ef[gh]ij".parse::<Ipv7Address>().unwrap();
+        assert_eq!(["ab", "ef", "ij"], address.supernets());
+        assert_eq!(["cd", "gh"], address.hypernets());
+    }
+
+    /// Tests the ABBAs and ABA/BAB pairs reported as the reasons for TLS and SSL support.
+    #[test]
+    fn test_support_evidence() {
+        let address = "abbaxyyx[bddb]zaz[azaz]".parse::<Ipv7Address>().unwrap();
+        assert_eq!(vec!["abba", "xyyx"], address.supernet_abbas());
+        assert_eq!(vec!["bddb"], address.hypernet_abbas());
+        let address = "zazbz[bzb]aba[bab]".parse::<Ipv7Address>().unwrap();
+        assert_eq!(
+            vec![("zbz", "bzb"), ("aba", "bab")],
+            address.aba_bab_pairs()
+        );
+    }
+
+    /// Tests splitting of addresses that start or end with a hypernet sequence, and the errors
+    /// returned for malformed addresses.
+    #[test]
+    fn test_parse_ipv7_address() {
+        let address = "[abba]xyyx[cd]".parse::<Ipv7Address>().unwrap();
+        assert_eq!(["xyyx"], address.supernets());
+        assert_eq!(["abba", "cd"], address.hypernets());
+        assert!(!address.supports_tls());
+        let error = |addr: &str| addr.parse::<Ipv7Address>().err().unwrap();
+        assert_eq!(
+            ParseIpv7AddressError::new(4, "nested hypernet"),
+            error("a[b[c]]d")
+        );
+        assert_eq!(
+            ParseIpv7AddressError::new(3, "unmatched \"]\""),
+            error("ab]cd")
+        );
+        assert_eq!(
+            ParseIpv7AddressError::new(6, "unclosed hypernet"),
+            error("ab[cd")
+        );
+        assert_eq!(
+            ParseIpv7AddressError::new(4, "empty hypernet"),
+            error("ab[]cd")
+        );
+        assert_eq!(
+            ParseIpv7AddressError::new(2, "invalid character"),
+            error("a1b")
+        );
+        assert_eq!(ParseIpv7AddressError::new(0, "empty address"), error(""));
+    }
+}
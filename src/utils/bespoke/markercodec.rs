@@ -0,0 +1,466 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::Range;
+
+/// Represents the versions of the experimental compression format described in the AOC 2016 Day 9
+/// problem (https://adventofcode.com/2016/day/9).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FormatVersion {
+    /// Markers within the data section of another marker are not decompressed.
+    V1,
+    /// Markers within the data section of another marker are also decompressed.
+    V2,
+}
+
+/// Custom error type indicating that decompression of a compressed string has failed. Positions
+/// are the index (starting at 0) of the opening parenthesis of the marker in the compressed string,
+/// ignoring whitespace.
+#[derive(Debug)]
+pub enum DecompressError {
+    /// The marker is not terminated or is not in the "(AxB)" format.
+    MalformedMarker { position: usize, marker: String },
+    /// The sequence length given by the marker runs past the end of the enclosing section.
+    SequenceOverrun { position: usize, marker: String },
+    /// Writing the decompressed output would exceed the output limit of the decompressor.
+    OutputLimitExceeded { limit: u64 },
+    /// Writing the decompressed output to the writer failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for DecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecompressError::MalformedMarker { position, marker } => {
+                write!(f, "malformed marker at position {position}: \"{marker}\"")
+            }
+            DecompressError::SequenceOverrun { position, marker } => write!(
+                f,
+                "marker at position {position} overruns end of data: \"{marker}\""
+            ),
+            DecompressError::OutputLimitExceeded { limit } => {
+                write!(f, "decompressed output exceeds limit of {limit} bytes")
+            }
+            DecompressError::Io(e) => write!(f, "failed to write decompressed output: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for DecompressError {
+    fn from(e: io::Error) -> Self {
+        DecompressError::Io(e)
+    }
+}
+
+/// Custom error type indicating that a string could not be compressed because it contains
+/// whitespace (which is ignored by the compression format) or non-ASCII characters.
+#[derive(Debug)]
+pub struct CompressError;
+
+/// Compresses strings into version one of the experimental compression format by replacing runs
+/// of a repeated sequence with a marker, wherever the marker is shorter than the run.
+pub struct Compressor {
+    max_sequence_length: usize,
+}
+
+impl Compressor {
+    /// Creates a new compressor that searches for repeated sequences up to the given length.
+    pub fn new(max_sequence_length: usize) -> Compressor {
+        Compressor {
+            max_sequence_length,
+        }
+    }
+
+    /// Compresses the string, choosing the marker that saves the most characters at each position
+    /// from left to right. Opening parentheses outside of a marker sequence are escaped as "(1x1)(".
+    pub fn compress(&self, s: &str) -> Result<String, CompressError> {
+        if s.chars().any(|c| c.is_whitespace() || !c.is_ascii()) {
+            return Err(CompressError);
+        }
+        let chars = s.chars().collect::<Vec<char>>();
+        let mut compressed = String::new();
+        let mut index = 0;
+        while index < chars.len() {
+            match self.best_repeat(&chars[index..]) {
+                Some((seq_length, repeats)) => {
+                    compressed.push_str(&format!("({seq_length}x{repeats})"));
+                    compressed.extend(&chars[index..index + seq_length]);
+                    index += seq_length * repeats;
+                }
+                None => {
+                    if chars[index] == '(' {
+                        compressed.push_str("(1x1)");
+                    }
+                    compressed.push(chars[index]);
+                    index += 1;
+                }
+            }
+        }
+        Ok(compressed)
+    }
+
+    /// Finds the sequence length and number of repeats of the run at the start of the characters
+    /// that saves the most characters when replaced by a marker. Returns None if no marker would
+    /// save any characters.
+    fn best_repeat(&self, chars: &[char]) -> Option<(usize, usize)> {
+        let mut best: Option<(usize, usize)> = None;
+        let mut best_saving = 0;
+        for seq_length in 1..=self.max_sequence_length.min(chars.len() / 2) {
+            let sequence = &chars[..seq_length];
+            let repeats = chars
+                .chunks_exact(seq_length)
+                .take_while(|chunk| *chunk == sequence)
+                .count();
+            let marker_length = format!("({seq_length}x{repeats})").len();
+            let saving = (seq_length * repeats).saturating_sub(seq_length + marker_length);
+            if saving > best_saving {
+                best = Some((seq_length, repeats));
+                best_saving = saving;
+            }
+        }
+        best
+    }
+}
+
+/// Decompresses strings in the experimental compression format, where a marker "(AxB)" indicates
+/// that the following A characters are repeated B times. Whitespace is ignored.
+pub struct Decompressor {
+    version: FormatVersion,
+    output_limit: Option<u64>,
+}
+
+impl Decompressor {
+    /// Creates a new decompressor for the given format version, without an output limit.
+    pub fn new(version: FormatVersion) -> Decompressor {
+        Decompressor {
+            version,
+            output_limit: None,
+        }
+    }
+
+    /// Sets the maximum number of bytes the decompressor will write before failing.
+    pub fn with_output_limit(mut self, output_limit: u64) -> Decompressor {
+        self.output_limit = Some(output_limit);
+        self
+    }
+
+    /// Gets the value of the "version" field.
+    pub fn version(&self) -> FormatVersion {
+        self.version
+    }
+
+    /// Calculates the decompressed length of the string without generating the decompressed
+    /// output. Lengths too large to be represented saturate at u64::MAX.
+    pub fn decompressed_length(&self, s: &str) -> Result<u64, DecompressError> {
+        let data = strip_whitespace(s);
+        self.section_length(&data, 0..data.len())
+    }
+
+    /// Writes the fully decompressed string to the writer, returning the number of bytes written.
+    /// Fails without writing anything if the compressed string is malformed or the decompressed
+    /// length exceeds the output limit.
+    pub fn decompress_to<W: Write>(&self, s: &str, writer: &mut W) -> Result<u64, DecompressError> {
+        let data = strip_whitespace(s);
+        let length = self.section_length(&data, 0..data.len())?;
+        if let Some(limit) = self.output_limit {
+            if length > limit {
+                return Err(DecompressError::OutputLimitExceeded { limit });
+            }
+        }
+        self.write_section(&data, 0..data.len(), writer)
+    }
+
+    /// Returns an iterator that yields the characters of the decompressed string on demand, without
+    /// generating the full decompressed output. Fails if the compressed string is malformed.
+    pub fn chars(&self, s: &str) -> Result<DecompressedChars, DecompressError> {
+        let data = strip_whitespace(s);
+        self.section_length(&data, 0..data.len())?;
+        let root = SectionFrame {
+            start: 0,
+            end: data.len(),
+            index: 0,
+            repeats_remaining: 1,
+            parse_markers: true,
+        };
+        Ok(DecompressedChars {
+            version: self.version,
+            data,
+            stack: vec![root],
+        })
+    }
+
+    /// Calculates the decompressed length of the section of compressed data.
+    fn section_length(&self, data: &[u8], section: Range<usize>) -> Result<u64, DecompressError> {
+        let mut length: u64 = 0;
+        let mut index = section.start;
+        while index < section.end {
+            if data[index] != b'(' {
+                length = length.saturating_add(1);
+                index += 1;
+                continue;
+            }
+            let (marker_end, seq_length, repeats) = parse_marker(data, index, section.end)?;
+            let sequence = marker_end..marker_end + seq_length;
+            let seq_decompressed_length = match self.version {
+                FormatVersion::V1 => seq_length as u64,
+                FormatVersion::V2 => self.section_length(data, sequence)?,
+            };
+            length = length.saturating_add(seq_decompressed_length.saturating_mul(repeats as u64));
+            index = marker_end + seq_length;
+        }
+        Ok(length)
+    }
+
+    /// Writes the decompressed section of compressed data to the writer, returning the number of
+    /// bytes written.
+    fn write_section<W: Write>(
+        &self,
+        data: &[u8],
+        section: Range<usize>,
+        writer: &mut W,
+    ) -> Result<u64, DecompressError> {
+        let mut written = 0;
+        let mut index = section.start;
+        while index < section.end {
+            // Write out the run of characters up to the next marker
+            let literal_end = data[index..section.end]
+                .iter()
+                .position(|&c| c == b'(')
+                .map_or(section.end, |i| index + i);
+            if literal_end > index {
+                writer.write_all(&data[index..literal_end])?;
+                written += (literal_end - index) as u64;
+                index = literal_end;
+                continue;
+            }
+            let (marker_end, seq_length, repeats) = parse_marker(data, index, section.end)?;
+            let sequence = marker_end..marker_end + seq_length;
+            for _ in 0..repeats {
+                written += match self.version {
+                    FormatVersion::V1 => {
+                        writer.write_all(&data[sequence.clone()])?;
+                        seq_length as u64
+                    }
+                    FormatVersion::V2 => self.write_section(data, sequence.clone(), writer)?,
+                };
+            }
+            index = sequence.end;
+        }
+        Ok(written)
+    }
+}
+
+/// Represents a section of compressed data being decompressed by the DecompressedChars iterator.
+struct SectionFrame {
+    start: usize,
+    end: usize,
+    index: usize,
+    repeats_remaining: usize,
+    parse_markers: bool,
+}
+
+/// Iterator that lazily yields the characters of a decompressed string. Only the compressed string
+/// and one frame per level of marker nesting are held in memory.
+pub struct DecompressedChars {
+    version: FormatVersion,
+    data: Vec<u8>,
+    stack: Vec<SectionFrame>,
+}
+
+impl Iterator for DecompressedChars {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.stack.last_mut()?;
+            // Repeat the section or return to the enclosing section once the end is reached
+            if frame.index >= frame.end {
+                if frame.repeats_remaining > 1 {
+                    frame.repeats_remaining -= 1;
+                    frame.index = frame.start;
+                } else {
+                    self.stack.pop();
+                }
+                continue;
+            }
+            let c = self.data[frame.index];
+            if c != b'(' || !frame.parse_markers {
+                frame.index += 1;
+                return Some(c as char);
+            }
+            // Descend into the sequence following the marker
+            let (marker_end, seq_length, repeats) =
+                parse_marker(&self.data, frame.index, frame.end)
+                    .expect("compressed data validated when iterator created");
+            frame.index = marker_end + seq_length;
+            if seq_length > 0 && repeats > 0 {
+                self.stack.push(SectionFrame {
+                    start: marker_end,
+                    end: marker_end + seq_length,
+                    index: marker_end,
+                    repeats_remaining: repeats,
+                    parse_markers: self.version == FormatVersion::V2,
+                });
+            }
+        }
+    }
+}
+
+/// Parses the marker starting at the given index of the compressed data, within a section ending
+/// at the given index. Returned value is the index after the end of the marker, the length of the
+/// marker sequence and the number of repeats.
+fn parse_marker(
+    data: &[u8],
+    index: usize,
+    section_end: usize,
+) -> Result<(usize, usize, usize), DecompressError> {
+    let Some(close) = data[index..section_end].iter().position(|&c| c == b')') else {
+        return Err(DecompressError::MalformedMarker {
+            position: index,
+            marker: String::from_utf8_lossy(&data[index..section_end]).to_string(),
+        });
+    };
+    let marker_end = index + close + 1;
+    let marker = String::from_utf8_lossy(&data[index..marker_end]).to_string();
+    let parsed = marker[1..marker.len() - 1]
+        .split_once('x')
+        .filter(|(a, b)| a.bytes().chain(b.bytes()).all(|c| c.is_ascii_digit()))
+        .map(|(a, b)| (a.parse::<usize>(), b.parse::<usize>()));
+    let Some((Ok(seq_length), Ok(repeats))) = parsed else {
+        return Err(DecompressError::MalformedMarker {
+            position: index,
+            marker,
+        });
+    };
+    if seq_length > section_end - marker_end {
+        return Err(DecompressError::SequenceOverrun {
+            position: index,
+            marker,
+        });
+    }
+    Ok((marker_end, seq_length, repeats))
+}
+
+/// Removes the whitespace from the string, which is ignored by the compression format.
+fn strip_whitespace(s: &str) -> Vec<u8> {
+    s.bytes().filter(|c| !c.is_ascii_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests version one decompression using the examples from the problem description.
+    #[test]
+    fn test_decompress_v1() {
+        let decompressor = Decompressor::new(FormatVersion::V1);
+        let examples = [
+            ("ADVENT", "ADVENT"),
+            ("A(1x5)BC", "ABBBBBC"),
+            ("(3x3)XYZ", "XYZXYZXYZ"),
+            ("A(2x2)BCD(2x2)EFG", "ABCBCDEFEFG"),
+            ("(6x1)(1x3)A", "(1x3)A"),
+            ("X(8x2)(3x3)ABCY", "X(3x3)ABC(3x3)ABCY"),
+        ];
+        for (compressed, expected) in examples {
+            let mut output: Vec<u8> = vec![];
+            let written = decompressor.decompress_to(compressed, &mut output).unwrap();
+            assert_eq!(expected.as_bytes(), output);
+            assert_eq!(
+                decompressor.decompressed_length(compressed).unwrap(),
+                written
+            );
+        }
+    }
+
+    /// Tests version two decompression and the output limit.
+    #[test]
+    fn test_decompress_v2() {
+        let decompressor = Decompressor::new(FormatVersion::V2).with_output_limit(20);
+        let mut output: Vec<u8> = vec![];
+        decompressor
+            .decompress_to("X(8x2)(3x3)ABCY", &mut output)
+            .unwrap();
+        assert_eq!(b"XABCABCABCABCABCABCY".to_vec(), output);
+        let compressed = "(27x12)(20x12)(13x14)(7x10)(1x12)A";
+        assert_eq!(
+            241920,
+            decompressor.decompressed_length(compressed).unwrap()
+        );
+        let mut output: Vec<u8> = vec![];
+        let result = decompressor.decompress_to(compressed, &mut output);
+        assert!(matches!(
+            result,
+            Err(DecompressError::OutputLimitExceeded { limit: 20 })
+        ));
+        assert!(output.is_empty());
+    }
+
+    /// Tests that compressed strings are shorter for repetitive input and round-trip through the
+    /// version one decompressor.
+    #[test]
+    fn test_compress_round_trip() {
+        let compressor = Compressor::new(16);
+        let decompressor = Decompressor::new(FormatVersion::V1);
+        assert_eq!("A(1x10)BC", compressor.compress("ABBBBBBBBBBC").unwrap());
+        assert_eq!("(3x5)XYZ", compressor.compress(&"XYZ".repeat(5)).unwrap());
+        let examples = [
+            String::from(""),
+            String::from("ADVENT"),
+            String::from("(1x3)A((("),
+            format!("{}({}){}", "ab".repeat(20), "x)y".repeat(7), "Q".repeat(30)),
+        ];
+        for original in examples {
+            let compressed = compressor.compress(&original).unwrap();
+            let decompressed = decompressor.chars(&compressed).unwrap().collect::<String>();
+            assert_eq!(original, decompressed);
+        }
+        assert!(compressor.compress("AB CD").is_err());
+        assert!(compressor.compress("ABÇD").is_err());
+    }
+
+    /// Tests that the lazy decompression iterator yields the same characters as the decompressor
+    /// writes out, including when sampling part way through a large decompressed string.
+    #[test]
+    fn test_decompressed_chars() {
+        for version in [FormatVersion::V1, FormatVersion::V2] {
+            let decompressor = Decompressor::new(version);
+            for compressed in ["A(2x2)BCD(2x2)EFG", "X(8x2)(3x3)ABCY", "(1x0)A(0x4)B(1x2)C"] {
+                let mut output: Vec<u8> = vec![];
+                decompressor.decompress_to(compressed, &mut output).unwrap();
+                let chars = decompressor.chars(compressed).unwrap().collect::<String>();
+                assert_eq!(String::from_utf8(output).unwrap(), chars);
+            }
+        }
+        let decompressor = Decompressor::new(FormatVersion::V2);
+        let compressed = "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN";
+        assert_eq!(445, decompressor.chars(compressed).unwrap().count());
+        assert_eq!(Some('N'), decompressor.chars(compressed).unwrap().nth(444));
+    }
+
+    /// Tests the errors returned for truncated and garbage markers.
+    #[test]
+    fn test_malformed_markers() {
+        let decompressor = Decompressor::new(FormatVersion::V2);
+        let error = |s: &str| decompressor.decompressed_length(s).unwrap_err().to_string();
+        assert_eq!("malformed marker at position 1: \"(3x\"", error("A(3x"));
+        assert_eq!("malformed marker at position 0: \"(axb)\"", error("(axb)C"));
+        assert_eq!(
+            "malformed marker at position 0: \"(3x+2)\"",
+            error("(3x+2)ABC")
+        );
+        assert_eq!("malformed marker at position 2: \"()\"", error("AB()"));
+        assert_eq!(
+            "marker at position 1 overruns end of data: \"(5x2)\"",
+            error("X(5x2)AB")
+        );
+        // Nested marker overrunning the end of its enclosing sequence
+        assert_eq!(
+            "marker at position 5 overruns end of data: \"(4x2)\"",
+            error("(7x2)(4x2)ABCD")
+        );
+        let mut output: Vec<u8> = vec![];
+        assert!(decompressor.decompress_to("AB(2x2", &mut output).is_err());
+        assert!(output.is_empty());
+        assert!(decompressor.chars("(99999999999999999999x1)A").is_err());
+    }
+}
@@ -1,5 +1,72 @@
+// The Assembunny modules and the modules that parse their input with regular expressions are only
+// built when the fancy-regex dependency is enabled by a day feature that needs them
+#[cfg(feature = "fancy-regex")]
+mod assembunnyanalysis;
+#[cfg(feature = "fancy-regex")]
+mod assembunnyassembler;
+#[cfg(feature = "fancy-regex")]
+mod assembunnycompiler;
+#[cfg(feature = "fancy-regex")]
+mod assembunnydevice;
+#[cfg(feature = "arbitrary")]
+mod assembunnyfuzz;
+#[cfg(feature = "fancy-regex")]
 mod assembunnyinterpreter;
+#[cfg(feature = "fancy-regex")]
+mod assembunnytrace;
+mod cubiclemaze;
+mod ductmap;
+mod firewall;
+mod ipv7address;
+mod markercodec;
 mod room;
+#[cfg(feature = "fancy-regex")]
+mod rtgfacility;
+#[cfg(feature = "fancy-regex")]
+mod scrambler;
+#[cfg(feature = "arbitrary")]
+mod scramblerfuzz;
+#[cfg(feature = "fancy-regex")]
+mod screen;
+#[cfg(feature = "fancy-regex")]
+mod storagenode;
 
-pub use assembunnyinterpreter::AssembunnyInterpreter;
+#[cfg(feature = "fancy-regex")]
+pub use assembunnyanalysis::{LoopAnalysis, LoopEffect};
+#[cfg(feature = "fancy-regex")]
+pub use assembunnycompiler::ExecutionBackend;
+#[cfg(feature = "fancy-regex")]
+pub use assembunnydevice::{ClockSignalValidator, Device, Printer};
+#[cfg(feature = "arbitrary")]
+pub use assembunnyfuzz::ArbitraryProgram;
+#[cfg(feature = "fancy-regex")]
+pub use assembunnyinterpreter::{
+    AssembunnyInterpreter, ExecutionStats, InstructionLimitExceeded, OutputCycle,
+    ParseAssembunnyError, PatchProgramError, RestoreSnapshotError, RunProgramError,
+};
+#[cfg(feature = "fancy-regex")]
+pub use assembunnytrace::{TraceEvent, TracePrinter, TraceRecorder, TraceSink};
+pub use cubiclemaze::{CubicleMaze, MazeSearch};
+pub use ductmap::{parse_duct_map, DistanceMatrix, DuctMap, SearchTree, TileType};
+pub use firewall::{merge_ranges, parse_ranges, CidrBlock, Firewall, ParseRangeError};
+pub use ipv7address::{Ipv7Address, ParseIpv7AddressError};
+pub use markercodec::{
+    CompressError, Compressor, DecompressError, DecompressedChars, Decompressor, FormatVersion,
+};
 pub use room::Room;
+#[cfg(feature = "fancy-regex")]
+pub use rtgfacility::{
+    parse_facility, Component, ComponentType, FacilityBuilder, FacilityInputError, FacilitySearch,
+    FacilityState,
+};
+#[cfg(feature = "fancy-regex")]
+pub use scrambler::{
+    ParseScrambleOperationError, ScrambleOperation, ScrambleOperationError, ScramblePermutation,
+    Scrambler,
+};
+#[cfg(feature = "arbitrary")]
+pub use scramblerfuzz::ArbitraryScramble;
+#[cfg(feature = "fancy-regex")]
+pub use screen::{Screen, ScreenInstruction};
+#[cfg(feature = "fancy-regex")]
+pub use storagenode::{convert_nodes_to_tiles, parse_nodes, NodeData, NodeType};
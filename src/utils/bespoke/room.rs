@@ -1,3 +1,4 @@
+use std::cell::OnceCell;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
@@ -7,6 +8,7 @@ pub struct Room {
     name: String,
     sector_id: u32,
     checksum: String,
+    decrypted_name: OnceCell<String>,
 }
 
 impl Room {
@@ -15,6 +17,7 @@ impl Room {
             name: name.to_string(),
             sector_id,
             checksum: checksum.to_string(),
+            decrypted_name: OnceCell::new(),
         }
     }
 
@@ -61,18 +64,41 @@ impl Room {
         checksum_candidate == self.checksum
     }
 
-    /// Determines the unencrypted name for the room.
-    pub fn decrypted_name(&self) -> String {
-        let mut decrypted_name = String::new();
-        for c in self.name.chars() {
-            if c == '-' {
-                decrypted_name.push(' ');
-                continue;
-            }
-            let c_shifted =
-                char::from_u32('a' as u32 + (c as u32 - 'a' as u32 + self.sector_id) % 26).unwrap();
-            decrypted_name.push(c_shifted);
-        }
-        decrypted_name
+    /// Gets the unencrypted name for the room, determined by rotating each letter of the encrypted
+    /// name forward through the alphabet by the sector ID and replacing dashes with spaces. The
+    /// decrypted name is cached after it is first determined.
+    pub fn decrypted_name(&self) -> &str {
+        self.decrypted_name.get_or_init(|| {
+            let shift = self.sector_id % 26;
+            self.name
+                .chars()
+                .map(|c| match c {
+                    '-' => ' ',
+                    _ => char::from_u32('a' as u32 + (c as u32 - 'a' as u32 + shift) % 26).unwrap(),
+                })
+                .collect::<String>()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that the room name is decrypted using the example from the problem description.
+    #[test]
+    fn test_decrypted_name() {
+        let room = Room::new("qzmt-zixmtkozy-ivhz", 343, "zimth");
+        assert_eq!("very encrypted name", room.decrypted_name());
+        assert_eq!("very encrypted name", room.decrypted_name());
+    }
+
+    /// Tests that real rooms are identified using the examples from the problem description.
+    #[test]
+    fn test_is_real_room() {
+        assert!(Room::new("aaaaa-bbb-z-y-x", 123, "abxyz").is_real_room());
+        assert!(Room::new("a-b-c-d-e-f-g-h", 987, "abcde").is_real_room());
+        assert!(Room::new("not-a-real-room", 404, "oarel").is_real_room());
+        assert!(!Room::new("totally-real-room", 200, "decoy").is_real_room());
     }
 }
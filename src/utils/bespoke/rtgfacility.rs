@@ -0,0 +1,600 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+
+use fancy_regex::Regex;
+use itertools::Itertools;
+
+use crate::utils::search::SearchProblem;
+
+/// Ordinal words used to name the floors of the facility in the input file, from the bottom floor.
+/// This limits facility descriptions to 20 floors, with any further floors reported as an error.
+const FLOOR_ORDINALS: [&str; 20] = [
+    "first",
+    "second",
+    "third",
+    "fourth",
+    "fifth",
+    "sixth",
+    "seventh",
+    "eighth",
+    "ninth",
+    "tenth",
+    "eleventh",
+    "twelfth",
+    "thirteenth",
+    "fourteenth",
+    "fifteenth",
+    "sixteenth",
+    "seventeenth",
+    "eighteenth",
+    "nineteenth",
+    "twentieth",
+];
+
+/// Represents the two different types of Components found within the "Radioisotope Testing
+/// Facility" described in the AOC 2016 Day 11 problem (https://adventofcode.com/2016/day/11).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComponentType {
+    Generator,
+    Microchip,
+}
+
+/// Represents an individual Component found within the "Radioisotope Testing Facility".
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Component {
+    comp_type: ComponentType,
+    name: String,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.comp_type {
+            ComponentType::Generator => write!(f, "{} generator", self.name),
+            ComponentType::Microchip => write!(f, "{}-compatible microchip", self.name),
+        }
+    }
+}
+
+impl Component {
+    pub fn new(comp_type: ComponentType, name: &str) -> Component {
+        Component {
+            comp_type,
+            name: name.to_string(),
+        }
+    }
+
+    /// Gets the value of the "comp_type" field.
+    pub fn comp_type(&self) -> ComponentType {
+        self.comp_type
+    }
+
+    /// Gets the value of the "name" field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Custom error type indicating that the facility description in the input file is invalid.
+#[derive(Debug, PartialEq, Eq)]
+pub enum FacilityInputError {
+    /// The input file does not describe any floors
+    NoFloors,
+    /// The given line does not describe the contents of a floor
+    BadLine { line: usize, text: String },
+    /// The floor described on the given line is not the next floor up from the previous line
+    FloorOutOfOrder {
+        line: usize,
+        expected: String,
+        found: String,
+    },
+    /// The facility described has more floors than can be named in the input file
+    TooManyFloors { line: usize, max: usize },
+    /// The component appears more than once in the facility
+    DuplicateComponent { name: String },
+    /// The microchip's generator does not appear in the facility
+    UnpairedMicrochip { name: String },
+    /// The generator's microchip does not appear in the facility
+    UnpairedGenerator { name: String },
+    /// A microchip on the given floor (starting at 1) starts next to a mismatched generator
+    UnsafeFloor { floor: usize },
+}
+
+impl fmt::Display for FacilityInputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FacilityInputError::NoFloors => write!(f, "no floors described"),
+            FacilityInputError::BadLine { line, text } => {
+                write!(f, "line {line}: cannot parse floor \"{text}\"")
+            }
+            FacilityInputError::FloorOutOfOrder {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: expected {expected} floor, found \"{found}\""
+            ),
+            FacilityInputError::TooManyFloors { line, max } => {
+                write!(f, "line {line}: facility has more than {max} floors")
+            }
+            FacilityInputError::DuplicateComponent { name } => {
+                write!(f, "duplicate component \"{name}\"")
+            }
+            FacilityInputError::UnpairedMicrochip { name } => {
+                write!(f, "{name}-compatible microchip has no generator")
+            }
+            FacilityInputError::UnpairedGenerator { name } => {
+                write!(f, "{name} generator has no microchip")
+            }
+            FacilityInputError::UnsafeFloor { floor } => {
+                write!(
+                    f,
+                    "floor {floor}: microchip starts next to a mismatched generator"
+                )
+            }
+        }
+    }
+}
+
+/// Represents the current state of the "Radioisotope Testing Facility".
+#[derive(Clone)]
+pub struct FacilityState {
+    /// Current floor of the elevator
+    elev_floor: usize,
+    /// State of the floor comps - floors need to be in sorted order to enable stable hashing
+    floor_comps: Vec<BTreeSet<Component>>,
+}
+
+impl FacilityState {
+    /// Gets the value of the "elev_floor" field (starting at 0).
+    pub fn elev_floor(&self) -> usize {
+        self.elev_floor
+    }
+
+    /// Gets the Components on each floor, from the first floor up.
+    pub fn floor_comps(&self) -> &[BTreeSet<Component>] {
+        &self.floor_comps
+    }
+}
+
+/// Assembles a facility directly from the floors of the generator and microchip of each element,
+/// instead of from a description of the contents of each floor.
+pub struct FacilityBuilder {
+    elev_floor: usize,
+    floor_comps: Vec<BTreeSet<Component>>,
+    duplicate: Option<String>,
+}
+
+impl FacilityBuilder {
+    /// Creates a builder for a facility with the given number of empty floors, with the elevator on
+    /// the first floor.
+    pub fn new(floors: usize) -> FacilityBuilder {
+        FacilityBuilder {
+            elev_floor: 0,
+            floor_comps: vec![BTreeSet::new(); floors],
+            duplicate: None,
+        }
+    }
+
+    /// Places the generator and microchip of the named element on the given floors (starting at 0).
+    ///
+    /// Panics if either floor is outside of the facility.
+    pub fn element(mut self, name: &str, generator_floor: usize, microchip_floor: usize) -> Self {
+        for (comp_type, floor) in [
+            (ComponentType::Generator, generator_floor),
+            (ComponentType::Microchip, microchip_floor),
+        ] {
+            if floor >= self.floor_comps.len() {
+                panic!("Floor outside of facility! // {floor}");
+            }
+            let comp = Component::new(comp_type, name);
+            if self.floor_comps.iter().any(|floor| floor.contains(&comp)) {
+                self.duplicate.get_or_insert_with(|| name.to_string());
+            }
+            self.floor_comps[floor].insert(comp);
+        }
+        self
+    }
+
+    /// Places the elevator on the given floor (starting at 0).
+    ///
+    /// Panics if the floor is outside of the facility.
+    pub fn elevator(mut self, floor: usize) -> Self {
+        if floor >= self.floor_comps.len() {
+            panic!("Floor outside of facility! // {floor}");
+        }
+        self.elev_floor = floor;
+        self
+    }
+
+    /// Builds the facility state, after checking the facility in the same way as a parsed
+    /// description.
+    pub fn build(self) -> Result<FacilityState, FacilityInputError> {
+        if let Some(name) = self.duplicate {
+            return Err(FacilityInputError::DuplicateComponent { name });
+        }
+        validate_facility(&self.floor_comps)?;
+        Ok(FacilityState {
+            elev_floor: self.elev_floor,
+            floor_comps: self.floor_comps,
+        })
+    }
+}
+
+/// Represents the search for the minimum number of moves required to move all Components from
+/// their starting floors to the top floor.
+pub struct FacilitySearch {
+    initial_state: FacilityState,
+}
+
+impl FacilitySearch {
+    /// Creates the search starting from the given Components on each floor, with the elevator on
+    /// the first floor.
+    pub fn new(floor_comps: Vec<BTreeSet<Component>>) -> FacilitySearch {
+        FacilitySearch::from_state(FacilityState {
+            elev_floor: 0,
+            floor_comps,
+        })
+    }
+
+    /// Creates the search starting from the given facility state (such as one assembled by a
+    /// facility builder), including the floor the elevator starts on.
+    pub fn from_state(initial_state: FacilityState) -> FacilitySearch {
+        FacilitySearch { initial_state }
+    }
+}
+
+/// Key identifying equivalent facility states - the elevator floor and the sorted floors of the
+/// generator and microchip of each element. States that differ only by swapping the names of
+/// elements are equivalent.
+type FacilityKey = (usize, Vec<(Option<usize>, Option<usize>)>);
+
+impl SearchProblem for FacilitySearch {
+    type State = FacilityState;
+    type Key = FacilityKey;
+
+    fn key(&self, state: &FacilityState) -> FacilityKey {
+        let mut element_floors: BTreeMap<&String, (Option<usize>, Option<usize>)> = BTreeMap::new();
+        for (floor, comps) in state.floor_comps.iter().enumerate() {
+            for comp in comps.iter() {
+                let floors = element_floors.entry(&comp.name).or_default();
+                match comp.comp_type {
+                    ComponentType::Generator => floors.0 = Some(floor),
+                    ComponentType::Microchip => floors.1 = Some(floor),
+                }
+            }
+        }
+        let mut pairs = element_floors.into_values().collect::<Vec<_>>();
+        pairs.sort();
+        (state.elev_floor, pairs)
+    }
+
+    fn initial_state(&self) -> FacilityState {
+        self.initial_state.clone()
+    }
+
+    fn is_goal(&self, state: &FacilityState) -> bool {
+        state.elev_floor == state.floor_comps.len() - 1
+            && check_if_all_components_at_top_floor(&state.floor_comps)
+    }
+
+    fn successors(&self, state: &FacilityState) -> Vec<FacilityState> {
+        get_next_states(state)
+    }
+
+    /// Each move crosses the boundary between two adjacent floors. For the k components on or
+    /// below a floor to cross the boundary above it, with at most two components carried up and at
+    /// least one carried back down on each trip, at least 2k-3 crossings are needed (one for a
+    /// single component) if the elevator starts at or below the boundary, or 2k if it starts above.
+    fn heuristic(&self, state: &FacilityState) -> usize {
+        let mut comps_below = 0;
+        let mut estimate = 0;
+        for (floor, comps) in state
+            .floor_comps
+            .iter()
+            .enumerate()
+            .take(state.floor_comps.len() - 1)
+        {
+            comps_below += comps.len();
+            if comps_below == 0 {
+                continue;
+            }
+            estimate += if state.elev_floor <= floor {
+                (2 * comps_below).saturating_sub(3).max(1)
+            } else {
+                2 * comps_below
+            };
+        }
+        estimate
+    }
+}
+
+/// Parses the description of the Components on each floor of the facility. The number of floors is
+/// given by the number of lines (at most 20), which must describe the floors in order from the first
+/// floor up.
+pub fn parse_facility(raw_input: &str) -> Result<Vec<BTreeSet<Component>>, FacilityInputError> {
+    let mut floor_comps: Vec<BTreeSet<Component>> = vec![];
+    let regex_floor = Regex::new(r"^The ([a-z]+) floor contains (.*)$").unwrap();
+    let regex_generator = Regex::new(r"([a-z]+) generator").unwrap();
+    let regex_microchip = Regex::new(r"([a-z]+)-compatible microchip").unwrap();
+    for (i, line) in raw_input.lines().enumerate() {
+        // Ignore empty lines from input
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Ok(Some(caps)) = regex_floor.captures(line) else {
+            return Err(FacilityInputError::BadLine {
+                line: i + 1,
+                text: line.to_string(),
+            });
+        };
+        // Check that the floors are given in order
+        let Some(&expected) = FLOOR_ORDINALS.get(floor_comps.len()) else {
+            return Err(FacilityInputError::TooManyFloors {
+                line: i + 1,
+                max: FLOOR_ORDINALS.len(),
+            });
+        };
+        if &caps[1] != expected {
+            return Err(FacilityInputError::FloorOutOfOrder {
+                line: i + 1,
+                expected: expected.to_string(),
+                found: caps[1].to_string(),
+            });
+        }
+        let mut floor: BTreeSet<Component> = BTreeSet::new();
+        // Find generators and microchips
+        let found_comps = itertools::chain(
+            regex_generator
+                .captures_iter(&caps[2])
+                .map(|caps| (ComponentType::Generator, caps.unwrap()[1].to_string())),
+            regex_microchip
+                .captures_iter(&caps[2])
+                .map(|caps| (ComponentType::Microchip, caps.unwrap()[1].to_string())),
+        );
+        for (comp_type, name) in found_comps {
+            let comp = Component::new(comp_type, &name);
+            if floor_comps.iter().any(|floor| floor.contains(&comp)) || !floor.insert(comp) {
+                return Err(FacilityInputError::DuplicateComponent { name });
+            }
+        }
+        // Add floor to output
+        floor_comps.push(floor);
+    }
+    validate_facility(&floor_comps)?;
+    Ok(floor_comps)
+}
+
+/// Checks that the facility has at least one floor, every generator and microchip is paired with
+/// its counterpart, and no microchip starts next to a mismatched generator.
+fn validate_facility(floor_comps: &[BTreeSet<Component>]) -> Result<(), FacilityInputError> {
+    if floor_comps.is_empty() {
+        return Err(FacilityInputError::NoFloors);
+    }
+    let all_comps = floor_comps
+        .iter()
+        .flatten()
+        .collect::<HashSet<&Component>>();
+    for comp in all_comps.iter() {
+        let counterpart = match comp.comp_type {
+            ComponentType::Generator => Component::new(ComponentType::Microchip, &comp.name),
+            ComponentType::Microchip => Component::new(ComponentType::Generator, &comp.name),
+        };
+        if !all_comps.contains(&counterpart) {
+            let name = comp.name.to_string();
+            return Err(match comp.comp_type {
+                ComponentType::Generator => FacilityInputError::UnpairedGenerator { name },
+                ComponentType::Microchip => FacilityInputError::UnpairedMicrochip { name },
+            });
+        }
+    }
+    if let Some(i) = floor_comps.iter().position(|floor| !validate_floor(floor)) {
+        return Err(FacilityInputError::UnsafeFloor { floor: i + 1 });
+    }
+    Ok(())
+}
+
+/// Determines the next possible states from the given facility state.
+fn get_next_states(state: &FacilityState) -> Vec<FacilityState> {
+    let mut next_states: Vec<FacilityState> = vec![];
+    let move_options = itertools::chain(
+        state.floor_comps[state.elev_floor].iter().combinations(2),
+        state.floor_comps[state.elev_floor].iter().combinations(1),
+    );
+    let mut next_states_two_up: Vec<FacilityState> = vec![];
+    let mut next_states_one_up: Vec<FacilityState> = vec![];
+    let mut next_states_two_down: Vec<FacilityState> = vec![];
+    let mut next_states_one_down: Vec<FacilityState> = vec![];
+    for comps in move_options {
+        for floor_delta in [1, -1] {
+            // Skip move if at top or bottom floor and no floor to move to
+            if state.elev_floor == 0 && floor_delta == -1
+                || state.elev_floor == state.floor_comps.len() - 1 && floor_delta == 1
+            {
+                continue;
+            }
+            // Don't move one component up if two components can be moved up
+            if floor_delta == 1 && !next_states_two_up.is_empty() && comps.len() == 1 {
+                continue;
+            }
+            // Don't move two components down if one component can be moved down
+            if floor_delta == -1 && !next_states_one_down.is_empty() && comps.len() == 2 {
+                continue;
+            }
+            // Don't move down if all floors below are empty
+            if floor_delta == -1 {
+                let mut skip = true;
+                for floor in state.floor_comps.iter().take(state.elev_floor) {
+                    if !floor.is_empty() {
+                        skip = false;
+                        break;
+                    }
+                }
+                if skip {
+                    continue;
+                }
+            }
+            // Modify next floor
+            let mut next_state = state.clone();
+            next_state.elev_floor = (state.elev_floor as i64 + floor_delta) as usize;
+            for comp in comps.iter() {
+                next_state.floor_comps[state.elev_floor].remove(comp);
+                next_state.floor_comps[next_state.elev_floor].insert((*comp).clone());
+            }
+            // Validate affected floors
+            if !validate_floor(&next_state.floor_comps[state.elev_floor])
+                || !validate_floor(&next_state.floor_comps[next_state.elev_floor])
+            {
+                continue;
+            }
+            // We have now found a valid next state
+            match floor_delta {
+                1 => match comps.len() {
+                    1 => next_states_one_up.push(next_state),
+                    2 => {
+                        next_states_one_up = vec![];
+                        next_states_two_up.push(next_state);
+                    }
+                    _ => (),
+                },
+                -1 => match comps.len() {
+                    1 => {
+                        next_states_two_down = vec![];
+                        next_states_one_down.push(next_state);
+                    }
+                    2 => next_states_two_down.push(next_state),
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+    }
+    // Combine the valid next states into single collection
+    next_states.append(&mut next_states_two_up);
+    next_states.append(&mut next_states_two_down);
+    next_states.append(&mut next_states_one_up);
+    next_states.append(&mut next_states_one_down);
+    next_states
+}
+
+/// Checks if the given floor represents a valid state. A floor is invalid if it contains a
+/// microchip without its matching generator in the presence of a mismatched generator
+fn validate_floor(floor: &BTreeSet<Component>) -> bool {
+    // Extract the names of the generators and microchips
+    let generators = floor
+        .iter()
+        .filter(|comp| comp.comp_type == ComponentType::Generator)
+        .map(|comp| &comp.name)
+        .collect::<HashSet<&String>>();
+    let microchips = floor
+        .iter()
+        .filter(|comp| comp.comp_type == ComponentType::Microchip)
+        .map(|comp| &comp.name)
+        .collect::<HashSet<&String>>();
+    // Valid if there is only one type of Component
+    if generators.is_empty() || microchips.is_empty() {
+        return true;
+    }
+    // Invalid if microchip is in the presence of a mismatched generator
+    for chip in microchips {
+        if !generators.contains(&chip) {
+            return false;
+        }
+    }
+    // Valid if all microchips have a matching generator
+    true
+}
+
+/// Checks if all components are at the top floor.
+fn check_if_all_components_at_top_floor(floor_items: &[BTreeSet<Component>]) -> bool {
+    for floor in floor_items.iter().take(floor_items.len() - 1) {
+        if !floor.is_empty() {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::search::{self, SearchStrategy};
+
+    use super::*;
+
+    /// Tests facility states assembled directly from the floors of each element, including that
+    /// states differing only by the names of the elements share a search key.
+    #[test]
+    fn test_facility_builder() {
+        let example = "\
+            The first floor contains a hydrogen-compatible microchip and a lithium-compatible microchip.
+            The second floor contains a hydrogen generator.
+            The third floor contains a lithium generator.
+            The fourth floor contains nothing relevant.";
+        let state = FacilityBuilder::new(4)
+            .element("hydrogen", 1, 0)
+            .element("lithium", 2, 0)
+            .build()
+            .unwrap();
+        assert_eq!(parse_facility(example).unwrap(), state.floor_comps);
+        let problem = FacilitySearch::from_state(state.clone());
+        let outcome = search::search(&problem, SearchStrategy::AStar).unwrap();
+        assert_eq!(11, outcome.moves());
+        let renamed = FacilityBuilder::new(4)
+            .element("lithium", 1, 0)
+            .element("hydrogen", 2, 0)
+            .build()
+            .unwrap();
+        assert_eq!(problem.key(&state), problem.key(&renamed));
+        let finished = FacilityBuilder::new(4)
+            .element("hydrogen", 3, 3)
+            .elevator(3)
+            .build()
+            .unwrap();
+        assert!(problem.is_goal(&finished));
+        assert_eq!(0, problem.heuristic(&finished));
+        // Components are never carried down to empty floors
+        assert!(problem.successors(&finished).is_empty());
+        assert_eq!(
+            Some(FacilityInputError::UnsafeFloor { floor: 1 }),
+            FacilityBuilder::new(2)
+                .element("cobalt", 0, 1)
+                .element("lithium", 1, 0)
+                .build()
+                .err()
+        );
+        assert!(matches!(
+            FacilityBuilder::new(2)
+                .element("cobalt", 0, 0)
+                .element("cobalt", 1, 1)
+                .build(),
+            Err(FacilityInputError::DuplicateComponent { .. })
+        ));
+        // The search starts with the elevator on the floor given to the builder
+        let builder = || FacilityBuilder::new(2).element("cobalt", 1, 1);
+        let problem = FacilitySearch::from_state(builder().elevator(1).build().unwrap());
+        assert_eq!(1, problem.initial_state().elev_floor());
+        assert_eq!(
+            Some(0),
+            search::search(&problem, SearchStrategy::BreadthFirst).map(|o| o.moves())
+        );
+        let problem = FacilitySearch::from_state(builder().build().unwrap());
+        assert!(search::search(&problem, SearchStrategy::BreadthFirst).is_none());
+    }
+
+    /// Tests that a facility with more floors than can be named in the input file is reported as an
+    /// error.
+    #[test]
+    fn test_parse_facility_too_many_floors() {
+        let mut floors = FLOOR_ORDINALS
+            .iter()
+            .map(|ordinal| format!("The {ordinal} floor contains nothing relevant."))
+            .collect::<Vec<String>>();
+        assert_eq!(20, parse_facility(&floors.join("\n")).unwrap().len());
+        floors.push(String::from("The next floor contains nothing relevant."));
+        assert_eq!(
+            Err(FacilityInputError::TooManyFloors { line: 21, max: 20 }),
+            parse_facility(&floors.join("\n"))
+        );
+    }
+}
@@ -0,0 +1,657 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::iter;
+use std::str::FromStr;
+
+use fancy_regex::Regex;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref REGEX_SWAP_POSITION: Regex =
+        Regex::new(r"^swap position (\d+) with position (\d+)$").unwrap();
+    static ref REGEX_SWAP_LETTER: Regex =
+        Regex::new(r"^swap letter ([a-z]) with letter ([a-z])$").unwrap();
+    static ref REGEX_ROTATE_LEFT: Regex = Regex::new(r"^rotate left (\d+) step[s]?$").unwrap();
+    static ref REGEX_ROTATE_RIGHT: Regex = Regex::new(r"^rotate right (\d+) step[s]?$").unwrap();
+    static ref REGEX_ROTATE_BASED_LETTER: Regex =
+        Regex::new(r"^rotate based on position of letter ([a-z])$").unwrap();
+    static ref REGEX_REVERSE_POSITIONS: Regex =
+        Regex::new(r"^reverse positions (\d+) through (\d+)$").unwrap();
+    static ref REGEX_MOVE_POSITIONS: Regex =
+        Regex::new(r"^move position (\d+) to position (\d+)$").unwrap();
+}
+
+/// Custom error type to indicate that the parsing of a ScrambleOperation from a given string has
+/// failed.
+#[derive(Debug)]
+pub struct ParseScrambleOperationError;
+
+/// Custom error type to indicate that a scramble or unscramble operation has failed, because an
+/// operation refers to a position or letter that is not in the string, or the string is not ASCII.
+#[derive(Debug)]
+pub struct ScrambleOperationError;
+
+/// Represents the different operations in the scrambling function, as described in the AOC 2016
+/// Day 21 problem (https://adventofcode.com/2016/day/21). Letters are held as their ASCII bytes.
+#[derive(Clone, Copy, Debug)]
+pub enum ScrambleOperation {
+    SwapPosition { pos_x: usize, pos_y: usize },
+    SwapLetter { letter_x: u8, letter_y: u8 },
+    RotateLeft { steps: usize },
+    RotateRight { steps: usize },
+    RotateBasedLetter { letter: u8 },
+    ReversePositions { start: usize, end: usize },
+    MovePosition { pos_x: usize, pos_y: usize },
+}
+
+impl ScrambleOperation {
+    /// Checks if the positions moved by the operation depend on the letters in the password.
+    pub fn is_letter_dependent(&self) -> bool {
+        matches!(
+            self,
+            ScrambleOperation::SwapLetter { .. } | ScrambleOperation::RotateBasedLetter { .. }
+        )
+    }
+}
+
+impl FromStr for ScrambleOperation {
+    type Err = ParseScrambleOperationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(Some(caps)) = REGEX_SWAP_POSITION.captures(s) {
+            let pos_x = caps[1]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            let pos_y = caps[2]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            return Ok(ScrambleOperation::SwapPosition { pos_x, pos_y });
+        } else if let Ok(Some(caps)) = REGEX_SWAP_LETTER.captures(s) {
+            let letter_x = caps[1].as_bytes()[0];
+            let letter_y = caps[2].as_bytes()[0];
+            return Ok(ScrambleOperation::SwapLetter { letter_x, letter_y });
+        } else if let Ok(Some(caps)) = REGEX_ROTATE_LEFT.captures(s) {
+            let steps = caps[1]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            return Ok(ScrambleOperation::RotateLeft { steps });
+        } else if let Ok(Some(caps)) = REGEX_ROTATE_RIGHT.captures(s) {
+            let steps = caps[1]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            return Ok(ScrambleOperation::RotateRight { steps });
+        } else if let Ok(Some(caps)) = REGEX_ROTATE_BASED_LETTER.captures(s) {
+            let letter = caps[1].as_bytes()[0];
+            return Ok(ScrambleOperation::RotateBasedLetter { letter });
+        } else if let Ok(Some(caps)) = REGEX_REVERSE_POSITIONS.captures(s) {
+            let start = caps[1]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            let end = caps[2]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            return Ok(ScrambleOperation::ReversePositions { start, end });
+        } else if let Ok(Some(caps)) = REGEX_MOVE_POSITIONS.captures(s) {
+            let pos_x = caps[1]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            let pos_y = caps[2]
+                .parse::<usize>()
+                .map_err(|_| ParseScrambleOperationError)?;
+            return Ok(ScrambleOperation::MovePosition { pos_x, pos_y });
+        }
+        Err(ParseScrambleOperationError)
+    }
+}
+
+/// A scrambling program precomposed into a single permutation of letter positions for passwords of
+/// a fixed length. Applying the permutation takes O(n) time per password, regardless of the number
+/// of operations in the program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScramblePermutation {
+    source_positions: Vec<usize>,
+}
+
+impl ScramblePermutation {
+    /// Gets the length of the passwords that the permutation applies to.
+    pub fn password_len(&self) -> usize {
+        self.source_positions.len()
+    }
+
+    /// Gets the position in the input password of the letter moved to each output position.
+    pub fn source_positions(&self) -> &[usize] {
+        &self.source_positions
+    }
+
+    /// Applies the permutation to the password. Returns an error if the password length does not
+    /// match the length the permutation was composed for, or the password is not ASCII.
+    pub fn apply(&self, password: &str) -> Result<String, ScrambleOperationError> {
+        let letters = ascii_letters(password)?;
+        if letters.len() != self.source_positions.len() {
+            return Err(ScrambleOperationError);
+        }
+        let output = self.source_positions.iter().map(|&i| letters[i]).collect();
+        Ok(ascii_string(output))
+    }
+}
+
+/// Scrambles and unscrambles passwords using a program of scrambling operations, which is parsed
+/// once and can then be applied to any number of passwords.
+#[derive(Debug)]
+pub struct Scrambler {
+    operations: Vec<ScrambleOperation>,
+}
+
+impl FromStr for Scrambler {
+    type Err = ParseScrambleOperationError;
+
+    /// Parses the scrambling program from the given string, with one operation per line. Blank lines
+    /// are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let operations = s
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(ScrambleOperation::from_str)
+            .collect::<Result<Vec<ScrambleOperation>, ParseScrambleOperationError>>()?;
+        Ok(Scrambler::new(operations))
+    }
+}
+
+impl Scrambler {
+    /// Creates a new scrambler that applies the given operations in order.
+    pub fn new(operations: Vec<ScrambleOperation>) -> Scrambler {
+        Scrambler { operations }
+    }
+
+    /// Gets the operations applied by the scrambler.
+    pub fn operations(&self) -> &[ScrambleOperation] {
+        &self.operations
+    }
+
+    /// Scrambles the password by applying each of the operations in order.
+    pub fn scramble(&self, password: &str) -> Result<String, ScrambleOperationError> {
+        apply_scramble_operations(password, &self.operations)
+    }
+
+    /// Composes the operations into a single permutation for passwords of the given length. Returns
+    /// Ok(None) if the program contains letter-dependent operations (swap letter or rotate based on
+    /// letter), as the resulting permutation then depends on the password being scrambled.
+    pub fn compose_permutation(
+        &self,
+        password_len: usize,
+    ) -> Result<Option<ScramblePermutation>, ScrambleOperationError> {
+        if self.operations.iter().any(|op| op.is_letter_dependent()) {
+            return Ok(None);
+        }
+        let placeholder = vec![0; password_len];
+        trace_source_positions(&placeholder, &self.operations).map(Some)
+    }
+
+    /// Resolves the permutation that the operations apply to the given password, including the
+    /// position changes made by letter-dependent operations for that particular password.
+    pub fn resolve_permutation(
+        &self,
+        password: &str,
+    ) -> Result<ScramblePermutation, ScrambleOperationError> {
+        trace_source_positions(ascii_letters(password)?, &self.operations)
+    }
+
+    /// Scrambles each of the passwords in one pass. The operations are composed into a single
+    /// permutation once for each password length when the program has no letter-dependent
+    /// operations, otherwise each password is scrambled by applying the operations in order.
+    pub fn scramble_batch(
+        &self,
+        passwords: &[&str],
+    ) -> Result<Vec<String>, ScrambleOperationError> {
+        let mut permutations: HashMap<usize, Option<ScramblePermutation>> = HashMap::new();
+        let mut output: Vec<String> = Vec::with_capacity(passwords.len());
+        for password in passwords {
+            let password_len = password.len();
+            let permutation = match permutations.entry(password_len) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(self.compose_permutation(password_len)?),
+            };
+            match permutation {
+                Some(permutation) => output.push(permutation.apply(password)?),
+                None => output.push(self.scramble(password)?),
+            }
+        }
+        Ok(output)
+    }
+
+    /// Unscrambles the scrambled password by applying the inverse of each operation in reverse
+    /// order. Where more than one password scrambles into the given string (possible for lengths
+    /// other than 8), the first in sorted order is returned.
+    pub fn unscramble(&self, scrambled: &str) -> Result<String, ScrambleOperationError> {
+        apply_unscramble_operations(scrambled, &self.operations)
+    }
+
+    /// Finds all of the passwords that scramble into the given string, in sorted order.
+    pub fn unscramble_candidates(
+        &self,
+        scrambled: &str,
+    ) -> Result<Vec<String>, ScrambleOperationError> {
+        find_unscramble_candidates(scrambled, &self.operations)
+    }
+
+    /// Asserts that scrambling and then unscrambling the password gives back the password. Where
+    /// the scrambled password has more than one candidate (so unscramble returns the first sorted
+    /// candidate), the password must instead be one of the candidates. Intended for use in property
+    /// tests of the inverse operations.
+    ///
+    /// Panics if the round trip fails, or if the operations cannot be applied to the password.
+    pub fn assert_round_trip(&self, password: &str) {
+        let scrambled = self.scramble(password).unwrap();
+        let candidates = self.unscramble_candidates(&scrambled).unwrap();
+        assert!(
+            candidates.iter().any(|candidate| candidate == password),
+            "Password is not an unscramble candidate! // {password} -> {scrambled}"
+        );
+        let unscrambled = self.unscramble(&scrambled).unwrap();
+        if candidates.len() == 1 {
+            assert_eq!(password, unscrambled, "Round trip failed! // {scrambled}");
+        } else {
+            assert_eq!(
+                candidates[0], unscrambled,
+                "Round trip failed! // {scrambled}"
+            );
+        }
+    }
+}
+
+/// Gets the ASCII bytes of the letters in the string. Returns an error if the string is not ASCII,
+/// as the operations move single bytes around.
+fn ascii_letters(s: &str) -> Result<&[u8], ScrambleOperationError> {
+    if !s.is_ascii() {
+        return Err(ScrambleOperationError);
+    }
+    Ok(s.as_bytes())
+}
+
+/// Converts the ASCII letters back into a string. Panics if the letters are not ASCII, which cannot
+/// happen for letters moved around by the operations.
+fn ascii_string(letters: Vec<u8>) -> String {
+    String::from_utf8(letters).expect("Scrambled letters must be ASCII!")
+}
+
+/// Applies the scramble operations to the input string and returns the result.
+fn apply_scramble_operations(
+    s: &str,
+    operations: &[ScrambleOperation],
+) -> Result<String, ScrambleOperationError> {
+    let mut output = ascii_letters(s)?.to_vec();
+    for &op in operations.iter() {
+        match op {
+            ScrambleOperation::SwapPosition { pos_x, pos_y } => {
+                swap_positions(&mut output, pos_x, pos_y)?;
+            }
+            ScrambleOperation::SwapLetter { letter_x, letter_y } => {
+                swap_letters(&mut output, letter_x, letter_y)?;
+            }
+            ScrambleOperation::RotateLeft { steps } => {
+                rotate_left_by_steps(&mut output, steps);
+            }
+            ScrambleOperation::RotateRight { steps } => {
+                rotate_right_by_steps(&mut output, steps);
+            }
+            ScrambleOperation::RotateBasedLetter { letter } => {
+                rotate_based_on_letter_position(&mut output, letter)?;
+            }
+            ScrambleOperation::ReversePositions { start, end } => {
+                reverse_positions_in_slice(&mut output, start, end)?;
+            }
+            ScrambleOperation::MovePosition { pos_x, pos_y } => {
+                move_positions(&mut output, pos_x, pos_y)?;
+            }
+        }
+    }
+    Ok(ascii_string(output))
+}
+
+/// Applies the scramble operations to the letters, tracking the position in the input that each
+/// letter came from. The letter-dependent operations are resolved as the position changes they make
+/// for the given letters.
+fn trace_source_positions(
+    letters: &[u8],
+    operations: &[ScrambleOperation],
+) -> Result<ScramblePermutation, ScrambleOperationError> {
+    let mut output = letters
+        .iter()
+        .copied()
+        .zip(0..)
+        .collect::<Vec<(u8, usize)>>();
+    let find_letter = |output: &[(u8, usize)], letter: u8| {
+        output
+            .iter()
+            .position(|(c, _)| *c == letter)
+            .ok_or(ScrambleOperationError)
+    };
+    for &op in operations.iter() {
+        match op {
+            ScrambleOperation::SwapPosition { pos_x, pos_y } => {
+                swap_positions(&mut output, pos_x, pos_y)?;
+            }
+            ScrambleOperation::SwapLetter { letter_x, letter_y } => {
+                let pos_x = find_letter(&output, letter_x)?;
+                let pos_y = find_letter(&output, letter_y)?;
+                swap_positions(&mut output, pos_x, pos_y)?;
+            }
+            ScrambleOperation::RotateLeft { steps } => {
+                rotate_left_by_steps(&mut output, steps);
+            }
+            ScrambleOperation::RotateRight { steps } => {
+                rotate_right_by_steps(&mut output, steps);
+            }
+            ScrambleOperation::RotateBasedLetter { letter } => {
+                let pos = find_letter(&output, letter)?;
+                rotate_right_by_steps(&mut output, letter_rotation_steps(pos));
+            }
+            ScrambleOperation::ReversePositions { start, end } => {
+                reverse_positions_in_slice(&mut output, start, end)?;
+            }
+            ScrambleOperation::MovePosition { pos_x, pos_y } => {
+                move_positions(&mut output, pos_x, pos_y)?;
+            }
+        }
+    }
+    let source_positions = output.into_iter().map(|(_, i)| i).collect();
+    Ok(ScramblePermutation { source_positions })
+}
+
+/// Applies the inverse of the given operations to unscramble the input string s.
+///
+/// The inverse of the rotate-based-on-letter operation can be looked up directly when each final
+/// letter position is reached by exactly one starting position (such as for 8 letters). For other
+/// lengths, the candidate inverses of each operation are searched instead. Where more than one
+/// string scrambles into the input, the first in sorted order is returned (all of them can be found
+/// with find_unscramble_candidates). An error is returned if no string scrambles into the input.
+fn apply_unscramble_operations(
+    s: &str,
+    operations: &[ScrambleOperation],
+) -> Result<String, ScrambleOperationError> {
+    let Some(letter_rotation_mapping) = determine_letter_rotation_mapping(s.len()) else {
+        let candidates = find_unscramble_candidates(s, operations)?;
+        return candidates.into_iter().next().ok_or(ScrambleOperationError);
+    };
+    let mut output = ascii_letters(s)?.to_vec();
+    // Apply the inverse of the scramble operations in reverse order to unscramble input string.
+    for &op in operations.iter().rev() {
+        apply_inverse_operation(&mut output, op, &letter_rotation_mapping)?;
+    }
+    Ok(ascii_string(output))
+}
+
+/// Applies the inverse of a single scramble operation to the output buffer. The letter rotation
+/// mapping is used to invert the rotate-based-on-letter operation.
+fn apply_inverse_operation(
+    output: &mut Vec<u8>,
+    op: ScrambleOperation,
+    letter_rotation_mapping: &[usize],
+) -> Result<(), ScrambleOperationError> {
+    match op {
+        ScrambleOperation::SwapPosition { pos_x, pos_y } => {
+            swap_positions(output, pos_x, pos_y)?;
+        }
+        ScrambleOperation::SwapLetter { letter_x, letter_y } => {
+            swap_letters(output, letter_x, letter_y)?;
+        }
+        ScrambleOperation::RotateLeft { steps } => {
+            rotate_right_by_steps(output, steps);
+        }
+        ScrambleOperation::RotateRight { steps } => {
+            rotate_left_by_steps(output, steps);
+        }
+        ScrambleOperation::RotateBasedLetter { letter } => {
+            unscramble_rotate_based_on_letter_position(output, letter, letter_rotation_mapping)?;
+        }
+        ScrambleOperation::ReversePositions { start, end } => {
+            reverse_positions_in_slice(output, start, end)?;
+        }
+        ScrambleOperation::MovePosition { pos_x, pos_y } => {
+            move_positions(output, pos_y, pos_x)?;
+        }
+    }
+    Ok(())
+}
+
+/// Finds all of the strings that the given operations would scramble into the input string s, by
+/// searching the candidate inverses of each operation in reverse order. Only the
+/// rotate-based-on-letter operation can have more than one candidate inverse. The candidates are
+/// returned in sorted order, without duplicates.
+fn find_unscramble_candidates(
+    s: &str,
+    operations: &[ScrambleOperation],
+) -> Result<Vec<String>, ScrambleOperationError> {
+    let mut candidates: Vec<Vec<u8>> = vec![];
+    let mut stack: Vec<(Vec<u8>, usize)> = vec![(ascii_letters(s)?.to_vec(), operations.len())];
+    while let Some((output, remaining_ops)) = stack.pop() {
+        if remaining_ops == 0 {
+            candidates.push(output);
+            continue;
+        }
+        let op = operations[remaining_ops - 1];
+        match op {
+            ScrambleOperation::RotateBasedLetter { letter } => {
+                // Any left rotation that the operation maps back to the output is a candidate
+                for steps in 0..output.len().max(1) {
+                    let mut candidate = output.clone();
+                    rotate_left_by_steps(&mut candidate, steps);
+                    let mut check = candidate.clone();
+                    rotate_based_on_letter_position(&mut check, letter)?;
+                    if check == output {
+                        stack.push((candidate, remaining_ops - 1));
+                    }
+                }
+            }
+            _ => {
+                // All other operations have a single inverse, which does not use the mapping
+                let mut unscrambled = output;
+                apply_inverse_operation(&mut unscrambled, op, &[])?;
+                stack.push((unscrambled, remaining_ops - 1));
+            }
+        }
+    }
+    candidates.sort();
+    candidates.dedup();
+    Ok(candidates.into_iter().map(ascii_string).collect())
+}
+
+/// Determines how many right-rotation steps were undertaken for a character to end up at an index
+/// within a string of the given length. Returns None if more than one starting position ends up at
+/// the same index, in which case the rotation cannot be inverted from the final index alone.
+fn determine_letter_rotation_mapping(length: usize) -> Option<Vec<usize>> {
+    let mut output: Vec<Option<usize>> = iter::repeat_n(None, length).collect();
+    for pos in 0..length {
+        let steps = letter_rotation_steps(pos);
+        let i = (pos + steps) % length;
+        if output[i].is_some() {
+            return None;
+        }
+        output[i] = Some(steps);
+    }
+    output.into_iter().collect()
+}
+
+/// Swaps the letters at the two positions.
+fn swap_positions<T: Copy>(
+    output: &mut [T],
+    pos_x: usize,
+    pos_y: usize,
+) -> Result<(), ScrambleOperationError> {
+    if pos_x >= output.len() || pos_y >= output.len() {
+        return Err(ScrambleOperationError);
+    }
+    let (letter_x, letter_y) = (output[pos_x], output[pos_y]);
+    output[pos_y] = letter_x;
+    output[pos_x] = letter_y;
+    Ok(())
+}
+
+/// Swap the two letters, irrespective of their location in the output.
+fn swap_letters(
+    output: &mut [u8],
+    letter_x: u8,
+    letter_y: u8,
+) -> Result<(), ScrambleOperationError> {
+    let pos_x = output.iter().position(|c| *c == letter_x);
+    let pos_y = output.iter().position(|c| *c == letter_y);
+    if pos_x.is_none() || pos_y.is_none() {
+        return Err(ScrambleOperationError);
+    }
+    let (pos_x, pos_y) = (pos_x.unwrap(), pos_y.unwrap());
+    output[pos_y] = letter_x;
+    output[pos_x] = letter_y;
+    Ok(())
+}
+
+/// Rotates the output buffer to the left by the given number of steps.
+fn rotate_left_by_steps<T>(output: &mut [T], steps: usize) {
+    if !output.is_empty() {
+        output.rotate_left(steps % output.len());
+    }
+}
+
+/// Rotates the output buffer to the right by the given number of steps.
+fn rotate_right_by_steps<T>(output: &mut [T], steps: usize) {
+    if !output.is_empty() {
+        output.rotate_right(steps % output.len());
+    }
+}
+
+/// Reverses the positions of the characters in the slice bounded by the start and end indices
+/// (inclusive).
+fn reverse_positions_in_slice<T>(
+    output: &mut [T],
+    start: usize,
+    end: usize,
+) -> Result<(), ScrambleOperationError> {
+    if start > end || start >= output.len() || end >= output.len() {
+        return Err(ScrambleOperationError);
+    }
+    output[start..=end].reverse();
+    Ok(())
+}
+
+/// Rotates the output buffer to the right based on the index of the given letter prior to rotations
+/// being applied.
+fn rotate_based_on_letter_position(
+    output: &mut [u8],
+    letter: u8,
+) -> Result<(), ScrambleOperationError> {
+    let pos = output.iter().position(|c| *c == letter);
+    if pos.is_none() {
+        return Err(ScrambleOperationError);
+    }
+    rotate_right_by_steps(output, letter_rotation_steps(pos.unwrap()));
+    Ok(())
+}
+
+/// Calculates the number of steps to the right that the rotate-based-on-letter operation rotates by
+/// when the letter is at the given position.
+fn letter_rotation_steps(pos: usize) -> usize {
+    pos + 1 + (if pos >= 4 { 1 } else { 0 })
+}
+
+/// Removes the letter at position x and reinserts it at position y.
+fn move_positions<T>(
+    output: &mut Vec<T>,
+    pos_x: usize,
+    pos_y: usize,
+) -> Result<(), ScrambleOperationError> {
+    if pos_x >= output.len() || pos_y >= output.len() {
+        return Err(ScrambleOperationError);
+    }
+    let letter = output.remove(pos_x);
+    output.insert(pos_y, letter);
+    Ok(())
+}
+
+/// Applies the inverse of a ScrambedBasedLetter operation to the output buffer.
+fn unscramble_rotate_based_on_letter_position(
+    output: &mut [u8],
+    letter: u8,
+    letter_rotation_mapping: &[usize],
+) -> Result<(), ScrambleOperationError> {
+    let pos = output.iter().position(|c| *c == letter);
+    if pos.is_none() {
+        return Err(ScrambleOperationError);
+    }
+    let pos = pos.unwrap();
+    let steps = letter_rotation_mapping[pos];
+    rotate_left_by_steps(output, steps);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that unscrambling 8-letter passwords (which uses the rotation lookup) recovers the
+    /// original password for a program using every type of operation.
+    #[test]
+    fn test_scrambler_round_trip() {
+        let scrambler = Scrambler::from_str(
+            "swap position 4 with position 0\n\
+            swap letter d with letter b\n\
+            reverse positions 0 through 4\n\
+            rotate left 1 step\n\
+            rotate right 3 steps\n\
+            move position 1 to position 4\n\
+            move position 3 to position 0\n\
+            rotate based on position of letter b\n\
+            rotate based on position of letter d\n",
+        )
+        .unwrap();
+        assert_eq!(9, scrambler.operations().len());
+        for password in ["abcdefgh", "hgfedcba", "dhaegfbc"] {
+            let scrambled = scrambler.scramble(password).unwrap();
+            assert_eq!(password, scrambler.unscramble(&scrambled).unwrap());
+            assert_eq!(
+                vec![password.to_string()],
+                scrambler.unscramble_candidates(&scrambled).unwrap()
+            );
+        }
+        assert!(scrambler.scramble("abc").is_err());
+        assert!(scrambler.scramble("abcdéfg").is_err());
+        assert!(Scrambler::from_str("rotate sideways 1 step").is_err());
+        assert!(Scrambler::from_str("rotate left 99999999999999999999999 steps").is_err());
+    }
+
+    /// Tests that the precomposed permutation and batch scrambling match scrambling by applying the
+    /// operations in order, for programs with and without letter-dependent operations.
+    #[test]
+    fn test_scrambler_permutation_batch() {
+        let scrambler = Scrambler::from_str(
+            "swap position 4 with position 0\n\
+            reverse positions 0 through 4\n\
+            rotate left 1 step\n\
+            move position 1 to position 4\n\
+            move position 3 to position 0\n",
+        )
+        .unwrap();
+        let permutation = scrambler.compose_permutation(5).unwrap().unwrap();
+        assert_eq!(&[0, 3, 1, 4, 2], permutation.source_positions());
+        assert!(permutation.apply("abcdefgh").is_err());
+        let passwords = ["abcde", "edcba", "abcdefgh", "hgfedcba", "vwxyz"];
+        let expected = passwords
+            .iter()
+            .map(|p| scrambler.scramble(p).unwrap())
+            .collect::<Vec<String>>();
+        assert_eq!(expected, scrambler.scramble_batch(&passwords).unwrap());
+        let scrambler = Scrambler::from_str(
+            "swap letter d with letter b\n\
+            rotate based on position of letter b\n\
+            rotate right 3 steps\n",
+        )
+        .unwrap();
+        assert_eq!(None, scrambler.compose_permutation(8).unwrap());
+        let password = "abcdefgh";
+        let resolved = scrambler.resolve_permutation(password).unwrap();
+        assert_eq!(
+            scrambler.scramble(password).unwrap(),
+            resolved.apply(password).unwrap()
+        );
+        assert!(scrambler.scramble_batch(&["abc"]).is_err());
+    }
+}
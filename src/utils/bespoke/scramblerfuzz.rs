@@ -0,0 +1,121 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{ScrambleOperation, Scrambler};
+
+/// Maximum number of letters in a generated password.
+const MAX_PASSWORD_LEN: usize = 10;
+/// Maximum number of operations in a generated program.
+const MAX_PROGRAM_OPERATIONS: usize = 24;
+/// Letters that generated passwords are drawn from.
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Randomly generated Day 21 scrambling program and password, used for property testing and
+/// fuzzing the scramble and unscramble operations. Every generated operation is valid for the
+/// password: positions are within the password and letters appear in it. Passwords are made of
+/// distinct letters so the letter-based operations are unambiguous.
+#[derive(Debug)]
+pub struct ArbitraryScramble {
+    scrambler: Scrambler,
+    password: String,
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryScramble {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // Draw distinct letters for the password
+        let mut letters = LETTERS.to_vec();
+        let mut password: Vec<u8> = vec![];
+        for _ in 0..u.int_in_range(1..=MAX_PASSWORD_LEN)? {
+            password.push(letters.remove(u.choose_index(letters.len())?));
+        }
+        let mut operations: Vec<ScrambleOperation> = vec![];
+        for _ in 0..u.int_in_range(0..=MAX_PROGRAM_OPERATIONS)? {
+            operations.push(arbitrary_operation(u, &password)?);
+        }
+        Ok(ArbitraryScramble {
+            scrambler: Scrambler::new(operations),
+            password: String::from_utf8(password).unwrap(),
+        })
+    }
+}
+
+/// Generates a scramble operation that is valid for the given password. Rotations may step past
+/// the length of the password, so that wrapping around is also exercised.
+fn arbitrary_operation(u: &mut Unstructured, password: &[u8]) -> Result<ScrambleOperation> {
+    let len = password.len();
+    Ok(match u.int_in_range(0..=6)? {
+        0 => ScrambleOperation::SwapPosition {
+            pos_x: u.choose_index(len)?,
+            pos_y: u.choose_index(len)?,
+        },
+        1 => ScrambleOperation::SwapLetter {
+            letter_x: *u.choose(password)?,
+            letter_y: *u.choose(password)?,
+        },
+        2 => ScrambleOperation::RotateLeft {
+            steps: u.int_in_range(0..=2 * len)?,
+        },
+        3 => ScrambleOperation::RotateRight {
+            steps: u.int_in_range(0..=2 * len)?,
+        },
+        4 => ScrambleOperation::RotateBasedLetter {
+            letter: *u.choose(password)?,
+        },
+        5 => {
+            let start = u.choose_index(len)?;
+            let end = u.int_in_range(start..=len - 1)?;
+            ScrambleOperation::ReversePositions { start, end }
+        }
+        _ => ScrambleOperation::MovePosition {
+            pos_x: u.choose_index(len)?,
+            pos_y: u.choose_index(len)?,
+        },
+    })
+}
+
+impl ArbitraryScramble {
+    /// Gets the generated scrambler.
+    pub fn scrambler(&self) -> &Scrambler {
+        &self.scrambler
+    }
+
+    /// Gets the generated password.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// Checks that unscrambling the scrambled password recovers the password, and that the
+    /// precomposed permutation for the password scrambles it the same way as the operations do.
+    /// Panics if either check fails.
+    pub fn run(&self) {
+        self.scrambler.assert_round_trip(&self.password);
+        let permutation = self.scrambler.resolve_permutation(&self.password).unwrap();
+        assert_eq!(
+            self.scrambler.scramble(&self.password).unwrap(),
+            permutation.apply(&self.password).unwrap()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests the scramble round trip for programs generated from a range of fixed byte seeds.
+    #[test]
+    fn test_run_arbitrary_scrambles() {
+        for seed in 0..500_u64 {
+            // Generate pseudo-random bytes with a linear congruential generator
+            let mut state = seed;
+            let bytes = (0..512)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 56) as u8
+                })
+                .collect::<Vec<u8>>();
+            let mut u = Unstructured::new(&bytes);
+            ArbitraryScramble::arbitrary(&mut u).unwrap().run();
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::iter;
+
+use fancy_regex::Regex;
+use itertools::iproduct;
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref REGEX_RECT: Regex = Regex::new(r"^rect (\d+)x(\d+)$").unwrap();
+    static ref REGEX_ROTATE_ROW: Regex = Regex::new(r"^rotate row y=(\d+) by (\d+)$").unwrap();
+    static ref REGEX_ROTATE_COL: Regex = Regex::new(r"^rotate column x=(\d+) by (\d+)$").unwrap();
+}
+
+/// Character used to render a lit pixel of the screen.
+const LIT_PIXEL: char = '█';
+/// Character used to render an unlit pixel of the screen.
+const UNLIT_PIXEL: char = ' ';
+
+/// Represents a single instruction used to operate on the pixels of the screen, as described in the
+/// AOC 2016 Day 8 problem (https://adventofcode.com/2016/day/8).
+pub enum ScreenInstruction {
+    Rect { width: usize, height: usize },
+    RotateRow { row: usize, amount: usize },
+    RotateCol { col: usize, amount: usize },
+}
+
+impl fmt::Display for ScreenInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScreenInstruction::Rect { width, height } => write!(f, "rect {width}x{height}"),
+            ScreenInstruction::RotateRow { row, amount } => {
+                write!(f, "rotate row y={row} by {amount}")
+            }
+            ScreenInstruction::RotateCol { col, amount } => {
+                write!(f, "rotate column x={col} by {amount}")
+            }
+        }
+    }
+}
+
+impl ScreenInstruction {
+    /// Converts the given string into a ScreenInstruction. Returns None if the given string does
+    /// not match an expected format.
+    pub fn from_string(s: &str) -> Option<ScreenInstruction> {
+        if let Ok(Some(caps)) = REGEX_RECT.captures(s) {
+            let width = caps[1].parse::<usize>().unwrap();
+            let height = caps[2].parse::<usize>().unwrap();
+            return Some(ScreenInstruction::Rect { width, height });
+        } else if let Ok(Some(caps)) = REGEX_ROTATE_ROW.captures(s) {
+            let row = caps[1].parse::<usize>().unwrap();
+            let amount = caps[2].parse::<usize>().unwrap();
+            return Some(ScreenInstruction::RotateRow { row, amount });
+        } else if let Ok(Some(caps)) = REGEX_ROTATE_COL.captures(s) {
+            let col = caps[1].parse::<usize>().unwrap();
+            let amount = caps[2].parse::<usize>().unwrap();
+            return Some(ScreenInstruction::RotateCol { col, amount });
+        }
+        None
+    }
+}
+
+/// Represents the little screen on the door as described in the AOC 2016 Day 8 problem
+/// (https://adventofcode.com/2016/day/8). All pixels start off.
+///
+/// Displaying the screen renders each row of pixels on its own line, using block characters for
+/// the lit pixels.
+#[derive(Clone)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    pixels: Vec<Vec<bool>>,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Screen {
+        Screen {
+            width,
+            height,
+            pixels: vec![vec![false; width]; height],
+        }
+    }
+
+    /// Creates a screen from the given rows of pixels, with true indicating a lit pixel. Returns
+    /// None if the rows are not all the same length, or are empty (as rotating a row of a screen
+    /// with no columns is undefined).
+    pub fn from_pixels(pixels: Vec<Vec<bool>>) -> Option<Screen> {
+        let width = pixels.first().map_or(0, |row| row.len());
+        if (width == 0 && !pixels.is_empty()) || pixels.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        Some(Screen {
+            width,
+            height: pixels.len(),
+            pixels,
+        })
+    }
+
+    /// Returns an iterator over the states of a new screen as the instructions are applied one at
+    /// a time. The first state yielded is the initial screen with all pixels off, followed by the
+    /// state after each instruction.
+    pub fn replay(
+        width: usize,
+        height: usize,
+        instructions: &[ScreenInstruction],
+    ) -> impl Iterator<Item = Screen> + '_ {
+        let initial = Screen::new(width, height);
+        iter::once(initial.clone()).chain(instructions.iter().scan(initial, |screen, instruct| {
+            screen.apply(instruct);
+            Some(screen.clone())
+        }))
+    }
+
+    /// Gets the value of the "width" field.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the value of the "height" field.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the rows of pixels making up the screen, with true indicating a lit pixel.
+    pub fn pixels(&self) -> &[Vec<bool>] {
+        &self.pixels
+    }
+
+    /// Checks if the pixel at the given location is lit. Returns false if the location is outside
+    /// of the screen.
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y][x]
+    }
+
+    /// Sets whether the pixel at the given location is lit. Locations outside of the screen are
+    /// ignored.
+    pub fn set_pixel(&mut self, x: usize, y: usize, lit: bool) {
+        if x < self.width && y < self.height {
+            self.pixels[y][x] = lit;
+        }
+    }
+
+    /// Counts the number of lit pixels on the screen.
+    pub fn lit_count(&self) -> usize {
+        self.pixels
+            .iter()
+            .map(|row| row.iter().filter(|e| **e).count())
+            .sum()
+    }
+
+    /// Updates the screen by applying the instruction. Rectangles are clipped to the screen size
+    /// and rotations of rows or columns outside of the screen are ignored.
+    pub fn apply(&mut self, instruction: &ScreenInstruction) {
+        match *instruction {
+            ScreenInstruction::Rect { width, height } => {
+                let width = width.min(self.width);
+                let height = height.min(self.height);
+                for (y, x) in iproduct!(0..height, 0..width) {
+                    self.pixels[y][x] = true;
+                }
+            }
+            ScreenInstruction::RotateRow { row, amount } => {
+                if row < self.height {
+                    self.pixels[row].rotate_right(amount % self.width);
+                }
+            }
+            ScreenInstruction::RotateCol { col, amount } => {
+                if col < self.width {
+                    let mut col_buffer = self.pixels.iter().map(|row| row[col]).collect::<Vec<_>>();
+                    col_buffer.rotate_right(amount % self.height);
+                    for (row, state) in self.pixels.iter_mut().zip(col_buffer) {
+                        row[col] = state;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the screen as a binary PPM image, with each screen pixel drawn as a square of
+    /// "scale" by "scale" image pixels.
+    pub fn write_ppm<W: Write>(&self, writer: &mut W, scale: usize) -> io::Result<()> {
+        write!(
+            writer,
+            "P6\n{} {}\n255\n",
+            self.width * scale,
+            self.height * scale
+        )?;
+        writer.write_all(&self.scaled_image(scale, 3))
+    }
+
+    /// Writes the screen as a greyscale PNG image, with each screen pixel drawn as a square of
+    /// "scale" by "scale" image pixels.
+    pub fn write_png<W: Write>(&self, writer: W, scale: usize) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(
+            writer,
+            (self.width * scale) as u32,
+            (self.height * scale) as u32,
+        );
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut png_writer = encoder.write_header()?;
+        png_writer.write_image_data(&self.scaled_image(scale, 1))?;
+        png_writer.finish()?;
+        Ok(())
+    }
+
+    /// Generates the raw image data for the screen scaled up by the given factor, with lit pixels
+    /// white and unlit pixels black. Each image pixel is repeated across the given number of
+    /// channels.
+    fn scaled_image(&self, scale: usize, channels: usize) -> Vec<u8> {
+        let mut data: Vec<u8> =
+            Vec::with_capacity(self.width * self.height * scale * scale * channels);
+        for row in self.pixels.iter() {
+            for _ in 0..scale {
+                for &state in row.iter() {
+                    let value = if state { 255 } else { 0 };
+                    data.resize(data.len() + scale * channels, value);
+                }
+            }
+        }
+        data
+    }
+}
+
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.pixels.iter() {
+            let line = row
+                .iter()
+                .map(|&state| if state { LIT_PIXEL } else { UNLIT_PIXEL })
+                .collect::<String>();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests the screen updates using the example from the problem description.
+    #[test]
+    fn test_screen_example() {
+        let mut screen = Screen::new(7, 3);
+        for line in [
+            "rect 3x2",
+            "rotate column x=1 by 1",
+            "rotate row y=0 by 4",
+            "rotate column x=1 by 1",
+        ] {
+            screen.apply(&ScreenInstruction::from_string(line).unwrap());
+        }
+        assert_eq!(6, screen.lit_count());
+        assert_eq!(" █  █ █\n█ █    \n █     \n", screen.to_string());
+    }
+
+    /// Tests that a screen assembled from rows of pixels matches the screen built by applying
+    /// instructions, and that ragged and empty rows are rejected.
+    #[test]
+    fn test_screen_from_pixels() {
+        let mut screen = Screen::from_pixels(vec![vec![true, false], vec![false, false]]).unwrap();
+        screen.set_pixel(1, 1, true);
+        screen.set_pixel(2, 0, true);
+        let mut expected = Screen::new(2, 2);
+        expected.apply(&ScreenInstruction::Rect {
+            width: 1,
+            height: 1,
+        });
+        expected.set_pixel(1, 1, true);
+        assert_eq!(expected.pixels(), screen.pixels());
+        assert_eq!("█ \n █\n", screen.to_string());
+        assert!(Screen::from_pixels(vec![vec![true], vec![]]).is_none());
+        assert!(Screen::from_pixels(vec![vec![], vec![]]).is_none());
+    }
+
+    /// Tests that replaying the instructions yields the initial screen followed by the screen state
+    /// after each instruction.
+    #[test]
+    fn test_screen_replay() {
+        let instructions = ["rect 3x2", "rotate column x=1 by 1", "rotate row y=0 by 4"]
+            .iter()
+            .map(|line| ScreenInstruction::from_string(line).unwrap())
+            .collect::<Vec<ScreenInstruction>>();
+        let lit_counts = Screen::replay(7, 3, &instructions)
+            .map(|screen| screen.lit_count())
+            .collect::<Vec<usize>>();
+        assert_eq!(vec![0, 6, 6, 6], lit_counts);
+        assert_eq!("rotate column x=1 by 1", instructions[1].to_string());
+    }
+
+    /// Tests the final screen for the actual problem input against the recorded snapshot, with lit
+    /// pixels drawn as "#" and unlit pixels as ".".
+    #[test]
+    fn test_screen_final_snapshot() {
+        let instructions = std::fs::read_to_string("./input/day08.txt")
+            .unwrap()
+            .lines()
+            .map(|line| ScreenInstruction::from_string(line.trim()).unwrap())
+            .collect::<Vec<ScreenInstruction>>();
+        let screen = Screen::replay(50, 6, &instructions).last().unwrap();
+        let rows = screen
+            .pixels()
+            .iter()
+            .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+            .collect::<Vec<String>>();
+        insta::assert_snapshot!(rows.join("\n"));
+    }
+
+    /// Tests the PPM and PNG image exports of the screen.
+    #[test]
+    fn test_screen_image_export() {
+        let mut screen = Screen::new(2, 1);
+        screen.apply(&ScreenInstruction::Rect {
+            width: 1,
+            height: 1,
+        });
+        let mut ppm: Vec<u8> = vec![];
+        screen.write_ppm(&mut ppm, 2).unwrap();
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend([255; 6]);
+            expected.extend([0; 6]);
+        }
+        assert_eq!(expected, ppm);
+        let mut png_data: Vec<u8> = vec![];
+        screen.write_png(&mut png_data, 2).unwrap();
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png_data[..8]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use aoc_utils::cartography::Point2D;
+use fancy_regex::Regex;
+
+use crate::utils::grid::Grid;
+
+/// Represents the details for data held in a single storage node, as described in the AOC 2016 Day
+/// 22 problem (https://adventofcode.com/2016/day/22).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NodeData {
+    size: usize,      // Terabytes
+    used: usize,      // Terabytes
+    available: usize, // Terabytes
+    used_pct: usize,
+}
+
+impl NodeData {
+    /// Creates the details for a node of the given size holding the given amount of data, with the
+    /// available space and used percentage (rounded down) worked out as the "df" command does.
+    ///
+    /// Panics if the node is holding more data than its size.
+    pub fn new(size: usize, used: usize) -> NodeData {
+        if used > size {
+            panic!("Node holds more data than its size! // size: {size}, used: {used}");
+        }
+        NodeData {
+            size,
+            used,
+            available: size - used,
+            used_pct: (used * 100).checked_div(size).unwrap_or(0),
+        }
+    }
+
+    /// Gets the value of the "size" field (in terabytes).
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Gets the value of the "used" field (in terabytes).
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Gets the value of the "available" field (in terabytes).
+    pub fn available(&self) -> usize {
+        self.available
+    }
+
+    /// Gets the value of the "used_pct" field.
+    pub fn used_pct(&self) -> usize {
+        self.used_pct
+    }
+}
+
+/// Classification of a storage node by the data it holds, for moving data around the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeType {
+    Empty,       // Visitable
+    PartialUsed, // Visitable
+    Wall,        // Not visitable
+}
+
+/// Parses the output of the "df" command run on the grid nodes (including the command and header
+/// lines) into a hashmap mapping locations to the NodeData details for the data held there.
+///
+/// Panics if a line after the header lines is not in the format of the "df" command output.
+pub fn parse_nodes(raw_input: &str) -> HashMap<Point2D, NodeData> {
+    let regex_line =
+        Regex::new(r"^/dev/grid/node-x(\d+)-y(\d+)\s+(\d+)T\s+(\d+)T\s+(\d+)T\s+(\d+)%$").unwrap();
+    let mut output: HashMap<Point2D, NodeData> = HashMap::new();
+    for line in raw_input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .skip(2)
+    {
+        if let Ok(Some(caps)) = regex_line.captures(line) {
+            // Extract location and NodeData details from the input line
+            let x = caps[1].parse::<i64>().unwrap();
+            let y = caps[2].parse::<i64>().unwrap();
+            let size = caps[3].parse::<usize>().unwrap();
+            let used = caps[4].parse::<usize>().unwrap();
+            let available = caps[5].parse::<usize>().unwrap();
+            let used_pct = caps[6].parse::<usize>().unwrap();
+            // Create key and value
+            let loc = Point2D::new(x, y);
+            let node_data = NodeData {
+                size,
+                used,
+                available,
+                used_pct,
+            };
+            output.insert(loc, node_data);
+        } else {
+            panic!("Bad format input line! // {line}");
+        }
+    }
+    output
+}
+
+/// Converts the node data map into a dense grid of node tiles, with nodes holding no data treated as
+/// empty and nodes at or above the given used percentage treated as walls. Locations inside the grid without a node are also treated as walls.
+/// Returns None if there are no nodes or a node has a negative coordinate.
+pub fn convert_nodes_to_tiles(
+    nodes: &HashMap<Point2D, NodeData>,
+    wall_used_pct: usize,
+) -> Option<Grid<NodeType>> {
+    let tiles = nodes.iter().map(|(&loc, node_data)| {
+        let tile = if node_data.used == 0 {
+            NodeType::Empty
+        } else if node_data.used_pct < wall_used_pct {
+            NodeType::PartialUsed
+        } else {
+            NodeType::Wall
+        };
+        (loc, tile)
+    });
+    Grid::from_points(tiles, NodeType::Wall)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+
+    /// Tests the classification of the nodes for the actual problem input against the recorded
+    /// snapshot, with nodes at or above 90% used treated as walls.
+    #[test]
+    fn test_node_tiles_snapshot() {
+        let nodes = parse_nodes(&fs::read_to_string("./input/day22.txt").unwrap());
+        let node_tiles = convert_nodes_to_tiles(&nodes, 90).unwrap();
+        let rendered = node_tiles.render(|_, tile| match tile {
+            NodeType::Empty => '_',
+            NodeType::PartialUsed => '.',
+            NodeType::Wall => '#',
+        });
+        insta::assert_snapshot!(rendered);
+    }
+
+    /// Tests that only nodes holding no data are classified as empty, including large nodes with a
+    /// used percentage that rounds down to 0.
+    #[test]
+    fn test_node_tiles_empty_by_used() {
+        let nodes = HashMap::from([
+            (Point2D::new(0, 0), NodeData::new(1000, 0)),
+            (Point2D::new(1, 0), NodeData::new(1000, 5)),
+            (Point2D::new(2, 0), NodeData::new(10, 9)),
+        ]);
+        assert_eq!(0, nodes[&Point2D::new(1, 0)].used_pct());
+        let node_tiles = convert_nodes_to_tiles(&nodes, 90).unwrap();
+        assert_eq!(NodeType::Empty, node_tiles[Point2D::new(0, 0)]);
+        assert_eq!(NodeType::PartialUsed, node_tiles[Point2D::new(1, 0)]);
+        assert_eq!(NodeType::Wall, node_tiles[Point2D::new(2, 0)]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::env;
+use std::fmt::Display;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Command line flag used to replace the human-readable results printed by the solvers with a stream
+/// of events on stdout, written as one JSON object per line (NDJSON) as each event happens.
+pub const NDJSON_FLAG: &str = "--ndjson";
+
+/// Represents the events emitted while a solver runs, tagged with the "event" field when written as
+/// JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// The solver has started running, before the input file is processed.
+    RunStarted { day: u64, name: &'a str },
+    /// One part of the problem has been solved, taking the given time (in nanoseconds).
+    PartFinished {
+        day: u64,
+        part: u64,
+        answer: String,
+        duration_ns: u128,
+    },
+    /// Both parts of the problem have been solved, taking the given total time (in nanoseconds)
+    /// including the input processing.
+    RunCompleted { day: u64, duration_ns: u128 },
+}
+
+impl Event<'_> {
+    /// Formats the event as a single line of JSON, without the trailing newline.
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+}
+
+/// Stream of events written to stdout by a solver when the NDJSON flag is given on the command line.
+/// Emitting an event does nothing if the flag is not given.
+pub struct EventStream {
+    enabled: bool,
+}
+
+impl EventStream {
+    /// Creates an event stream that is enabled if the NDJSON flag is given on the command line.
+    pub fn from_args() -> EventStream {
+        EventStream {
+            enabled: env::args().any(|arg| arg == NDJSON_FLAG),
+        }
+    }
+
+    /// Checks if the event stream is enabled, in which case the solver should not print its
+    /// human-readable results.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Writes the event to stdout as a single line of JSON if the stream is enabled.
+    pub fn emit(&self, event: &Event) {
+        if self.enabled {
+            println!("{}", event.to_json_line());
+        }
+    }
+
+    /// Emits the event for the solver of the given day starting.
+    pub fn run_started(&self, day: u64, name: &str) {
+        self.emit(&Event::RunStarted { day, name });
+    }
+
+    /// Emits the event for the given part being solved with the answer.
+    pub fn part_finished<T: Display>(&self, day: u64, part: u64, answer: &T, duration: Duration) {
+        self.emit(&Event::PartFinished {
+            day,
+            part,
+            answer: answer.to_string(),
+            duration_ns: duration.as_nanos(),
+        });
+    }
+
+    /// Emits the event for the solver of the given day completing.
+    pub fn run_completed(&self, day: u64, duration: Duration) {
+        self.emit(&Event::RunCompleted {
+            day,
+            duration_ns: duration.as_nanos(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that each kind of event is written as a single line of JSON tagged with its kind.
+    #[test]
+    fn test_event_json_lines() {
+        let started = Event::RunStarted {
+            day: 8,
+            name: "Two-Factor Authentication",
+        };
+        assert_eq!(
+            r#"{"event":"run_started","day":8,"name":"Two-Factor Authentication"}"#,
+            started.to_json_line()
+        );
+        let finished = Event::PartFinished {
+            day: 8,
+            part: 2,
+            answer: String::from("EOARGPHYAO"),
+            duration_ns: 1500,
+        };
+        assert_eq!(
+            r#"{"event":"part_finished","day":8,"part":2,"answer":"EOARGPHYAO","duration_ns":1500}"#,
+            finished.to_json_line()
+        );
+        let completed = Event::RunCompleted {
+            day: 8,
+            duration_ns: 2000,
+        };
+        assert_eq!(
+            r#"{"event":"run_completed","day":8,"duration_ns":2000}"#,
+            completed.to_json_line()
+        );
+    }
+}
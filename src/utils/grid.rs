@@ -0,0 +1,176 @@
+use std::ops::{Index, IndexMut};
+
+use aoc_utils::cartography::Point2D;
+
+/// Dense two-dimensional grid of values, stored contiguously in row-major order. The grid covers
+/// the locations with x from 0 to width-1 and y from 0 to height-1, so looking up a location is an
+/// index calculation rather than a hash lookup.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a new grid of the given size with every location holding the fill value.
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Creates a grid holding the values at the given locations, sized to fit the furthest location
+    /// in each direction. Locations in the grid without a given value hold the fill value. Returns
+    /// None if no locations are given or any location has a negative coordinate.
+    pub fn from_points<I>(points: I, fill: T) -> Option<Grid<T>>
+    where
+        I: IntoIterator<Item = (Point2D, T)>,
+    {
+        let points = points.into_iter().collect::<Vec<(Point2D, T)>>();
+        if points.iter().any(|(loc, _)| loc.x() < 0 || loc.y() < 0) {
+            return None;
+        }
+        let width = points.iter().map(|(loc, _)| loc.x()).max()? as usize + 1;
+        let height = points.iter().map(|(loc, _)| loc.y()).max()? as usize + 1;
+        let mut grid = Grid::new(width, height, fill);
+        for (loc, value) in points {
+            grid[loc] = value;
+        }
+        Some(grid)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Gets the number of columns in the grid.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the number of rows in the grid.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the index of the given location in the row-major storage of the grid. Returns None if
+    /// the location is outside of the grid.
+    pub fn index_of(&self, loc: &Point2D) -> Option<usize> {
+        let x = usize::try_from(loc.x()).ok().filter(|&x| x < self.width)?;
+        let y = usize::try_from(loc.y()).ok().filter(|&y| y < self.height)?;
+        Some(y * self.width + x)
+    }
+
+    /// Gets the location at the given index in the row-major storage of the grid.
+    pub fn location_of(&self, index: usize) -> Point2D {
+        Point2D::new((index % self.width) as i64, (index / self.width) as i64)
+    }
+
+    /// Checks if the given location is inside the grid.
+    pub fn contains(&self, loc: &Point2D) -> bool {
+        self.index_of(loc).is_some()
+    }
+
+    /// Gets the value at the given location. Returns None if the location is outside of the grid.
+    pub fn get(&self, loc: &Point2D) -> Option<&T> {
+        self.index_of(loc).map(|i| &self.cells[i])
+    }
+
+    /// Gets a mutable reference to the value at the given location. Returns None if the location is
+    /// outside of the grid.
+    pub fn get_mut(&mut self, loc: &Point2D) -> Option<&mut T> {
+        self.index_of(loc).map(|i| &mut self.cells[i])
+    }
+
+    /// Returns an iterator over the locations and values of the grid, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point2D, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (self.location_of(i), value))
+    }
+
+    /// Returns an iterator over the rows of the grid, from top (y=0) to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    /// Renders the grid with one character per location, as determined by the given function, and
+    /// each row on its own line.
+    pub fn render<F>(&self, tile_char: F) -> String
+    where
+        F: Fn(Point2D, &T) -> char,
+    {
+        let mut output = String::with_capacity((self.width + 1) * self.height);
+        for (loc, value) in self.iter() {
+            output.push(tile_char(loc, value));
+            if loc.x() as usize == self.width - 1 {
+                output.push('\n');
+            }
+        }
+        output
+    }
+
+    /// Gets the locations directly above, below, left and right of the given location that are
+    /// inside the grid.
+    pub fn neighbours(&self, loc: &Point2D) -> Vec<Point2D> {
+        loc.get_adjacent_points()
+            .into_iter()
+            .filter(|next_loc| self.contains(next_loc))
+            .collect()
+    }
+}
+
+impl<T> Index<Point2D> for Grid<T> {
+    type Output = T;
+
+    /// Gets the value at the given location. Panics if the location is outside of the grid.
+    fn index(&self, loc: Point2D) -> &T {
+        match self.index_of(&loc) {
+            Some(i) => &self.cells[i],
+            None => panic!("Location outside of grid! // ({}, {})", loc.x(), loc.y()),
+        }
+    }
+}
+
+impl<T> IndexMut<Point2D> for Grid<T> {
+    /// Gets a mutable reference to the value at the given location. Panics if the location is
+    /// outside of the grid.
+    fn index_mut(&mut self, loc: Point2D) -> &mut T {
+        match self.index_of(&loc) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("Location outside of grid! // ({}, {})", loc.x(), loc.y()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests building a grid from sparse locations, and the lookups and neighbours of locations on
+    /// the edge of and outside the grid.
+    #[test]
+    fn test_grid_from_points() {
+        let grid =
+            Grid::from_points([(Point2D::new(2, 0), 'b'), (Point2D::new(0, 1), 'a')], '.').unwrap();
+        assert_eq!((3, 2), (grid.width(), grid.height()));
+        let rows = grid
+            .rows()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["..b", "a.."], rows);
+        assert_eq!("..b\na..\n", grid.render(|_, c| *c));
+        assert_eq!(Some(5), grid.index_of(&Point2D::new(2, 1)));
+        assert_eq!(Point2D::new(2, 1), grid.location_of(5));
+        assert_eq!(None, grid.get(&Point2D::new(3, 0)));
+        assert_eq!(None, grid.get(&Point2D::new(-1, 0)));
+        assert_eq!('a', grid[Point2D::new(0, 1)]);
+        let mut neighbours = grid.neighbours(&Point2D::new(0, 1));
+        neighbours.sort_by_key(|loc| (loc.x(), loc.y()));
+        assert_eq!(vec![Point2D::new(0, 0), Point2D::new(1, 1)], neighbours);
+        assert_eq!(None, Grid::from_points([(Point2D::new(-1, 0), 'a')], '.'));
+        assert_eq!(None, Grid::<char>::from_points([], '.'));
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::ascii::{encode_decimal, MAX_DECIMAL_DIGITS};
+use super::md5batch::{self, LANES};
+use super::nibbles::encode_lower_hex;
+
+/// Number of consecutive indices hashed together by the miner before the predicate is checked.
+#[cfg(not(feature = "parallel"))]
+const BLOCK_LEN: usize = LANES;
+/// Number of consecutive indices hashed together by the miner before the predicate is checked. The
+/// block is split between the threads of the rayon thread pool.
+#[cfg(feature = "parallel")]
+const BLOCK_LEN: usize = 256 * LANES;
+
+/// Hash algorithm that calculates the digests of a batch of messages at once.
+pub trait HashAlgorithm: Sync {
+    /// Digest produced by the algorithm.
+    type Digest: AsRef<[u8]> + Copy + Send;
+
+    /// Calculates the digests of the given messages, in the same order as the messages.
+    fn digest_batch(&self, messages: &[Vec<u8>]) -> Vec<Self::Digest>;
+}
+
+/// MD5 hash algorithm, optionally followed by rounds of key stretching that each hash the lowercase
+/// hexadecimal digest of the previous round. Uses the multi-lane MD5 backend when it is enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Md5 {
+    stretch_rounds: usize,
+}
+
+impl Md5 {
+    /// Creates the plain MD5 hash algorithm, without key stretching.
+    pub fn new() -> Md5 {
+        Md5::default()
+    }
+
+    /// Creates the MD5 hash algorithm with the given number of key stretching rounds.
+    pub fn stretched(stretch_rounds: usize) -> Md5 {
+        Md5 { stretch_rounds }
+    }
+}
+
+impl HashAlgorithm for Md5 {
+    type Digest = [u8; 16];
+
+    fn digest_batch(&self, messages: &[Vec<u8>]) -> Vec<[u8; 16]> {
+        let mut digests = md5batch::compute_batch(messages);
+        if self.stretch_rounds == 0 {
+            return digests;
+        }
+        let mut hex_digests = vec![[0u8; 32]; digests.len()];
+        for _ in 0..self.stretch_rounds {
+            for (digest, hex_digest) in digests.iter().zip(hex_digests.iter_mut()) {
+                encode_lower_hex(digest, hex_digest);
+            }
+            md5batch::compute_batch_into(&hex_digests, &mut digests);
+        }
+        digests
+    }
+}
+
+/// Hash found by a HashMiner, along with the index appended to the prefix to produce it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MinedHash<D> {
+    index: u64,
+    digest: D,
+}
+
+impl<D> MinedHash<D> {
+    /// Gets the index appended to the prefix to produce the hash.
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    /// Gets the digest of the hash.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+}
+
+/// Iterator over the hashes of a prefix followed by an increasing decimal index (starting from 0)
+/// whose digests satisfy the predicate, yielded in order of index.
+///
+/// The indices are hashed in blocks, so the hash algorithm can digest several messages at once and
+/// the blocks can be shared between threads when the "parallel" feature is enabled. The predicate is
+/// checked in order of index once each block has been hashed.
+pub struct HashMiner<H: HashAlgorithm, P> {
+    algorithm: H,
+    prefix_len: usize,
+    /// Messages for the current block of indices, which start with the prefix
+    messages: Vec<Vec<u8>>,
+    predicate: P,
+    next_index: u64,
+    found: VecDeque<MinedHash<H::Digest>>,
+}
+
+impl<H, P> HashMiner<H, P>
+where
+    H: HashAlgorithm,
+    P: FnMut(&H::Digest) -> bool,
+{
+    /// Creates a new miner for the hashes of the prefix followed by an index that satisfy the
+    /// predicate, calculated with the given hash algorithm.
+    pub fn new(algorithm: H, prefix: &[u8], predicate: P) -> HashMiner<H, P> {
+        HashMiner {
+            algorithm,
+            prefix_len: prefix.len(),
+            messages: vec![prefix.to_vec(); BLOCK_LEN],
+            predicate,
+            next_index: 0,
+            found: VecDeque::new(),
+        }
+    }
+
+    /// Hashes the next block of indices and records the hashes that satisfy the predicate.
+    fn mine_next_block(&mut self) {
+        let mut index_buffer = [0; MAX_DECIMAL_DIGITS];
+        let indices = self.next_index..self.next_index + BLOCK_LEN as u64;
+        for (message, index) in self.messages.iter_mut().zip(indices.clone()) {
+            message.truncate(self.prefix_len);
+            message.extend_from_slice(encode_decimal(index, &mut index_buffer));
+        }
+        for (index, digest) in indices.zip(Self::digest_block(&self.algorithm, &self.messages)) {
+            if (self.predicate)(&digest) {
+                self.found.push_back(MinedHash { index, digest });
+            }
+        }
+        self.next_index += BLOCK_LEN as u64;
+    }
+
+    /// Calculates the digests of the messages in a block.
+    #[cfg(not(feature = "parallel"))]
+    fn digest_block(algorithm: &H, messages: &[Vec<u8>]) -> Vec<H::Digest> {
+        algorithm.digest_batch(messages)
+    }
+
+    /// Calculates the digests of the messages in a block, with groups of LANES messages digested
+    /// on the rayon thread pool.
+    #[cfg(feature = "parallel")]
+    fn digest_block(algorithm: &H, messages: &[Vec<u8>]) -> Vec<H::Digest> {
+        messages
+            .par_chunks(LANES)
+            .flat_map_iter(|chunk| algorithm.digest_batch(chunk))
+            .collect()
+    }
+}
+
+impl<H, P> Iterator for HashMiner<H, P>
+where
+    H: HashAlgorithm,
+    P: FnMut(&H::Digest) -> bool,
+{
+    type Item = MinedHash<H::Digest>;
+
+    fn next(&mut self) -> Option<MinedHash<H::Digest>> {
+        while self.found.is_empty() {
+            self.mine_next_block();
+        }
+        self.found.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests mining hashes using the examples from the AOC 2016 Day 5 and Day 14 problem
+    /// descriptions.
+    #[test]
+    fn test_hash_miner() {
+        let mut miner = HashMiner::new(Md5::new(), b"abc", |digest: &[u8; 16]| {
+            digest[..2] == [0, 0] && digest[2] < 0x10
+        });
+        assert_eq!(3231929, miner.next().unwrap().index());
+        assert_eq!(5017308, miner.next().unwrap().index());
+        let stretched = Md5::stretched(2016).digest_batch(&[b"abc0".to_vec()]);
+        assert_eq!(
+            format!("{:x}", md5::Digest(stretched[0])),
+            "a107ff634856bb300138cac6568c0f24"
+        );
+    }
+}
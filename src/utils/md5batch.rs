@@ -0,0 +1,255 @@
+use super::metrics::Counter;
+
+/// Total number of MD5 digests computed.
+static MD5_HASHES: Counter = Counter::new("md5 hashes computed");
+
+/// Number of messages hashed together in each call to the multi-lane MD5 implementation.
+pub const LANES: usize = 8;
+
+/// Computes the MD5 digests of the given messages, in the same order as the messages.
+///
+/// With the "simd-md5" feature enabled, the messages are hashed in groups of LANES by a multi-lane
+/// implementation that runs each step of the MD5 compression function on all of the messages in the
+/// group at once. The multi-lane implementation is only used on CPUs supporting AVX2, otherwise the
+/// messages are hashed one at a time by the md5 crate.
+pub fn compute_batch<M: AsRef<[u8]>>(messages: &[M]) -> Vec<[u8; 16]> {
+    let mut digests = vec![[0; 16]; messages.len()];
+    compute_batch_into(messages, &mut digests);
+    digests
+}
+
+/// Computes the MD5 digests of the given messages into the output buffer, which must be the same
+/// length as the messages. Avoids allocating a new buffer when hashing repeatedly, such as for key
+/// stretching.
+pub fn compute_batch_into<M: AsRef<[u8]>>(messages: &[M], digests: &mut [[u8; 16]]) {
+    assert_eq!(messages.len(), digests.len());
+    MD5_HASHES.add(messages.len() as u64);
+    #[cfg(feature = "simd-md5")]
+    if lanes::is_supported() {
+        for (chunk, output) in messages.chunks(LANES).zip(digests.chunks_mut(LANES)) {
+            lanes::compute_lanes(chunk, output);
+        }
+        return;
+    }
+    for (message, digest) in messages.iter().zip(digests.iter_mut()) {
+        *digest = md5::compute(message).0;
+    }
+}
+
+/// Multi-lane MD5 implementation, which holds the state words of each message in arrays indexed by
+/// lane so the compiler can apply each step to all of the lanes with vector instructions.
+#[cfg(feature = "simd-md5")]
+mod lanes {
+    use super::LANES;
+
+    /// Per-step left rotation amounts, as given in RFC 1321.
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    /// Per-step additive constants, as given in RFC 1321.
+    const CONSTANTS: [u32; 64] = [
+        0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613,
+        0xfd469501, 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193,
+        0xa679438e, 0x49b40821, 0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d,
+        0x02441453, 0xd8a1e681, 0xe7d3fbc8, 0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed,
+        0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a, 0xfffa3942, 0x8771f681, 0x6d9d6122,
+        0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70, 0x289b7ec6, 0xeaa127fa,
+        0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665, 0xf4292244,
+        0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+        0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb,
+        0xeb86d391,
+    ];
+    /// Initial values of the four state words.
+    const INITIAL_STATE: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+    /// State words (or message words) of every lane, indexed by lane.
+    type LaneWords = [u32; LANES];
+
+    /// Checks if the CPU supports the vector instructions used by the multi-lane implementation.
+    pub fn is_supported() -> bool {
+        #[cfg(target_arch = "x86_64")]
+        {
+            is_x86_feature_detected!("avx2")
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            false
+        }
+    }
+
+    /// Computes the MD5 digests of up to LANES messages at once into the output buffer. Messages
+    /// needing fewer blocks than the longest message keep their state unchanged once their final
+    /// block has been processed.
+    ///
+    /// Panics if more than LANES messages are given, or the CPU does not support AVX2.
+    pub fn compute_lanes<M: AsRef<[u8]>>(messages: &[M], digests: &mut [[u8; 16]]) {
+        assert!(
+            messages.len() <= LANES,
+            "Too many messages for the MD5 lanes!"
+        );
+        assert!(is_supported(), "CPU does not support the MD5 lanes!");
+        let mut block_counts = [0; LANES];
+        for (block_count, message) in block_counts.iter_mut().zip(messages) {
+            *block_count = padded_block_count(message.as_ref());
+        }
+        let max_blocks = block_counts.iter().copied().max().unwrap_or(0);
+        let mut state = INITIAL_STATE.map(|word| [word; LANES]);
+        let mut block_bytes = [0; 64];
+        for block in 0..max_blocks {
+            let mut words = [[0; LANES]; 16];
+            for (lane, message) in messages.iter().enumerate() {
+                if block < block_counts[lane] {
+                    fill_padded_block(message.as_ref(), block, &mut block_bytes);
+                    for (i, word) in block_bytes.chunks_exact(4).enumerate() {
+                        words[i][lane] = u32::from_le_bytes(word.try_into().unwrap());
+                    }
+                }
+            }
+            let mut next_state = state;
+            #[cfg(target_arch = "x86_64")]
+            // SAFETY: AVX2 support was checked above
+            unsafe {
+                compress_avx2(&mut next_state, &words)
+            };
+            for (lane, block_count) in block_counts.iter().enumerate() {
+                if block < *block_count {
+                    for i in 0..4 {
+                        state[i][lane] = next_state[i][lane];
+                    }
+                }
+            }
+        }
+        for (lane, digest) in digests.iter_mut().enumerate().take(messages.len()) {
+            for (i, word) in state.iter().enumerate() {
+                digest[i * 4..(i + 1) * 4].copy_from_slice(&word[lane].to_le_bytes());
+            }
+        }
+    }
+
+    /// Calculates the number of 64-byte blocks in the message once it has been padded with a 1 bit,
+    /// zero bits and the message length in bits.
+    fn padded_block_count(message: &[u8]) -> usize {
+        (message.len() + 8) / 64 + 1
+    }
+
+    /// Fills the buffer with the given block of the padded message, without building the whole
+    /// padded message.
+    fn fill_padded_block(message: &[u8], block: usize, output: &mut [u8; 64]) {
+        let start = block * 64;
+        output.fill(0);
+        let message_bytes = &message[start.min(message.len())..(start + 64).min(message.len())];
+        output[..message_bytes.len()].copy_from_slice(message_bytes);
+        if (start..start + 64).contains(&message.len()) {
+            output[message.len() - start] = 0x80;
+        }
+        if block + 1 == padded_block_count(message) {
+            let bit_len = (message.len() as u64).wrapping_mul(8);
+            output[56..].copy_from_slice(&bit_len.to_le_bytes());
+        }
+    }
+
+    /// Applies the MD5 compression function to the state words of every lane, compiled with AVX2
+    /// enabled so each step is applied to eight lanes at once.
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2")]
+    unsafe fn compress_avx2(state: &mut [LaneWords; 4], words: &[LaneWords; 16]) {
+        compress(state, words);
+    }
+
+    /// Applies the MD5 compression function to the state words of every lane, using the message
+    /// words of the block being processed in each lane.
+    #[inline(always)]
+    fn compress(state: &mut [LaneWords; 4], words: &[LaneWords; 16]) {
+        let mut abcd = *state;
+        rounds(
+            &mut abcd,
+            words,
+            0,
+            |step| step,
+            |x, y, z| (x & y) | (!x & z),
+        );
+        rounds(
+            &mut abcd,
+            words,
+            16,
+            |step| (5 * step + 1) % 16,
+            |x, y, z| (x & z) | (y & !z),
+        );
+        rounds(
+            &mut abcd,
+            words,
+            32,
+            |step| (3 * step + 5) % 16,
+            |x, y, z| x ^ y ^ z,
+        );
+        rounds(
+            &mut abcd,
+            words,
+            48,
+            |step| (7 * step) % 16,
+            |x, y, z| y ^ (x | !z),
+        );
+        for (state_word, word) in state.iter_mut().zip(abcd) {
+            for lane in 0..LANES {
+                state_word[lane] = state_word[lane].wrapping_add(word[lane]);
+            }
+        }
+    }
+
+    /// Applies the 16 steps of one round of the compression function, starting from the given step.
+    /// Each step uses the message word picked by the index function and the round function f.
+    #[inline(always)]
+    fn rounds<I, F>(
+        abcd: &mut [LaneWords; 4],
+        words: &[LaneWords; 16],
+        first: usize,
+        index: I,
+        f: F,
+    ) where
+        I: Fn(usize) -> usize,
+        F: Fn(u32, u32, u32) -> u32,
+    {
+        let [mut a, mut b, mut c, mut d] = *abcd;
+        for step in first..first + 16 {
+            let word = &words[index(step)];
+            let mut next_b = [0; LANES];
+            for lane in 0..LANES {
+                let sum = a[lane]
+                    .wrapping_add(f(b[lane], c[lane], d[lane]))
+                    .wrapping_add(CONSTANTS[step])
+                    .wrapping_add(word[lane]);
+                next_b[lane] = b[lane].wrapping_add(sum.rotate_left(SHIFTS[step]));
+            }
+            (a, b, c, d) = (d, next_b, b, c);
+        }
+        *abcd = [a, b, c, d];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that the batch digests match the md5 crate for messages either side of the block and
+    /// padding boundaries, in a batch that does not fill the last group of lanes.
+    #[test]
+    fn test_compute_batch() {
+        let messages = [0, 1, 3, 55, 56, 63, 64, 65, 119, 120, 200]
+            .map(|len| (0..len).map(|i| (i * 7 + len) as u8).collect::<Vec<u8>>());
+        let expected = messages
+            .iter()
+            .map(|message| md5::compute(message).0)
+            .collect::<Vec<[u8; 16]>>();
+        assert_eq!(expected, compute_batch(&messages));
+        assert_eq!(
+            format!("{:x}", md5::compute("abc3231929")),
+            compute_batch(&["abc3231929"])[0]
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        );
+        assert!(compute_batch::<&[u8]>(&[]).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, Once};
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Command line flag used to write the metrics recorded by the solver, along with the execution
+/// times, as JSON to the file given after the flag.
+pub const METRICS_JSON_FLAG: &str = "--metrics-json";
+
+/// Every counter and gauge that has been updated, in the order they were first updated.
+static REGISTRY: Mutex<Vec<MetricHandle>> = Mutex::new(Vec::new());
+
+/// Reference to a registered counter or gauge.
+#[derive(Clone, Copy)]
+enum MetricHandle {
+    Counter(&'static Counter),
+    Gauge(&'static Gauge),
+}
+
+/// Represents the different kinds of metric.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricKind {
+    /// Total that only increases, such as the number of states explored.
+    Counter,
+    /// Value that is set to the latest (or greatest) measurement, such as the deepest search depth.
+    Gauge,
+}
+
+/// Value of a single metric at the time it was read.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct MetricValue {
+    name: &'static str,
+    kind: MetricKind,
+    value: u64,
+}
+
+impl MetricValue {
+    /// Gets the value of the "name" field.
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// Gets the value of the "kind" field.
+    pub fn kind(&self) -> MetricKind {
+        self.kind
+    }
+
+    /// Gets the value of the "value" field.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+/// Counter metric, declared as a static by the code that updates it. The counter is registered
+/// (and included in the reported metrics) the first time it is updated.
+///
+/// Updating a counter is a single atomic addition, so solvers should add to it in bulk (such as once
+/// per batch of hashes) rather than from their innermost loops.
+pub struct Counter {
+    name: &'static str,
+    value: AtomicU64,
+    registered: Once,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str) -> Counter {
+        Counter {
+            name,
+            value: AtomicU64::new(0),
+            registered: Once::new(),
+        }
+    }
+
+    /// Adds the amount to the counter.
+    pub fn add(&'static self, amount: u64) {
+        self.registered
+            .call_once(|| REGISTRY.lock().unwrap().push(MetricHandle::Counter(self)));
+        self.value.fetch_add(amount, Ordering::Relaxed);
+    }
+
+    /// Gets the current value of the counter.
+    pub fn value(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+/// Gauge metric, declared as a static by the code that updates it. The gauge is registered (and
+/// included in the reported metrics) the first time it is updated.
+pub struct Gauge {
+    name: &'static str,
+    value: AtomicU64,
+    registered: Once,
+}
+
+impl Gauge {
+    pub const fn new(name: &'static str) -> Gauge {
+        Gauge {
+            name,
+            value: AtomicU64::new(0),
+            registered: Once::new(),
+        }
+    }
+
+    /// Sets the gauge to the value.
+    pub fn set(&'static self, value: u64) {
+        self.register();
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    /// Sets the gauge to the value if it is greater than the current value.
+    pub fn record_max(&'static self, value: u64) {
+        self.register();
+        self.value.fetch_max(value, Ordering::Relaxed);
+    }
+
+    /// Gets the current value of the gauge.
+    pub fn value(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    /// Adds the gauge to the registry if it has not already been added.
+    fn register(&'static self) {
+        self.registered
+            .call_once(|| REGISTRY.lock().unwrap().push(MetricHandle::Gauge(self)));
+    }
+}
+
+/// Reads the current value of every metric that has been updated, sorted by name.
+pub fn snapshot() -> Vec<MetricValue> {
+    let mut values = REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|handle| match handle {
+            MetricHandle::Counter(counter) => MetricValue {
+                name: counter.name,
+                kind: MetricKind::Counter,
+                value: counter.value(),
+            },
+            MetricHandle::Gauge(gauge) => MetricValue {
+                name: gauge.name,
+                kind: MetricKind::Gauge,
+                value: gauge.value(),
+            },
+        })
+        .collect::<Vec<MetricValue>>();
+    values.sort_by_key(|value| value.name);
+    values
+}
+
+/// Prints the current value of every metric that has been updated to stdout, in the style of the
+/// execution times printed by the solvers. Prints nothing if no metrics have been updated.
+pub fn print_metrics() {
+    let values = snapshot();
+    if values.is_empty() {
+        return;
+    }
+    println!("~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~~");
+    println!("Metrics:");
+    for value in values {
+        println!("[+] {}: {}", value.name, value.value);
+    }
+}
+
+/// Report of the metrics recorded while solving a day, along with the execution times, written as
+/// JSON by the solvers.
+#[derive(Serialize)]
+struct MetricsReport<'a> {
+    day: u64,
+    /// Execution time of each stage of the solver, in nanoseconds.
+    timings_ns: BTreeMap<&'a str, u128>,
+    metrics: Vec<MetricValue>,
+}
+
+/// Formats the current value of every metric that has been updated as JSON, along with the day and
+/// the execution time of each named stage of the solver.
+pub fn format_json(day: u64, timings: &[(&str, Duration)]) -> String {
+    let report = MetricsReport {
+        day,
+        timings_ns: timings
+            .iter()
+            .map(|(stage, duration)| (*stage, duration.as_nanos()))
+            .collect(),
+        metrics: snapshot(),
+    };
+    serde_json::to_string_pretty(&report).unwrap()
+}
+
+/// Writes the metrics and execution times as JSON to the file given after the metrics JSON flag on
+/// the command line. Does nothing if the flag is not given.
+///
+/// Panics if no file is given after the flag or the file cannot be written.
+pub fn write_json_if_requested(day: u64, timings: &[(&str, Duration)]) {
+    let args = env::args().collect::<Vec<String>>();
+    let Some(i) = args.iter().position(|arg| arg == METRICS_JSON_FLAG) else {
+        return;
+    };
+    let Some(filename) = args.get(i + 1) else {
+        panic!("Missing JSON file after {METRICS_JSON_FLAG} flag!");
+    };
+    if let Err(e) = fs::write(filename, format_json(day, timings)) {
+        panic!("Failed to write metrics! // {filename}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    static TEST_COUNTER: Counter = Counter::new("test widgets counted");
+    static TEST_GAUGE: Gauge = Gauge::new("test widget height");
+
+    /// Tests that counters and gauges are registered when first updated, and are included in the
+    /// snapshot and the JSON report.
+    #[test]
+    fn test_metrics_snapshot() {
+        TEST_COUNTER.add(3);
+        TEST_COUNTER.add(4);
+        TEST_GAUGE.record_max(5);
+        TEST_GAUGE.record_max(2);
+        let values = snapshot();
+        let counter = values
+            .iter()
+            .find(|value| value.name() == "test widgets counted")
+            .unwrap();
+        assert_eq!((MetricKind::Counter, 7), (counter.kind(), counter.value()));
+        let gauge = values
+            .iter()
+            .find(|value| value.name() == "test widget height")
+            .unwrap();
+        assert_eq!((MetricKind::Gauge, 5), (gauge.kind(), gauge.value()));
+        TEST_GAUGE.set(1);
+        assert_eq!(1, TEST_GAUGE.value());
+        let json = format_json(8, &[("part1", Duration::from_micros(2))]);
+        assert!(json.contains("\"part1\": 2000"));
+        assert!(json.contains("\"kind\": \"counter\""));
+    }
+}
@@ -1 +1,18 @@
+pub mod algorithms;
+pub mod alloccount;
+pub mod animation;
+pub mod ascii;
 pub mod bespoke;
+pub mod events;
+pub mod grid;
+#[cfg(feature = "md5")]
+pub mod hashmining;
+#[cfg(feature = "md5")]
+pub mod md5batch;
+pub mod metrics;
+pub mod modular;
+pub mod nibbles;
+pub mod params;
+pub mod search;
+pub mod stats;
+pub mod visualize;
@@ -0,0 +1,57 @@
+/// Calculates the greatest common divisor of a and b, along with the Bezout coefficients x and y
+/// such that a*x + b*y = gcd(a, b).
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+    let (gcd, x, y) = extended_gcd(b, a % b);
+    (gcd, y, x - (a / b) * y)
+}
+
+/// Solves the system of congruences x = residue (mod modulus) given as (residue, modulus) pairs
+/// using the Chinese Remainder Theorem. The moduli do not need to be pairwise coprime.
+///
+/// Returns the smallest non-negative solution and the modulus (the lowest common multiple of the
+/// given moduli) that all solutions are congruent under. Returns None if the congruences are
+/// inconsistent, or if any modulus is not positive. An empty system has the solution (0, 1).
+pub fn chinese_remainder(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut solution: i128 = 0;
+    let mut lcm: i128 = 1;
+    for &(residue, modulus) in congruences {
+        if modulus <= 0 {
+            return None;
+        }
+        let (residue, modulus) = (residue as i128, modulus as i128);
+        // Find k such that solution + lcm*k = residue (mod modulus)
+        let (gcd, inverse, _) = extended_gcd(lcm, modulus);
+        let diff = residue - solution;
+        if diff % gcd != 0 {
+            return None;
+        }
+        let step = modulus / gcd;
+        let k = ((diff / gcd) % step * (inverse % step)).rem_euclid(step);
+        solution += lcm * k;
+        lcm *= step;
+        solution = solution.rem_euclid(lcm);
+    }
+    Some((i64::try_from(solution).ok()?, i64::try_from(lcm).ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests the CRT solver with coprime moduli, non-coprime moduli and inconsistent congruences.
+    #[test]
+    fn test_chinese_remainder() {
+        assert_eq!(Some((0, 1)), chinese_remainder(&[]));
+        assert_eq!(
+            Some((23, 105)),
+            chinese_remainder(&[(2, 3), (3, 5), (2, 7)])
+        );
+        assert_eq!(Some((4, 5)), chinese_remainder(&[(-1, 5)]));
+        assert_eq!(Some((10, 12)), chinese_remainder(&[(2, 4), (4, 6)]));
+        assert_eq!(None, chinese_remainder(&[(1, 4), (2, 6)]));
+        assert_eq!(None, chinese_remainder(&[(1, 0)]));
+    }
+}
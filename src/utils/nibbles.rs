@@ -0,0 +1,84 @@
+/// Lowercase hexadecimal digits, indexed by nibble value.
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Returns an iterator over the nibbles (4-bit values) of the given bytes, high nibble first. This
+/// matches the order of the characters in the hexadecimal representation of the bytes.
+pub fn nibbles(bytes: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f])
+}
+
+/// Returns an iterator over the runs of identical consecutive nibbles in the given bytes, as
+/// (nibble value, run length) pairs in order of appearance.
+fn nibble_runs(bytes: &[u8]) -> impl Iterator<Item = (u8, usize)> + '_ {
+    let mut nibbles = nibbles(bytes).peekable();
+    std::iter::from_fn(move || {
+        let nibble = nibbles.next()?;
+        let mut run_len = 1;
+        while nibbles.next_if_eq(&nibble).is_some() {
+            run_len += 1;
+        }
+        Some((nibble, run_len))
+    })
+}
+
+/// Finds the value of the first nibble in the given bytes that appears in a run of at least the
+/// given length. Returns None if there is no such run.
+pub fn first_nibble_run(bytes: &[u8], min_run_len: usize) -> Option<u8> {
+    nibble_runs(bytes)
+        .find(|(_, run_len)| *run_len >= min_run_len)
+        .map(|(nibble, _)| nibble)
+}
+
+/// Calculates a bitmask of the nibble values in the given bytes that appear in a run of at least
+/// the given length. Bit n of the mask is set if nibble value n appears in such a run.
+pub fn nibble_run_mask(bytes: &[u8], min_run_len: usize) -> u16 {
+    nibble_runs(bytes)
+        .filter(|(_, run_len)| *run_len >= min_run_len)
+        .fold(0, |mask, (nibble, _)| mask | (1 << nibble))
+}
+
+/// Counts the number of zero nibbles at the start of the given bytes. This matches the number of
+/// leading "0" characters in the hexadecimal representation of the bytes.
+pub fn leading_zero_nibbles(bytes: &[u8]) -> usize {
+    nibbles(bytes).take_while(|nibble| *nibble == 0).count()
+}
+
+/// Gets the lowercase hexadecimal digit for the given nibble value. Panics if the value is not a
+/// nibble (0 to 15).
+pub fn lower_hex_digit(nibble: u8) -> u8 {
+    HEX_DIGITS[nibble as usize]
+}
+
+/// Encodes the given bytes as lowercase hexadecimal into the output buffer, which must be twice the
+/// length of the input. Avoids the allocation made when formatting the bytes into a String.
+pub fn encode_lower_hex(bytes: &[u8], output: &mut [u8]) {
+    assert_eq!(bytes.len() * 2, output.len());
+    for (i, nibble) in nibbles(bytes).enumerate() {
+        output[i] = HEX_DIGITS[nibble as usize];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests the nibble run detection against the hexadecimal digest 0x34eee9999987aaa0.
+    #[test]
+    fn test_nibble_runs() {
+        let bytes = [0x34, 0xee, 0xe9, 0x99, 0x99, 0x87, 0xaa, 0xa0];
+        assert_eq!(Some(0xe), first_nibble_run(&bytes, 3));
+        assert_eq!(Some(0x9), first_nibble_run(&bytes, 5));
+        assert_eq!(None, first_nibble_run(&bytes, 6));
+        assert_eq!(
+            (1 << 0xe) | (1 << 0x9) | (1 << 0xa),
+            nibble_run_mask(&bytes, 3)
+        );
+        assert_eq!(1 << 0x9, nibble_run_mask(&bytes, 5));
+        let mut hex = [0; 16];
+        encode_lower_hex(&bytes, &mut hex);
+        assert_eq!(b"34eee9999987aaa0", &hex);
+        assert_eq!(0, leading_zero_nibbles(&bytes));
+        assert_eq!(3, leading_zero_nibbles(&[0x00, 0x0a, 0xff]));
+        assert_eq!(b'a', lower_hex_digit(0xa));
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::str::FromStr;
+
+/// Command line flag used to override one of the solver's tunable puzzle constants, given as
+/// "key=value" after the flag. The flag can be given more than once to override several constants.
+pub const PARAM_FLAG: &str = "--param";
+
+/// Puzzle constants overridden on the command line, held as the raw values given for each key until
+/// they are read by the solver.
+#[derive(Debug, Default)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    /// Parses every "key=value" pair given after a param flag in the arguments. A later pair for the
+    /// same key replaces the earlier one. Returns an error if a flag is not followed by a pair, or
+    /// the key is not one of the known keys.
+    pub fn parse(args: &[String], known_keys: &[&str]) -> Result<Params, String> {
+        let mut values = BTreeMap::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg != PARAM_FLAG {
+                continue;
+            }
+            let Some((key, value)) = args.next().and_then(|pair| pair.split_once('=')) else {
+                return Err(format!(
+                    "Parameter must be given as \"key=value\" after {PARAM_FLAG} flag"
+                ));
+            };
+            if !known_keys.contains(&key) {
+                return Err(format!(
+                    "Unknown parameter \"{key}\" ({})",
+                    known_keys.join(", ")
+                ));
+            }
+            values.insert(key.to_string(), value.to_string());
+        }
+        Ok(Params { values })
+    }
+
+    /// Reads the parameters overridden on the command line.
+    ///
+    /// Panics if a param flag is not followed by a "key=value" pair, or the key is not one of the
+    /// known keys.
+    pub fn from_args(known_keys: &[&str]) -> Params {
+        let args = env::args().collect::<Vec<String>>();
+        match Params::parse(&args, known_keys) {
+            Ok(params) => params,
+            Err(e) => panic!("{e}!"),
+        }
+    }
+
+    /// Gets the value of the parameter using the given function to parse the value, or the default
+    /// if the parameter was not overridden.
+    ///
+    /// Panics if the value of the parameter cannot be parsed.
+    pub fn get_with<T>(&self, key: &str, default: T, parse: impl Fn(&str) -> Option<T>) -> T {
+        let Some(value) = self.values.get(key) else {
+            return default;
+        };
+        match parse(value) {
+            Some(value) => value,
+            None => panic!("Invalid value for parameter! // {key}={value}"),
+        }
+    }
+
+    /// Gets the value of the parameter, or the default if the parameter was not overridden.
+    ///
+    /// Panics if the value of the parameter cannot be parsed.
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> T {
+        self.get_with(key, default, |value| value.trim().parse::<T>().ok())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that the parameters are parsed from the arguments, falling back to the defaults for the
+    /// parameters not given, and that malformed pairs and unknown keys are rejected.
+    #[test]
+    fn test_params_parse() {
+        let args = [
+            "day18",
+            PARAM_FLAG,
+            "rows=10",
+            "--visualize",
+            PARAM_FLAG,
+            "rows=12",
+        ]
+        .map(String::from);
+        let params = Params::parse(&args, &["rows", "width"]).unwrap();
+        assert_eq!(12, params.get("rows", 40));
+        assert_eq!(5, params.get("width", 5));
+        assert_eq!(
+            (1, 2),
+            params.get_with("rows", (0, 0), |value| Some((1, value.len())))
+        );
+        assert_eq!(
+            Err(String::from(
+                "Parameter must be given as \"key=value\" after --param flag"
+            )),
+            Params::parse(&args[..2], &["rows"]).map(|_| ())
+        );
+        assert_eq!(
+            Err(String::from("Unknown parameter \"rows\" (width)")),
+            Params::parse(&args, &["width"]).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,530 @@
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::env;
+use std::hash::Hash;
+
+use super::metrics::{Counter, Gauge};
+
+/// Command line flag shared by the search-based solvers, used to print commentary on the progress of
+/// each search. The flag can be followed by the number of states expanded between progress reports,
+/// in place of the default.
+pub const EXPLAIN_FLAG: &str = "--explain";
+/// Default number of states expanded between progress reports when explaining a search.
+pub const DEFAULT_EXPLAIN_INTERVAL: usize = 10000;
+/// Command line flag shared by the search-based solvers, used to select the search strategy (bfs or
+/// astar).
+pub const SEARCH_STRATEGY_FLAG: &str = "--search";
+
+/// Total number of states expanded by every search.
+static STATES_EXPANDED: Counter = Counter::new("search states expanded");
+/// Total number of repeated states pruned by every search.
+static STATES_PRUNED: Counter = Counter::new("search states pruned");
+/// Greatest depth reached by any search.
+static SEARCH_DEPTH: Gauge = Gauge::new("search depth reached");
+
+/// Represents a search problem over a state space where every move between states costs one step.
+/// States are considered equivalent (and only searched once) if they have the same key, allowing
+/// symmetric states to be pruned from the search.
+pub trait SearchProblem {
+    type State: Clone;
+    type Key: Eq + Hash;
+
+    /// Gets the key identifying the state (and any states equivalent to it).
+    fn key(&self, state: &Self::State) -> Self::Key;
+
+    /// Gets the state the search starts from.
+    fn initial_state(&self) -> Self::State;
+
+    /// Checks if the state is a goal state.
+    fn is_goal(&self, state: &Self::State) -> bool;
+
+    /// Gets the states reachable from the state in a single move.
+    fn successors(&self, state: &Self::State) -> Vec<Self::State>;
+
+    /// Gets a lower bound on the number of moves required to reach a goal state from the state.
+    /// The default of zero is always admissible, but gives no guidance to a heuristic search.
+    fn heuristic(&self, _state: &Self::State) -> usize {
+        0
+    }
+}
+
+/// Represents the different strategies available to search for a minimal path to a goal state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum SearchStrategy {
+    /// Expands states in order of the number of moves taken to reach them.
+    #[default]
+    BreadthFirst,
+    /// Expands states in order of the number of moves taken plus the heuristic estimate of the
+    /// moves remaining. Finds a minimal path if the heuristic of the problem is admissible.
+    AStar,
+}
+
+impl SearchStrategy {
+    /// Parses the search strategy given after the search strategy flag in the arguments, defaulting
+    /// to breadth-first search if the flag is not given. Returns an error if the flag is not
+    /// followed by a known strategy.
+    pub fn parse_args(args: &[String]) -> Result<SearchStrategy, String> {
+        let Some(i) = args.iter().position(|arg| arg == SEARCH_STRATEGY_FLAG) else {
+            return Ok(SearchStrategy::default());
+        };
+        match args.get(i + 1).map(|arg| arg.as_str()) {
+            Some("bfs") => Ok(SearchStrategy::BreadthFirst),
+            Some("astar") => Ok(SearchStrategy::AStar),
+            _ => Err(format!(
+                "Search strategy must be given after {SEARCH_STRATEGY_FLAG} flag (bfs or astar)"
+            )),
+        }
+    }
+
+    /// Reads the search strategy given on the command line, defaulting to breadth-first search.
+    ///
+    /// Panics if the search strategy flag is not followed by a known strategy.
+    pub fn from_args() -> SearchStrategy {
+        let args = env::args().collect::<Vec<String>>();
+        match SearchStrategy::parse_args(&args) {
+            Ok(strategy) => strategy,
+            Err(e) => panic!("{e}!"),
+        }
+    }
+}
+
+/// Represents the outcome of a successful search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchOutcome<S> {
+    path: Vec<S>,
+    expanded: usize,
+}
+
+impl<S> SearchOutcome<S> {
+    /// Gets the number of moves on the path found to the goal state.
+    pub fn moves(&self) -> usize {
+        self.path.len() - 1
+    }
+
+    /// Gets the number of states expanded (had their successors generated) during the search.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+
+    /// Gets the states on the path found, from the initial state to the goal state.
+    pub fn path(&self) -> &[S] {
+        &self.path
+    }
+}
+
+/// Snapshot of the progress of a search, reported to a search observer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct SearchProgress {
+    /// Number of states expanded (had their successors generated) so far.
+    pub expanded: usize,
+    /// Number of states reached but not yet expanded.
+    pub frontier: usize,
+    /// Greatest number of moves from the initial state to a state expanded so far.
+    pub depth: usize,
+    /// Number of states skipped because an equivalent state had already been reached by a path at
+    /// least as short.
+    pub pruned: usize,
+}
+
+/// Receives reports on the progress of a search as it runs.
+pub trait SearchObserver {
+    /// Receives the progress of the search each time the reporting interval of expanded states is
+    /// reached.
+    fn progress(&mut self, progress: &SearchProgress);
+
+    /// Receives the final progress of the search once it ends, and whether a goal state was found.
+    fn finished(&mut self, _progress: &SearchProgress, _found_goal: bool) {}
+}
+
+/// Search observer that ignores every report, used when searching without an observer.
+struct NoObserver;
+
+impl SearchObserver for NoObserver {
+    fn progress(&mut self, _progress: &SearchProgress) {}
+}
+
+/// Search observer that prints each progress report to stdout as a line of commentary, describing
+/// how the search is growing for readers following the algorithm.
+#[derive(Default)]
+pub struct SearchCommentary {
+    last: SearchProgress,
+}
+
+impl SearchObserver for SearchCommentary {
+    fn progress(&mut self, progress: &SearchProgress) {
+        println!("[?] {}", describe_progress(&self.last, progress));
+        self.last = *progress;
+    }
+
+    fn finished(&mut self, progress: &SearchProgress, found_goal: bool) {
+        let outcome = if found_goal {
+            format!("found a goal state at depth {}", progress.depth)
+        } else {
+            String::from("ran out of states without finding a goal state")
+        };
+        println!(
+            "[?] Search {outcome} after expanding {} states and pruning {} repeated states.",
+            progress.expanded, progress.pruned
+        );
+    }
+}
+
+/// Describes the progress of a search since the previous report as a sentence of commentary.
+fn describe_progress(previous: &SearchProgress, current: &SearchProgress) -> String {
+    let depth = if current.depth > previous.depth {
+        format!("pushed on to depth {}", current.depth)
+    } else {
+        format!("is still working through depth {}", current.depth)
+    };
+    format!(
+        "Expanded {} states so far and {depth}; {} states are waiting in the frontier and {} \
+        repeated states have been pruned.",
+        current.expanded, current.frontier, current.pruned
+    )
+}
+
+/// Searches for a minimal path from the initial state of the problem to a goal state using the
+/// given strategy. Returns None if no goal state is reachable.
+pub fn search<P: SearchProblem>(
+    problem: &P,
+    strategy: SearchStrategy,
+) -> Option<SearchOutcome<P::State>> {
+    search_observed(problem, strategy, &mut NoObserver, usize::MAX)
+}
+
+/// Searches for a minimal path in the same way as the search function, reporting the progress of
+/// the search to the observer each time the given number of states have been expanded and once the
+/// search ends.
+///
+/// Panics if the interval is zero.
+pub fn search_observed<P: SearchProblem>(
+    problem: &P,
+    strategy: SearchStrategy,
+    observer: &mut dyn SearchObserver,
+    interval: usize,
+) -> Option<SearchOutcome<P::State>> {
+    assert!(interval > 0, "Search progress interval must be positive!");
+    let mut recorder = MetricsRecorder { observer };
+    match strategy {
+        SearchStrategy::BreadthFirst => breadth_first_search(problem, &mut recorder, interval),
+        SearchStrategy::AStar => a_star_search(problem, &mut recorder, interval),
+    }
+}
+
+/// Search observer that adds the final progress of the search to the search metrics, before passing
+/// each report on to the wrapped observer.
+struct MetricsRecorder<'a> {
+    observer: &'a mut dyn SearchObserver,
+}
+
+impl SearchObserver for MetricsRecorder<'_> {
+    fn progress(&mut self, progress: &SearchProgress) {
+        self.observer.progress(progress);
+    }
+
+    fn finished(&mut self, progress: &SearchProgress, found_goal: bool) {
+        STATES_EXPANDED.add(progress.expanded as u64);
+        STATES_PRUNED.add(progress.pruned as u64);
+        SEARCH_DEPTH.record_max(progress.depth as u64);
+        self.observer.finished(progress, found_goal);
+    }
+}
+
+/// Searches for a minimal path in the same way as the search function, printing commentary on the
+/// progress of the search if the explain flag was given on the command line.
+pub fn search_explained<P: SearchProblem>(
+    problem: &P,
+    strategy: SearchStrategy,
+) -> Option<SearchOutcome<P::State>> {
+    match read_explain_interval() {
+        Some(interval) => search_observed(
+            problem,
+            strategy,
+            &mut SearchCommentary::default(),
+            interval,
+        ),
+        None => search(problem, strategy),
+    }
+}
+
+/// Reads the progress reporting interval from the command line, given by a positive number after
+/// the explain flag or the default if no number follows. Returns None if the explain flag is not
+/// given.
+fn read_explain_interval() -> Option<usize> {
+    let args = env::args().collect::<Vec<String>>();
+    let i = args.iter().position(|arg| arg == EXPLAIN_FLAG)?;
+    let interval = args
+        .get(i + 1)
+        .and_then(|interval| interval.parse::<usize>().ok())
+        .filter(|interval| *interval > 0);
+    Some(interval.unwrap_or(DEFAULT_EXPLAIN_INTERVAL))
+}
+
+/// Represents a state reached during a search, with the index of the state it was reached from and
+/// the number of moves taken to reach it.
+struct SearchNode<S> {
+    state: S,
+    parent: Option<usize>,
+    moves: usize,
+}
+
+/// Reconstructs the path to the node at the given index by following the parent indices back to
+/// the initial state.
+fn reconstruct_path<S: Clone>(nodes: &[SearchNode<S>], index: usize) -> Vec<S> {
+    let mut path: Vec<S> = vec![];
+    let mut current = Some(index);
+    while let Some(i) = current {
+        path.push(nodes[i].state.clone());
+        current = nodes[i].parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Searches the problem state space in breadth-first order.
+fn breadth_first_search<P: SearchProblem>(
+    problem: &P,
+    observer: &mut dyn SearchObserver,
+    interval: usize,
+) -> Option<SearchOutcome<P::State>> {
+    let initial_state = problem.initial_state();
+    let mut observed: HashSet<P::Key> = HashSet::from([problem.key(&initial_state)]);
+    let mut nodes = vec![SearchNode {
+        state: initial_state,
+        parent: None,
+        moves: 0,
+    }];
+    let mut queue: VecDeque<usize> = VecDeque::from([0]);
+    let mut progress = SearchProgress::default();
+    while let Some(index) = queue.pop_front() {
+        progress.depth = progress.depth.max(nodes[index].moves);
+        if problem.is_goal(&nodes[index].state) {
+            progress.frontier = queue.len();
+            observer.finished(&progress, true);
+            let path = reconstruct_path(&nodes, index);
+            let expanded = progress.expanded;
+            return Some(SearchOutcome { path, expanded });
+        }
+        progress.expanded += 1;
+        for next_state in problem.successors(&nodes[index].state) {
+            if observed.insert(problem.key(&next_state)) {
+                queue.push_back(nodes.len());
+                nodes.push(SearchNode {
+                    state: next_state,
+                    parent: Some(index),
+                    moves: nodes[index].moves + 1,
+                });
+            } else {
+                progress.pruned += 1;
+            }
+        }
+        if progress.expanded % interval == 0 {
+            progress.frontier = queue.len();
+            observer.progress(&progress);
+        }
+    }
+    progress.frontier = 0;
+    observer.finished(&progress, false);
+    None
+}
+
+/// Searches the problem state space in order of the estimated total path length through each
+/// state, skipping states already reached by a path at least as short.
+fn a_star_search<P: SearchProblem>(
+    problem: &P,
+    observer: &mut dyn SearchObserver,
+    interval: usize,
+) -> Option<SearchOutcome<P::State>> {
+    let initial_state = problem.initial_state();
+    let mut best_moves: HashMap<P::Key, usize> = HashMap::from([(problem.key(&initial_state), 0)]);
+    // The priority queue refers to nodes by index so it only needs to order integers
+    let mut open: BinaryHeap<Reverse<(usize, usize, usize)>> =
+        BinaryHeap::from([Reverse((problem.heuristic(&initial_state), 0, 0))]);
+    let mut nodes = vec![SearchNode {
+        state: initial_state,
+        parent: None,
+        moves: 0,
+    }];
+    let mut progress = SearchProgress::default();
+    while let Some(Reverse((_, moves, index))) = open.pop() {
+        if best_moves[&problem.key(&nodes[index].state)] < moves {
+            continue;
+        }
+        progress.depth = progress.depth.max(moves);
+        if problem.is_goal(&nodes[index].state) {
+            progress.frontier = open.len();
+            observer.finished(&progress, true);
+            let path = reconstruct_path(&nodes, index);
+            let expanded = progress.expanded;
+            return Some(SearchOutcome { path, expanded });
+        }
+        progress.expanded += 1;
+        for next_state in problem.successors(&nodes[index].state) {
+            let next_moves = moves + 1;
+            match best_moves.entry(problem.key(&next_state)) {
+                Entry::Occupied(mut e) => {
+                    if *e.get() <= next_moves {
+                        progress.pruned += 1;
+                        continue;
+                    }
+                    e.insert(next_moves);
+                }
+                Entry::Vacant(e) => _ = e.insert(next_moves),
+            }
+            let estimate = next_moves + problem.heuristic(&next_state);
+            open.push(Reverse((estimate, next_moves, nodes.len())));
+            nodes.push(SearchNode {
+                state: next_state,
+                parent: Some(index),
+                moves: next_moves,
+            });
+        }
+        if progress.expanded % interval == 0 {
+            progress.frontier = open.len();
+            observer.progress(&progress);
+        }
+    }
+    progress.frontier = 0;
+    observer.finished(&progress, false);
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Search problem on the number line: move by +1, -1 or x2 from the start to reach the target.
+    struct NumberLine {
+        start: i64,
+        target: i64,
+    }
+
+    impl SearchProblem for NumberLine {
+        type State = i64;
+        type Key = i64;
+
+        fn key(&self, state: &i64) -> i64 {
+            *state
+        }
+
+        fn initial_state(&self) -> i64 {
+            self.start
+        }
+
+        fn is_goal(&self, state: &i64) -> bool {
+            *state == self.target
+        }
+
+        fn successors(&self, state: &i64) -> Vec<i64> {
+            vec![state + 1, state - 1, state * 2]
+                .into_iter()
+                .filter(|n| (-100..=100).contains(n))
+                .collect()
+        }
+
+        fn heuristic(&self, state: &i64) -> usize {
+            // Each move at most doubles the distance covered, so at least one move is needed if
+            // the state is not the target
+            usize::from(*state != self.target)
+        }
+    }
+
+    /// Tests that breadth-first and A* search find the same minimal number of moves, and that
+    /// unreachable goals are reported.
+    #[test]
+    fn test_search_strategies() {
+        let problem = NumberLine {
+            start: 3,
+            target: 25,
+        };
+        let bfs = search(&problem, SearchStrategy::BreadthFirst).unwrap();
+        let a_star = search(&problem, SearchStrategy::AStar).unwrap();
+        assert_eq!(4, bfs.moves());
+        assert_eq!(4, a_star.moves());
+        assert!(a_star.expanded() <= bfs.expanded());
+        assert_eq!(&[3, 6, 12, 24, 25], a_star.path());
+        let problem = NumberLine {
+            start: 3,
+            target: 101,
+        };
+        assert_eq!(None, search(&problem, SearchStrategy::BreadthFirst));
+        assert_eq!(None, search(&problem, SearchStrategy::AStar));
+    }
+
+    /// Search observer that retains every report received.
+    #[derive(Default)]
+    struct ProgressRecorder {
+        reports: Vec<SearchProgress>,
+        finished: Option<(SearchProgress, bool)>,
+    }
+
+    impl SearchObserver for ProgressRecorder {
+        fn progress(&mut self, progress: &SearchProgress) {
+            self.reports.push(*progress);
+        }
+
+        fn finished(&mut self, progress: &SearchProgress, found_goal: bool) {
+            self.finished = Some((*progress, found_goal));
+        }
+    }
+
+    /// Tests that progress is reported at the given interval of expanded states with a growing
+    /// depth, and that the final report matches the outcome of the search.
+    #[test]
+    fn test_search_observed() {
+        let problem = NumberLine {
+            start: 3,
+            target: 25,
+        };
+        for strategy in [SearchStrategy::BreadthFirst, SearchStrategy::AStar] {
+            let mut recorder = ProgressRecorder::default();
+            let outcome = search_observed(&problem, strategy, &mut recorder, 2).unwrap();
+            assert_eq!(search(&problem, strategy), Some(outcome.clone()));
+            assert_eq!(outcome.expanded() / 2, recorder.reports.len());
+            assert!(recorder
+                .reports
+                .windows(2)
+                .all(|w| w[0].expanded + 2 == w[1].expanded && w[0].depth <= w[1].depth));
+            let (last, found_goal) = recorder.finished.unwrap();
+            assert!(found_goal);
+            assert_eq!(outcome.expanded(), last.expanded);
+            assert_eq!(outcome.moves(), last.depth);
+        }
+        let previous = SearchProgress {
+            expanded: 10,
+            frontier: 4,
+            depth: 2,
+            pruned: 5,
+        };
+        let current = SearchProgress {
+            expanded: 20,
+            depth: 3,
+            ..previous
+        };
+        assert_eq!(
+            "Expanded 20 states so far and pushed on to depth 3; 4 states are waiting in the \
+            frontier and 5 repeated states have been pruned.",
+            describe_progress(&previous, &current)
+        );
+    }
+
+    /// Tests that the search strategy is read from the arguments after the search strategy flag.
+    #[test]
+    fn test_search_strategy_parse_args() {
+        let args = ["day13", "--visualize"].map(String::from);
+        assert_eq!(
+            Ok(SearchStrategy::BreadthFirst),
+            SearchStrategy::parse_args(&args)
+        );
+        let args = ["day13", SEARCH_STRATEGY_FLAG, "astar"].map(String::from);
+        assert_eq!(Ok(SearchStrategy::AStar), SearchStrategy::parse_args(&args));
+        let args = ["day13", SEARCH_STRATEGY_FLAG, "dfs"].map(String::from);
+        assert_eq!(
+            Err(String::from(
+                "Search strategy must be given after --search flag (bfs or astar)"
+            )),
+            SearchStrategy::parse_args(&args)
+        );
+    }
+}
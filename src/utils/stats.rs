@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+/// Critical values of Student's t-distribution for a two-sided 95% confidence interval, indexed by
+/// the degrees of freedom minus one. The normal approximation is used for larger samples.
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+    2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+    2.052, 2.048, 2.045, 2.042,
+];
+/// Critical value of the normal distribution for a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+/// Multiple of the interquartile range beyond the quartiles at which samples are rejected as
+/// outliers (Tukey's fences).
+const OUTLIER_IQR_FACTOR: f64 = 1.5;
+
+/// Summary statistics of a set of timing samples, calculated after rejecting outliers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimingSummary {
+    median: Duration,
+    mean: Duration,
+    ci95: Option<Duration>,
+    kept: usize,
+    outliers: usize,
+}
+
+impl TimingSummary {
+    /// Summarises the timing samples. Samples more than 1.5 times the interquartile range below the
+    /// first quartile or above the third quartile are rejected as outliers before the median, mean
+    /// and confidence interval are calculated. Returns None if there are no samples.
+    pub fn from_samples(samples: &[Duration]) -> Option<TimingSummary> {
+        let mut secs = samples
+            .iter()
+            .map(|sample| sample.as_secs_f64())
+            .collect::<Vec<f64>>();
+        secs.sort_by(f64::total_cmp);
+        let (q1, q3) = (quantile(&secs, 0.25)?, quantile(&secs, 0.75)?);
+        let fence = OUTLIER_IQR_FACTOR * (q3 - q1);
+        let kept = secs
+            .iter()
+            .copied()
+            .filter(|s| (q1 - fence..=q3 + fence).contains(s))
+            .collect::<Vec<f64>>();
+        let n = kept.len() as f64;
+        let mean = kept.iter().sum::<f64>() / n;
+        let ci95 = (kept.len() > 1).then(|| {
+            let variance = kept.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            let t = T_95.get(kept.len() - 2).copied().unwrap_or(Z_95);
+            Duration::from_secs_f64(t * variance.sqrt() / n.sqrt())
+        });
+        Some(TimingSummary {
+            median: Duration::from_secs_f64(quantile(&kept, 0.5)?),
+            mean: Duration::from_secs_f64(mean),
+            ci95,
+            kept: kept.len(),
+            outliers: secs.len() - kept.len(),
+        })
+    }
+
+    /// Gets the median of the samples kept.
+    pub fn median(&self) -> Duration {
+        self.median
+    }
+
+    /// Gets the mean of the samples kept.
+    pub fn mean(&self) -> Duration {
+        self.mean
+    }
+
+    /// Gets the half-width of the 95% confidence interval for the mean of the samples kept. Returns
+    /// None if fewer than two samples were kept.
+    pub fn ci95(&self) -> Option<Duration> {
+        self.ci95
+    }
+
+    /// Gets the number of samples kept after rejecting outliers.
+    pub fn kept(&self) -> usize {
+        self.kept
+    }
+
+    /// Gets the number of samples rejected as outliers.
+    pub fn outliers(&self) -> usize {
+        self.outliers
+    }
+}
+
+/// Calculates the quantile of the sorted values, interpolating linearly between the closest ranks.
+/// Returns None if there are no values.
+fn quantile(sorted: &[f64], q: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = q * last as f64;
+    let (lower, upper) = (rank.floor() as usize, rank.ceil() as usize);
+    Some(sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that a slow outlier is rejected before the median, mean and confidence interval are
+    /// calculated, and that a single sample has no confidence interval.
+    #[test]
+    fn test_timing_summary() {
+        let samples = [10, 12, 11, 13, 250, 12].map(Duration::from_millis);
+        let summary = TimingSummary::from_samples(&samples).unwrap();
+        assert_eq!((5, 1), (summary.kept(), summary.outliers()));
+        assert_eq!(Duration::from_millis(12), summary.median());
+        assert_eq!(11.6, (summary.mean().as_secs_f64() * 1e4).round() / 10.0);
+        // Sample standard deviation of 1.14ms over 5 samples, with t = 2.776 for 4 degrees of freedom
+        let ci95 = summary.ci95().unwrap().as_secs_f64() * 1e3;
+        assert!((ci95 - 1.416).abs() < 0.001, "{ci95}");
+        let single = TimingSummary::from_samples(&samples[..1]).unwrap();
+        assert_eq!(
+            (Duration::from_millis(10), None),
+            (single.median(), single.ci95())
+        );
+        assert_eq!(None, TimingSummary::from_samples(&[]));
+    }
+}
@@ -0,0 +1,119 @@
+use std::env;
+
+/// Command line flag shared by the solvers with a visualization, used to print the frames of the
+/// visualization.
+pub const VIZ_FLAG: &str = "--viz";
+
+/// Single frame of a visualization, holding the rendered text of a state with one line per row and
+/// a caption describing the state.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    caption: String,
+    text: String,
+}
+
+impl Frame {
+    pub fn new(caption: &str, text: String) -> Frame {
+        Frame {
+            caption: caption.to_string(),
+            text,
+        }
+    }
+
+    /// Gets a reference to the "caption" field.
+    pub fn caption(&self) -> &str {
+        &self.caption
+    }
+
+    /// Gets a reference to the "text" field.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Receives the frames of a visualization in order.
+pub trait FrameSink {
+    /// Receives the next frame of the visualization.
+    fn frame(&mut self, frame: Frame);
+}
+
+/// Implemented by the solver states that can be visualized as a sequence of frames, such as a grid
+/// being searched or a screen being drawn.
+pub trait Visualize {
+    /// Renders the frames of the visualization in order into the sink.
+    fn visualize(&self, sink: &mut dyn FrameSink);
+}
+
+/// Frame sink that prints each frame to stdout as it is received, with the frame number and caption
+/// on the line before the frame.
+#[derive(Default)]
+pub struct FramePrinter {
+    frame_count: usize,
+}
+
+impl FrameSink for FramePrinter {
+    fn frame(&mut self, frame: Frame) {
+        self.frame_count += 1;
+        println!("[{}] {}", self.frame_count, frame.caption);
+        print!("{}", frame.text);
+        if !frame.text.ends_with('\n') {
+            println!();
+        }
+    }
+}
+
+/// Frame sink that retains every frame received.
+#[derive(Default)]
+pub struct FrameRecorder {
+    frames: Vec<Frame>,
+}
+
+impl FrameRecorder {
+    /// Gets the frames received by the recorder, in order.
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+}
+
+impl FrameSink for FrameRecorder {
+    fn frame(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+}
+
+/// Checks if the visualization flag was given on the command line.
+pub fn is_requested() -> bool {
+    env::args().any(|arg| arg == VIZ_FLAG)
+}
+
+/// Prints the frames of the visualization to stdout.
+pub fn print_frames<V: Visualize>(value: &V) {
+    value.visualize(&mut FramePrinter::default());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Visualization of counting up to a number, with one frame per number.
+    struct CountUp(usize);
+
+    impl Visualize for CountUp {
+        fn visualize(&self, sink: &mut dyn FrameSink) {
+            for i in 1..=self.0 {
+                sink.frame(Frame::new(&format!("count {i}"), "#".repeat(i)));
+            }
+        }
+    }
+
+    /// Tests that the recorder retains the frames of a visualization in order.
+    #[test]
+    fn test_frame_recorder() {
+        let mut recorder = FrameRecorder::default();
+        CountUp(3).visualize(&mut recorder);
+        let frames = recorder.frames();
+        assert_eq!(3, frames.len());
+        assert_eq!("count 2", frames[1].caption());
+        assert_eq!("###", frames[2].text());
+    }
+}
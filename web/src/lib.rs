@@ -0,0 +1,84 @@
+use aoc2016::utils::bespoke::{Screen, ScreenInstruction};
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+const SCREEN_WIDTH: usize = 50;
+const SCREEN_HEIGHT: usize = 6;
+
+/// Colour used to fill the canvas behind the unlit pixels.
+const UNLIT_COLOUR: &str = "#0f0f23";
+/// Colour used to draw the lit pixels.
+const LIT_COLOUR: &str = "#ffff66";
+
+/// Runs the AOC 2016 Day 08 screen instructions given in the input on a new 50px-by-6px screen and
+/// draws the final screen to the canvas, with each screen pixel drawn as a square of "scale" by
+/// "scale" canvas pixels. The canvas is resized to fit the screen.
+///
+/// Returns the number of lit pixels (the Part 1 solution), or an error if any line of the input is
+/// not a valid instruction or the canvas has no 2D context.
+#[wasm_bindgen]
+pub fn run_day08(input: &str, canvas: &HtmlCanvasElement, scale: u32) -> Result<usize, JsValue> {
+    let instructions = parse_instructions(input).map_err(|e| JsValue::from_str(&e))?;
+    let screen = Screen::replay(SCREEN_WIDTH, SCREEN_HEIGHT, &instructions)
+        .last()
+        .unwrap();
+    draw_screen(&screen, canvas, scale)?;
+    Ok(screen.lit_count())
+}
+
+/// Parses the screen instructions from the input, one per non-empty line. Returns an error naming
+/// the first line that is not a valid instruction.
+fn parse_instructions(input: &str) -> Result<Vec<ScreenInstruction>, String> {
+    input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            ScreenInstruction::from_string(line).ok_or(format!("Bad screen instruction: {line}"))
+        })
+        .collect()
+}
+
+/// Draws the screen to the canvas, resizing the canvas to fit the screen scaled up by the given
+/// factor.
+fn draw_screen(screen: &Screen, canvas: &HtmlCanvasElement, scale: u32) -> Result<(), JsValue> {
+    canvas.set_width(screen.width() as u32 * scale);
+    canvas.set_height(screen.height() as u32 * scale);
+    let context = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("Canvas has no 2D context"))?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    let scale = f64::from(scale);
+    context.set_fill_style_str(UNLIT_COLOUR);
+    context.fill_rect(
+        0.0,
+        0.0,
+        f64::from(canvas.width()),
+        f64::from(canvas.height()),
+    );
+    context.set_fill_style_str(LIT_COLOUR);
+    for (y, row) in screen.pixels().iter().enumerate() {
+        for (x, _) in row.iter().enumerate().filter(|(_, &lit)| lit) {
+            context.fill_rect(x as f64 * scale, y as f64 * scale, scale, scale);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Tests that the instructions are parsed from the input, skipping empty lines and reporting the
+    /// first invalid line.
+    #[test]
+    fn test_parse_instructions() {
+        let instructions = parse_instructions("rect 3x2\n\nrotate column x=1 by 1\n").unwrap();
+        assert_eq!(2, instructions.len());
+        assert_eq!("rotate column x=1 by 1", instructions[1].to_string());
+        assert_eq!(
+            Err(String::from("Bad screen instruction: rect 3by2")),
+            parse_instructions("rect 3x2\nrect 3by2").map(|_| ())
+        );
+    }
+}